@@ -1,9 +1,10 @@
-use crate::alias_admin_parse::{parse_bool_flag, parse_env_assignment};
-use crate::alias_doc::{load_alias_doc, save_alias_doc, AliasDoc, AliasJournalDoc};
+use crate::alias_admin_parse::{apply_pathlist_assignment, parse_bool_flag, parse_env_assignment};
+use crate::alias_doc::{load_alias_doc, save_alias_doc, AliasDoc, AliasExecDoc, AliasJournalDoc};
 use crate::alias_validation;
+use crate::manifest::ProfileRequest;
 use crate::path_mutation::PathMutationConfig;
 use anyhow::{anyhow, Context, Result};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -64,6 +65,8 @@ struct MutationInput {
     args: Vec<String>,
     env_set: Vec<(String, String)>,
     env_remove: Vec<String>,
+    env_remove_matching: Vec<String>,
+    cwd: Option<String>,
     path_remove_all: Vec<String>,
     path_remove_one: Vec<String>,
     path_append_all: Vec<String>,
@@ -71,6 +74,8 @@ struct MutationInput {
     path_prepend_all: Vec<String>,
     path_prepend_one: Vec<String>,
     path_replacement: Option<PathFieldReplacement>,
+    pathlists: Vec<String>,
+    pathlist_clear: Vec<String>,
     journal_namespace: Option<String>,
     journal_stderr: Option<bool>,
     journal_identifier: Option<String>,
@@ -89,6 +94,8 @@ impl MutationInput {
             && self.args.is_empty()
             && self.env_set.is_empty()
             && self.env_remove.is_empty()
+            && self.env_remove_matching.is_empty()
+            && self.cwd.is_none()
             && self.path_remove_all.is_empty()
             && self.path_remove_one.is_empty()
             && self.path_append_all.is_empty()
@@ -105,6 +112,8 @@ impl MutationInput {
             && self.journal_rate_limit_interval_usec.is_none()
             && self.journal_rate_limit_burst.is_none()
             && !self.journal_clear
+            && self.pathlists.is_empty()
+            && self.pathlist_clear.is_empty()
     }
 }
 
@@ -138,12 +147,18 @@ fn run_alias_action_inner(raw_args: &[String]) -> Result<()> {
             if raw_args.get(1).map(String::as_str) == Some("--help") {
                 return print_alias_subcommand_help("get");
             }
-            if raw_args.len() != 2 {
-                return Err(anyhow!("usage: chopper --alias get <alias>"));
-            }
+            let profile = match &raw_args[1..] {
+                [_] => None,
+                [_, flag, profile] if flag == "--profile" => Some(profile.as_str()),
+                _ => {
+                    return Err(anyhow!(
+                        "usage: chopper --alias get <alias> [--profile <name>]"
+                    ));
+                }
+            };
             let alias = &raw_args[1];
             validate_alias(alias)?;
-            run_get(alias)
+            run_get(alias, profile)
         }
         "add" => {
             if raw_args.get(1).map(String::as_str) == Some("--help") {
@@ -177,8 +192,9 @@ fn print_alias_help() {
     println!("  chopper --alias <subcommand> [options]");
     println!();
     println!("Subcommands:");
-    println!("  get <alias>");
+    println!("  get <alias> [--profile <name>]");
     println!("      Print the configuration for an alias as JSON.");
+    println!("      --profile shows the effective configuration for that profile.");
     println!();
     println!("  add <alias> --exec <command> [options]");
     println!("      Create a new alias. Fails if the alias already exists.");
@@ -198,6 +214,10 @@ fn print_alias_help() {
     println!("  --arg <value>              Append a fixed argument (repeatable)");
     println!("  --env KEY=VALUE            Set an environment variable (repeatable)");
     println!("  --env-remove KEY           Unset an environment variable (repeatable)");
+    println!(
+        "  --env-remove-matching REGEX  Unset inherited variables whose names match (repeatable)"
+    );
+    println!("  --cwd <dir>                Working directory for the command");
     println!("  --path-remove-all REGEX    Remove all matching PATH entries (repeatable)");
     println!("  --path-remove-one REGEX    Remove first matching PATH entry (repeatable)");
     println!("  --path-append-all PATH     Append PATH after removing all equivalent entries (repeatable)");
@@ -206,6 +226,9 @@ fn print_alias_help() {
     println!("  --path-prepend-one PATH    Prepend PATH after removing first equivalent entry (repeatable)");
     println!("                             With `set`, a path option replaces that field from the rest of argv.");
     println!("                             Place it last; use `--` before values if helpful.");
+    println!("  --pathlist VAR.FIELD=VALUE Edit [pathlists.VAR] (repeatable); FIELD is a path");
+    println!("                             operation, separator, dedupe, or prune_missing");
+    println!("  --pathlist-clear VAR       With `set`, drop [pathlists.VAR] before other edits");
     println!("  --journal-namespace <ns>   systemd journal namespace (enables journaling)");
     println!("  --journal-stderr true|false  Capture stderr to journal (default: true)");
     println!("  --journal-identifier <id>  Journal syslog identifier override");
@@ -229,11 +252,12 @@ fn print_alias_help() {
 fn print_alias_subcommand_help(sub: &str) -> Result<()> {
     match sub {
         "get" => {
-            println!("Usage: chopper --alias get <alias>");
+            println!("Usage: chopper --alias get <alias> [--profile <name>]");
             println!();
             println!("Print the full configuration for an alias as formatted JSON.");
+            println!("With --profile, the named [profile.<name>] overlay is applied first.");
             println!(
-                "Includes exec, args, env, env_remove, path, journal, reconcile, and bashcomp fields."
+                "Includes exec, exec_fallbacks, args, args_after, env, env_remove, env_remove_matching, env_default, env_append, env_prepend, env_file, env_secret (redacted), env_mode, env_keep, path, pathlists, cwd, journal, reconcile, bashcomp, limits, requires, guard, hooks, timeout, retry, concurrency, audit, subcommands, profiles, and default_profile fields."
             );
             println!("When present, path includes remove_all, remove_one, append_all, append_one,");
             println!("prepend_all, and prepend_one.");
@@ -255,12 +279,21 @@ fn print_alias_subcommand_help(sub: &str) -> Result<()> {
             println!("  --arg <value>              Append a fixed argument (repeatable)");
             println!("  --env KEY=VALUE            Set an environment variable (repeatable)");
             println!("  --env-remove KEY           Unset an environment variable at runtime (repeatable)");
+            println!("  --env-remove-matching REGEX  Unset inherited variables whose names match (repeatable)");
+            println!("  --cwd <dir>                Run the command from this directory");
             println!("  --path-remove-all REGEX    Remove all matching PATH entries (repeatable)");
             println!("  --path-remove-one REGEX    Remove first matching PATH entry (repeatable)");
             println!("  --path-append-all PATH     Append PATH after removing all equivalent entries (repeatable)");
             println!("  --path-append-one PATH     Append PATH after removing first equivalent entry (repeatable)");
             println!("  --path-prepend-all PATH    Prepend PATH after removing all equivalent entries (repeatable)");
             println!("  --path-prepend-one PATH    Prepend PATH after removing first equivalent entry (repeatable)");
+            println!("  --pathlist VAR.FIELD=VALUE Add to [pathlists.VAR]; FIELD is remove_all, remove_one,");
+            println!(
+                "                             append_all, append_one, prepend_all, prepend_one,"
+            );
+            println!(
+                "                             separator, dedupe, or prune_missing (repeatable)"
+            );
             println!("  --journal-namespace <ns>   Enable systemd journaling under this namespace");
             println!("  --journal-stderr true|false  Capture stderr to journal (default: true)");
             println!(
@@ -297,6 +330,12 @@ fn print_alias_subcommand_help(sub: &str) -> Result<()> {
                 "  --env KEY=VALUE            Add or update an environment variable (repeatable)"
             );
             println!("  --env-remove KEY           Add a key to the runtime env-remove list (repeatable)");
+            println!(
+                "  --env-remove-matching REGEX  Add a pattern to env_remove_matching (repeatable)"
+            );
+            println!(
+                "  --cwd <dir>                Set the working directory (empty string clears it)"
+            );
             println!("  --path-remove-all [--] ... Replace path.remove_all with regexes from the rest of argv");
             println!("  --path-remove-one [--] ... Replace path.remove_one with regexes from the rest of argv");
             println!("  --path-append-all [--] ... Replace path.append_all with paths from the rest of argv");
@@ -304,6 +343,13 @@ fn print_alias_subcommand_help(sub: &str) -> Result<()> {
             println!("  --path-prepend-all [--] ... Replace path.prepend_all with paths from the rest of argv");
             println!("  --path-prepend-one [--] ... Replace path.prepend_one with paths from the rest of argv");
             println!("                             Path replacement options consume the rest of argv and may be empty to clear.");
+            println!(
+                "  --pathlist VAR.FIELD=VALUE Append an operation to [pathlists.VAR], or set its"
+            );
+            println!(
+                "                             separator, dedupe, or prune_missing (repeatable)"
+            );
+            println!("  --pathlist-clear VAR       Remove [pathlists.VAR] before applying --pathlist edits");
             println!("  --journal-namespace <ns>   Set/update the journal namespace");
             println!("  --journal-stderr true|false  Update stderr capture setting");
             println!("  --journal-identifier <id>  Update the syslog identifier (empty string clears it)");
@@ -350,21 +396,52 @@ fn print_alias_subcommand_help(sub: &str) -> Result<()> {
     Ok(())
 }
 
-fn run_get(alias: &str) -> Result<()> {
+fn run_get(alias: &str, profile: Option<&str>) -> Result<()> {
     let config_path = crate::find_config(alias)
         .ok_or_else(|| anyhow!("alias `{alias}` not found in configuration"))?;
-    let manifest = crate::parser::parse(&config_path)?;
+    let mut manifest = crate::parser::parse(&config_path)?;
+    if let Some(profile) = profile {
+        manifest = manifest.apply_profile(Some(&ProfileRequest::Flag(profile.to_string())))?;
+    }
     for warning in crate::config_diagnostics::manifest_missing_target_warnings(&manifest) {
         eprintln!("warning: {warning}");
     }
     let output = serde_json::json!({
         "alias": alias,
         "config_path": config_path,
+        "profile": profile,
         "exec": manifest.exec,
+        "exec_fallbacks": manifest.exec_fallbacks,
         "args": manifest.args,
+        "args_after": manifest.args_after,
         "env": manifest.env,
         "env_remove": manifest.env_remove,
+        "env_remove_matching": manifest.env_remove_matching,
+        "env_default": manifest.env_default,
+        "env_append": manifest.env_append,
+        "env_prepend": manifest.env_prepend,
+        "env_file": manifest.env_file,
+        "env_secret": manifest
+            .env_secret
+            .iter()
+            .map(|(key, source)| (key.clone(), source.redacted()))
+            .collect::<BTreeMap<_, _>>(),
+        "env_mode": manifest.env_mode,
+        "env_keep": manifest.env_keep,
         "path": manifest.path,
+        "pathlists": manifest.pathlists,
+        "cwd": manifest.cwd,
+        "limits": manifest.limits,
+        "requires": manifest.requires,
+        "guard": manifest.guard,
+        "hooks": manifest.hooks,
+        "timeout": manifest.timeout,
+        "retry": manifest.retry,
+        "concurrency": manifest.concurrency,
+        "audit": manifest.audit,
+        "subcommands": manifest.subcommands,
+        "profiles": manifest.profiles,
+        "default_profile": manifest.default_profile,
         "journal": manifest.journal,
         "reconcile": manifest.reconcile,
         "bashcomp": manifest.bashcomp,
@@ -412,20 +489,50 @@ fn run_add_or_set(mode: AliasMutationMode, raw_args: &[String]) -> Result<()> {
             .clone()
             .ok_or_else(|| anyhow!("`add` requires --exec <command>"))?;
         let path = path_doc_from_mutation(&mutation);
+        let mut pathlists = BTreeMap::new();
+        for entry in &mutation.pathlists {
+            apply_pathlist_assignment(&mut pathlists, entry)?;
+        }
         let journal = build_journal_from_mutation(&mutation, true)?;
         let mut env = HashMap::new();
         for (key, value) in mutation.env_set {
             env.insert(key, value);
         }
         let doc = AliasDoc {
-            exec,
+            extends: None,
+            include: Vec::new(),
+            exec: AliasExecDoc::Command(exec),
             args: mutation.args,
+            args_after: Vec::new(),
             env,
             env_remove: mutation.env_remove,
+            env_remove_matching: mutation.env_remove_matching,
+            env_default: BTreeMap::new(),
+            env_append: BTreeMap::new(),
+            env_prepend: BTreeMap::new(),
+            env_file: Vec::new(),
+            env_secret: BTreeMap::new(),
+            env_mode: None,
+            env_keep: Vec::new(),
             path,
+            pathlists,
+            cwd: mutation.cwd.filter(|cwd| !cwd.trim().is_empty()),
             journal,
             reconcile: None,
             bashcomp: None,
+            limits: None,
+            requires: None,
+            guard: None,
+            hooks: None,
+            timeout: None,
+            timeout_signal: None,
+            kill_after: None,
+            retry: None,
+            concurrency: None,
+            audit: None,
+            subcommands: BTreeMap::new(),
+            default_profile: None,
+            profile: BTreeMap::new(),
         };
         save_alias_doc(&target_path, &doc)?;
         if !mutation.no_wrapper_sync {
@@ -448,7 +555,7 @@ fn run_add_or_set(mode: AliasMutationMode, raw_args: &[String]) -> Result<()> {
     let mut replaced_path_field = None;
 
     if let Some(exec) = mutation.exec {
-        doc.exec = exec;
+        doc.exec = AliasExecDoc::Command(exec);
     }
     if !mutation.args.is_empty() {
         doc.args = mutation.args;
@@ -463,6 +570,18 @@ fn run_add_or_set(mode: AliasMutationMode, raw_args: &[String]) -> Result<()> {
             }
         }
     }
+    for pattern in mutation.env_remove_matching {
+        if !doc.env_remove_matching.contains(&pattern) {
+            doc.env_remove_matching.push(pattern);
+        }
+    }
+    if let Some(cwd) = mutation.cwd {
+        doc.cwd = if cwd.trim().is_empty() {
+            None
+        } else {
+            Some(cwd)
+        };
+    }
     if let Some(path_mutation) = path_mutation {
         let path = doc.path.get_or_insert_with(PathMutationConfig::default);
         path.remove_all.extend(path_mutation.remove_all);
@@ -484,6 +603,13 @@ fn run_add_or_set(mode: AliasMutationMode, raw_args: &[String]) -> Result<()> {
         }
         replaced_path_field = Some((replacement, previous_values));
     }
+    for key in &mutation.pathlist_clear {
+        doc.pathlists.remove(key.trim());
+    }
+    for entry in &mutation.pathlists {
+        apply_pathlist_assignment(&mut doc.pathlists, entry)?;
+    }
+    doc.pathlists.retain(|_, list| !list.is_empty());
     if mutation.journal_clear {
         doc.journal = None;
     } else if mutation.journal_namespace.is_some()
@@ -676,6 +802,8 @@ fn parse_mutation_args(raw_args: &[String], mode: AliasMutationMode) -> Result<M
     let mut args = Vec::new();
     let mut env_set = Vec::new();
     let mut env_remove = Vec::new();
+    let mut env_remove_matching = Vec::new();
+    let mut cwd = None;
     let mut path_remove_all = Vec::new();
     let mut path_remove_one = Vec::new();
     let mut path_append_all = Vec::new();
@@ -692,6 +820,8 @@ fn parse_mutation_args(raw_args: &[String], mode: AliasMutationMode) -> Result<M
     let mut journal_rate_limit_interval_usec = None;
     let mut journal_rate_limit_burst = None;
     let mut journal_clear = false;
+    let mut pathlists = Vec::new();
+    let mut pathlist_clear = Vec::new();
     let mut no_wrapper_sync = false;
 
     let mut idx = 0;
@@ -725,6 +855,20 @@ fn parse_mutation_args(raw_args: &[String], mode: AliasMutationMode) -> Result<M
                 env_remove.push(value.to_string());
                 idx += 2;
             }
+            "--env-remove-matching" => {
+                let value = raw_args
+                    .get(idx + 1)
+                    .ok_or_else(|| anyhow!("--env-remove-matching requires a regex"))?;
+                env_remove_matching.push(value.to_string());
+                idx += 2;
+            }
+            "--cwd" => {
+                let value = raw_args
+                    .get(idx + 1)
+                    .ok_or_else(|| anyhow!("--cwd requires a directory"))?;
+                cwd = Some(value.to_string());
+                idx += 2;
+            }
             "--path-remove-all" => {
                 if mode == AliasMutationMode::Set {
                     path_replacement = Some(parse_path_replacement(
@@ -869,6 +1013,21 @@ fn parse_mutation_args(raw_args: &[String], mode: AliasMutationMode) -> Result<M
                 })?);
                 idx += 2;
             }
+            "--pathlist" => {
+                let value = raw_args
+                    .get(idx + 1)
+                    .ok_or_else(|| anyhow!("--pathlist requires VAR.field=VALUE"))?;
+                apply_pathlist_assignment(&mut BTreeMap::new(), value)?;
+                pathlists.push(value.to_string());
+                idx += 2;
+            }
+            "--pathlist-clear" => {
+                let value = raw_args
+                    .get(idx + 1)
+                    .ok_or_else(|| anyhow!("--pathlist-clear requires a variable name"))?;
+                pathlist_clear.push(value.to_string());
+                idx += 2;
+            }
             "--journal-clear" => {
                 journal_clear = true;
                 idx += 1;
@@ -886,6 +1045,8 @@ fn parse_mutation_args(raw_args: &[String], mode: AliasMutationMode) -> Result<M
         args,
         env_set,
         env_remove,
+        env_remove_matching,
+        cwd,
         path_remove_all,
         path_remove_one,
         path_append_all,
@@ -902,6 +1063,8 @@ fn parse_mutation_args(raw_args: &[String], mode: AliasMutationMode) -> Result<M
         journal_rate_limit_interval_usec,
         journal_rate_limit_burst,
         journal_clear,
+        pathlists,
+        pathlist_clear,
         no_wrapper_sync,
     })
 }
@@ -1041,14 +1204,40 @@ pub(crate) fn default_toml_path(alias: &str) -> PathBuf {
 
 pub(crate) fn minimal_alias_doc() -> AliasDoc {
     AliasDoc {
-        exec: "echo".to_string(),
+        extends: None,
+        include: Vec::new(),
+        exec: AliasExecDoc::Command("echo".to_string()),
         args: Vec::new(),
+        args_after: Vec::new(),
         env: HashMap::new(),
         env_remove: Vec::new(),
+        env_remove_matching: Vec::new(),
+        env_default: BTreeMap::new(),
+        env_append: BTreeMap::new(),
+        env_prepend: BTreeMap::new(),
+        env_file: Vec::new(),
+        env_secret: BTreeMap::new(),
+        env_mode: None,
+        env_keep: Vec::new(),
         path: None,
+        pathlists: BTreeMap::new(),
+        cwd: None,
         journal: None,
         reconcile: None,
         bashcomp: None,
+        limits: None,
+        requires: None,
+        guard: None,
+        hooks: None,
+        timeout: None,
+        timeout_signal: None,
+        kill_after: None,
+        retry: None,
+        concurrency: None,
+        audit: None,
+        subcommands: BTreeMap::new(),
+        default_profile: None,
+        profile: BTreeMap::new(),
     }
 }
 
@@ -1180,7 +1369,7 @@ fn validate_alias(alias: &str) -> Result<()> {
 mod tests {
     use super::{
         create_alias, duplicate_alias, load_or_seed_alias_doc, parse_mutation_args, rename_alias,
-        AliasMutationMode, PathField,
+        AliasExecDoc, AliasMutationMode, PathField,
     };
     use crate::test_support::ENV_LOCK;
     use std::env;
@@ -1199,6 +1388,10 @@ mod tests {
                 "A=1".into(),
                 "--env-remove".into(),
                 "OLD".into(),
+                "--env-remove-matching".into(),
+                "^AWS_".into(),
+                "--cwd".into(),
+                "/srv/repo".into(),
                 "--path-remove-all".into(),
                 "^/tmp".into(),
                 "--path-append-one".into(),
@@ -1222,6 +1415,8 @@ mod tests {
         assert_eq!(mutation.args, vec!["hello"]);
         assert_eq!(mutation.env_set, vec![("A".into(), "1".into())]);
         assert_eq!(mutation.env_remove, vec!["OLD"]);
+        assert_eq!(mutation.env_remove_matching, vec!["^AWS_"]);
+        assert_eq!(mutation.cwd.as_deref(), Some("/srv/repo"));
         assert_eq!(mutation.path_remove_all, vec!["^/tmp"]);
         assert_eq!(mutation.path_append_one, vec!["/custom/bin"]);
         assert_eq!(mutation.journal_namespace.as_deref(), Some("ops"));
@@ -1232,6 +1427,41 @@ mod tests {
         assert!(mutation.no_wrapper_sync);
     }
 
+    #[test]
+    fn parses_pathlist_flags_and_rejects_unknown_fields() {
+        let mutation = parse_mutation_args(
+            &[
+                "--pathlist-clear".into(),
+                "PYTHONPATH".into(),
+                "--pathlist".into(),
+                "LD_LIBRARY_PATH.prepend_one=/opt/lib".into(),
+                "--pathlist".into(),
+                "LD_LIBRARY_PATH.dedupe=true".into(),
+            ],
+            AliasMutationMode::Set,
+        )
+        .expect("mutation parse");
+        assert_eq!(
+            mutation.pathlists,
+            vec![
+                "LD_LIBRARY_PATH.prepend_one=/opt/lib",
+                "LD_LIBRARY_PATH.dedupe=true"
+            ]
+        );
+        assert_eq!(mutation.pathlist_clear, vec!["PYTHONPATH"]);
+        assert!(!mutation.is_empty());
+
+        let err = parse_mutation_args(
+            &["--pathlist".into(), "LD_LIBRARY_PATH.prepend=/x".into()],
+            AliasMutationMode::Set,
+        )
+        .expect_err("unknown field should fail");
+        assert!(
+            err.to_string().contains("unknown pathlist field `prepend`"),
+            "{err}"
+        );
+    }
+
     #[test]
     fn parses_set_path_replacement_with_optional_separator() {
         let mutation = parse_mutation_args(
@@ -1345,7 +1575,7 @@ mod tests {
         let temp = TempDir::new().expect("tempdir");
         env::set_var("CHOPPER_CONFIG_DIR", temp.path());
         let (doc, path) = load_or_seed_alias_doc("missing").expect("seed alias doc");
-        assert_eq!(doc.exec, "echo");
+        assert_eq!(doc.exec, AliasExecDoc::Command("echo".to_string()));
         assert_eq!(path, temp.path().join("missing/exe.toml"));
         env::remove_var("CHOPPER_CONFIG_DIR");
     }
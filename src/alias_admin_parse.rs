@@ -1,4 +1,6 @@
+use crate::path_mutation::PathListConfig;
 use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
 
 pub fn parse_env_assignment(value: &str) -> Result<(String, String)> {
     let mut parts = value.splitn(2, '=');
@@ -26,9 +28,76 @@ pub fn parse_bool_flag(value: &str, field: &str) -> Result<bool> {
     }
 }
 
+/// Applies one `VAR.field=value` pathlist assignment. Operation fields
+/// (`prepend_one`, ...) append an entry; `separator`, `dedupe`, and
+/// `prune_missing` replace the current value.
+pub fn apply_pathlist_assignment(
+    pathlists: &mut BTreeMap<String, PathListConfig>,
+    value: &str,
+) -> Result<()> {
+    let invalid = || anyhow!("pathlist assignment must be in VAR.field=VALUE form; got `{value}`");
+    let (target, entry) = value.split_once('=').ok_or_else(invalid)?;
+    let (key, field) = target.trim().rsplit_once('.').ok_or_else(invalid)?;
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(anyhow!("pathlist assignment variable cannot be blank"));
+    }
+    let field = field.trim();
+    let flag = match field {
+        "dedupe" | "prune_missing" => Some(parse_bool_flag(entry, &format!("pathlist {field}"))?),
+        _ => None,
+    };
+    let list = pathlists.entry(key.to_string()).or_default();
+    match field {
+        "separator" => list.separator = Some(entry.to_string()),
+        "dedupe" => list.dedupe = flag.unwrap_or_default(),
+        "prune_missing" => list.prune_missing = flag.unwrap_or_default(),
+        field => {
+            let Some((_, values)) = list
+                .operations_mut()
+                .into_iter()
+                .find(|(name, _)| *name == field)
+            else {
+                if list.is_empty() {
+                    pathlists.remove(key);
+                }
+                return Err(anyhow!(
+                    "unknown pathlist field `{field}`; expected remove_all, remove_one, append_all, append_one, prepend_all, prepend_one, separator, dedupe, or prune_missing"
+                ));
+            };
+            values.push(entry.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Renders `pathlists` as the `VAR.field=value` assignments that
+/// [`apply_pathlist_assignment`] reads back.
+pub fn pathlist_assignments(pathlists: &BTreeMap<String, PathListConfig>) -> Vec<String> {
+    let mut out = Vec::new();
+    for (key, list) in pathlists {
+        for (field, values) in list.operation_lists() {
+            out.extend(values.iter().map(|value| format!("{key}.{field}={value}")));
+        }
+        if let Some(separator) = &list.separator {
+            out.push(format!("{key}.separator={separator}"));
+        }
+        if list.dedupe {
+            out.push(format!("{key}.dedupe=true"));
+        }
+        if list.prune_missing {
+            out.push(format!("{key}.prune_missing=true"));
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{parse_bool_flag, parse_env_assignment};
+    use super::{
+        apply_pathlist_assignment, parse_bool_flag, parse_env_assignment, pathlist_assignments,
+    };
+    use std::collections::BTreeMap;
 
     #[test]
     fn env_assignment_parses_key_value_pairs() {
@@ -61,4 +130,38 @@ mod tests {
         let err = parse_bool_flag("maybe", "flag").expect_err("unknown should fail");
         assert!(err.to_string().contains("true/false"));
     }
+
+    #[test]
+    fn pathlist_assignments_round_trip() {
+        let mut pathlists = BTreeMap::new();
+        for entry in [
+            "LD_LIBRARY_PATH.prepend_one=/opt/lib",
+            "LD_LIBRARY_PATH.dedupe=true",
+            "PYTHONPATH.separator=;",
+            "PYTHONPATH.append_all=/srv/py",
+        ] {
+            apply_pathlist_assignment(&mut pathlists, entry).expect("assignment parse");
+        }
+        assert_eq!(
+            pathlist_assignments(&pathlists),
+            vec![
+                "LD_LIBRARY_PATH.prepend_one=/opt/lib",
+                "LD_LIBRARY_PATH.dedupe=true",
+                "PYTHONPATH.append_all=/srv/py",
+                "PYTHONPATH.separator=;",
+            ]
+        );
+
+        for (entry, expected) in [
+            ("LD_LIBRARY_PATH", "VAR.field=VALUE"),
+            ("prepend_one=/x", "VAR.field=VALUE"),
+            (".prepend_one=/x", "cannot be blank"),
+            ("X.prepend=/x", "unknown pathlist field `prepend`"),
+            ("X.dedupe=maybe", "true/false"),
+        ] {
+            let err = apply_pathlist_assignment(&mut pathlists, entry)
+                .expect_err("invalid assignment should fail");
+            assert!(err.to_string().contains(expected), "{entry}: {err}");
+        }
+    }
 }
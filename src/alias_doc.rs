@@ -1,29 +1,120 @@
 use crate::arg_validation::{self, ArgViolation};
 use crate::env_validation::{self, EnvKeyViolation, EnvValueViolation};
 use crate::journal_validation::{self, JournalIdentifierViolation, JournalNamespaceViolation};
-use crate::path_mutation::PathMutationConfig;
+use crate::path_mutation::{PathListConfig, PathMutationConfig};
 use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct AliasDoc {
-    pub exec: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    #[serde(default, skip_serializing_if = "AliasExecDoc::is_empty")]
+    pub exec: AliasExecDoc,
     #[serde(default)]
     pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args_after: Vec<String>,
     #[serde(default)]
     pub env: HashMap<String, String>,
     #[serde(default)]
     pub env_remove: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_remove_matching: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env_default: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env_append: BTreeMap<String, AliasEnvJoinDoc>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env_prepend: BTreeMap<String, AliasEnvJoinDoc>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_file: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env_secret: BTreeMap<String, AliasSecretDoc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_keep: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub path: Option<PathMutationConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pathlists: BTreeMap<String, PathListConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub journal: Option<AliasJournalDoc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reconcile: Option<AliasReconcileDoc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bashcomp: Option<AliasBashcompDoc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<AliasLimitsDoc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requires: Option<AliasRequiresDoc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guard: Option<AliasGuardDoc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<AliasHooksDoc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_signal: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kill_after: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<AliasRetryDoc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<AliasConcurrencyDoc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit: Option<AliasAuditDoc>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub subcommands: BTreeMap<String, AliasSubcommandDoc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profile: BTreeMap<String, AliasProfileDoc>,
+}
+
+/// `exec` is either one command or a list of fallback candidates.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AliasExecDoc {
+    Command(String),
+    Candidates(Vec<String>),
+}
+
+impl Default for AliasExecDoc {
+    fn default() -> Self {
+        Self::Command(String::new())
+    }
+}
+
+impl AliasExecDoc {
+    /// Keeps zero or one value in the plain `exec = "..."` form.
+    pub fn from_candidates(mut candidates: Vec<String>) -> Self {
+        match candidates.len() {
+            0 => Self::default(),
+            1 => Self::Command(candidates.remove(0)),
+            _ => Self::Candidates(candidates),
+        }
+    }
+
+    pub fn candidates(&self) -> &[String] {
+        match self {
+            Self::Command(command) => std::slice::from_ref(command),
+            Self::Candidates(candidates) => candidates,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates()
+            .iter()
+            .all(|candidate| candidate.is_empty())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -67,17 +158,199 @@ pub struct AliasBashcompDoc {
     pub rhai_function: Option<String>,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AliasSubcommandDoc {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_remove: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathMutationConfig>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AliasProfileDoc {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exec: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_remove: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathMutationConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub journal: Option<AliasJournalDoc>,
+}
+
+/// One `[env_append]`/`[env_prepend]` entry: a bare value, or a table that
+/// also names the separator.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AliasEnvJoinDoc {
+    Value(String),
+    Table {
+        value: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        separator: Option<String>,
+    },
+}
+
+impl AliasEnvJoinDoc {
+    fn parts(&self) -> (&str, Option<&str>) {
+        match self {
+            Self::Value(value) => (value, None),
+            Self::Table { value, separator } => (value, separator.as_deref()),
+        }
+    }
+}
+
+/// One `[env_secret]` descriptor; exactly one source is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AliasSecretDoc {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyring: Option<String>,
+}
+
+impl AliasSecretDoc {
+    /// Describes the source without its value, e.g. `<redacted: file ~/.token>`.
+    pub fn redacted(&self) -> String {
+        match (&self.file, &self.command, &self.keyring) {
+            (Some(file), _, _) => format!("<redacted: file {file}>"),
+            (_, Some(command), _) => format!("<redacted: command {}>", command.join(" ")),
+            (_, _, Some(keyring)) => format!("<redacted: keyring {keyring}>"),
+            _ => "<redacted>".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AliasRequiresDoc {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_cmd: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_regex: Option<String>,
+    pub min_version: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AliasGuardDoc {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub require: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub confirm: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub non_tty: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AliasHooksDoc {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre: Vec<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub journal: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AliasRetryDoc {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backoff: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_delay: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on_exit_codes: Vec<i32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AliasConcurrencyDoc {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AliasAuditDoc {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact_options: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact_matching: Vec<String>,
+}
+
+/// `[limits]` is round-tripped as written; the parser validates the values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AliasLimitsDoc {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nofile: Option<AliasLimitValueDoc>,
+    #[serde(rename = "as", skip_serializing_if = "Option::is_none")]
+    pub address_space: Option<AliasLimitValueDoc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<AliasLimitValueDoc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub core: Option<AliasLimitValueDoc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nproc: Option<AliasLimitValueDoc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nice: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ioprio: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub umask: Option<AliasLimitValueDoc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AliasLimitValueDoc {
+    Integer(i64),
+    Text(String),
+}
+
 fn default_true() -> bool {
     true
 }
 
 impl AliasDoc {
     pub fn validate(&self) -> Result<()> {
-        if self.exec.trim().is_empty() {
+        if let Some(extends) = &self.extends {
+            if extends.trim().is_empty() {
+                return Err(anyhow!("`extends` cannot be blank"));
+            }
+            if extends.contains('\0') {
+                return Err(anyhow!("`extends` cannot contain NUL bytes"));
+            }
+        } else if self
+            .exec
+            .candidates()
+            .iter()
+            .all(|candidate| candidate.trim().is_empty())
+        {
             return Err(anyhow!("`exec` cannot be blank"));
         }
-        if self.exec.contains('\0') {
-            return Err(anyhow!("`exec` cannot contain NUL bytes"));
+        for include in &self.include {
+            validate_script_shape(include.trim(), "`include` entries")?;
+        }
+        for candidate in self.exec.candidates() {
+            if candidate.contains('\0') {
+                return Err(anyhow!("`exec` cannot contain NUL bytes"));
+            }
+            if matches!(self.exec, AliasExecDoc::Candidates(_)) && candidate.trim().is_empty() {
+                return Err(anyhow!("`exec` entries cannot be blank"));
+            }
         }
         for arg in &self.args {
             if matches!(
@@ -87,6 +360,14 @@ impl AliasDoc {
                 return Err(anyhow!("`args` entries cannot contain NUL bytes"));
             }
         }
+        for arg in &self.args_after {
+            if matches!(
+                arg_validation::validate_arg_value(arg),
+                Err(ArgViolation::ContainsNul)
+            ) {
+                return Err(anyhow!("`args_after` entries cannot contain NUL bytes"));
+            }
+        }
         for (key, value) in &self.env {
             match env_validation::validate_env_key(key) {
                 Ok(()) => {}
@@ -117,9 +398,263 @@ impl AliasDoc {
                 }
             }
         }
+        for pattern in &self.env_remove_matching {
+            if pattern.contains('\0') {
+                return Err(anyhow!(
+                    "`env_remove_matching` entries cannot contain NUL bytes"
+                ));
+            }
+            crate::manifest::compile_env_pattern(pattern, "`env_remove_matching`")?;
+        }
+        let env_operations = self
+            .env_default
+            .iter()
+            .map(|(key, value)| ("env_default", key, value.as_str(), None))
+            .chain(self.env_append.iter().map(|(key, join)| {
+                let (value, separator) = join.parts();
+                ("env_append", key, value, separator)
+            }))
+            .chain(self.env_prepend.iter().map(|(key, join)| {
+                let (value, separator) = join.parts();
+                ("env_prepend", key, value, separator)
+            }));
+        for (field, key, value, separator) in env_operations {
+            match env_validation::validate_env_key(key) {
+                Ok(()) if !key.trim().is_empty() => {}
+                Ok(()) => return Err(anyhow!("`{field}` keys cannot be blank")),
+                Err(EnvKeyViolation::ContainsEquals) => {
+                    return Err(anyhow!("`{field}` key `{key}` cannot contain `=`"));
+                }
+                Err(EnvKeyViolation::ContainsNul) => {
+                    return Err(anyhow!("`{field}` keys cannot contain NUL bytes"));
+                }
+            }
+            if value.contains('\0') || separator.is_some_and(|separator| separator.contains('\0')) {
+                return Err(anyhow!(
+                    "`{field}` value for key `{key}` cannot contain NUL bytes"
+                ));
+            }
+        }
+        for env_file in &self.env_file {
+            validate_script_shape(env_file.trim(), "`env_file` entries")?;
+        }
+        if let Some(env_mode) = &self.env_mode {
+            if !matches!(env_mode.trim(), "inherit" | "clean") {
+                return Err(anyhow!("`env_mode` must be `inherit` or `clean`"));
+            }
+        }
+        if self.env_keep.iter().any(|entry| entry.contains('\0')) {
+            return Err(anyhow!("`env_keep` entries cannot contain NUL bytes"));
+        }
+        for (key, secret) in &self.env_secret {
+            match env_validation::validate_env_key(key) {
+                Ok(()) if !key.trim().is_empty() => {}
+                Ok(()) => return Err(anyhow!("`env_secret` keys cannot be blank")),
+                Err(EnvKeyViolation::ContainsEquals) => {
+                    return Err(anyhow!("`env_secret` key `{key}` cannot contain `=`"));
+                }
+                Err(EnvKeyViolation::ContainsNul) => {
+                    return Err(anyhow!("`env_secret` key `{key}` cannot contain NUL bytes"));
+                }
+            }
+            let sources = [
+                secret.file.is_some(),
+                secret.command.is_some(),
+                secret.keyring.is_some(),
+            ];
+            if sources.into_iter().filter(|set| *set).count() != 1 {
+                return Err(anyhow!(
+                    "`env_secret.{key}` must set exactly one of `file`, `command`, or `keyring`"
+                ));
+            }
+            let has_nul = secret
+                .file
+                .iter()
+                .chain(secret.command.iter().flatten())
+                .chain(&secret.keyring)
+                .any(|value| value.contains('\0'));
+            if has_nul {
+                return Err(anyhow!("`env_secret.{key}` cannot contain NUL bytes"));
+            }
+        }
         if let Some(path) = &self.path {
             path.validate("path")?;
         }
+        for (key, list) in &self.pathlists {
+            match env_validation::validate_env_key(key) {
+                Ok(()) if !key.trim().is_empty() => {}
+                Ok(()) => return Err(anyhow!("`pathlists` keys cannot be blank")),
+                Err(EnvKeyViolation::ContainsEquals) => {
+                    return Err(anyhow!("`pathlists` key `{key}` cannot contain `=`"));
+                }
+                Err(EnvKeyViolation::ContainsNul) => {
+                    return Err(anyhow!("`pathlists` keys cannot contain NUL bytes"));
+                }
+            }
+            list.validate(&format!("pathlists.{key}"))?;
+        }
+        if let Some(requires) = &self.requires {
+            if requires.min_version.trim().is_empty() {
+                return Err(anyhow!("`requires.min_version` cannot be blank"));
+            }
+            let has_nul = std::iter::once(&requires.min_version)
+                .chain(requires.version_cmd.iter().flatten())
+                .chain(&requires.version_regex)
+                .any(|value| value.contains('\0'));
+            if has_nul {
+                return Err(anyhow!("`requires` values cannot contain NUL bytes"));
+            }
+        }
+        if let Some(guard) = &self.guard {
+            let rules = guard
+                .deny
+                .iter()
+                .chain(&guard.require)
+                .chain(&guard.confirm);
+            for rule in rules {
+                if rule.trim().is_empty() {
+                    return Err(anyhow!("`guard` rules cannot be blank"));
+                }
+                if rule.contains('\0') {
+                    return Err(anyhow!("`guard` rules cannot contain NUL bytes"));
+                }
+            }
+        }
+        for (field, value) in [
+            ("timeout", &self.timeout),
+            ("timeout_signal", &self.timeout_signal),
+            ("kill_after", &self.kill_after),
+        ] {
+            if value.as_deref().is_some_and(|value| value.contains('\0')) {
+                return Err(anyhow!("`{field}` cannot contain NUL bytes"));
+            }
+        }
+        if let Some(retry) = &self.retry {
+            if retry.attempts == Some(0) {
+                return Err(anyhow!("`retry.attempts` must be at least 1"));
+            }
+            for (field, value) in [
+                ("retry.backoff", &retry.backoff),
+                ("retry.initial_delay", &retry.initial_delay),
+            ] {
+                if value.as_deref().is_some_and(|value| value.contains('\0')) {
+                    return Err(anyhow!("`{field}` cannot contain NUL bytes"));
+                }
+            }
+        }
+        if let Some(concurrency) = &self.concurrency {
+            for (field, value) in [
+                ("concurrency.mode", &concurrency.mode),
+                ("concurrency.lock_key", &concurrency.lock_key),
+            ] {
+                if value.as_deref().is_some_and(|value| value.contains('\0')) {
+                    return Err(anyhow!("`{field}` cannot contain NUL bytes"));
+                }
+            }
+        }
+        if let Some(audit) = &self.audit {
+            if audit
+                .redact_options
+                .iter()
+                .chain(&audit.redact_matching)
+                .any(|rule| rule.contains('\0'))
+            {
+                return Err(anyhow!("`audit` redaction rules cannot contain NUL bytes"));
+            }
+        }
+        if let Some(hooks) = &self.hooks {
+            for command in hooks.pre.iter().chain(&hooks.post) {
+                if command
+                    .first()
+                    .is_none_or(|program| program.trim().is_empty())
+                {
+                    return Err(anyhow!("`hooks` commands cannot be empty"));
+                }
+                if command.iter().any(|arg| arg.contains('\0')) {
+                    return Err(anyhow!("`hooks` commands cannot contain NUL bytes"));
+                }
+            }
+        }
+        for (name, overlay) in &self.subcommands {
+            if name.trim().is_empty() {
+                return Err(anyhow!("`subcommands` keys cannot be blank"));
+            }
+            if name.contains('\0') {
+                return Err(anyhow!("`subcommands` keys cannot contain NUL bytes"));
+            }
+            if overlay.args.iter().any(|arg| arg.contains('\0')) {
+                return Err(anyhow!(
+                    "`subcommands.{name}.args` entries cannot contain NUL bytes"
+                ));
+            }
+            for key in overlay.env.keys().chain(&overlay.env_remove) {
+                if env_validation::validate_env_key(key).is_err() {
+                    return Err(anyhow!(
+                        "`subcommands.{name}` env key `{key}` cannot contain `=` or NUL bytes"
+                    ));
+                }
+            }
+            if overlay.env.values().any(|value| value.contains('\0')) {
+                return Err(anyhow!(
+                    "`subcommands.{name}.env` values cannot contain NUL bytes"
+                ));
+            }
+            if let Some(path) = &overlay.path {
+                path.validate(&format!("subcommands.{name}.path"))?;
+            }
+        }
+        if let Some(default_profile) = &self.default_profile {
+            if default_profile.contains('\0') {
+                return Err(anyhow!("`default_profile` cannot contain NUL bytes"));
+            }
+        }
+        for (name, profile) in &self.profile {
+            if name.trim().is_empty() {
+                return Err(anyhow!("`profile` names cannot be blank"));
+            }
+            if name.contains('\0') {
+                return Err(anyhow!("`profile` names cannot contain NUL bytes"));
+            }
+            if profile
+                .exec
+                .as_deref()
+                .is_some_and(|exec| exec.contains('\0'))
+            {
+                return Err(anyhow!("`profile.{name}.exec` cannot contain NUL bytes"));
+            }
+            if profile.args.iter().flatten().any(|arg| arg.contains('\0')) {
+                return Err(anyhow!(
+                    "`profile.{name}.args` entries cannot contain NUL bytes"
+                ));
+            }
+            for key in profile.env.keys().chain(&profile.env_remove) {
+                if env_validation::validate_env_key(key).is_err() {
+                    return Err(anyhow!(
+                        "`profile.{name}` env key `{key}` cannot contain `=` or NUL bytes"
+                    ));
+                }
+            }
+            if profile.env.values().any(|value| value.contains('\0')) {
+                return Err(anyhow!(
+                    "`profile.{name}.env` values cannot contain NUL bytes"
+                ));
+            }
+            if let Some(path) = &profile.path {
+                path.validate(&format!("profile.{name}.path"))?;
+            }
+            if let Some(journal) = &profile.journal {
+                if journal.namespace.trim().is_empty() {
+                    return Err(anyhow!(
+                        "`profile.{name}.journal.namespace` cannot be blank"
+                    ));
+                }
+            }
+        }
+        if let Some(cwd) = &self.cwd {
+            if cwd.contains('\0') {
+                return Err(anyhow!("`cwd` cannot contain NUL bytes"));
+            }
+        }
         if let Some(journal) = &self.journal {
             match journal_validation::normalize_namespace(&journal.namespace) {
                 Ok(_) => {}
@@ -257,19 +792,47 @@ pub fn save_alias_doc(path: &Path, doc: &AliasDoc) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::{
-        load_alias_doc, save_alias_doc, AliasBashcompDoc, AliasDoc, AliasJournalDoc,
-        AliasReconcileDoc,
+        load_alias_doc, save_alias_doc, AliasAuditDoc, AliasBashcompDoc, AliasConcurrencyDoc,
+        AliasDoc, AliasEnvJoinDoc, AliasExecDoc, AliasGuardDoc, AliasHooksDoc, AliasJournalDoc,
+        AliasLimitValueDoc, AliasLimitsDoc, AliasProfileDoc, AliasReconcileDoc, AliasRequiresDoc,
+        AliasRetryDoc, AliasSecretDoc, AliasSubcommandDoc,
     };
-    use crate::path_mutation::PathMutationConfig;
-    use std::collections::HashMap;
+    use crate::path_mutation::{PathListConfig, PathMutationConfig};
+    use std::collections::{BTreeMap, HashMap};
     use tempfile::TempDir;
 
     fn valid_doc() -> AliasDoc {
         AliasDoc {
-            exec: "echo".to_string(),
+            extends: None,
+            include: vec!["../_shared/env.toml".to_string()],
+            exec: AliasExecDoc::Command("echo".to_string()),
             args: vec!["hello".to_string()],
+            args_after: Vec::new(),
             env: HashMap::from([("A".to_string(), "1".to_string())]),
             env_remove: vec!["OLD".to_string()],
+            env_remove_matching: vec!["^AWS_".to_string()],
+            env_default: BTreeMap::from([("EDITOR".to_string(), "vi".to_string())]),
+            env_append: BTreeMap::from([(
+                "RUSTFLAGS".to_string(),
+                AliasEnvJoinDoc::Value("-Dwarnings".to_string()),
+            )]),
+            env_prepend: BTreeMap::from([(
+                "JAVA_OPTS".to_string(),
+                AliasEnvJoinDoc::Table {
+                    value: "-Xmx2g".to_string(),
+                    separator: Some(" ".to_string()),
+                },
+            )]),
+            env_file: vec!["app.env".to_string()],
+            env_secret: BTreeMap::from([(
+                "API_TOKEN".to_string(),
+                AliasSecretDoc {
+                    file: Some("~/.secrets/token".to_string()),
+                    ..AliasSecretDoc::default()
+                },
+            )]),
+            env_mode: Some("clean".to_string()),
+            env_keep: vec!["HOME".to_string(), "^LC_".to_string()],
             path: Some(PathMutationConfig {
                 remove_all: vec!["^/tmp".to_string()],
                 remove_one: Vec::new(),
@@ -278,6 +841,16 @@ mod tests {
                 prepend_all: Vec::new(),
                 prepend_one: vec!["/preferred/bin".to_string()],
             }),
+            pathlists: BTreeMap::from([(
+                "LD_LIBRARY_PATH".to_string(),
+                PathListConfig {
+                    prepend_one: vec!["/opt/lib".to_string()],
+                    separator: Some(":".to_string()),
+                    dedupe: true,
+                    ..PathListConfig::default()
+                },
+            )]),
+            cwd: Some("../repo".to_string()),
             journal: Some(AliasJournalDoc {
                 namespace: "ops".to_string(),
                 stderr: true,
@@ -299,6 +872,61 @@ mod tests {
                 rhai_script: Some("comp/custom.rhai".to_string()),
                 rhai_function: Some("complete".to_string()),
             }),
+            limits: Some(AliasLimitsDoc {
+                nofile: Some(AliasLimitValueDoc::Integer(4096)),
+                address_space: Some(AliasLimitValueDoc::Text("2G".to_string())),
+                umask: Some(AliasLimitValueDoc::Text("022".to_string())),
+                ..AliasLimitsDoc::default()
+            }),
+            requires: Some(AliasRequiresDoc {
+                version_cmd: None,
+                version_regex: Some(r"v(\d+\.\d+)".to_string()),
+                min_version: "0.9".to_string(),
+            }),
+            guard: Some(AliasGuardDoc {
+                deny: vec!["^-auto-approve$".to_string()],
+                confirm: vec!["^apply$".to_string()],
+                ..AliasGuardDoc::default()
+            }),
+            hooks: Some(AliasHooksDoc {
+                pre: vec![vec!["git".to_string(), "fetch".to_string()]],
+                post: vec![vec!["notify-send".to_string(), "done".to_string()]],
+                journal: Some(true),
+            }),
+            timeout: Some("30m".to_string()),
+            timeout_signal: Some("INT".to_string()),
+            kill_after: Some("30s".to_string()),
+            retry: Some(AliasRetryDoc {
+                attempts: Some(3),
+                backoff: Some("exponential".to_string()),
+                initial_delay: Some("1s".to_string()),
+                on_exit_codes: vec![1, 75],
+            }),
+            concurrency: Some(AliasConcurrencyDoc {
+                mode: Some("wait".to_string()),
+                lock_key: Some("db-prod".to_string()),
+            }),
+            audit: Some(AliasAuditDoc {
+                enabled: Some(true),
+                redact_options: vec!["--password".to_string()],
+                redact_matching: vec!["^postgres://".to_string()],
+            }),
+            subcommands: BTreeMap::from([(
+                "delete".to_string(),
+                AliasSubcommandDoc {
+                    args: vec!["--dry-run=client".to_string()],
+                    ..AliasSubcommandDoc::default()
+                },
+            )]),
+            default_profile: Some("dev".to_string()),
+            profile: BTreeMap::from([(
+                "dev".to_string(),
+                AliasProfileDoc {
+                    args: Some(vec!["--context=dev".to_string()]),
+                    env: HashMap::from([("STAGE".to_string(), "dev".to_string())]),
+                    ..AliasProfileDoc::default()
+                },
+            )]),
         }
     }
 
@@ -315,11 +943,45 @@ mod tests {
     #[test]
     fn alias_doc_validation_rejects_blank_exec() {
         let mut doc = valid_doc();
-        doc.exec = "   ".to_string();
+        doc.exec = AliasExecDoc::Command("   ".to_string());
         let err = doc.validate().expect_err("blank exec should fail");
         assert!(err.to_string().contains("`exec` cannot be blank"));
     }
 
+    #[test]
+    fn alias_doc_round_trips_exec_candidates_and_rejects_blank_entries() {
+        let temp = TempDir::new().expect("tempdir");
+        let path = temp.path().join("alias.toml");
+        let mut doc = valid_doc();
+        doc.exec = AliasExecDoc::from_candidates(vec!["nvim".to_string(), "vim".to_string()]);
+        save_alias_doc(&path, &doc).expect("save alias doc");
+        let content = std::fs::read_to_string(&path).expect("read alias doc");
+        assert!(content.contains("exec = ["), "{content}");
+        assert_eq!(load_alias_doc(&path).expect("load alias doc"), doc);
+
+        assert_eq!(
+            AliasExecDoc::from_candidates(vec!["vi".to_string()]),
+            AliasExecDoc::Command("vi".to_string())
+        );
+        doc.exec = AliasExecDoc::Candidates(vec!["nvim".to_string(), " ".to_string()]);
+        let err = doc.validate().expect_err("blank candidate should fail");
+        assert!(err.to_string().contains("`exec` entries cannot be blank"));
+    }
+
+    #[test]
+    fn alias_doc_allows_blank_exec_when_extending_another_alias() {
+        let temp = TempDir::new().expect("tempdir");
+        let path = temp.path().join("alias.toml");
+        let mut doc = valid_doc();
+        doc.extends = Some("base".to_string());
+        doc.exec = AliasExecDoc::default();
+        save_alias_doc(&path, &doc).expect("save alias doc");
+        let content = std::fs::read_to_string(&path).expect("read alias doc");
+        assert!(!content.contains("exec"), "{content}");
+        let loaded = load_alias_doc(&path).expect("load alias doc");
+        assert_eq!(loaded, doc);
+    }
+
     #[test]
     fn alias_doc_validation_rejects_env_key_with_equals() {
         let mut doc = valid_doc();
@@ -7,19 +7,30 @@ use std::os::unix::fs::symlink;
 
 const EXEC_ALIAS_FILE: &str = "exe.toml";
 const LEGACY_ALIASES_DIR: &str = "aliases";
+/// Settings that apply to every alias, such as the global `[audit]` table.
+/// It sits in the config root, so it is never mistaken for a legacy alias.
+const SETTINGS_FILE: &str = "chopper.toml";
 
 pub(crate) fn default_exec_config_path(config_root: &Path, alias: &str) -> PathBuf {
     config_root.join(alias).join(EXEC_ALIAS_FILE)
 }
 
-fn exec_config_candidates(config_root: &Path, alias: &str) -> [PathBuf; 3] {
-    [
+pub fn settings_path(config_root: &Path) -> PathBuf {
+    config_root.join(SETTINGS_FILE)
+}
+
+fn exec_config_candidates(config_root: &Path, alias: &str) -> Vec<PathBuf> {
+    let mut candidates = vec![
         default_exec_config_path(config_root, alias),
         config_root
             .join(LEGACY_ALIASES_DIR)
             .join(format!("{alias}.toml")),
-        config_root.join(format!("{alias}.toml")),
-    ]
+    ];
+    let legacy_root_path = config_root.join(format!("{alias}.toml"));
+    if legacy_root_path != settings_path(config_root) {
+        candidates.push(legacy_root_path);
+    }
+    candidates
 }
 
 pub fn find_exec_config(config_root: &Path, alias: &str) -> Option<PathBuf> {
@@ -33,8 +44,8 @@ pub fn discover_exec_aliases(config_root: &Path) -> Result<Vec<String>> {
     let _ = auto_upgrade_exec_configs(config_root);
     let mut aliases = BTreeSet::new();
     discover_canonical_exec_aliases(config_root, &mut aliases)?;
-    discover_legacy_aliases_in_dir(&config_root.join(LEGACY_ALIASES_DIR), &mut aliases)?;
-    discover_legacy_aliases_in_dir(config_root, &mut aliases)?;
+    discover_legacy_aliases_in_dir(&config_root.join(LEGACY_ALIASES_DIR), None, &mut aliases)?;
+    discover_legacy_aliases_in_dir(config_root, Some(SETTINGS_FILE), &mut aliases)?;
     Ok(aliases.into_iter().collect())
 }
 
@@ -76,8 +87,8 @@ pub(crate) fn try_remove_empty_alias_dir(config_root: &Path, alias: &str, config
 
 fn auto_upgrade_exec_configs(config_root: &Path) -> Result<()> {
     let mut aliases = BTreeSet::new();
-    discover_legacy_aliases_in_dir(&config_root.join(LEGACY_ALIASES_DIR), &mut aliases)?;
-    discover_legacy_aliases_in_dir(config_root, &mut aliases)?;
+    discover_legacy_aliases_in_dir(&config_root.join(LEGACY_ALIASES_DIR), None, &mut aliases)?;
+    discover_legacy_aliases_in_dir(config_root, Some(SETTINGS_FILE), &mut aliases)?;
 
     for alias in aliases {
         let _ = auto_upgrade_exec_config(config_root, &alias);
@@ -226,7 +237,11 @@ fn discover_canonical_exec_aliases(
     Ok(())
 }
 
-fn discover_legacy_aliases_in_dir(dir: &Path, aliases: &mut BTreeSet<String>) -> Result<()> {
+fn discover_legacy_aliases_in_dir(
+    dir: &Path,
+    skip: Option<&str>,
+    aliases: &mut BTreeSet<String>,
+) -> Result<()> {
     let entries = match std::fs::read_dir(dir) {
         Ok(entries) => entries,
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
@@ -240,6 +255,9 @@ fn discover_legacy_aliases_in_dir(dir: &Path, aliases: &mut BTreeSet<String>) ->
         }
         let file_name = entry.file_name();
         let file_name = file_name.to_string_lossy();
+        if skip == Some(file_name.as_ref()) {
+            continue;
+        }
         let Some(alias) = file_name.strip_suffix(".toml") else {
             continue;
         };
@@ -260,7 +278,7 @@ fn paths_equal(left: &Path, right: &Path) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::find_exec_config;
+    use super::{discover_exec_aliases, find_exec_config, settings_path};
     use std::fs;
     use tempfile::TempDir;
 
@@ -306,6 +324,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn settings_file_is_not_treated_as_a_legacy_root_alias() {
+        let temp = TempDir::new().expect("tempdir");
+        fs::write(settings_path(temp.path()), "[audit]\n").expect("write settings");
+
+        assert_eq!(find_exec_config(temp.path(), "chopper"), None);
+        assert!(discover_exec_aliases(temp.path())
+            .expect("discover aliases")
+            .is_empty());
+        assert!(!temp.path().join("chopper").exists());
+    }
+
     #[cfg(unix)]
     #[test]
     fn find_exec_config_upgrades_legacy_symlink_to_canonical_symlink() {
@@ -0,0 +1,170 @@
+/// Where runtime args are spliced into alias `args`.
+///
+/// A slot is an `args` entry that is exactly `{@}` (all runtime args), `{N}`
+/// (the Nth runtime arg, 1-based), or `{N..}` (runtime args from N onward).
+/// Slots that select past the end of the runtime args expand to nothing.
+/// Doubling the braces, as in `{{1}}`, passes the slot text on literally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RuntimeArgSlot {
+    All,
+    Single(usize),
+    From(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SlotViolation {
+    ZeroPosition,
+}
+
+pub(crate) fn parse_slot(token: &str) -> Result<Option<RuntimeArgSlot>, SlotViolation> {
+    let Some(inner) = token
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+    else {
+        return Ok(None);
+    };
+    if inner == "@" {
+        return Ok(Some(RuntimeArgSlot::All));
+    }
+
+    let (digits, open_ended) = match inner.strip_suffix("..") {
+        Some(digits) => (digits, true),
+        None => (inner, false),
+    };
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Ok(None);
+    }
+    let Ok(position) = digits.parse::<usize>() else {
+        return Ok(None);
+    };
+    if position == 0 {
+        return Err(SlotViolation::ZeroPosition);
+    }
+    Ok(Some(if open_ended {
+        RuntimeArgSlot::From(position)
+    } else {
+        RuntimeArgSlot::Single(position)
+    }))
+}
+
+/// The literal argument an escaped slot stands for: `{{1}}` is `{1}`, and
+/// `{{{1}}}` is `{{1}}`. Other arguments are returned unchanged.
+pub(crate) fn unescape(arg: &str) -> &str {
+    match arg
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+    {
+        Some(inner) if !matches!(parse_slot(inner), Ok(None)) || unescape(inner) != inner => inner,
+        _ => arg,
+    }
+}
+
+pub(crate) fn has_slots(args: &[String]) -> bool {
+    args.iter()
+        .any(|arg| matches!(parse_slot(arg), Ok(Some(_))))
+}
+
+/// Which input an element of the composed args is, by 0-based index into
+/// alias `args` or the runtime args.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Placed {
+    Alias(usize),
+    Runtime(usize),
+}
+
+/// Composes alias `args` with `runtime_args`. Without any slot, runtime args
+/// follow the alias args; otherwise they appear only where slots select them.
+pub(crate) fn placement(args: &[String], runtime_len: usize) -> Vec<Placed> {
+    if !has_slots(args) {
+        return (0..args.len())
+            .map(Placed::Alias)
+            .chain((0..runtime_len).map(Placed::Runtime))
+            .collect();
+    }
+
+    let mut out = Vec::with_capacity(args.len() + runtime_len);
+    for (index, arg) in args.iter().enumerate() {
+        match parse_slot(arg) {
+            Ok(Some(RuntimeArgSlot::All)) => out.extend((0..runtime_len).map(Placed::Runtime)),
+            Ok(Some(RuntimeArgSlot::Single(position))) => {
+                if position <= runtime_len {
+                    out.push(Placed::Runtime(position - 1));
+                }
+            }
+            Ok(Some(RuntimeArgSlot::From(position))) => {
+                out.extend((position - 1..runtime_len).map(Placed::Runtime));
+            }
+            Ok(None) | Err(_) => out.push(Placed::Alias(index)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_slot, placement, unescape, Placed, RuntimeArgSlot, SlotViolation};
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn place_runtime_args(args: &[String], runtime_args: &[String]) -> Vec<String> {
+        placement(args, runtime_args.len())
+            .into_iter()
+            .map(|placed| match placed {
+                Placed::Alias(index) => args[index].clone(),
+                Placed::Runtime(index) => runtime_args[index].clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_slot_tokens_and_ignores_other_braces() {
+        assert_eq!(parse_slot("{@}"), Ok(Some(RuntimeArgSlot::All)));
+        assert_eq!(parse_slot("{1}"), Ok(Some(RuntimeArgSlot::Single(1))));
+        assert_eq!(parse_slot("{2..}"), Ok(Some(RuntimeArgSlot::From(2))));
+        assert_eq!(parse_slot("{0}"), Err(SlotViolation::ZeroPosition));
+        assert_eq!(parse_slot("{0..}"), Err(SlotViolation::ZeroPosition));
+        for literal in ["{}", "{..}", "{x}", "{1..2}", "{-1}", " {1}", "brace{1}"] {
+            assert_eq!(parse_slot(literal), Ok(None), "{literal}");
+        }
+    }
+
+    #[test]
+    fn doubled_braces_escape_slot_tokens() {
+        assert_eq!(unescape("{{1}}"), "{1}");
+        assert_eq!(unescape("{{@}}"), "{@}");
+        assert_eq!(unescape("{{2..}}"), "{2..}");
+        assert_eq!(unescape("{{0}}"), "{0}");
+        assert_eq!(unescape("{{{1}}}"), "{{1}}");
+        for unchanged in ["{1}", "{{x}}", "{{}}", "x{{1}}", "plain"] {
+            assert_eq!(unescape(unchanged), unchanged, "{unchanged}");
+        }
+        assert_eq!(parse_slot("{{1}}"), Ok(None));
+    }
+
+    #[test]
+    fn appends_runtime_args_when_no_slot_is_present() {
+        assert_eq!(
+            place_runtime_args(&strings(&["run", "--rm"]), &strings(&["a", "b"])),
+            strings(&["run", "--rm", "a", "b"])
+        );
+    }
+
+    #[test]
+    fn places_runtime_args_at_slots() {
+        let args = strings(&["run", "--rm", "{1}", "--", "{2..}", "--extra"]);
+        assert_eq!(
+            place_runtime_args(&args, &strings(&["image", "sh", "-c", "true"])),
+            strings(&["run", "--rm", "image", "--", "sh", "-c", "true", "--extra"])
+        );
+        assert_eq!(
+            place_runtime_args(&args, &[]),
+            strings(&["run", "--rm", "--", "--extra"])
+        );
+        assert_eq!(
+            place_runtime_args(&strings(&["{@}", "{@}"]), &strings(&["x"])),
+            strings(&["x", "x"])
+        );
+    }
+}
@@ -0,0 +1,891 @@
+use crate::manifest::{Invocation, Manifest, RuntimePatch};
+use anyhow::{anyhow, Context, Result};
+use nix::libc;
+use nix::unistd::{getuid, User};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{self, DirBuilder, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Written in place of redacted argument text.
+pub const REDACTED: &str = "<redacted>";
+
+const DEFAULT_MAX_SIZE: u64 = 10 << 20;
+const DEFAULT_KEEP: usize = 5;
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+/// One line of the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Launch time as RFC 3339 in UTC, with milliseconds.
+    pub timestamp: String,
+    pub alias: String,
+    pub exec: PathBuf,
+    /// `exec` followed by the args, after redaction.
+    pub argv: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    /// Real uid of the user who ran the alias.
+    pub uid: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub pid: u32,
+    /// `None` when no alias config exists and the command came from `PATH`.
+    #[serde(default)]
+    pub config: Option<PathBuf>,
+    /// Content hash of the alias config and every file it includes or
+    /// extends; see [`config_fingerprint`].
+    #[serde(default)]
+    pub config_fingerprint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reconcile: Option<PatchSummary>,
+    /// Set in supervised modes, where chopper waits for the command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Set when a supervised launch failed before the command exited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What a reconcile patch changed. Environment values are left out; only
+/// the variable names each operation touched are kept.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PatchSummary {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub replace_args: bool,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub append_args: usize,
+    /// Variable names keyed by patch field (`set_env`, `remove_env`, ...).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove_env_matching: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_cwd: Option<PathBuf>,
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+impl PatchSummary {
+    fn new(patch: &RuntimePatch) -> Self {
+        let mut env = BTreeMap::new();
+        for (field, keys) in [
+            ("set_env", patch.set_env.keys().cloned().collect::<Vec<_>>()),
+            ("default_env", patch.default_env.keys().cloned().collect()),
+            ("append_env", patch.append_env.keys().cloned().collect()),
+            ("prepend_env", patch.prepend_env.keys().cloned().collect()),
+            ("remove_env", patch.remove_env.clone()),
+        ] {
+            if !keys.is_empty() {
+                let mut keys = keys;
+                keys.sort();
+                env.insert(field.to_string(), keys);
+            }
+        }
+        Self {
+            replace_args: patch.replace_args.is_some(),
+            append_args: patch.append_args.len(),
+            env,
+            remove_env_matching: patch.remove_env_matching.clone(),
+            set_cwd: patch.set_cwd.clone(),
+        }
+    }
+}
+
+impl AuditRecord {
+    /// One line for `chopper --audit tail|query`.
+    pub fn render(&self) -> String {
+        let user = match &self.user {
+            Some(user) => format!("uid {} ({user})", self.uid),
+            None => format!("uid {}", self.uid),
+        };
+        let outcome = match (self.exit_code, &self.error) {
+            (Some(code), _) => format!(
+                "exit {code} after {:.3}s",
+                Duration::from_millis(self.duration_ms.unwrap_or(0)).as_secs_f64()
+            ),
+            (None, Some(error)) => format!("failed: {error}"),
+            (None, None) => "exec".to_string(),
+        };
+        format!(
+            "{}  {}  {user}  {outcome}  {}",
+            self.timestamp,
+            self.alias,
+            shell_words::join(&self.argv)
+        )
+    }
+
+    /// Launch time in Unix milliseconds.
+    fn unix_millis(&self) -> Option<u64> {
+        parse_timestamp(&self.timestamp)
+    }
+}
+
+/// The `[audit]` table of the global settings file (`chopper.toml` in the
+/// config root). Aliases may override `enabled` and add redaction rules;
+/// where the log lives and how it rotates is only set here.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditSettings {
+    pub enabled: bool,
+    pub path: PathBuf,
+    /// The log is rotated before a record would grow it past this size.
+    pub max_size: u64,
+    /// Rotated files kept, as `<path>.1` (newest) to `<path>.<keep>`.
+    pub keep: usize,
+    pub redact_options: Vec<String>,
+    pub redact_matching: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SettingsInput {
+    audit: Option<AuditSettingsInput>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AuditSettingsInput {
+    enabled: Option<bool>,
+    path: Option<String>,
+    max_size: Option<String>,
+    keep: Option<i64>,
+    #[serde(default)]
+    redact_options: Vec<String>,
+    #[serde(default)]
+    redact_matching: Vec<String>,
+}
+
+impl AuditSettings {
+    /// Reads the global settings file, or returns the defaults (auditing
+    /// off) when there is none.
+    pub fn load() -> Result<Self> {
+        let path = crate::settings_path();
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                Self::parse(&content).with_context(|| format!("failed to parse {}", path.display()))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Self::parse(""),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    /// Auditing off, with the default log location and rotation.
+    fn defaults() -> Self {
+        Self {
+            enabled: false,
+            path: default_log_path(),
+            max_size: DEFAULT_MAX_SIZE,
+            keep: DEFAULT_KEEP,
+            redact_options: Vec::new(),
+            redact_matching: Vec::new(),
+        }
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let input: SettingsInput = toml::from_str(content)?;
+        let Some(audit) = input.audit else {
+            return Ok(Self::defaults());
+        };
+        validate_redaction_rules("audit", &audit.redact_options, &audit.redact_matching)?;
+        let path = match audit.path.as_deref().map(str::trim) {
+            None => default_log_path(),
+            Some(path) if path.is_empty() || path.contains('\0') => {
+                return Err(anyhow!("field `audit.path` must be a non-empty path"));
+            }
+            Some(path) if !Path::new(path).is_absolute() => {
+                return Err(anyhow!("field `audit.path` must be absolute: `{path}`"));
+            }
+            Some(path) => PathBuf::from(path),
+        };
+        let max_size = match audit.max_size.as_deref() {
+            None => DEFAULT_MAX_SIZE,
+            Some(value) => crate::process_limits::parse_byte_size(value)
+                .filter(|size| *size > 0)
+                .ok_or_else(|| {
+                    anyhow!("field `audit.max_size` must be a positive size like `10M`: `{value}`")
+                })?,
+        };
+        let keep = match audit.keep {
+            None => DEFAULT_KEEP,
+            Some(keep) => usize::try_from(keep)
+                .ok()
+                .filter(|keep| *keep >= 1)
+                .ok_or_else(|| anyhow!("field `audit.keep` must be at least 1: `{keep}`"))?,
+        };
+        Ok(Self {
+            enabled: audit.enabled.unwrap_or(true),
+            path,
+            max_size,
+            keep,
+            redact_options: audit.redact_options,
+            redact_matching: audit.redact_matching,
+        })
+    }
+
+    /// The log followed by its rotated files, oldest first.
+    fn files_oldest_first(&self) -> Vec<PathBuf> {
+        (1..=self.keep)
+            .rev()
+            .map(|n| self.rotated_path(n))
+            .chain(std::iter::once(self.path.clone()))
+            .collect()
+    }
+
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+
+    /// Taken exclusively around rotation and appends, and shared by readers,
+    /// so a reader never sees a file mid-rotation.
+    fn lock_path(&self) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(".lock");
+        PathBuf::from(name)
+    }
+
+    fn append(&self, line: &[u8]) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let lock_path = self.lock_path();
+        let lock = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .mode(0o600)
+            .open(&lock_path)
+            .with_context(|| format!("failed to open {}", lock_path.display()))?;
+        crate::concurrency::try_flock(&lock, libc::LOCK_EX)?;
+
+        let len = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata.len(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => 0,
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to stat {}", self.path.display()))
+            }
+        };
+        if len > 0 && len.saturating_add(line.len() as u64) > self.max_size {
+            self.rotate()?;
+        }
+        // One write on an O_APPEND descriptor, so a record is never split.
+        OpenOptions::new()
+            .append(true)
+            .create(true)
+            .mode(0o600)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line))
+            .with_context(|| format!("failed to append to {}", self.path.display()))
+    }
+
+    fn rotate(&self) -> Result<()> {
+        for n in (1..self.keep).rev() {
+            match fs::rename(self.rotated_path(n), self.rotated_path(n + 1)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                    return Err(err)
+                        .with_context(|| format!("failed to rotate {}", self.path.display()));
+                }
+                _ => {}
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))
+            .with_context(|| format!("failed to rotate {}", self.path.display()))
+    }
+}
+
+/// `$XDG_STATE_HOME/chopper/audit.jsonl`, or the data dir where there is no
+/// state dir.
+fn default_log_path() -> PathBuf {
+    directories::ProjectDirs::from("", "", "chopper")
+        .map(|dirs| {
+            dirs.state_dir()
+                .unwrap_or_else(|| dirs.data_local_dir())
+                .to_path_buf()
+        })
+        .unwrap_or_else(|| PathBuf::from(".chopper"))
+        .join("audit.jsonl")
+}
+
+/// Checks `redact_options` and `redact_matching` of the `[audit]` table
+/// named `table`.
+pub(crate) fn validate_redaction_rules(
+    table: &str,
+    options: &[String],
+    matching: &[String],
+) -> Result<()> {
+    for option in options {
+        if !option.starts_with('-') || option.len() < 2 || option.contains(['=', '\0']) {
+            return Err(anyhow!(
+                "field `{table}.redact_options` entries must be option names like `--password`: `{option}`"
+            ));
+        }
+        if option.chars().any(char::is_whitespace) {
+            return Err(anyhow!(
+                "field `{table}.redact_options` entries cannot contain whitespace: `{option}`"
+            ));
+        }
+    }
+    for pattern in matching {
+        if pattern.trim().is_empty() {
+            return Err(anyhow!(
+                "field `{table}.redact_matching` entries cannot be blank"
+            ));
+        }
+        Regex::new(pattern).map_err(|err| {
+            anyhow!("field `{table}.redact_matching` entry `{pattern}` is not a valid regex: {err}")
+        })?;
+    }
+    Ok(())
+}
+
+/// A launch record waiting for the command's outcome.
+pub(crate) struct PendingAudit {
+    settings: AuditSettings,
+    record: AuditRecord,
+}
+
+/// Starts the audit record for one launch, or returns `None` when auditing
+/// is off for the alias. `manifest` is the one secrets were resolved from:
+/// secret values that show up in an argument are replaced by their
+/// placeholders.
+///
+/// Like writing a record, this never stops the command: an unreadable
+/// `chopper.toml` is reported and treated as having no `[audit]` table, and
+/// a record that cannot be started is reported and skipped.
+pub(crate) fn prepare(
+    alias: &str,
+    config: Option<&Path>,
+    manifest: &Manifest,
+    invocation: &Invocation,
+    patch: Option<&RuntimePatch>,
+) -> Option<PendingAudit> {
+    let settings = AuditSettings::load().unwrap_or_else(|err| {
+        eprintln!("warning: ignoring global settings: {err:#}");
+        AuditSettings::defaults()
+    });
+    // An alias can turn auditing on, but not off when it is on globally.
+    let enabled = settings.enabled || manifest.audit.as_ref().is_some_and(|audit| audit.enabled);
+    if !enabled {
+        return None;
+    }
+    match start_record(settings, alias, config, manifest, invocation, patch) {
+        Ok(pending) => Some(pending),
+        Err(err) => {
+            eprintln!("warning: failed to start audit record: {err:#}");
+            None
+        }
+    }
+}
+
+fn start_record(
+    settings: AuditSettings,
+    alias: &str,
+    config: Option<&Path>,
+    manifest: &Manifest,
+    invocation: &Invocation,
+    patch: Option<&RuntimePatch>,
+) -> Result<PendingAudit> {
+    let local = manifest.audit.clone().unwrap_or_default();
+    let options = [settings.redact_options.as_slice(), &local.redact_options].concat();
+    let matching = settings
+        .redact_matching
+        .iter()
+        .chain(&local.redact_matching)
+        .map(|pattern| Regex::new(pattern))
+        .collect::<Result<Vec<_>, _>>()
+        .context("invalid audit redaction pattern")?;
+    let secrets = manifest
+        .env_secret
+        .iter()
+        .filter_map(|(key, source)| {
+            let value = invocation.env.get(key)?;
+            (!value.is_empty()).then(|| (value.clone(), source.redacted()))
+        })
+        .collect::<Vec<_>>();
+
+    let uid = getuid();
+    let record = AuditRecord {
+        timestamp: format_timestamp(SystemTime::now()),
+        alias: alias.to_string(),
+        exec: invocation.exec.clone(),
+        argv: std::iter::once(invocation.exec.to_string_lossy().into_owned())
+            .chain(redact_args(&invocation.args, &options, &matching, &secrets))
+            .collect(),
+        cwd: invocation.cwd.clone().or_else(|| env::current_dir().ok()),
+        uid: uid.as_raw(),
+        user: User::from_uid(uid).ok().flatten().map(|user| user.name),
+        pid: std::process::id(),
+        config: config.map(Path::to_path_buf),
+        config_fingerprint: config.map(config_fingerprint).transpose()?,
+        reconcile: patch.map(PatchSummary::new),
+        exit_code: None,
+        duration_ms: None,
+        error: None,
+    };
+    Ok(PendingAudit { settings, record })
+}
+
+impl PendingAudit {
+    /// Records a launch chopper hands off to with `exec`.
+    pub(crate) fn launched(self) {
+        self.write();
+    }
+
+    pub(crate) fn finished(mut self, exit_code: i32, duration: Duration) {
+        self.record.exit_code = Some(exit_code);
+        self.record.duration_ms = Some(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX));
+        self.write();
+    }
+
+    pub(crate) fn failed(mut self, err: &anyhow::Error) {
+        self.record.error = Some(format!("{err:#}"));
+        self.write();
+    }
+
+    /// Failing to audit does not stop the command; it is reported instead.
+    fn write(self) {
+        let result = serde_json::to_vec(&self.record)
+            .map_err(anyhow::Error::from)
+            .and_then(|mut line| {
+                line.push(b'\n');
+                self.settings.append(&line)
+            });
+        if let Err(err) = result {
+            eprintln!("warning: failed to write audit record: {err:#}");
+        }
+    }
+}
+
+fn redact_args(
+    args: &[String],
+    options: &[String],
+    matching: &[Regex],
+    secrets: &[(String, String)],
+) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut value_follows = false;
+    for arg in args {
+        if std::mem::take(&mut value_follows) || matching.iter().any(|re| re.is_match(arg)) {
+            redacted.push(REDACTED.to_string());
+            continue;
+        }
+        if options.iter().any(|option| option == arg) {
+            value_follows = true;
+            redacted.push(arg.clone());
+            continue;
+        }
+        if let Some(option) = options.iter().find(|option| {
+            arg.strip_prefix(option.as_str())
+                .is_some_and(|rest| rest.starts_with('='))
+        }) {
+            redacted.push(format!("{option}={REDACTED}"));
+            continue;
+        }
+        let mut arg = arg.clone();
+        for (value, placeholder) in secrets {
+            arg = arg.replace(value, placeholder);
+        }
+        redacted.push(arg);
+    }
+    redacted
+}
+
+/// A content hash (64-bit FNV-1a) over the alias config and the files it
+/// includes or extends, with their paths. It tells which version of the
+/// config was in effect; it is not meant to resist tampering.
+pub fn config_fingerprint(path: &Path) -> Result<String> {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    for source in crate::parser::config_source_files(path) {
+        let content =
+            fs::read(&source).with_context(|| format!("failed to read {}", source.display()))?;
+        feed(source.as_os_str().as_bytes());
+        feed(&[0]);
+        feed(&content);
+        feed(&[0]);
+    }
+    Ok(format!("fnv1a64:{hash:016x}"))
+}
+
+/// Filters for [`read`]; bounds are Unix milliseconds, `until` exclusive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditQuery {
+    pub alias: Option<String>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
+impl AuditQuery {
+    fn matches(&self, record: &AuditRecord) -> bool {
+        if self
+            .alias
+            .as_deref()
+            .is_some_and(|alias| alias != record.alias)
+        {
+            return false;
+        }
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        record.unix_millis().is_some_and(|millis| {
+            self.since.is_none_or(|since| millis >= since)
+                && self.until.is_none_or(|until| millis < until)
+        })
+    }
+}
+
+/// Reads the records matching `query` from the log and its rotated files,
+/// oldest first. Lines that do not parse are skipped with a warning.
+pub fn read(settings: &AuditSettings, query: &AuditQuery) -> Result<Vec<AuditRecord>> {
+    let lock_path = settings.lock_path();
+    let _lock = match File::open(&lock_path) {
+        Ok(lock) => {
+            crate::concurrency::try_flock(&lock, libc::LOCK_SH)?;
+            Some(lock)
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to open {}", lock_path.display()))
+        }
+    };
+
+    let mut records = Vec::new();
+    for path in settings.files_oldest_first() {
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to open {}", path.display()))
+            }
+        };
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.with_context(|| format!("failed to read {}", path.display()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<AuditRecord>(&line) {
+                Ok(record) if query.matches(&record) => records.push(record),
+                Ok(_) => {}
+                Err(err) => eprintln!(
+                    "warning: skipping unreadable audit record at {}:{}: {err}",
+                    path.display(),
+                    index + 1
+                ),
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// Parses a `--since`/`--until` bound into Unix milliseconds: a UTC date or
+/// time (`2026-10-01`, `2026-10-01T12:30:00Z`) or a duration before `now`
+/// (`30m`, `7d`).
+pub fn parse_time_bound(value: &str, now: SystemTime) -> Option<u64> {
+    if let Some(millis) = parse_timestamp(value) {
+        return Some(millis);
+    }
+    let ago = crate::durations::parse_duration(value)?;
+    let now = unix_millis(now);
+    Some(now.saturating_sub(u64::try_from(ago.as_millis()).unwrap_or(u64::MAX)))
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX))
+        .unwrap_or(0)
+}
+
+fn format_timestamp(time: SystemTime) -> String {
+    let millis = unix_millis(time);
+    let days = i64::try_from(millis / MILLIS_PER_DAY as u64).unwrap_or(i64::MAX);
+    let (year, month, day) = civil_from_days(days);
+    let of_day = millis % MILLIS_PER_DAY as u64;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        of_day / 3_600_000,
+        of_day / 60_000 % 60,
+        of_day / 1000 % 60,
+        of_day % 1000
+    )
+}
+
+/// Parses `YYYY-MM-DD`, optionally followed by `THH:MM[:SS[.fff]]` and `Z`,
+/// as UTC.
+fn parse_timestamp(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let value = value.strip_suffix(['Z', 'z']).unwrap_or(value);
+    let (date, time) = match value.split_once(['T', 't', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+
+    let mut date_parts = date.split('-');
+    let (year, month, day) = (date_parts.next()?, date_parts.next()?, date_parts.next()?);
+    if date_parts.next().is_some() || year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let (year, month, day) = (
+        parse_digits(year)?,
+        u32::try_from(parse_digits(month)?).ok()?,
+        u32::try_from(parse_digits(day)?).ok()?,
+    );
+    let days = days_from_civil(year, month, day);
+    if civil_from_days(days) != (year, month, day) {
+        return None;
+    }
+
+    let mut millis = days.checked_mul(MILLIS_PER_DAY)?;
+    if let Some(time) = time {
+        let (clock, fraction) = match time.split_once('.') {
+            Some((clock, fraction)) => (clock, Some(fraction)),
+            None => (time, None),
+        };
+        let mut clock_parts = clock.split(':');
+        let hours = parse_digits(clock_parts.next()?)?;
+        let minutes = parse_digits(clock_parts.next()?)?;
+        let seconds = clock_parts.next().map(parse_digits).unwrap_or(Some(0))?;
+        if clock_parts.next().is_some() || hours > 23 || minutes > 59 || seconds > 59 {
+            return None;
+        }
+        let fraction_millis = match fraction {
+            None => 0,
+            Some(fraction) => {
+                let digits = format!("{fraction:0<3}");
+                parse_digits(digits.get(..3)?)?
+            }
+        };
+        millis += ((hours * 60 + minutes) * 60 + seconds) * 1000 + fraction_millis;
+    }
+    u64::try_from(millis).ok()
+}
+
+fn parse_digits(value: &str) -> Option<i64> {
+    if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+/// Days since 1970-01-01 to a proleptic Gregorian date (Howard Hinnant's
+/// `civil_from_days`).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month as u32, day as u32)
+}
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = i64::from((month + 9) % 12);
+    let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        format_timestamp, parse_time_bound, parse_timestamp, read, redact_args, AuditQuery,
+        AuditRecord, AuditSettings, REDACTED,
+    };
+    use regex::Regex;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{Duration, UNIX_EPOCH};
+    use tempfile::TempDir;
+
+    fn record(alias: &str, timestamp: &str) -> AuditRecord {
+        AuditRecord {
+            timestamp: timestamp.to_string(),
+            alias: alias.to_string(),
+            exec: PathBuf::from("/usr/bin/true"),
+            argv: vec!["/usr/bin/true".to_string()],
+            cwd: None,
+            uid: 1000,
+            user: None,
+            pid: 1,
+            config: None,
+            config_fingerprint: None,
+            reconcile: None,
+            exit_code: None,
+            duration_ms: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn redacts_option_values_matching_args_and_secret_values() {
+        let args = [
+            "--password",
+            "hunter2",
+            "--token=abc",
+            "--tokenizer=fast",
+            "postgres://app:pw@db/prod",
+            "--header=Bearer s3cret",
+            "plain",
+        ]
+        .map(String::from);
+        let redacted = redact_args(
+            &args,
+            &["--password".to_string(), "--token".to_string()],
+            &[Regex::new("^postgres://").expect("regex")],
+            &[(
+                "s3cret".to_string(),
+                "<redacted: keyring user:api>".to_string(),
+            )],
+        );
+        assert_eq!(
+            redacted,
+            vec![
+                "--password".to_string(),
+                REDACTED.to_string(),
+                format!("--token={REDACTED}"),
+                "--tokenizer=fast".to_string(),
+                REDACTED.to_string(),
+                "--header=Bearer <redacted: keyring user:api>".to_string(),
+                "plain".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn timestamps_round_trip_and_bounds_accept_dates_and_durations() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_792_200_843_123);
+        let formatted = format_timestamp(time);
+        assert_eq!(formatted, "2026-10-17T01:34:03.123Z");
+        assert_eq!(parse_timestamp(&formatted), Some(1_792_200_843_123));
+        assert_eq!(parse_timestamp("1970-01-01"), Some(0));
+        assert_eq!(parse_timestamp("2024-02-29T00:00"), Some(1_709_164_800_000));
+        for invalid in [
+            "2023-02-29",
+            "2026-10-17T24:00",
+            "26-10-17",
+            "2026-1-7",
+            "soon",
+        ] {
+            assert_eq!(parse_timestamp(invalid), None, "{invalid}");
+        }
+        assert_eq!(
+            parse_time_bound("1h", time),
+            Some(1_792_200_843_123 - 3_600_000)
+        );
+        assert_eq!(
+            parse_time_bound("2026-10-17", time),
+            Some(1_792_195_200_000)
+        );
+        assert_eq!(parse_time_bound("yesterday", time), None);
+    }
+
+    #[test]
+    fn parses_settings_and_rejects_invalid_values() {
+        let settings = AuditSettings::parse(
+            "[audit]\npath = \"/var/log/chopper/audit.jsonl\"\nmax_size = \"1M\"\nkeep = 2\nredact_options = [\"--password\"]\n",
+        )
+        .expect("parse settings");
+        assert!(settings.enabled);
+        assert_eq!(settings.path, PathBuf::from("/var/log/chopper/audit.jsonl"));
+        assert_eq!(settings.max_size, 1 << 20);
+        assert_eq!(settings.keep, 2);
+        assert!(!AuditSettings::parse("").expect("defaults").enabled);
+
+        for (body, expected) in [
+            (
+                "path = \"audit.jsonl\"",
+                "field `audit.path` must be absolute",
+            ),
+            (
+                "max_size = \"0\"",
+                "field `audit.max_size` must be a positive size",
+            ),
+            ("keep = 0", "field `audit.keep` must be at least 1"),
+            ("redact_options = [\"password\"]", "must be option names"),
+            ("redact_matching = [\"(\"]", "is not a valid regex"),
+            ("rotate = true", "unknown field `rotate`"),
+        ] {
+            let err = AuditSettings::parse(&format!("[audit]\n{body}\n"))
+                .expect_err("invalid settings should fail");
+            assert!(format!("{err:#}").contains(expected), "{err:#}");
+        }
+    }
+
+    #[test]
+    fn appends_rotate_by_size_and_reads_back_oldest_first() {
+        let temp = TempDir::new().expect("create tempdir");
+        let mut settings = AuditSettings::parse("[audit]\n").expect("parse settings");
+        settings.path = temp.path().join("logs/audit.jsonl");
+        settings.keep = 2;
+        let lines = ["2026-10-01", "2026-10-02", "2026-10-03", "2026-10-04"].map(|day| {
+            let alias = if day.ends_with('3') {
+                "deploy"
+            } else {
+                "migrate"
+            };
+            let mut line = serde_json::to_vec(&record(alias, &format!("{day}T00:00:00.000Z")))
+                .expect("encode");
+            line.push(b'\n');
+            line
+        });
+        settings.max_size = lines[0].len() as u64 + 1;
+        for line in &lines {
+            settings.append(line).expect("append");
+        }
+
+        // Each record rotated the previous one away; the oldest fell off.
+        assert!(!settings.rotated_path(3).exists());
+        assert_eq!(
+            fs::read(&settings.path).expect("read log"),
+            lines[3].as_slice()
+        );
+        let days = |records: Vec<AuditRecord>| {
+            records
+                .into_iter()
+                .map(|record| record.timestamp[..10].to_string())
+                .collect::<Vec<_>>()
+        };
+        let all = read(&settings, &AuditQuery::default()).expect("read all");
+        assert_eq!(days(all), vec!["2026-10-02", "2026-10-03", "2026-10-04"]);
+
+        let query = AuditQuery {
+            alias: Some("migrate".to_string()),
+            since: parse_timestamp("2026-10-02T12:00"),
+            until: None,
+        };
+        assert_eq!(
+            days(read(&settings, &query).expect("query")),
+            vec!["2026-10-04"]
+        );
+    }
+}
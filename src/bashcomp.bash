@@ -426,10 +426,32 @@ _chopper_complete_alias_admin_mutation() {
             COMPREPLY=($(compgen -f -- "$cur"))
             return 0
             ;;
+        --cwd)
+            COMPREPLY=($(compgen -d -- "$cur"))
+            return 0
+            ;;
         --env-remove)
             COMPREPLY=($(compgen -v -- "$cur"))
             return 0
             ;;
+        --env-remove-matching)
+            COMPREPLY=()
+            return 0
+            ;;
+        --pathlist-clear)
+            COMPREPLY=($(compgen -v -- "$cur"))
+            return 0
+            ;;
+        --pathlist)
+            COMPREPLY=()
+            if [[ "$cur" != *.* ]]; then
+                local key
+                while IFS= read -r key; do
+                    COMPREPLY+=("${key}.")
+                done < <(compgen -v -- "$cur")
+            fi
+            return 0
+            ;;
         --env)
             COMPREPLY=()
             if [[ "$cur" != *=* ]]; then
@@ -462,7 +484,7 @@ _chopper_complete_alias_admin_mutation() {
             ;;
     esac
 
-    local opts="--exec --arg --env --env-remove --path-remove-all --path-remove-one --path-append-all --path-append-one --path-prepend-all --path-prepend-one --journal-namespace --journal-stderr --journal-identifier --journal-user-scope --journal-ensure --journal-max-use --journal-rate-limit-interval-usec --journal-rate-limit-burst --journal-clear --no-wrapper-sync"
+    local opts="--exec --arg --env --env-remove --env-remove-matching --cwd --path-remove-all --path-remove-one --path-append-all --path-append-one --path-prepend-all --path-prepend-one --pathlist --pathlist-clear --journal-namespace --journal-stderr --journal-identifier --journal-user-scope --journal-ensure --journal-max-use --journal-rate-limit-interval-usec --journal-rate-limit-burst --journal-clear --no-wrapper-sync"
     COMPREPLY=($(compgen -W "$opts" -- "$cur"))
 }
 
@@ -534,7 +556,15 @@ _chopper_complete_alias_admin() {
     fi
 
     case "$sub" in
-        get|help|--help)
+        get)
+            if (( COMP_CWORD == 4 )) && [[ "${COMP_WORDS[3]}" != "--help" ]]; then
+                COMPREPLY=($(compgen -W "--profile" -- "$cur"))
+            else
+                COMPREPLY=()
+            fi
+            return 0
+            ;;
+        help|--help)
             COMPREPLY=()
             return 0
             ;;
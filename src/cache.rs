@@ -2,7 +2,7 @@ use crate::arg_validation::{self, ArgViolation};
 use crate::env_util;
 use crate::env_validation::{self, EnvKeyViolation, EnvValueViolation};
 use crate::journal_validation::{self, JournalIdentifierViolation, JournalNamespaceViolation};
-use crate::manifest::Manifest;
+use crate::manifest::{Manifest, SecretSource, VersionRequirement};
 use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -11,7 +11,8 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::time::UNIX_EPOCH;
 
-const CACHE_ENTRY_VERSION: u32 = 5;
+const CACHE_ENTRY_VERSION: u32 = 25;
+const VERSION_PROBE_ENTRY_VERSION: u32 = 1;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SourceFingerprint {
@@ -21,6 +22,10 @@ pub struct SourceFingerprint {
     pub source_changed_ns: u128,
     pub source_device: u64,
     pub source_inode: u64,
+    /// Fingerprints of the other files the manifest is assembled from
+    /// (`include` fragments and `extends` parents), so editing any of them
+    /// invalidates every alias that depends on it.
+    pub dependency_sources: Vec<SourceFingerprint>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -30,7 +35,51 @@ struct CacheEntry {
     manifest: Manifest,
 }
 
+/// Identifies one `[requires]` version probe: the binary as it sits on disk
+/// plus the probe settings, so replacing the binary or editing the probe
+/// reruns it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VersionProbeKey {
+    pub exec: PathBuf,
+    pub device: u64,
+    pub inode: u64,
+    pub modified_ns: u128,
+    pub version_cmd: Vec<String>,
+    pub version_regex: String,
+}
+
+impl VersionProbeKey {
+    pub fn for_binary(exec: &Path, requirement: &VersionRequirement) -> Option<Self> {
+        let fingerprint = file_fingerprint(exec).ok()?;
+        Some(Self {
+            exec: exec.to_path_buf(),
+            device: fingerprint.source_device,
+            inode: fingerprint.source_inode,
+            modified_ns: fingerprint.source_modified_ns,
+            version_cmd: requirement.version_cmd.clone(),
+            version_regex: requirement.version_regex.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VersionProbeEntry {
+    version: u32,
+    key: VersionProbeKey,
+    reported: String,
+}
+
 pub fn source_fingerprint(path: &Path) -> Result<SourceFingerprint> {
+    let mut fingerprint = file_fingerprint(path)?;
+    for dependency in crate::parser::config_source_files(path).into_iter().skip(1) {
+        fingerprint
+            .dependency_sources
+            .push(file_fingerprint(&dependency)?);
+    }
+    Ok(fingerprint)
+}
+
+fn file_fingerprint(path: &Path) -> Result<SourceFingerprint> {
     let metadata = fs::metadata(path)
         .with_context(|| format!("failed to stat alias config {}", path.display()))?;
     let modified = metadata
@@ -50,6 +99,7 @@ pub fn source_fingerprint(path: &Path) -> Result<SourceFingerprint> {
         source_changed_ns,
         source_device,
         source_inode,
+        dependency_sources: Vec::new(),
     })
 }
 
@@ -63,6 +113,43 @@ pub fn prune_alias(alias: &str) {
     delete_cache_file_best_effort(&primary_path);
 }
 
+/// Returns the version a previous probe of the same binary reported.
+pub fn load_version_probe(key: &VersionProbeKey) -> Option<String> {
+    let path = version_probe_path(key);
+    let bytes = fs::read(&path).ok()?;
+    match bincode::deserialize::<VersionProbeEntry>(&bytes) {
+        Ok(entry) if entry.version == VERSION_PROBE_ENTRY_VERSION && entry.key == *key => {
+            Some(entry.reported)
+        }
+        _ => {
+            delete_cache_file_best_effort(&path);
+            None
+        }
+    }
+}
+
+pub fn store_version_probe(key: &VersionProbeKey, reported: &str) -> Result<()> {
+    let path = version_probe_path(key);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create cache directory {}", parent.display()))?;
+    }
+    let entry = VersionProbeEntry {
+        version: VERSION_PROBE_ENTRY_VERSION,
+        key: key.clone(),
+        reported: reported.to_string(),
+    };
+    let bytes = bincode::serialize(&entry).context("failed to serialize version probe entry")?;
+    write_atomically(&path, &bytes)
+}
+
+fn version_probe_path(key: &VersionProbeKey) -> PathBuf {
+    let bytes = bincode::serialize(key).unwrap_or_default();
+    cache_dir()
+        .join("versions")
+        .join(format!("{:016x}.bin", fnv1a_hash(&bytes)))
+}
+
 fn load_from_path(path: &Path, fingerprint: &SourceFingerprint) -> Option<Manifest> {
     let bytes = fs::read(path).ok()?;
     let entry: CacheEntry = match bincode::deserialize(&bytes) {
@@ -91,8 +178,11 @@ fn load_from_path(path: &Path, fingerprint: &SourceFingerprint) -> Option<Manife
 
 fn validate_cached_manifest(manifest: &Manifest) -> Result<()> {
     validate_cached_command_path(&manifest.exec, "cached manifest exec path")?;
+    for fallback in &manifest.exec_fallbacks {
+        validate_cached_command_path(fallback, "cached manifest exec fallback path")?;
+    }
 
-    for arg in &manifest.args {
+    for arg in manifest.args.iter().chain(&manifest.args_after) {
         if matches!(
             arg_validation::validate_arg_value(arg),
             Err(ArgViolation::ContainsNul)
@@ -165,11 +255,85 @@ fn validate_cached_manifest(manifest: &Manifest) -> Result<()> {
         }
     }
 
+    for env_file in &manifest.env_file {
+        if env_file.as_os_str().is_empty() || path_contains_nul(env_file) {
+            return Err(anyhow!(
+                "cached manifest env_file paths cannot be empty or contain NUL bytes"
+            ));
+        }
+    }
+
+    for (key, source) in &manifest.env_secret {
+        if key.trim().is_empty() || key.trim() != key {
+            return Err(anyhow!(
+                "cached manifest env_secret keys cannot be empty or include surrounding whitespace"
+            ));
+        }
+        if env_validation::validate_env_key(key).is_err() {
+            return Err(anyhow!(
+                "cached manifest env_secret keys cannot contain `=` or NUL bytes"
+            ));
+        }
+        let valid = match source {
+            SecretSource::File(path) => !path.as_os_str().is_empty() && !path_contains_nul(path),
+            SecretSource::Command(argv) => {
+                !argv.is_empty() && argv.iter().all(|arg| !arg.contains('\0'))
+            }
+            SecretSource::Keyring(spec) => crate::secrets::parse_keyring_spec(spec).is_some(),
+        };
+        if !valid {
+            return Err(anyhow!(
+                "cached manifest env_secret `{key}` has an invalid source"
+            ));
+        }
+    }
+
+    let env_operations = manifest
+        .env_default
+        .iter()
+        .map(|(key, value)| ("env_default", key, value, ""))
+        .chain(
+            manifest
+                .env_append
+                .iter()
+                .map(|(key, join)| ("env_append", key, &join.value, join.separator.as_str())),
+        )
+        .chain(
+            manifest
+                .env_prepend
+                .iter()
+                .map(|(key, join)| ("env_prepend", key, &join.value, join.separator.as_str())),
+        );
+    for (field, key, value, separator) in env_operations {
+        if key.trim().is_empty()
+            || key.trim() != key
+            || env_validation::validate_env_key(key).is_err()
+        {
+            return Err(anyhow!(
+                "cached manifest {field} keys must be valid environment keys"
+            ));
+        }
+        if value.contains('\0') || separator.contains('\0') {
+            return Err(anyhow!(
+                "cached manifest {field} values cannot contain NUL bytes"
+            ));
+        }
+    }
+
     if let Some(path) = &manifest.path {
         path.validate("cached manifest path")
             .map_err(|err| anyhow!("{err}"))?;
     }
 
+    for (key, list) in &manifest.pathlists {
+        if key.trim() != key || key.is_empty() || env_validation::validate_env_key(key).is_err() {
+            return Err(anyhow!(
+                "cached manifest pathlists keys must be valid environment keys"
+            ));
+        }
+        list.validate("cached manifest pathlists")?;
+    }
+
     if let Some(journal) = &manifest.journal {
         match journal_validation::normalize_namespace(&journal.namespace) {
             Ok(normalized) => {
@@ -268,6 +432,252 @@ fn validate_cached_manifest(manifest: &Manifest) -> Result<()> {
         }
     }
 
+    if let Some(limits) = &manifest.limits {
+        if let Some(nice) = limits.nice {
+            if !crate::process_limits::NICE_RANGE.contains(&i64::from(nice)) {
+                return Err(anyhow!("cached manifest limits nice is out of range"));
+            }
+        }
+        if let Some(ioprio) = limits.ioprio {
+            if ioprio.level > crate::process_limits::MAX_IOPRIO_LEVEL {
+                return Err(anyhow!(
+                    "cached manifest limits ioprio level is out of range"
+                ));
+            }
+        }
+        if let Some(umask) = limits.umask {
+            if umask > crate::process_limits::MAX_UMASK {
+                return Err(anyhow!("cached manifest limits umask is out of range"));
+            }
+        }
+    }
+
+    if let Some(requires) = &manifest.requires {
+        if requires
+            .version_cmd
+            .iter()
+            .any(|arg| arg_validation::validate_arg_value(arg).is_err())
+        {
+            return Err(anyhow!(
+                "cached manifest requires version_cmd cannot contain NUL bytes"
+            ));
+        }
+        if regex::Regex::new(&requires.version_regex).is_err() {
+            return Err(anyhow!(
+                "cached manifest requires version_regex is not a valid pattern"
+            ));
+        }
+        if crate::version_requirement::parse_version(&requires.min_version).is_none() {
+            return Err(anyhow!(
+                "cached manifest requires min_version is not a dotted version"
+            ));
+        }
+    }
+
+    for entry in &manifest.env_keep {
+        let valid = crate::manifest::is_exact_env_keep(entry)
+            || (!entry.trim().is_empty() && regex::Regex::new(entry).is_ok());
+        if !valid {
+            return Err(anyhow!(
+                "cached manifest env_keep entries must be names or valid patterns"
+            ));
+        }
+    }
+
+    for pattern in &manifest.env_remove_matching {
+        if pattern.trim().is_empty() || regex::Regex::new(pattern).is_err() {
+            return Err(anyhow!(
+                "cached manifest env_remove_matching entries must be valid patterns"
+            ));
+        }
+    }
+
+    if let Some(guard) = &manifest.guard {
+        let rules = guard
+            .deny
+            .iter()
+            .chain(&guard.require)
+            .chain(&guard.confirm);
+        for rule in rules {
+            if rule.trim().is_empty() || regex::Regex::new(rule).is_err() {
+                return Err(anyhow!(
+                    "cached manifest guard rules must be non-blank valid patterns"
+                ));
+            }
+        }
+    }
+
+    if let Some(hooks) = &manifest.hooks {
+        for command in hooks.pre.iter().chain(&hooks.post) {
+            if command
+                .first()
+                .is_none_or(|program| program.trim().is_empty())
+                || command.iter().any(|arg| arg.contains('\0'))
+            {
+                return Err(anyhow!(
+                    "cached manifest hook commands must be non-empty and free of NUL bytes"
+                ));
+            }
+        }
+    }
+
+    if let Some(timeout) = &manifest.timeout {
+        if timeout.after_ms == 0
+            || timeout.kill_after_ms == Some(0)
+            || crate::signal_relay::parse_signal(&timeout.signal).is_none()
+        {
+            return Err(anyhow!(
+                "cached manifest timeout must have non-zero durations and a valid signal"
+            ));
+        }
+    }
+
+    if let Some(retry) = &manifest.retry {
+        if retry.attempts < 2
+            || retry
+                .on_exit_codes
+                .iter()
+                .any(|code| !(1..=255).contains(code))
+        {
+            return Err(anyhow!(
+                "cached manifest retry must allow at least two attempts and valid exit codes"
+            ));
+        }
+    }
+
+    if let Some(key) = manifest
+        .concurrency
+        .as_ref()
+        .and_then(|concurrency| concurrency.lock_key.as_deref())
+    {
+        if crate::alias_validation::validate_alias_identifier(key).is_err() {
+            return Err(anyhow!(
+                "cached manifest concurrency lock_key must be a valid alias-style name"
+            ));
+        }
+    }
+
+    if let Some(audit) = &manifest.audit {
+        if crate::audit::validate_redaction_rules(
+            "audit",
+            &audit.redact_options,
+            &audit.redact_matching,
+        )
+        .is_err()
+        {
+            return Err(anyhow!(
+                "cached manifest audit redaction rules must be option names or valid patterns"
+            ));
+        }
+    }
+
+    for (name, overlay) in &manifest.subcommands {
+        if name.is_empty() || name.contains('\0') || name.split(' ').any(str::is_empty) {
+            return Err(anyhow!(
+                "cached manifest subcommand keys must be single-space-separated words"
+            ));
+        }
+        for arg in &overlay.args {
+            if matches!(
+                arg_validation::validate_arg_value(arg),
+                Err(ArgViolation::ContainsNul)
+            ) {
+                return Err(anyhow!(
+                    "cached manifest subcommand args cannot contain NUL bytes"
+                ));
+            }
+        }
+        for key in overlay.env.keys().chain(&overlay.env_remove) {
+            if key.is_empty() || key.trim() != key || env_validation::validate_env_key(key).is_err()
+            {
+                return Err(anyhow!(
+                    "cached manifest subcommand env keys must be normalized"
+                ));
+            }
+        }
+        if overlay.env.values().any(|value| {
+            matches!(
+                env_validation::validate_env_value(value),
+                Err(EnvValueViolation::ContainsNul)
+            )
+        }) {
+            return Err(anyhow!(
+                "cached manifest subcommand env values cannot contain NUL bytes"
+            ));
+        }
+        if let Some(path) = &overlay.path {
+            path.validate("cached manifest subcommand path")
+                .map_err(|err| anyhow!("{err}"))?;
+        }
+    }
+
+    for (name, profile) in &manifest.profiles {
+        if name.is_empty() || name.contains('\0') || name.chars().any(char::is_whitespace) {
+            return Err(anyhow!("cached manifest profile names must be normalized"));
+        }
+        if let Some(exec) = &profile.exec {
+            validate_cached_command_path(exec, "cached manifest profile exec path")?;
+        }
+        if profile.args.iter().flatten().any(|arg| {
+            matches!(
+                arg_validation::validate_arg_value(arg),
+                Err(ArgViolation::ContainsNul)
+            )
+        }) {
+            return Err(anyhow!(
+                "cached manifest profile args cannot contain NUL bytes"
+            ));
+        }
+        for key in profile.env.keys().chain(&profile.env_remove) {
+            if key.is_empty() || key.trim() != key || env_validation::validate_env_key(key).is_err()
+            {
+                return Err(anyhow!(
+                    "cached manifest profile env keys must be normalized"
+                ));
+            }
+        }
+        if profile.env.values().any(|value| value.contains('\0')) {
+            return Err(anyhow!(
+                "cached manifest profile env values cannot contain NUL bytes"
+            ));
+        }
+        if let Some(path) = &profile.path {
+            path.validate("cached manifest profile path")
+                .map_err(|err| anyhow!("{err}"))?;
+        }
+        if let Some(journal) = &profile.journal {
+            if journal.namespace.trim().is_empty() || journal.namespace.contains('\0') {
+                return Err(anyhow!(
+                    "cached manifest profile journal namespace must be normalized"
+                ));
+            }
+        }
+    }
+    if let Some(default_profile) = &manifest.default_profile {
+        if !manifest.profiles.contains_key(default_profile) {
+            return Err(anyhow!(
+                "cached manifest default_profile must name a defined profile"
+            ));
+        }
+    }
+
+    if let Some(cwd) = &manifest.cwd {
+        if path_contains_nul(cwd) {
+            return Err(anyhow!("cached manifest cwd cannot contain NUL bytes"));
+        }
+        if cwd.as_os_str().is_empty() {
+            return Err(anyhow!("cached manifest cwd cannot be empty"));
+        }
+    }
+
+    if let Some(alias_dir) = &manifest.alias_dir {
+        if path_contains_nul(alias_dir) {
+            return Err(anyhow!(
+                "cached manifest alias_dir cannot contain NUL bytes"
+            ));
+        }
+    }
+
     Ok(())
 }
 
@@ -365,11 +775,15 @@ fn sanitize_alias_for_cache(alias: &str) -> String {
 }
 
 fn alias_cache_hash(alias: &str) -> u64 {
+    fnv1a_hash(alias.as_bytes())
+}
+
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
     const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
     const FNV_PRIME: u64 = 0x100000001b3;
 
     let mut hash = FNV_OFFSET_BASIS;
-    for byte in alias.as_bytes() {
+    for byte in bytes {
         hash ^= u64::from(*byte);
         hash = hash.wrapping_mul(FNV_PRIME);
     }
@@ -464,7 +878,7 @@ mod tests {
         cache_path, cache_temp_path, load, sanitize_alias_for_cache, source_fingerprint, store,
         CacheEntry, CACHE_ENTRY_VERSION,
     };
-    use crate::manifest::{JournalConfig, Manifest, ReconcileConfig};
+    use crate::manifest::{JournalConfig, Manifest, ReconcileConfig, SecretSource};
     use crate::test_support::ENV_LOCK;
     use std::env;
     use std::fs;
@@ -499,6 +913,59 @@ mod tests {
         env::remove_var("XDG_CACHE_HOME");
     }
 
+    #[test]
+    fn editing_extended_parent_invalidates_child_cache_entry() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        let home = TempDir::new().expect("create tempdir");
+        env::set_var("XDG_CACHE_HOME", home.path());
+        let config_dir = TempDir::new().expect("create config dir");
+        env::set_var("CHOPPER_CONFIG_DIR", config_dir.path());
+
+        let parent_file = config_dir.path().join("base").join("exe.toml");
+        let child_file = config_dir.path().join("child").join("exe.toml");
+        fs::create_dir_all(parent_file.parent().expect("parent dir")).expect("create parent dir");
+        fs::create_dir_all(child_file.parent().expect("child dir")).expect("create child dir");
+        fs::write(&parent_file, "exec = \"echo\"\n").expect("write parent");
+        fs::write(&child_file, "extends = \"base\"\n").expect("write child");
+
+        let fingerprint = source_fingerprint(&child_file).expect("source fingerprint");
+        assert_eq!(fingerprint.dependency_sources.len(), 1);
+        assert_eq!(fingerprint.dependency_sources[0].source_path, parent_file);
+        store(
+            "child",
+            &fingerprint,
+            &Manifest::simple(PathBuf::from("echo")),
+        )
+        .expect("store cache");
+
+        fs::write(&parent_file, "exec = \"printf\"\nargs = [\"x\"]\n").expect("rewrite parent");
+        let new_fingerprint = source_fingerprint(&child_file).expect("new fingerprint");
+        assert!(load("child", &new_fingerprint).is_none());
+
+        env::remove_var("CHOPPER_CONFIG_DIR");
+        env::remove_var("XDG_CACHE_HOME");
+    }
+
+    #[test]
+    fn editing_include_fragment_changes_alias_fingerprint() {
+        let config_dir = TempDir::new().expect("create config dir");
+        let fragment = config_dir.path().join("shared.toml");
+        let source_file = config_dir.path().join("a.toml");
+        fs::write(&fragment, "[env]\nA = \"1\"\n").expect("write fragment");
+        fs::write(
+            &source_file,
+            "exec = \"echo\"\ninclude = [\"shared.toml\"]\n",
+        )
+        .expect("write source");
+
+        let fingerprint = source_fingerprint(&source_file).expect("source fingerprint");
+        assert_eq!(fingerprint.dependency_sources.len(), 1);
+
+        fs::write(&fragment, "[env]\nA = \"22\"\n").expect("rewrite fragment");
+        let new_fingerprint = source_fingerprint(&source_file).expect("new fingerprint");
+        assert_ne!(fingerprint, new_fingerprint);
+    }
+
     #[test]
     fn cache_round_trip_preserves_symbolic_string_shapes() {
         let _guard = ENV_LOCK.lock().expect("lock env mutex");
@@ -1202,6 +1669,30 @@ mod tests {
         env::remove_var("XDG_CACHE_HOME");
     }
 
+    #[test]
+    fn store_rejects_manifest_with_empty_cwd_and_skips_cache_write() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        let home = TempDir::new().expect("create tempdir");
+        env::set_var("XDG_CACHE_HOME", home.path());
+
+        let config_dir = TempDir::new().expect("create config dir");
+        let source_file = config_dir.path().join("a.toml");
+        fs::write(&source_file, "exec = \"echo\"\n").expect("write source");
+        let fingerprint = source_fingerprint(&source_file).expect("source fingerprint");
+
+        let mut invalid_manifest = Manifest::simple(PathBuf::from("echo"));
+        invalid_manifest.cwd = Some(PathBuf::new());
+
+        let err = store("invalid-store-empty-cwd", &fingerprint, &invalid_manifest)
+            .expect_err("empty cwd should be rejected on store");
+        assert!(
+            format!("{err:#}").contains("cached manifest cwd cannot be empty"),
+            "{err:#}"
+        );
+        assert!(!cache_path("invalid-store-empty-cwd").exists());
+        env::remove_var("XDG_CACHE_HOME");
+    }
+
     #[test]
     fn store_rejects_manifest_with_empty_reconcile_script_and_skips_cache_write() {
         let _guard = ENV_LOCK.lock().expect("lock env mutex");
@@ -3420,6 +3911,43 @@ mod tests {
         env::remove_var("XDG_CACHE_HOME");
     }
 
+    #[test]
+    fn cached_manifest_with_invalid_env_secret_source_is_pruned() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        let home = TempDir::new().expect("create tempdir");
+        env::set_var("XDG_CACHE_HOME", home.path());
+
+        let config_dir = TempDir::new().expect("create config dir");
+        let source_file = config_dir.path().join("a.toml");
+        fs::write(&source_file, "exec = \"echo\"\n").expect("write source");
+        let fingerprint = source_fingerprint(&source_file).expect("source fingerprint");
+
+        let mut manifest = Manifest::simple(PathBuf::from("echo"));
+        manifest
+            .env_secret
+            .insert("TOKEN".to_string(), SecretSource::Command(Vec::new()));
+
+        let path = cache_path("unsafe-env-secret");
+        fs::create_dir_all(path.parent().expect("cache path parent")).expect("create cache dir");
+        let entry = CacheEntry {
+            version: CACHE_ENTRY_VERSION,
+            fingerprint: fingerprint.clone(),
+            manifest,
+        };
+        fs::write(
+            &path,
+            bincode::serialize(&entry).expect("serialize cache entry"),
+        )
+        .expect("write cache file");
+
+        assert!(load("unsafe-env-secret", &fingerprint).is_none());
+        assert!(
+            !path.exists(),
+            "invalid cached env_secret sources should be pruned on load"
+        );
+        env::remove_var("XDG_CACHE_HOME");
+    }
+
     #[test]
     fn cached_manifest_with_equals_env_remove_key_is_pruned() {
         let _guard = ENV_LOCK.lock().expect("lock env mutex");
@@ -1,4 +1,21 @@
 fn main() -> anyhow::Result<()> {
     let args: Vec<String> = std::env::args().collect();
-    chopper::exe_runtime::run(&args)
+    let result = chopper::exe_runtime::run(&args);
+    if let Some(violation) = result
+        .as_ref()
+        .err()
+        .and_then(|err| err.downcast_ref::<chopper::guard::GuardViolation>())
+    {
+        eprintln!("Error: {violation}");
+        std::process::exit(chopper::guard::GUARD_EXIT_CODE);
+    }
+    if let Some(held) = result
+        .as_ref()
+        .err()
+        .and_then(|err| err.downcast_ref::<chopper::concurrency::LockHeld>())
+    {
+        eprintln!("Error: {held}");
+        std::process::exit(chopper::concurrency::LOCK_HELD_EXIT_CODE);
+    }
+    result
 }
@@ -0,0 +1,173 @@
+use crate::manifest::{ConcurrencyConfig, ConcurrencyMode};
+use anyhow::{Context, Result};
+use nix::errno::Errno;
+use nix::fcntl::{fcntl, FcntlArg, FdFlag};
+use nix::libc;
+use std::fmt;
+use std::fs::{DirBuilder, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::PathBuf;
+
+/// Exit status of `chopper-exe` when `mode = "exclusive"` finds the lock
+/// taken (`EX_TEMPFAIL` from sysexits.h), so callers can retry later.
+pub const LOCK_HELD_EXIT_CODE: i32 = 75;
+
+/// Another process holds the alias's `[concurrency]` lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockHeld {
+    message: String,
+}
+
+impl fmt::Display for LockHeld {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for LockHeld {}
+
+/// Takes the `[concurrency]` lock for `alias`, or returns `None` when
+/// `mode = "skip"` found it taken and the command should not run.
+///
+/// The returned file must stay open until chopper execs or exits. It is
+/// close-on-exec, so hooks and the journal sink never hold the lock; the
+/// `exec` path passes it on with [`inherit_across_exec`] so the `flock`
+/// lasts as long as the command does.
+pub(crate) fn acquire(config: &ConcurrencyConfig, alias: &str) -> Result<Option<File>> {
+    let key = config.lock_key.as_deref().unwrap_or(alias);
+    let dir = lock_dir();
+    DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&dir)
+        .with_context(|| format!("failed to create lock directory {}", dir.display()))?;
+    let path = dir.join(format!("{key}.lock"));
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .mode(0o600)
+        .open(&path)
+        .with_context(|| format!("failed to open lock file {}", path.display()))?;
+
+    if !try_flock(&file, libc::LOCK_EX | libc::LOCK_NB)? {
+        let holder = match holder_pid(&mut file) {
+            Some(pid) => format!("pid {pid} holds lock `{key}`"),
+            None => format!("lock `{key}` is held"),
+        };
+        match config.mode {
+            ConcurrencyMode::Exclusive => {
+                return Err(LockHeld {
+                    message: format!("`{alias}` is already running ({holder})"),
+                }
+                .into());
+            }
+            ConcurrencyMode::Skip => {
+                eprintln!("chopper: skipping `{alias}`: already running ({holder})");
+                return Ok(None);
+            }
+            ConcurrencyMode::Wait => {
+                eprintln!("chopper: waiting for `{alias}` to finish ({holder})");
+                try_flock(&file, libc::LOCK_EX)?;
+            }
+        }
+    }
+
+    record_holder(&mut file)
+        .with_context(|| format!("failed to write lock file {}", path.display()))?;
+    Ok(Some(file))
+}
+
+/// Clears close-on-exec on the lock so the command chopper is about to
+/// `exec` keeps holding it.
+pub(crate) fn inherit_across_exec(lock: &File) -> Result<()> {
+    fcntl(lock.as_raw_fd(), FcntlArg::F_SETFD(FdFlag::empty()))
+        .context("failed to make the lock inheritable")?;
+    Ok(())
+}
+
+fn lock_dir() -> PathBuf {
+    crate::runtime_dir().join("locks")
+}
+
+/// Returns `false` when a non-blocking lock is already taken.
+pub(crate) fn try_flock(file: &File, operation: libc::c_int) -> Result<bool> {
+    loop {
+        // SAFETY: flock(2) on a descriptor owned by `file`.
+        match Errno::result(unsafe { libc::flock(file.as_raw_fd(), operation) }) {
+            Ok(_) => return Ok(true),
+            Err(Errno::EWOULDBLOCK) => return Ok(false),
+            Err(Errno::EINTR) => continue,
+            Err(err) => return Err(err).context("failed to lock"),
+        }
+    }
+}
+
+/// The pid the current holder recorded, if it got that far.
+fn holder_pid(file: &mut File) -> Option<u32> {
+    let mut contents = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok()
+}
+
+/// Stores chopper's pid, which stays the command's pid after `exec`.
+fn record_holder(file: &mut File) -> std::io::Result<()> {
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    writeln!(file, "{}", std::process::id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{acquire, LockHeld};
+    use crate::manifest::{ConcurrencyConfig, ConcurrencyMode};
+    use crate::test_support::ENV_LOCK;
+    use std::env;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn acquire_records_the_holder_and_reports_contention() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        let runtime = TempDir::new().expect("create runtime dir");
+        let previous = env::var_os("XDG_RUNTIME_DIR");
+        env::set_var("XDG_RUNTIME_DIR", runtime.path());
+
+        let exclusive = ConcurrencyConfig {
+            mode: ConcurrencyMode::Exclusive,
+            lock_key: Some("migrate".to_string()),
+        };
+        let held = acquire(&exclusive, "db").expect("acquire").expect("lock");
+        let lock_path = runtime.path().join("chopper/locks/migrate.lock");
+        assert_eq!(
+            fs::read_to_string(&lock_path).expect("read lock file"),
+            format!("{}\n", std::process::id())
+        );
+
+        // flock locks belong to the open file, so a second open contends
+        // even within one process.
+        let err = acquire(&exclusive, "db").expect_err("contended lock");
+        let held_err = err.downcast_ref::<LockHeld>().expect("LockHeld error");
+        assert!(
+            held_err
+                .to_string()
+                .contains(&format!("pid {}", std::process::id())),
+            "{held_err}"
+        );
+        let skip = ConcurrencyConfig {
+            mode: ConcurrencyMode::Skip,
+            ..exclusive.clone()
+        };
+        assert!(acquire(&skip, "db").expect("skip").is_none());
+        drop(held);
+
+        match previous {
+            Some(value) => env::set_var("XDG_RUNTIME_DIR", value),
+            None => env::remove_var("XDG_RUNTIME_DIR"),
+        }
+    }
+}
@@ -98,7 +98,11 @@ fn collect_canonical_alias_dir_extension_warnings(config_root: &Path, warnings:
 
 pub(crate) fn manifest_missing_target_warnings(manifest: &Manifest) -> Vec<String> {
     let mut warnings = Vec::new();
-    if path_is_explicit(&manifest.exec) && !manifest.exec.exists() {
+    let exec_is_templated = manifest
+        .exec
+        .to_str()
+        .is_some_and(crate::interpolation::contains_placeholders);
+    if !exec_is_templated && path_is_explicit(&manifest.exec) && !manifest.exec.exists() {
         warnings.push(format!(
             "exec target does not exist: {}",
             manifest.exec.display()
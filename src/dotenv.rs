@@ -0,0 +1,384 @@
+use crate::env_validation::{self, EnvKeyViolation, EnvValueViolation};
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// The file and line each variable was defined on.
+pub(crate) type DefinedAt = HashMap<String, (PathBuf, usize)>;
+
+/// Reads `files` in order and returns the merged variables, plus the file and
+/// line each one was defined on; later files override earlier ones. `${VAR}`
+/// in a value refers to a key defined earlier (in the same or a previous
+/// file), falling back to the inherited environment; an undefined variable
+/// is an error. `${VAR:-default}` uses `default` when `VAR` is unset or
+/// empty, so `${VAR:-}` asks for an empty string explicitly.
+pub(crate) fn load_files(files: &[PathBuf]) -> Result<(HashMap<String, String>, DefinedAt)> {
+    let mut vars = HashMap::new();
+    let mut lines = HashMap::new();
+    for path in files {
+        let content = fs_err::read_to_string(path)
+            .with_context(|| format!("failed to read env_file {}", path.display()))?;
+        for (key, value, line) in parse(&content, path, &vars)? {
+            lines.insert(key.clone(), (path.clone(), line));
+            vars.insert(key, value);
+        }
+    }
+    Ok((vars, lines))
+}
+
+/// Parses dotenv syntax: `KEY=VALUE` lines with optional `export ` prefixes,
+/// `#` comments, single-quoted literals, double-quoted values with escapes,
+/// and `${VAR}` / `${VAR:-default}` references. Quoted values may span lines.
+/// References look at this file's earlier entries, then `defined`, then the
+/// inherited environment. Each entry carries the line its key is on.
+pub(crate) fn parse(
+    content: &str,
+    path: &Path,
+    defined: &HashMap<String, String>,
+) -> Result<Vec<(String, String, usize)>> {
+    let mut parser = Parser {
+        chars: content
+            .strip_prefix('\u{feff}')
+            .unwrap_or(content)
+            .chars()
+            .collect(),
+        pos: 0,
+        line: 1,
+        path,
+    };
+    let mut entries: Vec<(String, String, usize)> = Vec::new();
+    loop {
+        parser.skip_blank_lines_and_comments();
+        if parser.at_end() {
+            return Ok(entries);
+        }
+        let line = parser.line;
+        let key = parser.parse_key()?;
+        let value = parser.parse_value(|name| {
+            entries
+                .iter()
+                .rev()
+                .find(|(key, _, _)| key == name)
+                .map(|(_, value, _)| value.clone())
+                .or_else(|| defined.get(name).cloned())
+        })?;
+        if matches!(
+            env_validation::validate_env_value(&value),
+            Err(EnvValueViolation::ContainsNul)
+        ) {
+            return Err(anyhow!(
+                "env_file {} line {line}: value for `{key}` cannot contain NUL bytes",
+                path.display()
+            ));
+        }
+        entries.push((key, value, line));
+    }
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    path: &'a Path,
+}
+
+impl Parser<'_> {
+    fn at_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += 1;
+        if ch == '\n' {
+            self.line += 1;
+        }
+        Some(ch)
+    }
+
+    fn error(&self, message: impl std::fmt::Display) -> anyhow::Error {
+        anyhow!(
+            "env_file {} line {}: {message}",
+            self.path.display(),
+            self.line
+        )
+    }
+
+    fn skip_inline_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\r')) {
+            self.bump();
+        }
+    }
+
+    fn skip_to_line_end(&mut self) {
+        while let Some(ch) = self.bump() {
+            if ch == '\n' {
+                break;
+            }
+        }
+    }
+
+    fn skip_blank_lines_and_comments(&mut self) {
+        loop {
+            self.skip_inline_whitespace();
+            match self.peek() {
+                Some('\n') => {
+                    self.bump();
+                }
+                Some('#') => self.skip_to_line_end(),
+                _ => return,
+            }
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String> {
+        let start = self.pos;
+        while let Some(ch) = self.peek() {
+            if ch == '=' || ch == '\n' {
+                break;
+            }
+            self.bump();
+        }
+        if self.peek() != Some('=') {
+            return Err(self.error("expected `KEY=VALUE`"));
+        }
+        let raw: String = self.chars[start..self.pos].iter().collect();
+        self.bump();
+
+        let raw = raw.trim();
+        let key = raw
+            .strip_prefix("export")
+            .filter(|rest| rest.starts_with([' ', '\t']))
+            .map(str::trim_start)
+            .unwrap_or(raw);
+        if key.is_empty() {
+            return Err(self.error("key cannot be empty"));
+        }
+        if key.chars().any(char::is_whitespace) {
+            return Err(self.error(format!("key `{key}` cannot contain whitespace")));
+        }
+        match env_validation::validate_env_key(key) {
+            Ok(()) => Ok(key.to_string()),
+            Err(EnvKeyViolation::ContainsEquals) => {
+                Err(self.error(format!("key `{key}` cannot contain `=`")))
+            }
+            Err(EnvKeyViolation::ContainsNul) => Err(self.error("key cannot contain NUL bytes")),
+        }
+    }
+
+    fn parse_value(&mut self, earlier: impl Fn(&str) -> Option<String>) -> Result<String> {
+        self.skip_inline_whitespace();
+        let value = match self.peek() {
+            Some('\'') => {
+                self.bump();
+                let mut value = String::new();
+                loop {
+                    match self.bump() {
+                        Some('\'') => break,
+                        Some(ch) => value.push(ch),
+                        None => return Err(self.error("unterminated single-quoted value")),
+                    }
+                }
+                self.finish_quoted_line()?;
+                value
+            }
+            Some('"') => {
+                self.bump();
+                let mut value = String::new();
+                loop {
+                    match self.bump() {
+                        Some('"') => break,
+                        Some('\\') => match self.bump() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some('r') => value.push('\r'),
+                            Some(ch @ ('"' | '\\' | '$')) => value.push(ch),
+                            Some(ch) => {
+                                value.push('\\');
+                                value.push(ch);
+                            }
+                            None => return Err(self.error("unterminated double-quoted value")),
+                        },
+                        Some('$') if self.peek() == Some('{') => {
+                            value.push_str(&self.parse_reference(&earlier)?);
+                        }
+                        Some(ch) => value.push(ch),
+                        None => return Err(self.error("unterminated double-quoted value")),
+                    }
+                }
+                self.finish_quoted_line()?;
+                value
+            }
+            _ => {
+                let mut value = String::new();
+                while let Some(ch) = self.peek() {
+                    if ch == '\n' || (ch == '#' && value.ends_with([' ', '\t'])) {
+                        break;
+                    }
+                    self.bump();
+                    if ch == '$' && self.peek() == Some('{') {
+                        value.push_str(&self.parse_reference(&earlier)?);
+                    } else {
+                        value.push(ch);
+                    }
+                }
+                self.skip_to_line_end();
+                value.trim_end().to_string()
+            }
+        };
+        Ok(value)
+    }
+
+    /// Only whitespace or a comment may follow a closing quote.
+    fn finish_quoted_line(&mut self) -> Result<()> {
+        self.skip_inline_whitespace();
+        match self.peek() {
+            None | Some('\n') | Some('#') => {
+                self.skip_to_line_end();
+                Ok(())
+            }
+            Some(_) => Err(self.error("unexpected text after closing quote")),
+        }
+    }
+
+    fn parse_reference(&mut self, earlier: &impl Fn(&str) -> Option<String>) -> Result<String> {
+        self.bump();
+        let mut name = String::new();
+        loop {
+            match self.bump() {
+                Some('}') => break,
+                Some('\n') | None => return Err(self.error("unterminated `${` reference")),
+                Some(ch) => name.push(ch),
+            }
+        }
+        let (key, default) = match name.split_once(":-") {
+            Some((key, default)) => (key, Some(default)),
+            None => (name.as_str(), None),
+        };
+        if key.is_empty() || key.chars().any(char::is_whitespace) {
+            return Err(self.error(format!("invalid variable reference `${{{name}}}`")));
+        }
+        let value = earlier(key).or_else(|| env::var(key).ok());
+        match (value, default) {
+            (Some(value), Some(default)) if value.is_empty() => Ok(default.to_string()),
+            (Some(value), _) => Ok(value),
+            (None, Some(default)) => Ok(default.to_string()),
+            (None, None) => Err(self.error(format!(
+                "undefined variable `{key}` in `${{{key}}}`; write `${{{key}:-}}` for an empty default"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_files, parse};
+    use crate::test_support::ENV_LOCK;
+    use std::collections::HashMap;
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn parse_str(content: &str) -> Vec<(String, String)> {
+        parse(content, Path::new("test.env"), &HashMap::new())
+            .expect("parse dotenv")
+            .into_iter()
+            .map(|(key, value, _)| (key, value))
+            .collect()
+    }
+
+    #[test]
+    fn parses_quotes_comments_exports_and_references() {
+        let _guard = ENV_LOCK.lock().expect("lock env");
+        env::set_var("CHOPPER_DOTENV_INHERITED", "outer");
+        env::remove_var("CHOPPER_DOTENV_MISSING");
+
+        let parsed = parse_str(
+            "\
+# leading comment
+export HOST=db.local   # trailing comment
+PORT = 5432
+URL=postgres://${HOST}:${PORT}/app
+LITERAL='${HOST} # not a comment'
+QUOTED=\"line one\\nline \\\"two\\\" ${HOST}\"
+MULTI=\"a
+b\"
+HASH=a#b
+EMPTY=
+FROM_ENV=${CHOPPER_DOTENV_INHERITED}-${CHOPPER_DOTENV_MISSING:-}
+DEFAULTED=${CHOPPER_DOTENV_MISSING:-fallback value}|${EMPTY:-empty}|${HOST:-unused}
+",
+        );
+        env::remove_var("CHOPPER_DOTENV_INHERITED");
+
+        let expected = [
+            ("HOST", "db.local"),
+            ("PORT", "5432"),
+            ("URL", "postgres://db.local:5432/app"),
+            ("LITERAL", "${HOST} # not a comment"),
+            ("QUOTED", "line one\nline \"two\" db.local"),
+            ("MULTI", "a\nb"),
+            ("HASH", "a#b"),
+            ("EMPTY", ""),
+            ("FROM_ENV", "outer-"),
+            ("DEFAULTED", "fallback value|empty|db.local"),
+        ];
+        assert_eq!(
+            parsed,
+            expected
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn reports_file_and_line_for_malformed_entries() {
+        for (content, expected) in [
+            ("A=1\nNOEQUALS\n", "line 2: expected `KEY=VALUE`"),
+            ("=value\n", "line 1: key cannot be empty"),
+            ("BAD KEY=1\n", "key `BAD KEY` cannot contain whitespace"),
+            ("A='open\n", "unterminated single-quoted value"),
+            ("A=\"x\" trailing\n", "unexpected text after closing quote"),
+            ("A=${B\n", "unterminated `${` reference"),
+            (
+                "A=${CHOPPER_DOTENV_UNDEFINED}\n",
+                "undefined variable `CHOPPER_DOTENV_UNDEFINED`",
+            ),
+            ("A=${:-x}\n", "invalid variable reference `${:-x}`"),
+            ("A=x\0y\n", "value for `A` cannot contain NUL bytes"),
+        ] {
+            let err = parse(content, Path::new("/cfg/app.env"), &HashMap::new())
+                .expect_err("malformed dotenv should fail");
+            assert!(
+                err.to_string().starts_with("env_file /cfg/app.env line"),
+                "{err}"
+            );
+            assert!(err.to_string().contains(expected), "{content:?}: {err}");
+        }
+    }
+
+    #[test]
+    fn later_files_override_and_reference_earlier_files() {
+        let temp = TempDir::new().expect("create tempdir");
+        let base = temp.path().join("base.env");
+        let local = temp.path().join("local.env");
+        fs::write(&base, "NAME=base\nREGION=eu\n").expect("write base");
+        fs::write(&local, "NAME=local-${REGION}\n").expect("write local");
+
+        let (vars, lines) = load_files(&[base.clone(), local.clone()]).expect("load env files");
+        assert_eq!(vars.get("NAME"), Some(&"local-eu".to_string()));
+        assert_eq!(vars.get("REGION"), Some(&"eu".to_string()));
+        assert_eq!(lines.get("NAME"), Some(&(local, 1)));
+        assert_eq!(lines.get("REGION"), Some(&(base, 2)));
+
+        let err = load_files(&[temp.path().join("missing.env")]).expect_err("missing file");
+        assert!(err.to_string().contains("failed to read env_file"), "{err}");
+    }
+}
@@ -0,0 +1,323 @@
+use crate::manifest::{Invocation, RuntimePatch};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::path::PathBuf;
+
+/// What `chopper-exe` would run for one alias invocation, as reported by
+/// `chopper --dry-run`. Secret values appear as their redacted placeholders.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct DryRun {
+    pub alias: String,
+    /// `None` when no alias config exists and the command came from `PATH`.
+    pub config: Option<PathBuf>,
+    pub exec: PathBuf,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    /// The command starts from an empty environment (`env_mode = "clean"`).
+    pub clear_env: bool,
+    pub env: EnvDiff,
+    pub path: PathChange,
+    pub journal: Option<JournalTarget>,
+    pub reconcile_patch: Option<RuntimePatch>,
+}
+
+/// The command's environment compared with the one chopper inherited.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct EnvDiff {
+    pub added: BTreeMap<String, String>,
+    pub changed: BTreeMap<String, EnvChange>,
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct EnvChange {
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct PathChange {
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct JournalTarget {
+    /// The namespace as written in the alias config.
+    pub configured_namespace: String,
+    /// The namespace `systemd-cat` is started with, after `user_scope`
+    /// derivation.
+    pub namespace: String,
+    pub identifier: Option<String>,
+    pub stderr: bool,
+}
+
+impl DryRun {
+    pub(crate) fn new(
+        alias: String,
+        config: Option<PathBuf>,
+        invocation: Invocation,
+        reconcile_patch: Option<RuntimePatch>,
+    ) -> Result<Self> {
+        let inherited = env::vars_os()
+            .map(|(key, value)| {
+                (
+                    key.to_string_lossy().into_owned(),
+                    value.to_string_lossy().into_owned(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let target = command_environment(&inherited, &invocation);
+        let journal = match &invocation.journal {
+            Some(journal) => {
+                let (namespace, identifier) = crate::executor::journal_target(journal)?;
+                Some(JournalTarget {
+                    configured_namespace: journal.namespace.clone(),
+                    namespace,
+                    identifier,
+                    stderr: journal.stderr,
+                })
+            }
+            None => None,
+        };
+        Ok(Self {
+            alias,
+            config,
+            path: PathChange {
+                before: inherited.get("PATH").cloned(),
+                after: target.get("PATH").cloned(),
+            },
+            env: diff_environment(&inherited, &target),
+            exec: invocation.exec,
+            args: invocation.args,
+            cwd: invocation.cwd,
+            clear_env: invocation.clear_env,
+            journal,
+            reconcile_patch,
+        })
+    }
+
+    /// The human-readable report. `PATH` is left out of the env listing
+    /// because it gets its own before/after lines.
+    pub fn render(&self) -> String {
+        let mut lines = vec![
+            format!("alias:   {}", self.alias),
+            match &self.config {
+                Some(path) => format!("config:  {}", path.display()),
+                None => "config:  (none; command resolved from PATH)".to_string(),
+            },
+            format!("exec:    {}", self.exec.display()),
+            format!("args:    {}", shell_words::join(&self.args)),
+            match &self.cwd {
+                Some(cwd) => format!("cwd:     {}", cwd.display()),
+                None => "cwd:     (inherited)".to_string(),
+            },
+            if self.clear_env {
+                "env:     clean".to_string()
+            } else {
+                "env:     inherited".to_string()
+            },
+        ];
+        for (key, value) in &self.env.added {
+            if key != "PATH" {
+                lines.push(format!("  + {key}={value}"));
+            }
+        }
+        for (key, change) in &self.env.changed {
+            if key != "PATH" {
+                lines.push(format!(
+                    "  ~ {key}={} (was {})",
+                    change.after, change.before
+                ));
+            }
+        }
+        for key in &self.env.removed {
+            if key != "PATH" {
+                lines.push(format!("  - {key}"));
+            }
+        }
+        lines.push(format!(
+            "PATH before: {}",
+            self.path.before.as_deref().unwrap_or("(unset)")
+        ));
+        lines.push(if self.path.after == self.path.before {
+            "PATH after:  (unchanged)".to_string()
+        } else {
+            format!(
+                "PATH after:  {}",
+                self.path.after.as_deref().unwrap_or("(unset)")
+            )
+        });
+        lines.push(match &self.journal {
+            Some(journal) => {
+                let mut line = format!("journal: {}", journal.namespace);
+                if journal.namespace != journal.configured_namespace {
+                    line.push_str(&format!(" (from `{}`)", journal.configured_namespace));
+                }
+                if let Some(identifier) = &journal.identifier {
+                    line.push_str(&format!(", identifier {identifier}"));
+                }
+                if journal.stderr {
+                    line.push_str(", with stderr");
+                }
+                line
+            }
+            None => "journal: (none)".to_string(),
+        });
+        match &self.reconcile_patch {
+            Some(patch) => {
+                lines.push("reconcile patch:".to_string());
+                lines.extend(
+                    patch_lines(patch)
+                        .into_iter()
+                        .map(|line| format!("  {line}")),
+                );
+            }
+            None => lines.push("reconcile patch: (none)".to_string()),
+        }
+
+        let mut out = lines.join("\n");
+        out.push('\n');
+        out
+    }
+}
+
+/// Mirrors how the executor applies an invocation's environment: start from
+/// the inherited one (unless cleared), set `env`, then drop `env_remove`.
+fn command_environment(
+    inherited: &HashMap<String, String>,
+    invocation: &Invocation,
+) -> HashMap<String, String> {
+    let mut env = if invocation.clear_env {
+        HashMap::new()
+    } else {
+        inherited.clone()
+    };
+    env.extend(invocation.env.clone());
+    for key in &invocation.env_remove {
+        env.remove(key);
+    }
+    env
+}
+
+fn diff_environment(
+    inherited: &HashMap<String, String>,
+    target: &HashMap<String, String>,
+) -> EnvDiff {
+    let mut diff = EnvDiff::default();
+    for (key, after) in target {
+        match inherited.get(key) {
+            None => {
+                diff.added.insert(key.clone(), after.clone());
+            }
+            Some(before) if before != after => {
+                diff.changed.insert(
+                    key.clone(),
+                    EnvChange {
+                        before: before.clone(),
+                        after: after.clone(),
+                    },
+                );
+            }
+            Some(_) => {}
+        }
+    }
+    diff.removed = inherited
+        .keys()
+        .filter(|key| !target.contains_key(*key))
+        .cloned()
+        .collect();
+    diff.removed.sort();
+    diff
+}
+
+fn patch_lines(patch: &RuntimePatch) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(args) = &patch.replace_args {
+        lines.push(format!("replace_args: {}", shell_words::join(args)));
+    }
+    if !patch.append_args.is_empty() {
+        lines.push(format!(
+            "append_args: {}",
+            shell_words::join(&patch.append_args)
+        ));
+    }
+    for (label, values) in [
+        ("set_env", &patch.set_env),
+        ("default_env", &patch.default_env),
+    ] {
+        let sorted = values.iter().collect::<BTreeMap<_, _>>();
+        for (key, value) in sorted {
+            lines.push(format!("{label}: {key}={value}"));
+        }
+    }
+    for (label, joins) in [
+        ("append_env", &patch.append_env),
+        ("prepend_env", &patch.prepend_env),
+    ] {
+        for (key, join) in joins {
+            lines.push(format!(
+                "{label}: {key}={} (separator {:?})",
+                join.value, join.separator
+            ));
+        }
+    }
+    if !patch.remove_env.is_empty() {
+        lines.push(format!("remove_env: {}", patch.remove_env.join(" ")));
+    }
+    if !patch.remove_env_matching.is_empty() {
+        lines.push(format!(
+            "remove_env_matching: {}",
+            patch.remove_env_matching.join(" ")
+        ));
+    }
+    if let Some(cwd) = &patch.set_cwd {
+        lines.push(format!("set_cwd: {}", cwd.display()));
+    }
+    if lines.is_empty() {
+        lines.push("(empty)".to_string());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{command_environment, diff_environment, EnvChange};
+    use crate::manifest::Manifest;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    #[test]
+    fn diffs_the_command_environment_against_the_inherited_one() {
+        let inherited = HashMap::from([
+            ("HOME".to_string(), "/home/me".to_string()),
+            ("LANG".to_string(), "C".to_string()),
+            ("TOKEN".to_string(), "old".to_string()),
+        ]);
+        let mut invocation = Manifest::simple(PathBuf::from("/usr/bin/migrate"))
+            .build_invocation(&[], None)
+            .expect("build invocation");
+        invocation.env = HashMap::from([
+            ("TOKEN".to_string(), "new".to_string()),
+            ("MODE".to_string(), "dry".to_string()),
+        ]);
+        invocation.env_remove = vec!["LANG".to_string()];
+
+        let diff = diff_environment(&inherited, &command_environment(&inherited, &invocation));
+        assert_eq!(diff.added.get("MODE").map(String::as_str), Some("dry"));
+        assert_eq!(
+            diff.changed.get("TOKEN"),
+            Some(&EnvChange {
+                before: "old".to_string(),
+                after: "new".to_string(),
+            })
+        );
+        assert_eq!(diff.removed, vec!["LANG"]);
+
+        invocation.clear_env = true;
+        let diff = diff_environment(&inherited, &command_environment(&inherited, &invocation));
+        assert_eq!(diff.removed, vec!["HOME", "LANG"]);
+    }
+}
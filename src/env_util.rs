@@ -12,16 +12,21 @@ pub fn env_flag_enabled(name: &str) -> bool {
         || normalized.eq_ignore_ascii_case("on")
 }
 
-pub fn env_path_override(name: &str) -> Option<PathBuf> {
+/// Returns the trimmed value of `name`, or `None` when unset or blank.
+pub fn env_value(name: &str) -> Option<String> {
     let value = env::var(name).ok()?;
     let trimmed = value.trim();
     if trimmed.is_empty() {
         None
     } else {
-        Some(PathBuf::from(trimmed))
+        Some(trimmed.to_string())
     }
 }
 
+pub fn env_path_override(name: &str) -> Option<PathBuf> {
+    env_value(name).map(PathBuf::from)
+}
+
 #[cfg(test)]
 mod tests {
     use super::{env_flag_enabled, env_path_override};
@@ -1,20 +1,115 @@
+use crate::manifest::ProfileRequest;
 use anyhow::{anyhow, Result};
 use std::path::PathBuf;
 
+const PROFILE_FLAG_PREFIX: &str = "--chopper-profile=";
+
 pub fn run(args: &[String]) -> Result<()> {
+    let (invocation, config_path, manifest) = load_invocation_manifest(args)?;
+    let patch = crate::reconcile::maybe_reconcile(&manifest, &invocation.passthrough_args)?;
+    // The guard only looks at exec and args, so it runs against redacted
+    // secrets: a refused invocation never reads an `[env_secret]` source.
+    if let Some(guard) = &manifest.guard {
+        let redacted = manifest.redact_secrets();
+        let preview = redacted.build_invocation(&invocation.passthrough_args, patch.clone())?;
+        let (unpatched, provenance) =
+            redacted.build_invocation_traced(&invocation.passthrough_args, None)?;
+        let configured = unpatched
+            .args
+            .into_iter()
+            .zip(provenance.args)
+            .filter(|(_, origin)| matches!(origin, crate::provenance::Origin::Config { .. }))
+            .map(|(arg, _)| arg)
+            .collect::<Vec<_>>();
+        let guarded =
+            crate::guard::guarded_args(&invocation.passthrough_args, &configured, patch.as_ref());
+        crate::guard::check(
+            guard,
+            &preview.exec.to_string_lossy(),
+            &preview.args,
+            &guarded,
+        )?;
+    }
+    let resolved = manifest
+        .resolve_secrets()?
+        .build_invocation(&invocation.passthrough_args, patch.clone())?;
+    // Held until chopper exits, or handed to the command when it execs.
+    let lock = match &manifest.concurrency {
+        Some(concurrency) => match crate::concurrency::acquire(concurrency, &invocation.alias)? {
+            Some(lock) => Some(lock),
+            None => return Ok(()),
+        },
+        None => None,
+    };
+    let audit = crate::audit::prepare(
+        &invocation.alias,
+        config_path.as_deref(),
+        &manifest,
+        &resolved,
+        patch.as_ref(),
+    );
+    if let Err(err) = crate::instances::record(&invocation.alias, &resolved) {
+        eprintln!("warning: failed to record running instance: {err:#}");
+    }
+    crate::executor::run(resolved, audit, lock)
+}
+
+/// Runs the same pipeline as [`run`] (cache load, reconcile, invocation
+/// build) but stops before the guard, lock, and exec, and reports what would
+/// have run. Secrets are redacted rather than read. `args` takes the same
+/// shape as for [`run`].
+pub fn dry_run(args: &[String]) -> Result<crate::dry_run::DryRun> {
+    let (invocation, config_path, manifest) = load_invocation_manifest(args)?;
+    let patch = crate::reconcile::maybe_reconcile(&manifest, &invocation.passthrough_args)?;
+    let resolved = manifest
+        .redact_secrets()
+        .build_invocation(&invocation.passthrough_args, patch.clone())?;
+    crate::dry_run::DryRun::new(invocation.alias, config_path, resolved, patch)
+}
+
+/// Runs the same pipeline as [`dry_run`] and reports where every arg, env
+/// variable, and PATH component of the invocation came from.
+pub fn explain(args: &[String]) -> Result<crate::explain::Explanation> {
+    let (invocation, config_path, manifest) = load_invocation_manifest(args)?;
+    let request = ProfileRequest::resolve(invocation.profile.clone());
+    let profile = manifest
+        .selected_profile(request.as_ref())
+        .map(ToString::to_string);
+    let secret_keys = manifest.env_secret.keys().cloned().collect::<Vec<_>>();
+    let patch = crate::reconcile::maybe_reconcile(&manifest, &invocation.passthrough_args)?;
+    let (resolved, provenance) = manifest
+        .redact_secrets()
+        .build_invocation_traced(&invocation.passthrough_args, patch)?;
+    Ok(crate::explain::Explanation::new(
+        invocation.alias,
+        config_path,
+        profile,
+        resolved,
+        provenance,
+        &secret_keys,
+    ))
+}
+
+/// Parses the invocation and loads its manifest, with the profile applied
+/// and `requires` checked.
+fn load_invocation_manifest(
+    args: &[String],
+) -> Result<(InvocationInput, Option<PathBuf>, crate::manifest::Manifest)> {
     let invocation = parse_invocation(args)?;
+    let profile = ProfileRequest::resolve(invocation.profile.clone());
 
     let config_path = find_config(&invocation.alias);
-    let manifest = match config_path {
-        Some(path) => load_manifest(&invocation.alias, &path)?,
+    let manifest = match &config_path {
+        Some(path) => load_manifest(&invocation.alias, path, profile.as_ref())?,
         None => crate::manifest::Manifest::simple(crate::exec_resolution::resolve_command_path(
             &invocation.alias,
-        )),
+        ))
+        .apply_profile(profile.as_ref())?,
     };
-
-    let patch = crate::reconcile::maybe_reconcile(&manifest, &invocation.passthrough_args)?;
-    let resolved = manifest.build_invocation(&invocation.passthrough_args, patch)?;
-    crate::executor::run(resolved)
+    if let Some(requires) = &manifest.requires {
+        crate::version_requirement::check(requires, &manifest.exec, cache_enabled())?;
+    }
+    Ok((invocation, config_path, manifest))
 }
 
 fn config_dir() -> PathBuf {
@@ -31,7 +126,18 @@ fn find_config(name: &str) -> Option<PathBuf> {
     crate::alias_paths::find_exec_config(&config_dir(), name)
 }
 
-fn load_manifest(alias: &str, path: &std::path::Path) -> Result<crate::manifest::Manifest> {
+fn load_manifest(
+    alias: &str,
+    path: &std::path::Path,
+    profile: Option<&ProfileRequest>,
+) -> Result<crate::manifest::Manifest> {
+    load_raw_manifest(alias, path)?
+        .apply_profile(profile)?
+        .expand_placeholders(alias)?
+        .select_exec()
+}
+
+fn load_raw_manifest(alias: &str, path: &std::path::Path) -> Result<crate::manifest::Manifest> {
     if !cache_enabled() {
         return crate::parser::parse(path);
     }
@@ -53,6 +159,7 @@ fn cache_enabled() -> bool {
 #[derive(Debug, PartialEq, Eq)]
 struct InvocationInput {
     alias: String,
+    profile: Option<String>,
     passthrough_args: Vec<String>,
 }
 
@@ -67,23 +174,43 @@ fn parse_invocation(args: &[String]) -> Result<InvocationInput> {
         }
         let alias = args[1].clone();
         validate_alias_name(&alias)?;
-        let passthrough_args = normalize_passthrough(&args[2..]);
+        let (profile, rest) = split_profile_flag(&args[2..])?;
+        let passthrough_args = normalize_passthrough(rest);
         validate_passthrough_args(&passthrough_args)?;
         Ok(InvocationInput {
             alias,
+            profile,
             passthrough_args,
         })
     } else {
         validate_alias_name(&exe_name)?;
-        let passthrough_args = normalize_passthrough(&args[1..]);
+        let (profile, rest) = split_profile_flag(&args[1..])?;
+        let passthrough_args = normalize_passthrough(rest);
         validate_passthrough_args(&passthrough_args)?;
         Ok(InvocationInput {
             alias: exe_name,
+            profile,
             passthrough_args,
         })
     }
 }
 
+/// Strips a leading `--chopper-profile=<name>` from the runtime args. Only the
+/// first arg is considered, so the flag can still be passed through after `--`.
+fn split_profile_flag(args: &[String]) -> Result<(Option<String>, &[String])> {
+    let Some(name) = args
+        .first()
+        .and_then(|arg| arg.strip_prefix(PROFILE_FLAG_PREFIX))
+    else {
+        return Ok((None, args));
+    };
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("`--chopper-profile=` requires a profile name"));
+    }
+    Ok((Some(name.to_string()), &args[1..]))
+}
+
 fn validate_passthrough_args(args: &[String]) -> Result<()> {
     for arg in args {
         if matches!(
@@ -179,4 +306,37 @@ mod tests {
         assert_eq!(invocation.alias, "kubectl-prod");
         assert_eq!(invocation.passthrough_args, vec!["get", "pods"]);
     }
+
+    #[test]
+    fn strips_leading_profile_flag_only() {
+        let invocation = parse_invocation(&[
+            "chopper-exe".to_string(),
+            "kpods".to_string(),
+            "--chopper-profile=staging".to_string(),
+            "--".to_string(),
+            "--chopper-profile=literal".to_string(),
+        ])
+        .expect("valid invocation");
+        assert_eq!(invocation.profile.as_deref(), Some("staging"));
+        assert_eq!(
+            invocation.passthrough_args,
+            vec!["--chopper-profile=literal"]
+        );
+
+        let invocation = parse_invocation(&[
+            "kpods".to_string(),
+            "get".to_string(),
+            "--chopper-profile=prod".to_string(),
+        ])
+        .expect("valid invocation");
+        assert_eq!(invocation.profile, None);
+        assert_eq!(
+            invocation.passthrough_args,
+            vec!["get", "--chopper-profile=prod"]
+        );
+
+        let err = parse_invocation(&["kpods".to_string(), "--chopper-profile=".to_string()])
+            .expect_err("blank profile should fail");
+        assert!(err.to_string().contains("requires a profile name"), "{err}");
+    }
 }
@@ -44,6 +44,30 @@ pub fn resolve_command_path(command: &str) -> PathBuf {
     first_hit.unwrap_or_else(|| PathBuf::from(command))
 }
 
+/// Returns where `candidate` runs from when it names an executable file other
+/// than chopper itself. Bare command names are looked up on `PATH` with
+/// [`resolve_command_path`].
+pub fn resolve_exec_candidate(candidate: &Path) -> Option<PathBuf> {
+    let is_bare_name = !candidate.is_absolute() && candidate.components().count() == 1;
+    let resolved = if is_bare_name {
+        let resolved = resolve_command_path(candidate.to_str()?);
+        if resolved == candidate {
+            return None;
+        }
+        resolved
+    } else {
+        candidate.to_path_buf()
+    };
+    let metadata = fs::metadata(&resolved).ok()?;
+    if !metadata.is_file() || metadata.mode() & 0o111 == 0 {
+        return None;
+    }
+    if matches_any_binary_identity(&skip_identities(), &resolved) {
+        return None;
+    }
+    Some(resolved)
+}
+
 fn is_direct_chopper_name(name: &str) -> bool {
     name.eq_ignore_ascii_case("chopper")
 }
@@ -82,7 +106,7 @@ fn file_identity(path: &Path) -> Option<FileIdentity> {
 
 #[cfg(test)]
 mod tests {
-    use super::resolve_command_path;
+    use super::{resolve_command_path, resolve_exec_candidate};
     use crate::test_support::ENV_LOCK;
     use std::env;
     use std::fs;
@@ -148,4 +172,40 @@ mod tests {
             None => env::remove_var("PATH"),
         }
     }
+
+    #[test]
+    fn exec_candidates_must_be_executable_files_other_than_chopper() {
+        let _guard = ENV_LOCK.lock().expect("lock env");
+        let old_path = env::var("PATH").ok();
+
+        let bin_dir = TempDir::new().expect("create bin dir");
+        let tool = bin_dir.path().join("tool");
+        let plain = bin_dir.path().join("plain");
+        let wrapper = bin_dir.path().join("wrapper");
+        write_executable_script(&tool, "#!/bin/sh\n");
+        fs::write(&plain, "not executable").expect("write plain file");
+        symlink(env::current_exe().expect("current exe"), &wrapper).expect("create wrapper");
+        env::set_var("PATH", bin_dir.path());
+
+        assert_eq!(
+            resolve_exec_candidate(std::path::Path::new("tool")),
+            Some(tool.clone())
+        );
+        assert_eq!(resolve_exec_candidate(&tool), Some(tool));
+        assert_eq!(
+            resolve_exec_candidate(std::path::Path::new("missing")),
+            None
+        );
+        assert_eq!(resolve_exec_candidate(&plain), None);
+        assert_eq!(resolve_exec_candidate(bin_dir.path()), None);
+        assert_eq!(
+            resolve_exec_candidate(std::path::Path::new("wrapper")),
+            None
+        );
+
+        match old_path {
+            Some(value) => env::set_var("PATH", value),
+            None => env::remove_var("PATH"),
+        }
+    }
 }
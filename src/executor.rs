@@ -1,13 +1,17 @@
 use crate::arg_validation::{self, ArgViolation};
+use crate::audit::PendingAudit;
 use crate::env_validation::{self, EnvKeyViolation, EnvValueViolation};
 use crate::journal_broker_client::{self, JournalPolicyOptions};
 use crate::journal_validation::{self, JournalIdentifierViolation, JournalNamespaceViolation};
-use crate::manifest::{Invocation, JournalConfig};
+use crate::manifest::{HooksConfig, Invocation, JournalConfig, TimeoutConfig};
+use crate::signal_relay::{self, SignalRelay};
 use anyhow::{anyhow, Context, Result};
+use nix::sys::signal::Signal;
 use nix::unistd::geteuid;
 use std::env;
-use std::fs;
-use std::io;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::os::fd::OwnedFd;
 use std::os::unix::ffi::OsStrExt;
 use std::os::unix::process::CommandExt;
 use std::os::unix::process::ExitStatusExt;
@@ -15,75 +19,331 @@ use std::path::{Path, PathBuf};
 use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
 use std::thread;
 use std::time::{Duration, Instant};
+use wait_timeout::ChildExt;
+
+/// Set for post hooks to the child's exit code, or 128 + signal number.
+pub const EXIT_CODE_ENV: &str = "CHOPPER_EXIT_CODE";
+/// Exit status for a child stopped by `timeout`, as with coreutils `timeout`.
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+/// Set for the child to its 1-based attempt number when `[retry]` is set.
+pub const ATTEMPT_ENV: &str = "CHOPPER_ATTEMPT";
+
+/// Runs the command. `audit` is written just before the `exec`, or, when
+/// chopper waits for the command, once its exit code is known. `lock` is the
+/// `[concurrency]` lock: the command inherits it on the `exec` path, and
+/// chopper holds it while it waits otherwise.
+pub(crate) fn run(
+    invocation: Invocation,
+    audit: Option<PendingAudit>,
+    lock: Option<File>,
+) -> Result<()> {
+    let hooks = invocation.hooks.clone().unwrap_or_default();
+    run_pre_hooks(&invocation, &hooks)?;
+
+    let journal = invocation.journal.clone().filter(|journal| journal.stderr);
+    let supervised =
+        !hooks.post.is_empty() || invocation.timeout.is_some() || invocation.retry.is_some();
+    if !supervised && journal.is_none() {
+        if let Some(audit) = audit {
+            audit.launched();
+        }
+        return run_direct(invocation, lock.as_ref());
+    }
 
-pub fn run(invocation: Invocation) -> Result<()> {
-    if let Some(journal) = invocation.journal.clone() {
-        if journal.stderr {
-            return run_with_journal(invocation, journal);
+    let started = Instant::now();
+    let status = match journal {
+        Some(journal) => run_with_journal(&invocation, journal),
+        None => run_supervised(&invocation),
+    };
+    let status = match (status, audit) {
+        (Ok(status), Some(audit)) => {
+            audit.finished(exit_code_for_status(status), started.elapsed());
+            status
+        }
+        (Err(err), Some(audit)) => {
+            audit.failed(&err);
+            return Err(err);
         }
+        (status, None) => status?,
+    };
+    if supervised {
+        run_post_hooks(&invocation, &hooks, status);
     }
-    run_direct(invocation)
+    exit_like_child(status)
 }
 
-fn run_direct(invocation: Invocation) -> Result<()> {
+fn run_direct(invocation: Invocation, lock: Option<&File>) -> Result<()> {
     let mut cmd = command_for_invocation(&invocation)?;
+    if let Some(lock) = lock {
+        crate::concurrency::inherit_across_exec(lock)?;
+    }
     let err = cmd.exec();
     Err(anyhow!("exec failed: {}", err))
 }
 
-fn run_with_journal(invocation: Invocation, journal: JournalConfig) -> Result<()> {
-    let (namespace, identifier, policy) = normalize_journal_config_for_command(&journal)?;
-    let mut child_cmd = command_for_invocation(&invocation)?;
-    child_cmd.stderr(Stdio::piped());
+/// Spawns the child instead of exec'ing it, so chopper can enforce a
+/// timeout, retry failures, and run post hooks once it exits. Signals sent
+/// to chopper meanwhile go to the child.
+fn run_supervised(invocation: &Invocation) -> Result<ExitStatus> {
+    let mut cmd = command_for_invocation(invocation)?;
+    run_attempts(invocation, &mut cmd, None)
+}
 
-    if journal.ensure {
-        journal_broker_client::ensure_namespace_via_dbus(&namespace, &policy)
-            .context("journal namespace broker preflight failed")?;
+/// Runs `cmd` once, or under `[retry]` until it succeeds, fails with an exit
+/// code that is not retried, runs out of attempts, or ends after chopper
+/// relayed a termination signal to it. Each attempt sees
+/// `CHOPPER_ATTEMPT` and is announced in the journal sink when there is one.
+fn run_attempts(
+    invocation: &Invocation,
+    cmd: &mut Command,
+    mut journal_sink: Option<&mut ChildStdin>,
+) -> Result<ExitStatus> {
+    let Some(retry) = invocation.retry.as_ref() else {
+        return run_attempt(invocation, cmd, journal_sink);
+    };
+    let mut attempt = 1;
+    loop {
+        cmd.env(ATTEMPT_ENV, attempt.to_string());
+        if let Some(sink) = journal_sink.as_deref_mut() {
+            // Annotations are best effort; a closed sink surfaces below.
+            let _ = writeln!(
+                sink,
+                "chopper: attempt {attempt}/{} starting",
+                retry.attempts
+            );
+        }
+        let status = run_attempt(invocation, cmd, journal_sink.as_deref_mut())?;
+        if attempt >= retry.attempts
+            || !retry.should_retry(status.code())
+            || signal_relay::termination_relayed()
+        {
+            return Ok(status);
+        }
+
+        let delay = retry.delay_after(attempt);
+        let note = format!(
+            "attempt {attempt}/{} of {} failed with {status}; retrying in {delay:?}",
+            retry.attempts,
+            invocation.exec.display()
+        );
+        match journal_sink.as_deref_mut() {
+            Some(sink) => {
+                let _ = writeln!(sink, "chopper: {note}");
+            }
+            None => eprintln!("warning: {note}"),
+        }
+        thread::sleep(delay);
+        attempt += 1;
     }
-    let (mut journal_child, mut journal_stdin) =
-        start_journal_sink_with_retry(&namespace, identifier.as_deref())?;
+}
 
-    let mut child = match child_cmd.spawn() {
-        Ok(child) => child,
-        Err(err) => {
-            drop(journal_stdin);
-            let _ = journal_child.wait();
-            return Err(err)
-                .with_context(|| format!("failed to spawn {}", invocation.exec.display()));
+/// Spawns and waits for one run of `cmd`, pumping its stderr into the
+/// journal sink when there is one.
+fn run_attempt(
+    invocation: &Invocation,
+    cmd: &mut Command,
+    journal_sink: Option<&mut ChildStdin>,
+) -> Result<ExitStatus> {
+    let relay = SignalRelay::install()?;
+    relay.prepare(cmd);
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("failed to spawn {}", invocation.exec.display()))?;
+    relay.attach(child.id());
+    let Some(journal_sink) = journal_sink else {
+        return wait_for_child(invocation, &mut child, &relay);
+    };
+    let mut child_stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| anyhow!("failed to capture child stderr"))?;
+
+    // Relaying keeps chopper alive until the child exits, so the pump
+    // always drains into the sink.
+    thread::scope(|scope| {
+        let pump = scope.spawn(move || io::copy(&mut child_stderr, journal_sink));
+        let status = wait_for_child(invocation, &mut child, &relay);
+        let pump_result = pump
+            .join()
+            .map_err(|_| anyhow!("stderr pump thread panicked"))?;
+        let status = status?;
+        if let Err(err) = pump_result {
+            if err.kind() != io::ErrorKind::BrokenPipe {
+                return Err(err).context("failed piping stderr to systemd-cat");
+            }
         }
+        Ok(status)
+    })
+}
+
+/// Waits for the child, enforcing the invocation's `timeout`. A child that
+/// had to be stopped is reported as exit status 124, or 137 when it also
+/// needed the `kill_after` SIGKILL.
+fn wait_for_child(
+    invocation: &Invocation,
+    child: &mut Child,
+    relay: &SignalRelay,
+) -> Result<ExitStatus> {
+    let Some(timeout) = invocation.timeout.as_ref() else {
+        return child.wait().context("failed waiting for child process");
     };
-    let mut child_stderr = match child.stderr.take() {
-        Some(stderr) => stderr,
+    let after = Duration::from_millis(timeout.after_ms);
+    if let Some(status) = child
+        .wait_timeout(after)
+        .context("failed waiting for child process")?
+    {
+        return Ok(status);
+    }
+
+    let signal = timeout_signal(timeout)?;
+    eprintln!(
+        "warning: {} timed out after {after:?}; sending {signal}",
+        invocation.exec.display()
+    );
+    relay.signal_child(signal);
+    let mut exit_code = TIMEOUT_EXIT_CODE;
+    match timeout.kill_after_ms.map(Duration::from_millis) {
+        Some(kill_after) => {
+            if child
+                .wait_timeout(kill_after)
+                .context("failed waiting for child process")?
+                .is_none()
+            {
+                eprintln!(
+                    "warning: {} still running {kill_after:?} after {signal}; sending SIGKILL",
+                    invocation.exec.display()
+                );
+                relay.signal_child(Signal::SIGKILL);
+                child.wait().context("failed waiting for child process")?;
+                exit_code = 128 + Signal::SIGKILL as i32;
+            }
+        }
         None => {
-            drop(journal_stdin);
-            let _ = journal_child.wait();
-            return Err(anyhow!("failed to capture child stderr"));
+            child.wait().context("failed waiting for child process")?;
         }
-    };
+    }
+    Ok(ExitStatus::from_raw(exit_code << 8))
+}
 
-    let pump = thread::spawn(move || -> io::Result<()> {
-        io::copy(&mut child_stderr, &mut journal_stdin)?;
-        Ok(())
-    });
+fn timeout_signal(timeout: &TimeoutConfig) -> Result<Signal> {
+    signal_relay::parse_signal(&timeout.signal)
+        .ok_or_else(|| anyhow!("invalid timeout signal `{}`", timeout.signal))
+}
 
-    let child_status = child.wait().context("failed waiting for child process")?;
-    let pump_result = pump
-        .join()
-        .map_err(|_| anyhow!("stderr pump thread panicked"))?;
+fn run_with_journal(invocation: &Invocation, journal: JournalConfig) -> Result<ExitStatus> {
+    let mut child_cmd = command_for_invocation(invocation)?;
+    child_cmd.stderr(Stdio::piped());
+    let (mut journal_child, mut journal_stdin) = open_journal_sink(&journal)?;
 
+    let child_status = run_attempts(invocation, &mut child_cmd, Some(&mut journal_stdin));
+    // Close the sink's stdin so systemd-cat sees EOF and exits.
+    drop(journal_stdin);
     let journal_status = journal_child
         .wait()
         .context("failed waiting for systemd-cat process")?;
-    if let Err(err) = pump_result {
-        if err.kind() != io::ErrorKind::BrokenPipe {
-            return Err(err).context("failed piping stderr to systemd-cat");
-        }
-    }
+    let child_status = child_status?;
     if !journal_status.success() {
         return Err(journal_status_error(journal_status));
     }
 
-    exit_like_child(child_status)
+    Ok(child_status)
+}
+
+fn open_journal_sink(journal: &JournalConfig) -> Result<(Child, ChildStdin)> {
+    let (namespace, identifier, policy) = normalize_journal_config_for_command(journal)?;
+    if journal.ensure {
+        journal_broker_client::ensure_namespace_via_dbus(&namespace, &policy)
+            .context("journal namespace broker preflight failed")?;
+    }
+    start_journal_sink_with_retry(&namespace, identifier.as_deref())
+}
+
+fn run_pre_hooks(invocation: &Invocation, hooks: &HooksConfig) -> Result<()> {
+    for argv in &hooks.pre {
+        let status = run_hook(invocation, hooks, argv, None)?;
+        if !status.success() {
+            return Err(anyhow!(
+                "pre hook `{}` failed with {status}; not starting {}",
+                shell_words::join(argv),
+                invocation.exec.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Post hooks cannot change the outcome, so their failures are only
+/// reported and the remaining hooks still run.
+fn run_post_hooks(invocation: &Invocation, hooks: &HooksConfig, child_status: ExitStatus) {
+    let exit_code = exit_code_for_status(child_status);
+    for argv in &hooks.post {
+        match run_hook(invocation, hooks, argv, Some(exit_code)) {
+            Ok(status) if status.success() => {}
+            Ok(status) => eprintln!(
+                "warning: post hook `{}` failed with {status}",
+                shell_words::join(argv)
+            ),
+            Err(err) => eprintln!("warning: {err:#}"),
+        }
+    }
+}
+
+/// Runs one hook with the child's environment and working directory, but
+/// without its stdin or resource limits.
+fn run_hook(
+    invocation: &Invocation,
+    hooks: &HooksConfig,
+    argv: &[String],
+    exit_code: Option<i32>,
+) -> Result<ExitStatus> {
+    let stage = if exit_code.is_some() { "post" } else { "pre" };
+    let (program, args) = argv
+        .split_first()
+        .ok_or_else(|| anyhow!("{stage} hook command is empty"))?;
+    validate_args_for_command(argv)?;
+
+    let mut cmd = Command::new(program);
+    cmd.args(args).stdin(Stdio::null());
+    apply_invocation_environment(&mut cmd, invocation)?;
+    if let Some(exit_code) = exit_code {
+        cmd.env(EXIT_CODE_ENV, exit_code.to_string());
+    }
+
+    let sink = match invocation.journal.as_ref().filter(|_| hooks.journal) {
+        Some(journal) => Some(open_journal_sink(journal)?),
+        None => None,
+    };
+    let status = match sink {
+        Some((mut journal_child, journal_stdin)) => {
+            let output = OwnedFd::from(journal_stdin);
+            cmd.stderr(Stdio::from(
+                output
+                    .try_clone()
+                    .context("failed to duplicate systemd-cat stdin")?,
+            ));
+            cmd.stdout(Stdio::from(output));
+            let status = wait_relaying_signals(&mut cmd);
+            // Close our copies of the sink's stdin so systemd-cat sees EOF.
+            drop(cmd);
+            let journal_status = journal_child
+                .wait()
+                .context("failed waiting for systemd-cat process")?;
+            if !journal_status.success() {
+                return Err(journal_status_error(journal_status));
+            }
+            status
+        }
+        None => wait_relaying_signals(&mut cmd),
+    };
+    status.with_context(|| format!("failed to run {stage} hook `{}`", shell_words::join(argv)))
+}
+
+fn wait_relaying_signals(cmd: &mut Command) -> Result<ExitStatus> {
+    let relay = SignalRelay::install()?;
+    relay.prepare(cmd);
+    let mut child = cmd.spawn()?;
+    relay.attach(child.id());
+    Ok(child.wait()?)
 }
 
 fn start_journal_sink_with_retry(
@@ -120,6 +380,9 @@ fn spawn_journal_sink(namespace: &str, identifier: Option<&str>) -> Result<(Chil
     journal_cmd.stdin(Stdio::piped());
     journal_cmd.stdout(Stdio::null());
     journal_cmd.stderr(Stdio::inherit());
+    // Keep terminal ^C / ^\ away from the sink; it exits once its stdin
+    // closes, after the child's last stderr bytes are written.
+    journal_cmd.process_group(0);
 
     let mut journal_child = journal_cmd
         .spawn()
@@ -170,11 +433,31 @@ fn ensure_journal_sink_startup(journal_child: &mut std::process::Child) -> Resul
 fn command_for_invocation(invocation: &Invocation) -> Result<Command> {
     validate_exec_path_for_command(invocation)?;
     validate_no_recursive_self_exec(invocation)?;
-    validate_args_for_command(invocation)?;
+    validate_args_for_command(&invocation.args)?;
 
     let mut cmd = Command::new(&invocation.exec);
     cmd.args(&invocation.args);
+    apply_invocation_environment(&mut cmd, invocation)?;
 
+    if let Some(limits) = invocation.limits.clone() {
+        // SAFETY: `process_limits::apply` only issues async-signal-safe
+        // system calls and does not allocate.
+        unsafe {
+            cmd.pre_exec(move || crate::process_limits::apply(&limits));
+        }
+    }
+    Ok(cmd)
+}
+
+fn apply_invocation_environment(cmd: &mut Command, invocation: &Invocation) -> Result<()> {
+    if let Some(cwd) = &invocation.cwd {
+        validate_cwd_for_command(cwd)?;
+        cmd.current_dir(cwd);
+    }
+
+    if invocation.clear_env {
+        cmd.env_clear();
+    }
     for (key, val) in &invocation.env {
         validate_env_key_for_command(key)?;
         validate_env_value_for_command(key, val)?;
@@ -185,7 +468,7 @@ fn command_for_invocation(invocation: &Invocation) -> Result<Command> {
         validate_env_key_for_command(key)?;
         cmd.env_remove(key);
     }
-    Ok(cmd)
+    Ok(())
 }
 
 fn validate_exec_path_for_command(invocation: &Invocation) -> Result<()> {
@@ -195,6 +478,19 @@ fn validate_exec_path_for_command(invocation: &Invocation) -> Result<()> {
     Ok(())
 }
 
+fn validate_cwd_for_command(cwd: &Path) -> Result<()> {
+    if cwd.as_os_str().as_bytes().contains(&0) {
+        return Err(anyhow!("working directory cannot contain NUL bytes"));
+    }
+    if !cwd.is_dir() {
+        return Err(anyhow!(
+            "working directory {} does not exist or is not a directory",
+            cwd.display()
+        ));
+    }
+    Ok(())
+}
+
 fn validate_no_recursive_self_exec(invocation: &Invocation) -> Result<()> {
     // Allow explicit `exec = "chopper"` patterns for advanced workflows.
     if is_direct_chopper_name_path(&invocation.exec) {
@@ -239,8 +535,8 @@ fn is_direct_chopper_name_path(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
-fn validate_args_for_command(invocation: &Invocation) -> Result<()> {
-    for arg in &invocation.args {
+fn validate_args_for_command(args: &[String]) -> Result<()> {
+    for arg in args {
         if matches!(
             arg_validation::validate_arg_value(arg),
             Err(ArgViolation::ContainsNul)
@@ -272,6 +568,13 @@ fn validate_env_value_for_command(key: &str, value: &str) -> Result<()> {
     }
 }
 
+/// The namespace and identifier `systemd-cat` would be started with, after
+/// `user_scope` derivation.
+pub(crate) fn journal_target(journal: &JournalConfig) -> Result<(String, Option<String>)> {
+    let (namespace, identifier, _) = normalize_journal_config_for_command(journal)?;
+    Ok((namespace, identifier))
+}
+
 fn normalize_journal_config_for_command(
     journal: &JournalConfig,
 ) -> Result<(String, Option<String>, JournalPolicyOptions)> {
@@ -382,6 +685,13 @@ fn sanitize_namespace_component(value: &str) -> String {
     }
 }
 
+fn exit_code_for_status(status: ExitStatus) -> i32 {
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1)
+}
+
 fn exit_like_child(status: ExitStatus) -> Result<()> {
     if status.success() {
         return Ok(());
@@ -397,11 +707,12 @@ fn exit_like_child(status: ExitStatus) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use super::command_for_invocation;
-    use crate::manifest::{Invocation, JournalConfig};
+    use super::{command_for_invocation, run_hook, run_pre_hooks};
+    use crate::manifest::{HooksConfig, Invocation, JournalConfig};
     use crate::test_support::ENV_LOCK;
     use std::collections::HashMap;
     use std::env;
+    use std::fs;
     use std::os::unix::fs::symlink;
     use std::path::PathBuf;
     use tempfile::TempDir;
@@ -412,10 +723,59 @@ mod tests {
             args: vec!["ok".to_string()],
             env: HashMap::new(),
             env_remove: Vec::new(),
+            clear_env: false,
+            cwd: None,
             journal: None,
+            limits: None,
+            hooks: None,
+            timeout: None,
+            retry: None,
         }
     }
 
+    #[test]
+    fn post_hook_runs_with_invocation_environment_and_exit_code() {
+        let dir = TempDir::new().expect("create tempdir");
+        let out = dir.path().join("hook.out");
+        let mut invocation = invocation();
+        invocation.cwd = Some(dir.path().to_path_buf());
+        invocation
+            .env
+            .insert("CHOPPER_HOOK_TEST".to_string(), "from-env".to_string());
+        let hooks = HooksConfig {
+            post: vec![vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                "echo \"$CHOPPER_HOOK_TEST $CHOPPER_EXIT_CODE $(pwd)\" > hook.out".to_string(),
+            ]],
+            ..HooksConfig::default()
+        };
+
+        let status = run_hook(&invocation, &hooks, &hooks.post[0], Some(7)).expect("run hook");
+        assert!(status.success());
+        let written = fs::read_to_string(&out).expect("read hook output");
+        let cwd = fs::canonicalize(dir.path()).expect("canonicalize tempdir");
+        assert_eq!(written.trim(), format!("from-env 7 {}", cwd.display()));
+    }
+
+    #[test]
+    fn pre_hook_failure_aborts_the_launch() {
+        let mut invocation = invocation();
+        invocation.exec = PathBuf::from("/bin/echo");
+        let hooks = HooksConfig {
+            pre: vec![vec!["false".to_string()]],
+            ..HooksConfig::default()
+        };
+
+        let err = run_pre_hooks(&invocation, &hooks)
+            .expect_err("failing pre hook should abort")
+            .to_string();
+        assert!(
+            err.contains("pre hook `false` failed with exit status: 1; not starting /bin/echo"),
+            "{err}"
+        );
+    }
+
     #[test]
     fn command_builder_rejects_env_key_with_equals_sign() {
         let mut invocation = invocation();
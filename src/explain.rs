@@ -0,0 +1,671 @@
+use crate::manifest::Invocation;
+use crate::provenance::{Origin, Provenance};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use toml::de::{DeTable, DeValue};
+use toml::Spanned;
+
+/// Every arg, env variable, and PATH component of one alias invocation with
+/// the source it came from, as reported by `chopper --explain`.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Explanation {
+    pub alias: String,
+    /// `None` when no alias config exists and the command came from `PATH`.
+    pub config: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub exec: PathBuf,
+    pub args: Vec<Explained>,
+    pub cwd: Option<Explained>,
+    /// Sorted by name, including inherited variables the command does not
+    /// see.
+    pub env: Vec<ExplainedVar>,
+    /// Set when `[path]` rewrote PATH.
+    pub path: Option<Vec<ExplainedPathEntry>>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Explained {
+    pub value: String,
+    pub origin: Origin,
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VarStatus {
+    /// Passed through from chopper's environment.
+    Inherited,
+    /// Set or rewritten by chopper.
+    Set,
+    /// Present in chopper's environment but not the command's.
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ExplainedVar {
+    pub name: String,
+    /// `None` for removed variables.
+    pub value: Option<String>,
+    pub status: VarStatus,
+    pub origin: Origin,
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ExplainedPathEntry {
+    pub value: String,
+    /// The `[path]` step number; `None` for components of the PATH the
+    /// mutation started from.
+    pub step: Option<usize>,
+    pub origin: Origin,
+    pub location: Option<Location>,
+}
+
+/// A line of an alias config or env file.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Location {
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+impl Explanation {
+    pub(crate) fn new(
+        alias: String,
+        config: Option<PathBuf>,
+        profile: Option<String>,
+        invocation: Invocation,
+        provenance: Provenance,
+        secret_keys: &[String],
+    ) -> Self {
+        let source = config
+            .as_deref()
+            .and_then(|path| ConfigSource::read(path, profile.as_deref()));
+        let explain = |value: String, origin: Origin| {
+            let origin = relabel_secret(origin, secret_keys);
+            Explained {
+                location: locate(source.as_ref(), &origin),
+                value,
+                origin,
+            }
+        };
+
+        let args = invocation
+            .args
+            .into_iter()
+            .zip(provenance.args)
+            .map(|(value, origin)| explain(value, origin))
+            .collect();
+        let cwd = invocation
+            .cwd
+            .zip(provenance.cwd)
+            .map(|(cwd, origin)| explain(cwd.display().to_string(), origin));
+
+        let inherited = env::vars_os()
+            .map(|(key, value)| {
+                (
+                    key.to_string_lossy().into_owned(),
+                    value.to_string_lossy().into_owned(),
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        let mut vars = BTreeMap::new();
+        if !invocation.clear_env {
+            for (name, value) in &inherited {
+                vars.insert(
+                    name.clone(),
+                    (Some(value.clone()), VarStatus::Inherited, Origin::Inherited),
+                );
+            }
+        }
+        for (name, value) in invocation.env {
+            let origin = provenance
+                .env
+                .get(&name)
+                .cloned()
+                .unwrap_or(Origin::Inherited);
+            let status = if origin == Origin::Inherited || origin == Origin::config("env_keep") {
+                VarStatus::Inherited
+            } else {
+                VarStatus::Set
+            };
+            vars.insert(name, (Some(value), status, origin));
+        }
+        for name in &invocation.env_remove {
+            if inherited.contains_key(name) {
+                let origin = provenance
+                    .env_remove
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| Origin::config("env_remove"));
+                vars.insert(name.clone(), (None, VarStatus::Removed, origin));
+            }
+        }
+        let mut env = vars
+            .into_iter()
+            .map(|(name, (value, status, origin))| {
+                let origin = relabel_secret(origin, secret_keys);
+                ExplainedVar {
+                    location: locate(source.as_ref(), &origin),
+                    name,
+                    value,
+                    status,
+                    origin,
+                }
+            })
+            .collect::<Vec<_>>();
+        if invocation.clear_env {
+            let origin = Origin::config("env_mode");
+            let location = locate(source.as_ref(), &origin);
+            env.extend(
+                inherited
+                    .keys()
+                    .filter(|name| !env.iter().any(|var| &var.name == *name))
+                    .map(|name| ExplainedVar {
+                        name: name.clone(),
+                        value: None,
+                        status: VarStatus::Removed,
+                        origin: origin.clone(),
+                        location: location.clone(),
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            env.sort_by(|left, right| left.name.cmp(&right.name));
+        }
+
+        let path = provenance.path.map(|path| {
+            path.components
+                .into_iter()
+                .map(|component| {
+                    let (step, origin) = match component.step {
+                        Some(step) => (Some(step.number), Origin::config(step.field)),
+                        None => (None, path.base.clone().unwrap_or(Origin::Inherited)),
+                    };
+                    ExplainedPathEntry {
+                        location: locate(source.as_ref(), &origin),
+                        value: component.value,
+                        step,
+                        origin,
+                    }
+                })
+                .collect()
+        });
+
+        Self {
+            alias,
+            config,
+            profile,
+            exec: invocation.exec,
+            args,
+            cwd,
+            env,
+            path,
+        }
+    }
+
+    /// The human-readable report: one line per element, followed by its
+    /// source.
+    pub fn render(&self) -> String {
+        let mut out = format!("alias: {}\n", self.alias);
+        match &self.config {
+            Some(path) => out.push_str(&format!("config: {}\n", path.display())),
+            None => out.push_str("config: (none; command resolved from PATH)\n"),
+        }
+        if let Some(profile) = &self.profile {
+            out.push_str(&format!("profile: {profile}\n"));
+        }
+        out.push_str(&format!("exec: {}\n", self.exec.display()));
+
+        let mut rows = Vec::new();
+        out.push_str("args:\n");
+        for (index, arg) in self.args.iter().enumerate() {
+            rows.push((
+                format!("[{index}] {}", shell_words::quote(&arg.value)),
+                source_label(&arg.origin, arg.location.as_ref()),
+            ));
+        }
+        push_rows(&mut out, std::mem::take(&mut rows));
+        if let Some(cwd) = &self.cwd {
+            out.push_str("cwd:\n");
+            push_rows(
+                &mut out,
+                vec![(
+                    cwd.value.clone(),
+                    source_label(&cwd.origin, cwd.location.as_ref()),
+                )],
+            );
+        }
+        out.push_str("env:\n");
+        for var in &self.env {
+            let entry = match (&var.value, var.status) {
+                (None, _) => format!("-{}", var.name),
+                (Some(value), VarStatus::Set) => format!("+{}={value}", var.name),
+                (Some(value), _) => format!(" {}={value}", var.name),
+            };
+            rows.push((entry, source_label(&var.origin, var.location.as_ref())));
+        }
+        push_rows(&mut out, std::mem::take(&mut rows));
+        if let Some(path) = &self.path {
+            out.push_str("PATH:\n");
+            for entry in path {
+                let label = source_label(&entry.origin, entry.location.as_ref());
+                let label = match entry.step {
+                    Some(step) => format!("step {step}: {label}"),
+                    None => format!("starting PATH: {label}"),
+                };
+                rows.push((entry.value.clone(), label));
+            }
+            push_rows(&mut out, rows);
+        }
+        out
+    }
+}
+
+/// Secrets are folded into `env` before the invocation is built, so their
+/// origins name `env.<KEY>`; point those at `env_secret.<KEY>` instead.
+fn relabel_secret(origin: Origin, secret_keys: &[String]) -> Origin {
+    match &origin {
+        Origin::Config { field } => match field.strip_prefix("env.") {
+            Some(key) if secret_keys.iter().any(|secret| secret == key) => {
+                Origin::config(format!("env_secret.{key}"))
+            }
+            _ => origin,
+        },
+        _ => origin,
+    }
+}
+
+fn source_label(origin: &Origin, location: Option<&Location>) -> String {
+    match (origin, location) {
+        // Already names its file and line.
+        (Origin::EnvFile { .. }, _) | (_, None) => origin.to_string(),
+        (_, Some(location)) => {
+            format!("{origin} ({}:{})", location.file.display(), location.line)
+        }
+    }
+}
+
+/// Writes `rows` as two columns. Long entries push their source onto the
+/// next line rather than widening the whole table.
+fn push_rows(out: &mut String, rows: Vec<(String, String)>) {
+    const MAX_WIDTH: usize = 48;
+    let width = rows
+        .iter()
+        .map(|(entry, _)| entry.chars().count())
+        .filter(|len| *len <= MAX_WIDTH)
+        .max()
+        .unwrap_or(0);
+    for (entry, source) in rows {
+        if entry.chars().count() > MAX_WIDTH {
+            out.push_str(&format!("  {entry}\n  {:width$}  <- {source}\n", ""));
+        } else {
+            out.push_str(&format!("  {entry:width$}  <- {source}\n"));
+        }
+    }
+}
+
+fn locate(source: Option<&ConfigSource>, origin: &Origin) -> Option<Location> {
+    match origin {
+        Origin::Config { field } => source?.locate(field),
+        Origin::EnvFile { path, line } => Some(Location {
+            file: path.clone(),
+            line: *line,
+        }),
+        _ => None,
+    }
+}
+
+/// The text of every file an alias config is assembled from, for mapping
+/// config fields back to lines.
+struct ConfigSource {
+    /// Lowest precedence first: the farthest `extends` ancestor's fragments
+    /// and file, down to the alias's own fragments and file.
+    files: Vec<SourceFile>,
+    profile: Option<String>,
+}
+
+struct SourceFile {
+    path: PathBuf,
+    text: String,
+}
+
+/// Fields whose arrays accumulate across `include` fragments and `extends`
+/// parents, so an index counts through every file in merge order. Other
+/// fields come whole from the highest-precedence file declaring them.
+const ACCUMULATED_FIELDS: [&str; 8] = [
+    "args",
+    "args_after",
+    "env_remove",
+    "env_keep",
+    "env_remove_matching",
+    "env_file",
+    "path",
+    "pathlists",
+];
+
+/// One step of a config field: a key, optionally indexing into an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FieldStep {
+    key: String,
+    index: Option<usize>,
+}
+
+impl ConfigSource {
+    fn read(path: &Path, profile: Option<&str>) -> Option<Self> {
+        let files = crate::parser::config_layers(path)
+            .into_iter()
+            .rev()
+            .flat_map(|layer| layer.fragments.into_iter().chain([layer.file]))
+            .filter_map(|path| {
+                let text = std::fs::read_to_string(&path).ok()?;
+                Some(SourceFile { path, text })
+            })
+            .collect::<Vec<_>>();
+        if files.is_empty() {
+            return None;
+        }
+        Some(Self {
+            files,
+            profile: profile.map(ToString::to_string),
+        })
+    }
+
+    /// Finds the file and line a field was written on. Fields are reported
+    /// against the manifest with the profile folded in, so a profile's own
+    /// entry is preferred: its `args` replace the alias's, its map entries
+    /// win, and its list entries follow the alias's.
+    fn locate(&self, field: &str) -> Option<Location> {
+        let steps = parse_field(field)?;
+        let roots = self
+            .files
+            .iter()
+            .filter_map(|file| Some((file, DeTable::parse(&file.text).ok()?)))
+            .collect::<Vec<_>>();
+        let roots = roots
+            .iter()
+            .map(|(file, root)| (*file, root.get_ref()))
+            .collect::<Vec<_>>();
+        let profile = self.profile.as_deref().and_then(|name| {
+            roots.iter().rev().find_map(|(file, root)| {
+                let profiles = root.get("profile")?;
+                let DeValue::Table(profiles) = profiles.get_ref() else {
+                    return None;
+                };
+                match profiles.get(name)?.get_ref() {
+                    DeValue::Table(profile) => Some((*file, profile)),
+                    _ => None,
+                }
+            })
+        });
+
+        let (file, span) = match (steps.last().and_then(|step| step.index), profile) {
+            (Some(index), Some((file, profile))) if steps[0].key != "args" => {
+                let own_len = merged_len(&roots, &steps);
+                if index < own_len {
+                    find_merged(&roots, &steps)
+                } else {
+                    let mut shifted = steps.clone();
+                    if let Some(step) = shifted.last_mut() {
+                        step.index = Some(index - own_len);
+                    }
+                    find(profile, &shifted).map(|span| (file, span))
+                }
+            }
+            (_, Some((file, profile))) => find(profile, &steps)
+                .map(|span| (file, span))
+                .or_else(|| find_merged(&roots, &steps)),
+            (_, None) => find_merged(&roots, &steps),
+        }?;
+        Some(Location {
+            file: file.path.clone(),
+            line: file.text[..span.start].matches('\n').count() + 1,
+        })
+    }
+}
+
+/// Finds a field in the files a manifest was merged from, given lowest
+/// precedence first.
+fn find_merged<'a>(
+    roots: &[(&'a SourceFile, &DeTable<'_>)],
+    steps: &[FieldStep],
+) -> Option<(&'a SourceFile, std::ops::Range<usize>)> {
+    let Some((mut index, parent, last)) = accumulated_index(steps) else {
+        return roots
+            .iter()
+            .rev()
+            .find_map(|(file, root)| find(root, steps).map(|span| (*file, span)));
+    };
+    for (file, root) in roots {
+        let len = array_len(root, parent, last);
+        if index < len {
+            let mut shifted = steps.to_vec();
+            if let Some(step) = shifted.last_mut() {
+                step.index = Some(index);
+            }
+            return find(root, &shifted).map(|span| (*file, span));
+        }
+        index -= len;
+    }
+    None
+}
+
+/// The merged length of the array a field indexes into.
+fn merged_len(roots: &[(&SourceFile, &DeTable<'_>)], steps: &[FieldStep]) -> usize {
+    let parent = &steps[..steps.len() - 1];
+    let last = &steps[steps.len() - 1].key;
+    if accumulated_index(steps).is_some() {
+        roots
+            .iter()
+            .map(|(_, root)| array_len(root, parent, last))
+            .sum()
+    } else {
+        roots
+            .iter()
+            .rev()
+            .map(|(_, root)| array_len(root, parent, last))
+            .find(|len| *len > 0)
+            .unwrap_or(0)
+    }
+}
+
+/// Splits an indexed field of an accumulated array into its index, the
+/// steps leading to the array, and the array's key.
+fn accumulated_index(steps: &[FieldStep]) -> Option<(usize, &[FieldStep], &str)> {
+    let (last, parent) = steps.split_last()?;
+    let index = last.index?;
+    ACCUMULATED_FIELDS
+        .contains(&steps[0].key.as_str())
+        .then_some((index, parent, last.key.as_str()))
+}
+
+fn find(table: &DeTable<'_>, steps: &[FieldStep]) -> Option<std::ops::Range<usize>> {
+    let (first, rest) = steps.split_first()?;
+    let mut value = table.get(first.key.as_str())?;
+    if let Some(index) = first.index {
+        value = match value.get_ref() {
+            DeValue::Array(array) => array.get(index)?,
+            _ => return None,
+        };
+    }
+    if rest.is_empty() {
+        return Some(value.span());
+    }
+    match value.get_ref() {
+        DeValue::Table(table) => find(table, rest),
+        _ => None,
+    }
+}
+
+fn array_len(table: &DeTable<'_>, parent: &[FieldStep], key: &str) -> usize {
+    let table = if parent.is_empty() {
+        Some(table)
+    } else {
+        lookup_table(table, parent)
+    };
+    match table.and_then(|table| table.get(key)).map(Spanned::get_ref) {
+        Some(DeValue::Array(array)) => array.len(),
+        _ => 0,
+    }
+}
+
+fn lookup_table<'a, 'i>(table: &'a DeTable<'i>, steps: &[FieldStep]) -> Option<&'a DeTable<'i>> {
+    let (first, rest) = steps.split_first()?;
+    match table.get(first.key.as_str())?.get_ref() {
+        DeValue::Table(inner) if first.index.is_none() => {
+            if rest.is_empty() {
+                Some(inner)
+            } else {
+                lookup_table(inner, rest)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Splits a field like `subcommands."get pods".args[0]` into its steps.
+fn parse_field(field: &str) -> Option<Vec<FieldStep>> {
+    let mut steps = Vec::new();
+    let mut chars = field.chars().peekable();
+    loop {
+        let mut key = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => key.push(chars.next()?),
+                    other => key.push(other),
+                }
+            }
+        } else {
+            while let Some(&next) = chars.peek() {
+                if next == '.' || next == '[' {
+                    break;
+                }
+                key.push(next);
+                chars.next();
+            }
+        }
+        let mut index = None;
+        if chars.peek() == Some(&'[') {
+            chars.next();
+            let digits = chars.by_ref().take_while(|c| *c != ']').collect::<String>();
+            index = Some(digits.parse().ok()?);
+        }
+        if key.is_empty() {
+            return None;
+        }
+        steps.push(FieldStep { key, index });
+        match chars.next() {
+            None => return Some(steps),
+            Some('.') => continue,
+            Some(_) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_field, ConfigSource, FieldStep};
+    use crate::test_support::ENV_LOCK;
+    use std::env;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn step(key: &str, index: Option<usize>) -> FieldStep {
+        FieldStep {
+            key: key.to_string(),
+            index,
+        }
+    }
+
+    #[test]
+    fn parses_dotted_quoted_and_indexed_fields() {
+        assert_eq!(
+            parse_field("subcommands.\"get pods\".args[2]"),
+            Some(vec![
+                step("subcommands", None),
+                step("get pods", None),
+                step("args", Some(2)),
+            ])
+        );
+        assert_eq!(
+            parse_field("env.FOO"),
+            Some(vec![step("env", None), step("FOO", None)])
+        );
+        assert_eq!(parse_field("args[x]"), None);
+        assert_eq!(parse_field("env..FOO"), None);
+    }
+
+    #[test]
+    fn locates_fields_preferring_the_active_profile() {
+        let temp = TempDir::new().expect("create tempdir");
+        let path = temp.path().join("exe.toml");
+        fs::write(
+            &path,
+            r#"exec = "kubectl"
+args = [
+  "get",
+  "pods",
+]
+env_remove = ["A"]
+
+[env]
+REGION = "eu"
+
+[profile.prod]
+args = ["--context", "prod"]
+env_remove = ["B"]
+
+[profile.prod.env]
+REGION = "us"
+"#,
+        )
+        .expect("write config");
+
+        let base = ConfigSource::read(&path, None).expect("read config");
+        let line = |source: &ConfigSource, field: &str| source.locate(field).map(|at| at.line);
+        assert_eq!(line(&base, "args[1]"), Some(4));
+        assert_eq!(line(&base, "env.REGION"), Some(9));
+        assert_eq!(line(&base, "env.MISSING"), None);
+
+        let prod = ConfigSource::read(&path, Some("prod")).expect("read config");
+        assert_eq!(line(&prod, "args[1]"), Some(12));
+        assert_eq!(line(&prod, "env.REGION"), Some(16));
+        assert_eq!(line(&prod, "env_remove[1]"), Some(13));
+    }
+
+    #[test]
+    fn locates_fields_inherited_from_parents_and_fragments() {
+        let _guard = ENV_LOCK.lock().expect("lock env");
+        let temp = TempDir::new().expect("create tempdir");
+        let write = |relative: &str, content: &str| {
+            let path = temp.path().join(relative);
+            fs::create_dir_all(path.parent().expect("parent dir")).expect("create dir");
+            fs::write(&path, content).expect("write config");
+            path
+        };
+        let base = write(
+            "base/exe.toml",
+            "exec = \"kubectl\"\nargs = [\"--base\"]\n\n[env]\nREGION = \"eu\"\nOWNER = \"ops\"\n",
+        );
+        let fragment = write("child/shared.toml", "[env]\nSHARED = \"1\"\n");
+        let child = write(
+            "child/exe.toml",
+            "extends = \"base\"\ninclude = [\"shared.toml\"]\nargs = [\"--child\"]\n\n[env]\nREGION = \"us\"\n",
+        );
+        env::set_var("CHOPPER_CONFIG_DIR", temp.path());
+        let source = ConfigSource::read(&child, None);
+        env::remove_var("CHOPPER_CONFIG_DIR");
+        let source = source.expect("read config");
+
+        let at = |field: &str| source.locate(field).map(|at| (at.file, at.line));
+        assert_eq!(at("args[0]"), Some((base.clone(), 2)));
+        assert_eq!(at("args[1]"), Some((child.clone(), 3)));
+        assert_eq!(at("env.REGION"), Some((child, 6)));
+        assert_eq!(at("env.OWNER"), Some((base, 6)));
+        assert_eq!(at("env.SHARED"), Some((fragment, 2)));
+        assert_eq!(at("args[2]"), None);
+    }
+}
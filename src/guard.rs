@@ -0,0 +1,231 @@
+use crate::manifest::{GuardConfig, GuardFallback, RuntimePatch};
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Exit status of `chopper-exe` when a `[guard]` rule refuses the invocation
+/// (`EX_NOPERM` from sysexits.h), so callers can tell it apart from both
+/// chopper errors (1) and the command's own failures.
+pub const GUARD_EXIT_CODE: i32 = 77;
+
+/// A `[guard]` rule refused the invocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardViolation {
+    message: String,
+}
+
+impl fmt::Display for GuardViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for GuardViolation {}
+
+fn violation(message: String) -> anyhow::Error {
+    GuardViolation { message }.into()
+}
+
+/// The arguments `[guard]` rules are checked against: the caller's runtime
+/// args plus the reconcile patch's `replace_args` and `append_args`. The
+/// alias's own `args` and overlays (`configured`) are left out, so they can
+/// neither satisfy a `require` rule nor trip a `deny` rule. Reconcile scripts
+/// usually rebuild `replace_args` from the configured args, so each
+/// `replace_args` entry that matches a configured arg is left out as well.
+pub(crate) fn guarded_args(
+    runtime_args: &[String],
+    configured: &[String],
+    patch: Option<&RuntimePatch>,
+) -> Vec<String> {
+    let mut args = runtime_args.to_vec();
+    if let Some(patch) = patch {
+        let mut configured = configured.iter().collect::<Vec<_>>();
+        for arg in patch.replace_args.iter().flatten() {
+            match configured.iter().position(|known| *known == arg) {
+                Some(index) => {
+                    configured.swap_remove(index);
+                }
+                None => args.push(arg.clone()),
+            }
+        }
+        args.extend(patch.append_args.iter().cloned());
+    }
+    args
+}
+
+/// Checks `guarded` (see [`guarded_args`]) against `guard`. `deny` and
+/// `require` are decided here; a `confirm` match shows the final `args` on
+/// the terminal and asks, or falls back to `guard.non_tty`.
+pub(crate) fn check(
+    guard: &GuardConfig,
+    exec: &str,
+    args: &[String],
+    guarded: &[String],
+) -> Result<()> {
+    let Some((rule, arg)) = evaluate(guard, guarded)? else {
+        return Ok(());
+    };
+    let interactive = io::stdin().is_terminal() && io::stderr().is_terminal();
+    if !interactive {
+        return match guard.non_tty {
+            GuardFallback::Allow => Ok(()),
+            GuardFallback::Refuse => Err(violation(format!(
+                "guard confirm rule `{rule}` matched argument `{arg}`, and there is no terminal to confirm on"
+            ))),
+        };
+    }
+
+    let command_line = std::iter::once(exec)
+        .chain(args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut stderr = io::stderr().lock();
+    write!(
+        stderr,
+        "chopper: guard confirm rule `{rule}` matched argument `{arg}`.\nRun `{command_line}`? [y/N] "
+    )?;
+    stderr.flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    if is_yes(&answer) {
+        return Ok(());
+    }
+    Err(violation(format!(
+        "guard confirm rule `{rule}` was not confirmed"
+    )))
+}
+
+/// Applies `deny` and `require` rules and returns the first `confirm` rule
+/// that matched, with the argument it matched.
+pub(crate) fn evaluate(guard: &GuardConfig, args: &[String]) -> Result<Option<(String, String)>> {
+    for rule in &guard.deny {
+        if let Some(arg) = first_match(&compile(rule, "deny")?, args) {
+            return Err(violation(format!(
+                "guard deny rule `{rule}` matched argument `{arg}`"
+            )));
+        }
+    }
+    for rule in &guard.require {
+        if first_match(&compile(rule, "require")?, args).is_none() {
+            return Err(violation(format!(
+                "guard require rule `{rule}` matched no argument"
+            )));
+        }
+    }
+    for rule in &guard.confirm {
+        if let Some(arg) = first_match(&compile(rule, "confirm")?, args) {
+            return Ok(Some((rule.clone(), arg.to_string())));
+        }
+    }
+    Ok(None)
+}
+
+fn compile(rule: &str, kind: &str) -> Result<Regex> {
+    Regex::new(rule).map_err(|err| anyhow!("invalid guard {kind} rule `{rule}`: {err}"))
+}
+
+fn first_match<'a>(regex: &Regex, args: &'a [String]) -> Option<&'a str> {
+    args.iter()
+        .map(String::as_str)
+        .find(|arg| regex.is_match(arg))
+}
+
+fn is_yes(answer: &str) -> bool {
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate, guarded_args, is_yes, GuardViolation};
+    use crate::manifest::{GuardConfig, RuntimePatch};
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn deny_and_require_rules_refuse_with_the_matching_rule() {
+        let guard = GuardConfig {
+            deny: vec!["^-auto-approve$".to_string()],
+            require: vec!["^-var-file=".to_string()],
+            confirm: vec!["^apply$".to_string(), "^destroy$".to_string()],
+            ..GuardConfig::default()
+        };
+
+        let err = evaluate(&guard, &args(&["apply", "-auto-approve"]))
+            .expect_err("deny rule should refuse");
+        assert_eq!(
+            err.downcast_ref::<GuardViolation>()
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("guard deny rule `^-auto-approve$` matched argument `-auto-approve`")
+        );
+
+        let err = evaluate(&guard, &args(&["plan"])).expect_err("require rule should refuse");
+        assert!(err.downcast_ref::<GuardViolation>().is_some());
+        assert!(
+            err.to_string()
+                .contains("guard require rule `^-var-file=` matched no argument"),
+            "{err}"
+        );
+
+        assert_eq!(
+            evaluate(&guard, &args(&["plan", "-var-file=prod.tfvars"])).expect("allowed"),
+            None
+        );
+        assert_eq!(
+            evaluate(&guard, &args(&["destroy", "-var-file=prod.tfvars"])).expect("confirm"),
+            Some(("^destroy$".to_string(), "destroy".to_string()))
+        );
+
+        let invalid = GuardConfig {
+            deny: vec!["(".to_string()],
+            ..GuardConfig::default()
+        };
+        let err = evaluate(&invalid, &args(&["x"])).expect_err("invalid regex should fail");
+        assert!(err.downcast_ref::<GuardViolation>().is_none());
+        assert!(
+            err.to_string().contains("invalid guard deny rule `(`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn guarded_args_are_runtime_args_and_patch_args() {
+        let patch = RuntimePatch {
+            replace_args: Some(args(&["apply"])),
+            append_args: args(&["-auto-approve"]),
+            ..RuntimePatch::default()
+        };
+        assert_eq!(
+            guarded_args(&args(&["plan"]), &[], Some(&patch)),
+            args(&["plan", "apply", "-auto-approve"])
+        );
+        assert_eq!(
+            guarded_args(&args(&["plan"]), &args(&["-force"]), None),
+            args(&["plan"])
+        );
+
+        // Configured args copied into `replace_args` stay unchecked, once
+        // per configured occurrence; anything the script adds is checked.
+        let patch = RuntimePatch {
+            replace_args: Some(args(&["-force", "plan", "-force", "-destroy"])),
+            ..RuntimePatch::default()
+        };
+        assert_eq!(
+            guarded_args(&args(&["plan"]), &args(&["-force"]), Some(&patch)),
+            args(&["plan", "plan", "-force", "-destroy"])
+        );
+    }
+
+    #[test]
+    fn only_yes_answers_confirm() {
+        for answer in ["y\n", "Y", " yes \n", "YES"] {
+            assert!(is_yes(answer), "{answer:?}");
+        }
+        for answer in ["", "\n", "n", "no", "yess", "sure"] {
+            assert!(!is_yes(answer), "{answer:?}");
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use crate::manifest::Invocation;
+use anyhow::{anyhow, Context, Result};
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::Pid;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One launch recorded by `chopper-exe`, stored as `<pid>.json` in the
+/// instance registry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Instance {
+    pub alias: String,
+    pub pid: u32,
+    /// Process start time in clock ticks since boot (`/proc/<pid>/stat`
+    /// field 22), which tells a live instance from a reused pid.
+    pub start_ticks: u64,
+    /// Unix time in seconds.
+    pub started_at: u64,
+    pub argv: Vec<String>,
+    pub journal_namespace: Option<String>,
+}
+
+impl Instance {
+    /// Seconds since the instance started.
+    pub fn uptime_secs(&self) -> u64 {
+        unix_now().saturating_sub(self.started_at)
+    }
+}
+
+/// Records the current process as a running instance of `alias`. chopper
+/// keeps its pid across `exec`, so the record describes the command itself.
+///
+/// Nothing is left to remove the record once the command exits, so records
+/// of exited processes are pruned here as well as when listing; otherwise
+/// they would pile up in the cache dir fallback without `XDG_RUNTIME_DIR`.
+pub(crate) fn record(alias: &str, invocation: &Invocation) -> Result<()> {
+    let pid = std::process::id();
+    let start_ticks = process_start_ticks(pid)
+        .ok_or_else(|| anyhow!("failed to read the start time of pid {pid}"))?;
+    let instance = Instance {
+        alias: alias.to_string(),
+        pid,
+        start_ticks,
+        started_at: unix_now(),
+        argv: std::iter::once(invocation.exec.to_string_lossy().into_owned())
+            .chain(invocation.args.iter().cloned())
+            .collect(),
+        journal_namespace: invocation
+            .journal
+            .as_ref()
+            .map(|journal| journal.namespace.clone()),
+    };
+
+    let dir = instances_dir();
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("failed to create instance registry {}", dir.display()))?;
+    // Best effort: a registry that cannot be read is reported by `--ps`.
+    let _ = prune(&dir);
+    let path = dir.join(format!("{pid}.json"));
+    let tmp_path = dir.join(format!(".{pid}.json.tmp"));
+    fs::write(&tmp_path, serde_json::to_vec(&instance)?)
+        .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, &path).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+/// Lists running instances, optionally of one alias, oldest first. Records
+/// whose process has exited (or whose pid now belongs to another process)
+/// are removed along the way.
+pub fn live_instances(alias: Option<&str>) -> Result<Vec<Instance>> {
+    let dir = instances_dir();
+    let mut instances = match prune(&dir) {
+        Ok(instances) => instances,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("failed to read instance registry {}", dir.display()))
+        }
+    };
+    instances.retain(|instance| alias.is_none_or(|alias| alias == instance.alias));
+    instances.sort_by_key(|instance| (instance.started_at, instance.pid));
+    Ok(instances)
+}
+
+/// Reads every record in `dir`, removing those whose process has exited or
+/// whose pid now belongs to another process, and returns the rest.
+fn prune(dir: &Path) -> io::Result<Vec<Instance>> {
+    let mut instances = Vec::new();
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        match read_instance(&path) {
+            Some(instance) if process_start_ticks(instance.pid) == Some(instance.start_ticks) => {
+                instances.push(instance);
+            }
+            _ => {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+    Ok(instances)
+}
+
+/// Sends `signal` to every running instance of `alias` and returns the pids
+/// that received it.
+pub fn kill_instances(alias: &str, signal: &str) -> Result<Vec<u32>> {
+    let signal = crate::signal_relay::parse_signal(signal)
+        .ok_or_else(|| anyhow!("`{signal}` is not a signal name or number"))?;
+    let instances = live_instances(Some(alias))?;
+    if instances.is_empty() {
+        return Err(anyhow!("no running instances of `{alias}`"));
+    }
+    let mut signalled = Vec::with_capacity(instances.len());
+    for instance in instances {
+        send_signal(instance.pid, signal)
+            .with_context(|| format!("failed to send {signal} to pid {}", instance.pid))?;
+        signalled.push(instance.pid);
+    }
+    Ok(signalled)
+}
+
+fn send_signal(pid: u32, signal: Signal) -> Result<()> {
+    let pid = i32::try_from(pid).map_err(|_| anyhow!("pid out of range"))?;
+    kill(Pid::from_raw(pid), signal)?;
+    Ok(())
+}
+
+fn instances_dir() -> PathBuf {
+    crate::runtime_dir().join("instances")
+}
+
+fn read_instance(path: &Path) -> Option<Instance> {
+    let bytes = fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Reads the start time field of `/proc/<pid>/stat`. The command name can
+/// contain spaces and parentheses, so fields are counted after its last `)`.
+fn process_start_ticks(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let (_, fields) = stat.rsplit_once(')')?;
+    // `fields` starts at field 3 (state); start time is field 22.
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{live_instances, process_start_ticks, record, Instance};
+    use crate::manifest::Manifest;
+    use crate::test_support::ENV_LOCK;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn records_the_current_process_and_prunes_stale_entries() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        let runtime = TempDir::new().expect("create runtime dir");
+        let previous = env::var_os("XDG_RUNTIME_DIR");
+        env::set_var("XDG_RUNTIME_DIR", runtime.path());
+
+        let invocation = Manifest::simple(PathBuf::from("/usr/bin/migrate"))
+            .with_args(vec!["--up".to_string()])
+            .build_invocation(&[], None)
+            .expect("build invocation");
+        record("migrate", &invocation).expect("record instance");
+
+        let registry = runtime.path().join("chopper/instances");
+        let stale = Instance {
+            alias: "migrate".to_string(),
+            pid: std::process::id(),
+            start_ticks: process_start_ticks(std::process::id()).expect("own start time") + 1,
+            started_at: 0,
+            argv: vec!["old".to_string()],
+            journal_namespace: None,
+        };
+        let stale_path = registry.join("1.json");
+        fs::write(&stale_path, serde_json::to_vec(&stale).expect("encode")).expect("write");
+
+        let instances = live_instances(Some("migrate")).expect("list instances");
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].pid, std::process::id());
+        assert_eq!(instances[0].argv, vec!["/usr/bin/migrate", "--up"]);
+        assert!(!stale_path.exists());
+        assert!(live_instances(Some("deploy"))
+            .expect("list other alias")
+            .is_empty());
+
+        match previous {
+            Some(value) => env::set_var("XDG_RUNTIME_DIR", value),
+            None => env::remove_var("XDG_RUNTIME_DIR"),
+        }
+    }
+
+    #[test]
+    fn recording_prunes_entries_of_exited_processes() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        let runtime = TempDir::new().expect("create runtime dir");
+        let previous = env::var_os("XDG_RUNTIME_DIR");
+        env::set_var("XDG_RUNTIME_DIR", runtime.path());
+
+        let registry = runtime.path().join("chopper/instances");
+        fs::create_dir_all(&registry).expect("create registry");
+        let mut exited = std::process::Command::new("true")
+            .spawn()
+            .expect("spawn true");
+        let exited_pid = exited.id();
+        exited.wait().expect("wait for true");
+        let stale = Instance {
+            alias: "migrate".to_string(),
+            pid: exited_pid,
+            start_ticks: 1,
+            started_at: 0,
+            argv: vec!["old".to_string()],
+            journal_namespace: None,
+        };
+        let stale_path = registry.join(format!("{exited_pid}.json"));
+        fs::write(&stale_path, serde_json::to_vec(&stale).expect("encode")).expect("write");
+        let garbage_path = registry.join("7.json");
+        fs::write(&garbage_path, "not json").expect("write");
+
+        let invocation = Manifest::simple(PathBuf::from("/usr/bin/migrate"))
+            .build_invocation(&[], None)
+            .expect("build invocation");
+        record("migrate", &invocation).expect("record instance");
+
+        assert!(!stale_path.exists());
+        assert!(!garbage_path.exists());
+        assert!(registry
+            .join(format!("{}.json", std::process::id()))
+            .exists());
+
+        match previous {
+            Some(value) => env::set_var("XDG_RUNTIME_DIR", value),
+            None => env::remove_var("XDG_RUNTIME_DIR"),
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::env_validation::{self, EnvKeyViolation};
+use anyhow::{anyhow, Result};
+use std::env;
+use std::path::Path;
+
+/// Values available to `${...}` placeholders when an alias is invoked.
+pub(crate) struct InterpolationContext<'a> {
+    pub alias: &'a str,
+    pub alias_dir: Option<&'a Path>,
+}
+
+pub(crate) fn contains_placeholders(value: &str) -> bool {
+    value.contains("${") || has_tilde_prefix(value)
+}
+
+/// Expands `~`, `${HOME}`, `${env:NAME}`, `${env:NAME:-default}`,
+/// `${alias_dir}`, and `${alias}` in `value`. `$${` produces a literal `${`.
+/// Any other `${...}` text is left untouched so shell snippets in `args` keep
+/// working. `field` is used verbatim as the error prefix.
+pub(crate) fn expand(value: &str, field: &str, context: &InterpolationContext) -> Result<String> {
+    if !contains_placeholders(value) {
+        return Ok(value.to_string());
+    }
+
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    if has_tilde_prefix(value) {
+        out.push_str(&home_dir(field, "~")?);
+        rest = &value[1..];
+    }
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+        if let Some(after) = tail.strip_prefix("$${") {
+            out.push_str("${");
+            rest = after;
+            continue;
+        }
+        let Some(body) = tail.strip_prefix("${") else {
+            out.push('$');
+            rest = &tail[1..];
+            continue;
+        };
+        let Some(end) = body.find('}') else {
+            if is_reserved_placeholder(body) {
+                return Err(anyhow!(
+                    "{field} contains an unterminated placeholder `{tail}`"
+                ));
+            }
+            out.push_str(tail);
+            rest = "";
+            break;
+        };
+        let placeholder = &body[..end];
+        match resolve_placeholder(placeholder, field, context)? {
+            Some(resolved) => out.push_str(&resolved),
+            None => out.push_str(&tail[..end + 3]),
+        }
+        rest = &body[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+pub(crate) fn expand_all(
+    values: &mut [String],
+    field: &str,
+    context: &InterpolationContext,
+) -> Result<()> {
+    for value in values {
+        *value = expand(value, field, context)?;
+    }
+    Ok(())
+}
+
+fn has_tilde_prefix(value: &str) -> bool {
+    value == "~" || value.starts_with("~/")
+}
+
+fn is_reserved_placeholder(body: &str) -> bool {
+    body.starts_with("env:") || body.starts_with("HOME") || body.starts_with("alias")
+}
+
+fn resolve_placeholder(
+    placeholder: &str,
+    field: &str,
+    context: &InterpolationContext,
+) -> Result<Option<String>> {
+    match placeholder {
+        "HOME" => home_dir(field, "${HOME}").map(Some),
+        "alias" => Ok(Some(context.alias.to_string())),
+        "alias_dir" => context
+            .alias_dir
+            .map(|dir| Some(dir.to_string_lossy().into_owned()))
+            .ok_or_else(|| {
+                anyhow!("{field} references `${{alias_dir}}` but the alias has no config file")
+            }),
+        _ => match placeholder.strip_prefix("env:") {
+            Some(spec) => resolve_env_placeholder(spec, field).map(Some),
+            None => Ok(None),
+        },
+    }
+}
+
+fn resolve_env_placeholder(spec: &str, field: &str) -> Result<String> {
+    let (name, default) = match spec.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (spec, None),
+    };
+    if name.is_empty() {
+        return Err(anyhow!(
+            "{field} contains `${{env:{spec}}}` without a variable name"
+        ));
+    }
+    if name.chars().any(char::is_whitespace) {
+        return Err(anyhow!(
+            "{field} references invalid environment variable name `{name}`"
+        ));
+    }
+    match env_validation::validate_env_key(name) {
+        Ok(()) => {}
+        Err(EnvKeyViolation::ContainsEquals) | Err(EnvKeyViolation::ContainsNul) => {
+            return Err(anyhow!(
+                "{field} references invalid environment variable name `{name}`"
+            ));
+        }
+    }
+
+    let value = match env::var_os(name) {
+        Some(value) => Some(value.into_string().map_err(|_| {
+            anyhow!("{field} references environment variable `{name}` with a non-UTF-8 value")
+        })?),
+        None => None,
+    };
+    match (value, default) {
+        (Some(value), Some(default)) if value.is_empty() => Ok(default.to_string()),
+        (Some(value), _) => Ok(value),
+        (None, Some(default)) => Ok(default.to_string()),
+        (None, None) => Err(anyhow!(
+            "{field} references unset environment variable `{name}`"
+        )),
+    }
+}
+
+fn home_dir(field: &str, placeholder: &str) -> Result<String> {
+    match env::var("HOME") {
+        Ok(home) if !home.is_empty() => Ok(home),
+        _ => Err(anyhow!("{field} uses `{placeholder}` but HOME is not set")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, InterpolationContext};
+    use crate::test_support::ENV_LOCK;
+    use std::env;
+    use std::path::Path;
+
+    fn context() -> InterpolationContext<'static> {
+        InterpolationContext {
+            alias: "kpods",
+            alias_dir: Some(Path::new("/cfg/kpods")),
+        }
+    }
+
+    #[test]
+    fn expands_supported_placeholders() {
+        let _guard = ENV_LOCK.lock().expect("lock env");
+        let old_home = env::var_os("HOME");
+        env::set_var("HOME", "/home/tester");
+        env::set_var("CHOPPER_INTERP_SET", "value");
+        env::remove_var("CHOPPER_INTERP_UNSET");
+
+        let expanded = expand(
+            "${HOME}|${env:CHOPPER_INTERP_SET}|${env:CHOPPER_INTERP_UNSET:-fallback}|${alias}|${alias_dir}",
+            "field `args`",
+            &context(),
+        )
+        .expect("expand placeholders");
+        assert_eq!(expanded, "/home/tester|value|fallback|kpods|/cfg/kpods");
+        assert_eq!(
+            expand("~/bin", "field `exec`", &context()).expect("expand tilde"),
+            "/home/tester/bin"
+        );
+        env::remove_var("CHOPPER_INTERP_SET");
+        match old_home {
+            Some(home) => env::set_var("HOME", home),
+            None => env::remove_var("HOME"),
+        }
+    }
+
+    #[test]
+    fn leaves_unreserved_shell_syntax_and_escapes_untouched() {
+        let value = "printf '%s' \"${CHOPPER_DROP-unset}\" $$ ~user a~/b";
+        assert_eq!(
+            expand(value, "field `args`", &context()).expect("expand"),
+            value
+        );
+        assert_eq!(
+            expand("$${alias}", "field `args`", &context()).expect("expand escape"),
+            "${alias}"
+        );
+    }
+
+    #[test]
+    fn reports_field_name_for_invalid_references() {
+        let _guard = ENV_LOCK.lock().expect("lock env");
+        env::remove_var("CHOPPER_INTERP_MISSING");
+
+        let err = expand(
+            "${env:CHOPPER_INTERP_MISSING}",
+            "field `env.TOKEN`",
+            &context(),
+        )
+        .expect_err("unset variable should fail");
+        assert!(
+            err.to_string().contains(
+                "field `env.TOKEN` references unset environment variable `CHOPPER_INTERP_MISSING`"
+            ),
+            "{err}"
+        );
+
+        let err = expand("${env:}", "field `args`", &context()).expect_err("empty name");
+        assert!(
+            err.to_string().contains("field `args` contains `${env:}`"),
+            "{err}"
+        );
+
+        let err = expand("${env:HOME", "field `args`", &context()).expect_err("unterminated");
+        assert!(
+            err.to_string().contains("unterminated placeholder"),
+            "{err}"
+        );
+
+        let no_dir = InterpolationContext {
+            alias: "plain",
+            alias_dir: None,
+        };
+        let err = expand("${alias_dir}/x", "field `path.append_one`", &no_dir)
+            .expect_err("missing alias dir");
+        assert!(
+            err.to_string()
+                .contains("field `path.append_one` references `${alias_dir}`"),
+            "{err}"
+        );
+    }
+}
@@ -3,16 +3,26 @@ mod alias_admin_parse;
 mod alias_doc;
 pub mod alias_paths;
 mod alias_validation;
+mod arg_placement;
 mod arg_validation;
+pub mod audit;
 pub mod broker;
 pub mod cache;
 pub mod completion;
+pub mod concurrency;
 pub mod config_diagnostics;
+mod dotenv;
+pub mod dry_run;
+mod durations;
 pub mod env_util;
 mod env_validation;
 pub mod exe_runtime;
 pub mod exec_resolution;
 mod executor;
+pub mod explain;
+pub mod guard;
+pub mod instances;
+mod interpolation;
 mod journal_broker_client;
 mod journal_validation;
 pub mod manifest;
@@ -20,6 +30,8 @@ pub mod parser;
 mod path_mutation;
 mod path_mutation_validation;
 mod path_validation;
+mod process_limits;
+pub mod provenance;
 mod reconcile;
 mod rhai_api_catalog;
 mod rhai_engine;
@@ -27,9 +39,12 @@ mod rhai_facade;
 mod rhai_facade_validation;
 mod rhai_wiring;
 pub mod runner_resolution;
+mod secrets;
+mod signal_relay;
 mod string_validation;
 pub mod tui;
 mod tui_nvim;
+mod version_requirement;
 mod wrapper_sync;
 
 #[cfg(test)]
@@ -47,6 +62,20 @@ pub fn config_dir() -> PathBuf {
         .unwrap_or_else(|| PathBuf::from(".chopper"))
 }
 
+/// Per-user directory for state that only matters while commands run, such
+/// as `[concurrency]` locks and the instance registry: `$XDG_RUNTIME_DIR/chopper`
+/// (cleared at logout), or the cache dir when there is no runtime dir.
+pub fn runtime_dir() -> PathBuf {
+    directories::ProjectDirs::from("", "", "chopper")
+        .and_then(|dirs| dirs.runtime_dir().map(|dir| dir.to_path_buf()))
+        .unwrap_or_else(cache::cache_dir)
+}
+
+/// The global settings file, `chopper.toml` in the config root.
+pub fn settings_path() -> PathBuf {
+    alias_paths::settings_path(&config_dir())
+}
+
 pub fn find_config(name: &str) -> Option<PathBuf> {
     alias_paths::find_exec_config(&config_dir(), name)
 }
@@ -3,13 +3,14 @@ mod test_support;
 
 use anyhow::{anyhow, Result};
 use chopper::{
-    alias_admin, alias_paths, cache, completion, config_diagnostics, env_util, exec_resolution,
-    manifest, parser, runner_resolution, tui,
+    alias_admin, alias_paths, audit, cache, completion, config_diagnostics, env_util, exe_runtime,
+    exec_resolution, instances, manifest, parser, runner_resolution, tui,
 };
 use std::env;
 use std::os::unix::process::CommandExt;
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::SystemTime;
 
 fn main() -> Result<()> {
     let args: Vec<String> = env::args().collect();
@@ -59,6 +60,11 @@ enum BuiltinAction {
     Complete(Vec<String>),
     Alias(Vec<String>),
     Tui(Vec<String>),
+    Ps(Vec<String>),
+    Kill(Vec<String>),
+    DryRun(Vec<String>),
+    Explain(Vec<String>),
+    Audit(Vec<String>),
 }
 
 fn detect_builtin_action(args: &[String]) -> Option<BuiltinAction> {
@@ -109,6 +115,21 @@ fn detect_builtin_action(args: &[String]) -> Option<BuiltinAction> {
     if flag == "--tui" {
         return Some(BuiltinAction::Tui(args[2..].to_vec()));
     }
+    if flag == "--ps" {
+        return Some(BuiltinAction::Ps(args[2..].to_vec()));
+    }
+    if flag == "--kill" {
+        return Some(BuiltinAction::Kill(args[2..].to_vec()));
+    }
+    if flag == "--dry-run" {
+        return Some(BuiltinAction::DryRun(args[2..].to_vec()));
+    }
+    if flag == "--explain" {
+        return Some(BuiltinAction::Explain(args[2..].to_vec()));
+    }
+    if flag == "--audit" {
+        return Some(BuiltinAction::Audit(args[2..].to_vec()));
+    }
 
     None
 }
@@ -138,12 +159,26 @@ fn run_builtin_action(action: BuiltinAction) {
   --help --alias               Show alias subcommand help"
             );
             println!("  --tui                        Open interactive terminal UI");
+            println!("  --ps [alias] [--json]        List running alias instances");
+            println!("  --kill <alias> [--signal=SIG]");
+            println!("                               Signal running instances (default TERM)");
+            println!("  --dry-run [--json] <alias> [args...]");
+            println!(
+                "                               Show what an alias would run, without running it"
+            );
+            println!("  --explain [--json] <alias> [args...]");
+            println!("                               Show where each arg and env var comes from");
+            println!(
+                "  --audit tail|query [--alias NAME] [--since WHEN] [--until WHEN] [-n N] [--json]"
+            );
+            println!("                               Read the audit log");
             println!();
             println!("Environment overrides:");
             println!("  CHOPPER_CONFIG_DIR=/path/to/config-root");
             println!("  CHOPPER_CACHE_DIR=/path/to/cache-root");
             println!("  CHOPPER_DISABLE_CACHE=<truthy>   # 1,true,yes,on");
             println!("  CHOPPER_DISABLE_RECONCILE=<truthy>   # 1,true,yes,on");
+            println!("  CHOPPER_PROFILE=<name>           # select [profile.<name>]");
         }
         BuiltinAction::Version => {
             println!("chopper {}", env!("CARGO_PKG_VERSION"));
@@ -185,7 +220,372 @@ fn run_builtin_action(action: BuiltinAction) {
             };
             std::process::exit(tui::run_tui(options));
         }
+        BuiltinAction::Ps(raw_args) => {
+            let options = match parse_ps_options(&raw_args) {
+                Ok(options) => options,
+                Err(err) => {
+                    eprintln!("{err}");
+                    std::process::exit(2);
+                }
+            };
+            std::process::exit(run_ps(&options));
+        }
+        BuiltinAction::Kill(raw_args) => {
+            let options = match parse_kill_options(&raw_args) {
+                Ok(options) => options,
+                Err(err) => {
+                    eprintln!("{err}");
+                    std::process::exit(2);
+                }
+            };
+            std::process::exit(run_kill(&options));
+        }
+        BuiltinAction::DryRun(raw_args) => {
+            let options = match parse_report_options("--dry-run", &raw_args) {
+                Ok(options) => options,
+                Err(err) => {
+                    eprintln!("{err}");
+                    std::process::exit(2);
+                }
+            };
+            std::process::exit(run_dry_run(&options));
+        }
+        BuiltinAction::Explain(raw_args) => {
+            let options = match parse_report_options("--explain", &raw_args) {
+                Ok(options) => options,
+                Err(err) => {
+                    eprintln!("{err}");
+                    std::process::exit(2);
+                }
+            };
+            std::process::exit(run_explain(&options));
+        }
+        BuiltinAction::Audit(raw_args) => {
+            let options = match parse_audit_options(&raw_args, SystemTime::now()) {
+                Ok(options) => options,
+                Err(err) => {
+                    eprintln!("{err}");
+                    std::process::exit(2);
+                }
+            };
+            std::process::exit(run_audit(&options));
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PsOptions {
+    alias: Option<String>,
+    json: bool,
+}
+
+fn parse_ps_options(raw_args: &[String]) -> Result<PsOptions> {
+    let mut options = PsOptions {
+        alias: None,
+        json: false,
+    };
+    for arg in raw_args {
+        match arg.as_str() {
+            "--json" => options.json = true,
+            flag if flag.starts_with('-') => {
+                return Err(anyhow!(
+                    "unknown --ps option `{flag}`; usage: chopper --ps [alias] [--json]"
+                ));
+            }
+            alias if options.alias.is_none() => options.alias = Some(alias.to_string()),
+            extra => {
+                return Err(anyhow!(
+                    "unexpected --ps argument `{extra}`; usage: chopper --ps [alias] [--json]"
+                ));
+            }
+        }
+    }
+    Ok(options)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KillOptions {
+    alias: String,
+    signal: String,
+}
+
+fn parse_kill_options(raw_args: &[String]) -> Result<KillOptions> {
+    const USAGE: &str = "usage: chopper --kill <alias> [--signal=SIG]";
+    let mut alias = None;
+    let mut signal = None;
+    let mut args = raw_args.iter();
+    while let Some(arg) = args.next() {
+        if let Some(value) = arg.strip_prefix("--signal=") {
+            signal = Some(value.to_string());
+        } else if arg == "--signal" || arg == "-s" {
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow!("--signal needs a value; {USAGE}"))?;
+            signal = Some(value.clone());
+        } else if arg.starts_with('-') {
+            return Err(anyhow!("unknown --kill option `{arg}`; {USAGE}"));
+        } else if alias.is_none() {
+            alias = Some(arg.clone());
+        } else {
+            return Err(anyhow!("unexpected --kill argument `{arg}`; {USAGE}"));
+        }
+    }
+    Ok(KillOptions {
+        alias: alias.ok_or_else(|| anyhow!("--kill needs an alias; {USAGE}"))?,
+        signal: signal.unwrap_or_else(|| "TERM".to_string()),
+    })
+}
+
+fn run_ps(options: &PsOptions) -> i32 {
+    let instances = match instances::live_instances(options.alias.as_deref()) {
+        Ok(instances) => instances,
+        Err(err) => {
+            eprintln!("{err:#}");
+            return 1;
+        }
+    };
+    if options.json {
+        match serde_json::to_string_pretty(&instances) {
+            Ok(json) => println!("{json}"),
+            Err(err) => {
+                eprintln!("{err}");
+                return 1;
+            }
+        }
+        return 0;
+    }
+
+    let alias_width = instances
+        .iter()
+        .map(|instance| instance.alias.len())
+        .chain(std::iter::once("ALIAS".len()))
+        .max()
+        .unwrap_or(0);
+    println!(
+        "{:>7}  {:<alias_width$}  {:>9}  COMMAND",
+        "PID", "ALIAS", "UPTIME"
+    );
+    for instance in &instances {
+        println!(
+            "{:>7}  {:<alias_width$}  {:>9}  {}",
+            instance.pid,
+            instance.alias,
+            format_uptime(instance.uptime_secs()),
+            shell_words::join(&instance.argv)
+        );
+    }
+    0
+}
+
+fn format_uptime(secs: u64) -> String {
+    let (days, hours, minutes, seconds) =
+        (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{days}d{hours:02}h")
+    } else if hours > 0 {
+        format!("{hours}h{minutes:02}m")
+    } else if minutes > 0 {
+        format!("{minutes}m{seconds:02}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+fn run_kill(options: &KillOptions) -> i32 {
+    match instances::kill_instances(&options.alias, &options.signal) {
+        Ok(pids) => {
+            for pid in pids {
+                println!("signalled pid {pid} ({})", options.alias);
+            }
+            0
+        }
+        Err(err) => {
+            eprintln!("{err:#}");
+            1
+        }
+    }
+}
+
+/// Options of `--dry-run` and `--explain`, which both resolve an alias
+/// invocation and report on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ReportOptions {
+    json: bool,
+    alias: String,
+    args: Vec<String>,
+}
+
+impl ReportOptions {
+    /// The argv `chopper-exe` would get for this invocation.
+    fn exe_args(&self) -> Vec<String> {
+        ["chopper-exe".to_string(), self.alias.clone()]
+            .into_iter()
+            .chain(self.args.iter().cloned())
+            .collect()
+    }
+}
+
+/// Options go before the alias; everything after it is passed to the alias
+/// untouched, as on a real invocation.
+fn parse_report_options(flag: &str, raw_args: &[String]) -> Result<ReportOptions> {
+    let usage = format!("usage: chopper {flag} [--json] <alias> [args...]");
+    let mut json = false;
+    for (index, arg) in raw_args.iter().enumerate() {
+        match arg.as_str() {
+            "--json" => json = true,
+            option if option.starts_with('-') => {
+                return Err(anyhow!("unknown {flag} option `{option}`; {usage}"));
+            }
+            alias => {
+                return Ok(ReportOptions {
+                    json,
+                    alias: alias.to_string(),
+                    args: raw_args[index + 1..].to_vec(),
+                });
+            }
+        }
+    }
+    Err(anyhow!("{flag} needs an alias; {usage}"))
+}
+
+fn run_dry_run(options: &ReportOptions) -> i32 {
+    match exe_runtime::dry_run(&options.exe_args()) {
+        Ok(report) => print_report(options.json, &report, report.render()),
+        Err(err) => {
+            eprintln!("{err:#}");
+            1
+        }
+    }
+}
+
+fn run_explain(options: &ReportOptions) -> i32 {
+    match exe_runtime::explain(&options.exe_args()) {
+        Ok(report) => print_report(options.json, &report, report.render()),
+        Err(err) => {
+            eprintln!("{err:#}");
+            1
+        }
+    }
+}
+
+fn print_report(json: bool, report: &impl serde::Serialize, rendered: String) -> i32 {
+    if !json {
+        print!("{rendered}");
+        return 0;
+    }
+    match serde_json::to_string_pretty(report) {
+        Ok(json) => {
+            println!("{json}");
+            0
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AuditView {
+    /// The last `lines` matching records.
+    Tail,
+    /// Every matching record.
+    Query,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AuditOptions {
+    view: AuditView,
+    query: audit::AuditQuery,
+    lines: usize,
+    json: bool,
+}
+
+fn parse_audit_options(raw_args: &[String], now: SystemTime) -> Result<AuditOptions> {
+    const USAGE: &str = "usage: chopper --audit tail|query [--alias NAME] [--since WHEN] [--until WHEN] [-n N] [--json]";
+    let mut args = raw_args.iter();
+    let view = match args.next().map(String::as_str) {
+        Some("tail") => AuditView::Tail,
+        Some("query") => AuditView::Query,
+        Some(other) => return Err(anyhow!("unknown --audit command `{other}`; {USAGE}")),
+        None => return Err(anyhow!("--audit needs `tail` or `query`; {USAGE}")),
+    };
+    let mut options = AuditOptions {
+        view,
+        query: audit::AuditQuery::default(),
+        lines: 10,
+        json: false,
+    };
+    while let Some(arg) = args.next() {
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value)),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || -> Result<String> {
+            match inline {
+                Some(value) => Ok(value.to_string()),
+                None => args
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| anyhow!("{name} needs a value; {USAGE}")),
+            }
+        };
+        match name {
+            "--json" if inline.is_none() => options.json = true,
+            "--alias" => options.query.alias = Some(value()?),
+            "--since" | "--until" => {
+                let raw = value()?;
+                let bound = audit::parse_time_bound(&raw, now).ok_or_else(|| {
+                    anyhow!(
+                        "{name} must be a UTC date or time like 2026-10-01 or 2026-10-01T12:00:00Z, or a duration like 2h: `{raw}`"
+                    )
+                })?;
+                if name == "--since" {
+                    options.query.since = Some(bound);
+                } else {
+                    options.query.until = Some(bound);
+                }
+            }
+            "-n" | "--lines" if view == AuditView::Tail => {
+                let raw = value()?;
+                options.lines = raw
+                    .parse()
+                    .map_err(|_| anyhow!("{name} must be a count: `{raw}`; {USAGE}"))?;
+            }
+            _ => return Err(anyhow!("unknown --audit option `{arg}`; {USAGE}")),
+        }
     }
+    Ok(options)
+}
+
+fn run_audit(options: &AuditOptions) -> i32 {
+    let records = match audit::AuditSettings::load()
+        .and_then(|settings| audit::read(&settings, &options.query))
+    {
+        Ok(records) => records,
+        Err(err) => {
+            eprintln!("{err:#}");
+            return 1;
+        }
+    };
+    let shown = match options.view {
+        AuditView::Tail => &records[records.len().saturating_sub(options.lines)..],
+        AuditView::Query => &records[..],
+    };
+    for record in shown {
+        if !options.json {
+            println!("{}", record.render());
+            continue;
+        }
+        match serde_json::to_string(record) {
+            Ok(json) => println!("{json}"),
+            Err(err) => {
+                eprintln!("{err}");
+                return 1;
+            }
+        }
+    }
+    0
 }
 
 fn parse_tui_options(raw_args: &[String]) -> Result<tui::TuiOptions> {
@@ -307,6 +707,14 @@ fn run_complete_builtin(raw_args: &[String]) -> i32 {
 }
 
 fn load_manifest(alias: &str, path: &std::path::Path) -> Result<manifest::Manifest> {
+    let profile = manifest::ProfileRequest::resolve(None);
+    load_raw_manifest(alias, path)?
+        .apply_profile(profile.as_ref())?
+        .expand_placeholders(alias)?
+        .select_exec()
+}
+
+fn load_raw_manifest(alias: &str, path: &std::path::Path) -> Result<manifest::Manifest> {
     if !cache_enabled() {
         return parser::parse(path);
     }
@@ -358,13 +766,18 @@ fn is_direct_chopper_name(exe_name: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{cache_enabled, detect_builtin_action, parse_tui_options, BuiltinAction};
+    use super::{
+        cache_enabled, detect_builtin_action, format_uptime, parse_audit_options,
+        parse_kill_options, parse_ps_options, parse_report_options, parse_tui_options, AuditView,
+        BuiltinAction, KillOptions, PsOptions, ReportOptions,
+    };
     use crate::test_support::ENV_LOCK;
     use chopper::{config_dir, find_config};
     use std::env;
     use std::fs;
     use std::os::unix::fs::symlink;
     use std::path::PathBuf;
+    use std::time::{Duration, UNIX_EPOCH};
     use tempfile::TempDir;
 
     #[test]
@@ -467,4 +880,127 @@ mod tests {
         let err = parse_tui_options(&["--tmux=off".into()]).expect_err("tmux flags removed");
         assert!(err.to_string().contains("no options"), "{err}");
     }
+
+    #[test]
+    fn parses_ps_and_kill_options() {
+        assert_eq!(
+            detect_builtin_action(&["chopper".into(), "--ps".into()]),
+            Some(BuiltinAction::Ps(Vec::new()))
+        );
+        assert_eq!(
+            parse_ps_options(&["migrate".into(), "--json".into()]).expect("ps options"),
+            PsOptions {
+                alias: Some("migrate".to_string()),
+                json: true,
+            }
+        );
+        assert!(parse_ps_options(&["a".into(), "b".into()]).is_err());
+        assert!(parse_ps_options(&["--wide".into()]).is_err());
+
+        assert_eq!(
+            parse_kill_options(&["migrate".into()]).expect("kill options"),
+            KillOptions {
+                alias: "migrate".to_string(),
+                signal: "TERM".to_string(),
+            }
+        );
+        for args in [
+            vec!["--signal=INT".to_string(), "migrate".to_string()],
+            vec![
+                "migrate".to_string(),
+                "--signal".to_string(),
+                "INT".to_string(),
+            ],
+        ] {
+            assert_eq!(
+                parse_kill_options(&args).expect("kill options").signal,
+                "INT"
+            );
+        }
+        assert!(parse_kill_options(&[]).is_err());
+        assert!(parse_kill_options(&["migrate".into(), "--signal".into()]).is_err());
+    }
+
+    #[test]
+    fn parses_report_options_up_to_the_alias() {
+        assert_eq!(
+            detect_builtin_action(&["chopper".into(), "--dry-run".into(), "kpods".into()]),
+            Some(BuiltinAction::DryRun(vec!["kpods".to_string()]))
+        );
+        assert_eq!(
+            detect_builtin_action(&["chopper".into(), "--explain".into(), "kpods".into()]),
+            Some(BuiltinAction::Explain(vec!["kpods".to_string()]))
+        );
+        assert_eq!(
+            parse_report_options(
+                "--explain",
+                &[
+                    "--json".into(),
+                    "kpods".into(),
+                    "--json".into(),
+                    "-n".into(),
+                ]
+            )
+            .expect("report options"),
+            ReportOptions {
+                json: true,
+                alias: "kpods".to_string(),
+                args: vec!["--json".to_string(), "-n".to_string()],
+            }
+        );
+        assert!(parse_report_options("--dry-run", &[]).is_err());
+        assert!(parse_report_options("--dry-run", &["--json".into()]).is_err());
+        let err = parse_report_options("--explain", &["--verbose".into(), "kpods".into()])
+            .expect_err("unknown option");
+        assert!(
+            err.to_string().contains("unknown --explain option"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn parses_audit_views_filters_and_time_bounds() {
+        let now = UNIX_EPOCH + Duration::from_secs(86_400 * 10);
+        let args = [
+            "tail",
+            "--alias=kpods",
+            "--since",
+            "2h",
+            "-n",
+            "3",
+            "--json",
+        ]
+        .map(String::from);
+        let options = parse_audit_options(&args, now).expect("tail options");
+        assert_eq!(options.view, AuditView::Tail);
+        assert_eq!(options.query.alias.as_deref(), Some("kpods"));
+        assert_eq!(options.query.since, Some((86_400 * 10 - 7200) * 1000));
+        assert_eq!(options.lines, 3);
+        assert!(options.json);
+
+        let args = ["query", "--until=1970-01-02"].map(String::from);
+        let options = parse_audit_options(&args, now).expect("query options");
+        assert_eq!(options.view, AuditView::Query);
+        assert_eq!(options.query.until, Some(86_400_000));
+
+        for (args, expected) in [
+            (vec![], "--audit needs `tail` or `query`"),
+            (vec!["list"], "unknown --audit command `list`"),
+            (vec!["query", "-n", "3"], "unknown --audit option `-n`"),
+            (vec!["tail", "--alias"], "--alias needs a value"),
+            (vec!["tail", "--since", "last week"], "--since must be"),
+        ] {
+            let args = args.into_iter().map(String::from).collect::<Vec<_>>();
+            let err = parse_audit_options(&args, now).expect_err("invalid audit options");
+            assert!(err.to_string().contains(expected), "{err}");
+        }
+    }
+
+    #[test]
+    fn formats_uptime_with_the_two_largest_units() {
+        assert_eq!(format_uptime(42), "42s");
+        assert_eq!(format_uptime(125), "2m05s");
+        assert_eq!(format_uptime(3 * 3600 + 7 * 60), "3h07m");
+        assert_eq!(format_uptime(2 * 86400 + 5 * 3600), "2d05h");
+    }
 }
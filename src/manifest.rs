@@ -1,33 +1,130 @@
-use crate::path_mutation::{self, PathMutationConfig};
-use anyhow::Result;
+use crate::arg_placement::{self, Placed};
+use crate::dotenv;
+use crate::exec_resolution;
+use crate::interpolation::{self, InterpolationContext};
+use crate::path_mutation::{self, PathListConfig, PathMutationConfig};
+use crate::provenance::{Origin, PathProvenance, Provenance};
+use crate::secrets;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Manifest {
     pub exec: PathBuf,
+    /// Further `exec` candidates, tried in order; see [`Manifest::select_exec`].
+    #[serde(default)]
+    pub exec_fallbacks: Vec<PathBuf>,
     pub args: Vec<String>,
+    /// Fixed args placed after the runtime args.
+    #[serde(default)]
+    pub args_after: Vec<String>,
     pub env: HashMap<String, String>,
     pub env_remove: Vec<String>,
+    /// Regexes over inherited variable names to unset; configured values win.
+    #[serde(default)]
+    pub env_remove_matching: Vec<String>,
+    /// Values set only when the variable has no value by that point.
+    #[serde(default)]
+    pub env_default: HashMap<String, String>,
+    /// Values joined after / before the variable's current value.
+    #[serde(default)]
+    pub env_append: BTreeMap<String, EnvJoin>,
+    #[serde(default)]
+    pub env_prepend: BTreeMap<String, EnvJoin>,
+    /// Dotenv files loaded beneath `env` on every invocation (never cached).
+    #[serde(default)]
+    pub env_file: Vec<PathBuf>,
+    /// Where `[env_secret]` values come from. Only the descriptors are
+    /// cached; values are read by [`Manifest::resolve_secrets`].
+    #[serde(default)]
+    pub env_secret: BTreeMap<String, SecretSource>,
+    #[serde(default)]
+    pub env_mode: EnvMode,
+    /// Inherited variables that survive `env_mode = "clean"`: exact names,
+    /// or regexes when an entry contains anything but `[A-Za-z0-9_]`.
+    #[serde(default)]
+    pub env_keep: Vec<String>,
     pub path: Option<PathMutationConfig>,
+    /// `[path]`-style mutations for other search-path variables, applied
+    /// after `[path]` in key order.
+    #[serde(default)]
+    pub pathlists: BTreeMap<String, PathListConfig>,
+    /// Working directory for the child process; inherited when unset.
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
     pub journal: Option<JournalConfig>,
     pub reconcile: Option<ReconcileConfig>,
     pub bashcomp: Option<BashcompConfig>,
+    #[serde(default)]
+    pub limits: Option<LimitsConfig>,
+    #[serde(default)]
+    pub requires: Option<VersionRequirement>,
+    #[serde(default)]
+    pub guard: Option<GuardConfig>,
+    #[serde(default)]
+    pub hooks: Option<HooksConfig>,
+    #[serde(default)]
+    pub timeout: Option<TimeoutConfig>,
+    #[serde(default)]
+    pub retry: Option<RetryConfig>,
+    #[serde(default)]
+    pub concurrency: Option<ConcurrencyConfig>,
+    #[serde(default)]
+    pub audit: Option<AuditConfig>,
+    /// Overlays keyed by the space-joined leading runtime args they match.
+    #[serde(default)]
+    pub subcommands: BTreeMap<String, SubcommandOverlay>,
+    /// Named `[profile.<name>]` overlays; see [`Manifest::apply_profile`].
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileOverlay>,
+    #[serde(default)]
+    pub default_profile: Option<String>,
+    /// Directory of the alias config file, used for `${alias_dir}` and for
+    /// resolving relative `exec` values that contain placeholders.
+    #[serde(default)]
+    pub alias_dir: Option<PathBuf>,
 }
 
 impl Manifest {
     pub fn simple(exec: PathBuf) -> Self {
         Self {
             exec,
+            exec_fallbacks: Vec::new(),
             args: Vec::new(),
+            args_after: Vec::new(),
             env: HashMap::new(),
             env_remove: Vec::new(),
+            env_remove_matching: Vec::new(),
+            env_default: HashMap::new(),
+            env_append: BTreeMap::new(),
+            env_prepend: BTreeMap::new(),
+            env_file: Vec::new(),
+            env_secret: BTreeMap::new(),
+            env_mode: EnvMode::Inherit,
+            env_keep: Vec::new(),
             path: None,
+            pathlists: BTreeMap::new(),
+            cwd: None,
             journal: None,
             reconcile: None,
             bashcomp: None,
+            limits: None,
+            requires: None,
+            guard: None,
+            hooks: None,
+            timeout: None,
+            retry: None,
+            concurrency: None,
+            audit: None,
+            subcommands: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            default_profile: None,
+            alias_dir: None,
         }
     }
 
@@ -46,66 +143,783 @@ impl Manifest {
         self
     }
 
+    /// The profile `request` selects, if any. `CHOPPER_PROFILE` is ignored by
+    /// aliases without profiles so it can be exported globally. Without
+    /// either, `default_profile` applies.
+    pub fn selected_profile<'a>(&'a self, request: Option<&'a ProfileRequest>) -> Option<&'a str> {
+        match request {
+            Some(ProfileRequest::Flag(name)) => Some(name.as_str()),
+            Some(ProfileRequest::Env(_)) if self.profiles.is_empty() => None,
+            Some(ProfileRequest::Env(name)) => Some(name.as_str()),
+            None => self.default_profile.as_deref(),
+        }
+    }
+
+    /// Returns the manifest with the selected profile folded in. An explicit
+    /// `--chopper-profile=` must name a defined profile.
+    pub fn apply_profile(&self, request: Option<&ProfileRequest>) -> Result<Manifest> {
+        let Some(name) = self.selected_profile(request) else {
+            return Ok(self.clone());
+        };
+        let overlay = self.profiles.get(name).ok_or_else(|| {
+            let source = match request {
+                Some(ProfileRequest::Env(_)) => "CHOPPER_PROFILE",
+                Some(ProfileRequest::Flag(_)) => "--chopper-profile",
+                None => "default_profile",
+            };
+            anyhow!("{source} selects unknown profile `{name}`")
+        })?;
+
+        let mut applied = self.clone();
+        if let Some(exec) = &overlay.exec {
+            applied.exec = exec.clone();
+            applied.exec_fallbacks.clear();
+        }
+        if let Some(args) = &overlay.args {
+            applied.args = args.clone();
+        }
+        applied
+            .env_remove
+            .retain(|key| !overlay.env.contains_key(key));
+        applied
+            .env_secret
+            .retain(|key, _| !overlay.env.contains_key(key));
+        for key in &overlay.env_remove {
+            applied.env.remove(key);
+            if !applied.env_remove.contains(key) {
+                applied.env_remove.push(key.clone());
+            }
+        }
+        applied.env.extend(overlay.env.clone());
+        if let Some(path) = overlay.path.clone() {
+            applied
+                .path
+                .get_or_insert_with(PathMutationConfig::default)
+                .extend(path);
+        }
+        if let Some(journal) = &overlay.journal {
+            applied.journal = Some(journal.clone());
+        }
+        Ok(applied)
+    }
+
+    /// Expands `~` and `${...}` placeholders in `exec` candidates, `args`,
+    /// `env` values, and `[path]` entries, including those of `[subcommands]`
+    /// overlays. Cached manifests keep the raw strings, so this runs on every
+    /// invocation against the caller's environment.
+    pub fn expand_placeholders(&self, alias: &str) -> Result<Manifest> {
+        let context = InterpolationContext {
+            alias,
+            alias_dir: self.alias_dir.as_deref(),
+        };
+        let mut expanded = self.clone();
+
+        let base_dir = self.alias_dir.as_deref().unwrap_or_else(|| Path::new("."));
+        for exec in std::iter::once(&mut expanded.exec).chain(expanded.exec_fallbacks.iter_mut()) {
+            let Some(raw_exec) = exec.to_str() else {
+                continue;
+            };
+            if interpolation::contains_placeholders(raw_exec) {
+                let value = interpolation::expand(raw_exec, "field `exec`", &context)?;
+                let value = value.trim();
+                if value.is_empty() {
+                    return Err(anyhow!("field `exec` expands to an empty value"));
+                }
+                if value.contains('\0') {
+                    return Err(anyhow!("field `exec` expands to a value with NUL bytes"));
+                }
+                *exec = crate::parser::resolve_exec_path(base_dir, value);
+            }
+        }
+
+        if let Some(raw_cwd) = self.cwd.as_ref().and_then(|cwd| cwd.to_str()) {
+            if interpolation::contains_placeholders(raw_cwd) {
+                let cwd = interpolation::expand(raw_cwd, "field `cwd`", &context)?;
+                let cwd = cwd.trim();
+                if cwd.is_empty() {
+                    return Err(anyhow!("field `cwd` expands to an empty value"));
+                }
+                expanded.cwd = Some(match self.alias_dir.as_deref() {
+                    Some(alias_dir) => alias_dir.join(cwd),
+                    None => PathBuf::from(cwd),
+                });
+            }
+        }
+
+        for env_file in expanded.env_file.iter_mut() {
+            let Some(raw) = env_file.to_str() else {
+                continue;
+            };
+            if interpolation::contains_placeholders(raw) {
+                let path = interpolation::expand(raw, "field `env_file`", &context)?;
+                *env_file = match self.alias_dir.as_deref() {
+                    Some(alias_dir) => alias_dir.join(path.trim()),
+                    None => PathBuf::from(path.trim()),
+                };
+            }
+        }
+
+        for (key, source) in expanded.env_secret.iter_mut() {
+            let field = format!("field `env_secret.{key}`");
+            match source {
+                SecretSource::File(path) => {
+                    let Some(raw) = path.to_str() else {
+                        continue;
+                    };
+                    if interpolation::contains_placeholders(raw) {
+                        let value = interpolation::expand(raw, &field, &context)?;
+                        *path = match self.alias_dir.as_deref() {
+                            Some(alias_dir) => alias_dir.join(value.trim()),
+                            None => PathBuf::from(value.trim()),
+                        };
+                    }
+                }
+                SecretSource::Command(argv) => interpolation::expand_all(argv, &field, &context)?,
+                SecretSource::Keyring(_) => {}
+            }
+        }
+
+        interpolation::expand_all(&mut expanded.args, "field `args`", &context)?;
+        interpolation::expand_all(&mut expanded.args_after, "field `args_after`", &context)?;
+        expand_env_values(&mut expanded.env, "env", &context)?;
+        expand_env_values(&mut expanded.env_default, "env_default", &context)?;
+        for (field, joins) in [
+            ("env_append", &mut expanded.env_append),
+            ("env_prepend", &mut expanded.env_prepend),
+        ] {
+            for (key, join) in joins.iter_mut() {
+                join.value = interpolation::expand(
+                    &join.value,
+                    &format!("field `{field}.{key}`"),
+                    &context,
+                )?;
+            }
+        }
+        if let Some(path) = expanded.path.as_mut() {
+            expand_path_entries(path, "path", &context)?;
+        }
+        for (key, list) in expanded.pathlists.iter_mut() {
+            for (name, values) in list.operations_mut() {
+                interpolation::expand_all(
+                    values,
+                    &format!("field `pathlists.{key}.{name}`"),
+                    &context,
+                )?;
+            }
+        }
+        if let Some(hooks) = expanded.hooks.as_mut() {
+            for (stage, commands) in [("pre", &mut hooks.pre), ("post", &mut hooks.post)] {
+                for argv in commands.iter_mut() {
+                    interpolation::expand_all(argv, &format!("field `hooks.{stage}`"), &context)?;
+                }
+            }
+        }
+        for (name, overlay) in expanded.subcommands.iter_mut() {
+            let prefix = format!("subcommands.{name}");
+            interpolation::expand_all(
+                &mut overlay.args,
+                &format!("field `{prefix}.args`"),
+                &context,
+            )?;
+            expand_env_values(&mut overlay.env, &format!("{prefix}.env"), &context)?;
+            if let Some(path) = overlay.path.as_mut() {
+                expand_path_entries(path, &format!("{prefix}.path"), &context)?;
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// Settles an `exec = [...]` list on the first candidate that resolves to
+    /// an executable other than chopper itself. Bare command names are looked
+    /// up on `PATH` again, so the choice follows tools being installed or
+    /// removed even while the parsed manifest stays cached.
+    pub fn select_exec(&self) -> Result<Manifest> {
+        if self.exec_fallbacks.is_empty() {
+            return Ok(self.clone());
+        }
+        let candidates = std::iter::once(&self.exec).chain(&self.exec_fallbacks);
+        let Some(exec) = candidates
+            .clone()
+            .find_map(|candidate| exec_resolution::resolve_exec_candidate(candidate))
+        else {
+            let tried = candidates
+                .map(|candidate| candidate.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(anyhow!("none of the `exec` candidates were found: {tried}"));
+        };
+        let mut selected = self.clone();
+        selected.exec = exec;
+        selected.exec_fallbacks.clear();
+        Ok(selected)
+    }
+
+    /// Reads every `[env_secret]` value into `env`. The exe runtime calls this
+    /// right before building the invocation, so secret values only live in
+    /// the process that execs the command. Keys listed in `env_remove` are
+    /// skipped without being read.
+    pub fn resolve_secrets(&self) -> Result<Manifest> {
+        if self.env_secret.is_empty() {
+            return Ok(self.clone());
+        }
+        let mut resolved = self.clone();
+        for (key, source) in std::mem::take(&mut resolved.env_secret) {
+            if self.env_remove.contains(&key) {
+                continue;
+            }
+            let value = secrets::read(&key, &source)?;
+            resolved.env.insert(key, value);
+        }
+        Ok(resolved)
+    }
+
+    /// Like [`Manifest::resolve_secrets`], but stores each secret's
+    /// [`SecretSource::redacted`] placeholder instead of reading it, for
+    /// previews that must not touch secret stores.
+    pub fn redact_secrets(&self) -> Manifest {
+        let mut redacted = self.clone();
+        for (key, source) in std::mem::take(&mut redacted.env_secret) {
+            if !self.env_remove.contains(&key) {
+                redacted.env.insert(key, source.redacted());
+            }
+        }
+        redacted
+    }
+
+    /// Returns the `[subcommands]` overlay whose words prefix `runtime_args`,
+    /// preferring the one that matches the most words.
+    pub fn matching_subcommand(
+        &self,
+        runtime_args: &[String],
+    ) -> Option<(&str, &SubcommandOverlay)> {
+        self.subcommands
+            .iter()
+            .filter(|(name, _)| {
+                let words = name.split(' ').collect::<Vec<_>>();
+                words.len() <= runtime_args.len()
+                    && words
+                        .iter()
+                        .zip(runtime_args)
+                        .all(|(word, arg)| word == arg)
+            })
+            .max_by_key(|(name, _)| name.split(' ').count())
+            .map(|(name, overlay)| (name.as_str(), overlay))
+    }
+
     pub fn build_invocation(
         &self,
         runtime_args: &[String],
         patch: Option<RuntimePatch>,
     ) -> Result<Invocation> {
-        let mut args = self.args.clone();
-        args.extend(runtime_args.iter().cloned());
+        Ok(self.build_invocation_traced(runtime_args, patch)?.0)
+    }
+
+    /// Like [`Manifest::build_invocation`], also recording where each arg,
+    /// env variable, removal, and PATH component came from.
+    pub fn build_invocation_traced(
+        &self,
+        runtime_args: &[String],
+        patch: Option<RuntimePatch>,
+    ) -> Result<(Invocation, Provenance)> {
+        let overlay = self.matching_subcommand(runtime_args);
+        let runtime_origins = (1..=runtime_args.len())
+            .map(|position| Origin::Runtime { position })
+            .collect::<Vec<_>>();
+        let (runtime_args, runtime_origins) = match overlay {
+            Some((name, overlay)) if !overlay.args.is_empty() => {
+                let split = name.split(' ').count();
+                let (words, rest) = runtime_args.split_at(split);
+                let overlay_origins = (0..overlay.args.len())
+                    .map(|index| Origin::config(format!("subcommands.{name:?}.args[{index}]")));
+                let overlay_args = overlay
+                    .args
+                    .iter()
+                    .map(|arg| arg_placement::unescape(arg).to_string())
+                    .collect::<Vec<_>>();
+                (
+                    Cow::Owned([words, &overlay_args, rest].concat()),
+                    runtime_origins[..split]
+                        .iter()
+                        .cloned()
+                        .chain(overlay_origins)
+                        .chain(runtime_origins[split..].iter().cloned())
+                        .collect(),
+                )
+            }
+            _ => (Cow::Borrowed(runtime_args), runtime_origins),
+        };
+        let mut args = Vec::new();
+        let mut arg_origins = Vec::new();
+        for placed in arg_placement::placement(&self.args, runtime_args.len()) {
+            match placed {
+                Placed::Alias(index) => {
+                    args.push(arg_placement::unescape(&self.args[index]).to_string());
+                    arg_origins.push(Origin::config(format!("args[{index}]")));
+                }
+                Placed::Runtime(index) => {
+                    args.push(runtime_args[index].clone());
+                    arg_origins.push(runtime_origins[index].clone());
+                }
+            }
+        }
+        for (index, arg) in self.args_after.iter().enumerate() {
+            args.push(arg_placement::unescape(arg).to_string());
+            arg_origins.push(Origin::config(format!("args_after[{index}]")));
+        }
 
-        let mut env = self.env.clone();
+        // The last writer of each variable, and what removed each one.
+        let mut origins = HashMap::new();
+        let mut removals = HashMap::new();
+        let mut env = match self.env_mode {
+            EnvMode::Inherit => HashMap::new(),
+            EnvMode::Clean => kept_environment(&self.env_keep)?,
+        };
+        for key in env.keys() {
+            origins.insert(key.clone(), Origin::config("env_keep"));
+        }
+        let removed_inherited = match self.env_mode {
+            EnvMode::Inherit => inherited_keys_matching(&self.env_remove_matching)?,
+            EnvMode::Clean => {
+                let patterns = compile_env_patterns(&self.env_remove_matching)?;
+                env.retain(|key, _| !patterns.iter().any(|pattern| pattern.is_match(key)));
+                Vec::new()
+            }
+        };
+        for key in &removed_inherited {
+            removals.insert(key.clone(), Origin::config("env_remove_matching"));
+        }
+        let (file_env, file_lines) = dotenv::load_files(&self.env_file)?;
+        env.extend(file_env);
+        for (key, (path, line)) in file_lines {
+            origins.insert(key, Origin::EnvFile { path, line });
+        }
+        env.extend(self.env.clone());
+        for key in self.env.keys() {
+            origins.insert(key.clone(), Origin::config(format!("env.{key}")));
+        }
         let mut env_remove = self.env_remove.clone();
+        for key in &env_remove {
+            removals.insert(key.clone(), Origin::config("env_remove"));
+        }
+        let mut path = Cow::Borrowed(&self.path);
 
-        if let Some(path_config) = self.path.as_ref() {
-            let inherited_path = env::var("PATH").ok();
+        if let Some((name, overlay)) = overlay {
+            env_remove.retain(|remove_key| !overlay.env.contains_key(remove_key));
+            for key in &overlay.env_remove {
+                env.remove(key);
+                env_remove.push(key.clone());
+                removals.insert(
+                    key.clone(),
+                    Origin::config(format!("subcommands.{name:?}.env_remove")),
+                );
+            }
+            env.extend(overlay.env.clone());
+            for key in overlay.env.keys() {
+                origins.insert(
+                    key.clone(),
+                    Origin::config(format!("subcommands.{name:?}.env.{key}")),
+                );
+            }
+            if let Some(overlay_path) = overlay.path.clone() {
+                path.to_mut()
+                    .get_or_insert_with(PathMutationConfig::default)
+                    .extend(overlay_path);
+            }
+        }
+
+        let inherited = |key: &str| match self.env_mode {
+            EnvMode::Inherit if !removed_inherited.iter().any(|removed| removed == key) => {
+                env::var(key).ok()
+            }
+            _ => None,
+        };
+        for (operation, key) in apply_env_operations(
+            &mut env,
+            &mut env_remove,
+            &self.env_default,
+            &self.env_prepend,
+            &self.env_append,
+            inherited,
+        ) {
+            let table = match operation {
+                EnvOperation::Default => "env_default",
+                EnvOperation::Prepend => "env_prepend",
+                EnvOperation::Append => "env_append",
+            };
+            origins.insert(key.clone(), Origin::config(format!("{table}.{key}")));
+        }
+
+        let mut path_provenance = None;
+        if let Some(path_config) = path.as_ref() {
+            // In clean mode a kept PATH is already in `env`.
+            let inherited_path = match self.env_mode {
+                EnvMode::Inherit if !removed_inherited.iter().any(|key| key == "PATH") => {
+                    env::var("PATH").ok()
+                }
+                _ => None,
+            };
             let base_path =
-                effective_path_before_patch(inherited_path.as_deref(), &env, &env_remove);
-            let path_value = path_mutation::apply_runtime_path(base_path, path_config)?;
+                effective_value_before_patch("PATH", inherited_path.as_deref(), &env, &env_remove);
+            let base = match (base_path, origins.get("PATH")) {
+                (None, _) => None,
+                (Some(_), Some(origin)) if env.contains_key("PATH") => Some(origin.clone()),
+                (Some(_), _) => Some(Origin::Inherited),
+            };
+            let (path_value, mut components) =
+                path_mutation::apply_runtime_path(base_path, path_config)?;
+            if let Some((name, _)) = overlay {
+                attribute_overlay_path_steps(&mut components, self.path.as_ref(), name);
+            }
             env_remove.retain(|remove_key| remove_key != "PATH");
             env.insert("PATH".into(), path_value);
+            origins.insert("PATH".into(), Origin::config("path"));
+            path_provenance = Some(PathProvenance { base, components });
         }
 
+        for (key, list) in &self.pathlists {
+            let inherited = match self.env_mode {
+                EnvMode::Inherit if !removed_inherited.contains(key) => env::var(key).ok(),
+                _ => None,
+            };
+            let base = effective_value_before_patch(key, inherited.as_deref(), &env, &env_remove);
+            let field = format!("pathlists.{key}");
+            match path_mutation::apply_path_list(base, list, &field)? {
+                Some(value) => {
+                    env_remove.retain(|remove_key| remove_key != key);
+                    env.insert(key.clone(), value);
+                    origins.insert(key.clone(), Origin::config(field));
+                }
+                None => {
+                    env.remove(key);
+                    env_remove.push(key.clone());
+                    removals.insert(key.clone(), Origin::config(field));
+                }
+            }
+        }
+
+        let mut cwd = self.cwd.clone();
+        let mut cwd_origin = cwd.as_ref().map(|_| Origin::config("cwd"));
+
+        env_remove.extend(
+            removed_inherited
+                .into_iter()
+                .filter(|key| !env.contains_key(key)),
+        );
+
         if let Some(patch) = patch {
+            if let Some(set_cwd) = patch.set_cwd {
+                cwd = Some(set_cwd);
+                cwd_origin = Some(Origin::reconcile("set_cwd"));
+            }
             if let Some(replace) = patch.replace_args {
+                arg_origins = (0..replace.len())
+                    .map(|index| Origin::reconcile(format!("replace_args[{index}]")))
+                    .collect();
                 args = replace;
             }
+            arg_origins.extend(
+                (0..patch.append_args.len())
+                    .map(|index| Origin::reconcile(format!("append_args[{index}]"))),
+            );
             args.extend(patch.append_args);
 
             for (key, value) in patch.set_env {
                 env_remove.retain(|remove_key| remove_key != &key);
+                origins.insert(key.clone(), Origin::reconcile(format!("set_env.{key}")));
                 env.insert(key, value);
             }
+            for (operation, key) in apply_env_operations(
+                &mut env,
+                &mut env_remove,
+                &patch.default_env,
+                &patch.prepend_env,
+                &patch.append_env,
+                |key| match self.env_mode {
+                    EnvMode::Inherit => env::var(key).ok(),
+                    EnvMode::Clean => None,
+                },
+            ) {
+                let table = match operation {
+                    EnvOperation::Default => "default_env",
+                    EnvOperation::Prepend => "prepend_env",
+                    EnvOperation::Append => "append_env",
+                };
+                origins.insert(key.clone(), Origin::reconcile(format!("{table}.{key}")));
+            }
+            for key in &patch.remove_env {
+                removals.insert(key.clone(), Origin::reconcile("remove_env"));
+            }
             env_remove.extend(patch.remove_env);
+            if !patch.remove_env_matching.is_empty() {
+                let patterns = compile_env_patterns(&patch.remove_env_matching)?;
+                let matched = env
+                    .keys()
+                    .filter(|key| patterns.iter().any(|pattern| pattern.is_match(key)))
+                    .cloned()
+                    .chain(inherited_keys_matching(&patch.remove_env_matching)?)
+                    .collect::<Vec<_>>();
+                for key in &matched {
+                    removals.insert(key.clone(), Origin::reconcile("remove_env_matching"));
+                }
+                env_remove.extend(matched);
+            }
         }
 
         env_remove = dedupe_preserving_order(env_remove);
         for key in &env_remove {
             env.remove(key);
         }
+        origins.retain(|key, _| env.contains_key(key));
+        removals.retain(|key, _| env_remove.contains(key));
 
-        Ok(Invocation {
+        let provenance = Provenance {
+            args: arg_origins,
+            env: origins,
+            env_remove: removals,
+            cwd: cwd_origin,
+            path: path_provenance,
+        };
+        let invocation = Invocation {
             exec: self.exec.clone(),
             args,
             env,
             env_remove,
+            clear_env: self.env_mode == EnvMode::Clean,
+            cwd,
             journal: self.journal.clone(),
-        })
+            limits: self.limits.clone(),
+            hooks: self.hooks.clone(),
+            timeout: self.timeout.clone(),
+            retry: self.retry.clone(),
+        };
+        Ok((invocation, provenance))
+    }
+}
+
+/// The `env_default`/`env_prepend`/`env_append` table that wrote a variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnvOperation {
+    Default,
+    Prepend,
+    Append,
+}
+
+/// Applies `env_default`, then `env_prepend`, then `env_append` on top of the
+/// environment built so far. A variable's current value is its entry in
+/// `env`, or else `inherited(key)` unless `env_remove` already dropped it.
+fn apply_env_operations(
+    env: &mut HashMap<String, String>,
+    env_remove: &mut Vec<String>,
+    defaults: &HashMap<String, String>,
+    prepends: &BTreeMap<String, EnvJoin>,
+    appends: &BTreeMap<String, EnvJoin>,
+    inherited: impl Fn(&str) -> Option<String>,
+) -> Vec<(EnvOperation, String)> {
+    let current = |env: &HashMap<String, String>, env_remove: &[String], key: &str| {
+        if let Some(value) = env.get(key) {
+            return Some(value.clone());
+        }
+        if env_remove.iter().any(|remove_key| remove_key == key) {
+            return None;
+        }
+        inherited(key)
+    };
+
+    let mut written = Vec::new();
+    for (key, value) in defaults {
+        if current(env, env_remove, key).is_none() {
+            env_remove.retain(|remove_key| remove_key != key);
+            env.insert(key.clone(), value.clone());
+            written.push((EnvOperation::Default, key.clone()));
+        }
+    }
+    for (joins, operation) in [
+        (prepends, EnvOperation::Prepend),
+        (appends, EnvOperation::Append),
+    ] {
+        for (key, join) in joins {
+            let value = match current(env, env_remove, key).filter(|value| !value.is_empty()) {
+                Some(existing) if operation == EnvOperation::Prepend => {
+                    format!("{}{}{existing}", join.value, join.separator)
+                }
+                Some(existing) => format!("{existing}{}{}", join.separator, join.value),
+                None => join.value.clone(),
+            };
+            env_remove.retain(|remove_key| remove_key != key);
+            env.insert(key.clone(), value);
+            written.push((operation, key.clone()));
+        }
+    }
+    written
+}
+
+/// `[path]` steps are numbered over the merged config, where a matching
+/// `[subcommands]` overlay's entries follow the alias's own. Points the
+/// overlay's steps at the overlay's fields.
+fn attribute_overlay_path_steps(
+    components: &mut [path_mutation::PathComponent],
+    own: Option<&PathMutationConfig>,
+    subcommand: &str,
+) {
+    let own = own.cloned().unwrap_or_default();
+    for step in components
+        .iter_mut()
+        .filter_map(|component| component.step.as_mut())
+    {
+        let Some((op, rest)) = step
+            .field
+            .strip_prefix("path.")
+            .and_then(|field| field.split_once('['))
+        else {
+            continue;
+        };
+        let own_len = match op {
+            "remove_all" => own.remove_all.len(),
+            "remove_one" => own.remove_one.len(),
+            "append_all" => own.append_all.len(),
+            "append_one" => own.append_one.len(),
+            "prepend_all" => own.prepend_all.len(),
+            "prepend_one" => own.prepend_one.len(),
+            _ => continue,
+        };
+        let Some(index) = rest
+            .strip_suffix(']')
+            .and_then(|index| index.parse::<usize>().ok())
+        else {
+            continue;
+        };
+        if index >= own_len {
+            step.field = format!("subcommands.{subcommand:?}.path.{op}[{}]", index - own_len);
+        }
+    }
+}
+
+fn expand_env_values(
+    env: &mut HashMap<String, String>,
+    field: &str,
+    context: &InterpolationContext,
+) -> Result<()> {
+    for (key, value) in env.iter_mut() {
+        *value = interpolation::expand(value, &format!("field `{field}.{key}`"), context)?;
+    }
+    Ok(())
+}
+
+fn expand_path_entries(
+    path: &mut PathMutationConfig,
+    field: &str,
+    context: &InterpolationContext,
+) -> Result<()> {
+    for (name, values) in [
+        ("remove_all", &mut path.remove_all),
+        ("remove_one", &mut path.remove_one),
+        ("append_all", &mut path.append_all),
+        ("append_one", &mut path.append_one),
+        ("prepend_all", &mut path.prepend_all),
+        ("prepend_one", &mut path.prepend_one),
+    ] {
+        interpolation::expand_all(values, &format!("field `{field}.{name}`"), context)?;
+    }
+    Ok(())
+}
+
+/// Compiles one `env_remove_matching` pattern, reporting errors in the same
+/// shape as `[path]` regex operations. A `glob:` pattern matches whole names
+/// with `*` and `?` wildcards. A bare pattern of only name characters and
+/// wildcards, like `AWS_*`, is rejected: as an unanchored regex it would
+/// match names that merely contain `AWS`.
+pub(crate) fn compile_env_pattern(pattern: &str, context: &str) -> Result<regex::Regex> {
+    if let Some(glob) = pattern.strip_prefix("glob:") {
+        let mut source = String::from("^");
+        for ch in glob.trim().chars() {
+            match ch {
+                '*' => source.push_str(".*"),
+                '?' => source.push('.'),
+                other => source.push_str(&regex::escape(other.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        source.push('$');
+        return regex::Regex::new(&source)
+            .map_err(|err| anyhow!("{context} contains invalid glob `{pattern}`: {err}"));
+    }
+    if pattern.contains(['*', '?'])
+        && pattern
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '*' | '?'))
+    {
+        return Err(anyhow!(
+            "{context} pattern `{pattern}` looks like a glob but would be read as an unanchored regex; write `glob:{pattern}` or an anchored regex like `^AWS_`"
+        ));
+    }
+    regex::Regex::new(pattern)
+        .map_err(|err| anyhow!("{context} contains invalid regex `{pattern}`: {err}"))
+}
+
+fn compile_env_patterns(patterns: &[String]) -> Result<Vec<regex::Regex>> {
+    patterns
+        .iter()
+        .map(|pattern| compile_env_pattern(pattern, "field `env_remove_matching`"))
+        .collect()
+}
+
+/// Inherited variable names matching any of `patterns`, sorted.
+fn inherited_keys_matching(patterns: &[String]) -> Result<Vec<String>> {
+    if patterns.is_empty() {
+        return Ok(Vec::new());
     }
+    let patterns = compile_env_patterns(patterns)?;
+    let mut keys: Vec<_> = env::vars_os()
+        .filter_map(|(key, _)| key.into_string().ok())
+        .filter(|key| patterns.iter().any(|pattern| pattern.is_match(key)))
+        .collect();
+    keys.sort();
+    Ok(keys)
+}
+
+/// The inherited variables named by `env_keep`. Variables whose name or value
+/// is not UTF-8 are dropped.
+fn kept_environment(env_keep: &[String]) -> Result<HashMap<String, String>> {
+    let mut names = Vec::new();
+    let mut patterns = Vec::new();
+    for entry in env_keep {
+        if is_exact_env_keep(entry) {
+            names.push(entry.as_str());
+        } else {
+            patterns.push(
+                regex::Regex::new(entry)
+                    .map_err(|err| anyhow!("invalid `env_keep` pattern `{entry}`: {err}"))?,
+            );
+        }
+    }
+    Ok(env::vars_os()
+        .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+        .filter(|(key, _)| {
+            names.contains(&key.as_str()) || patterns.iter().any(|pattern| pattern.is_match(key))
+        })
+        .collect())
 }
 
-fn effective_path_before_patch<'a>(
-    inherited_path: Option<&'a str>,
+/// `env_keep` entries made only of `[A-Za-z0-9_]` match one name exactly.
+pub(crate) fn is_exact_env_keep(entry: &str) -> bool {
+    !entry.is_empty()
+        && entry
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+}
+
+fn effective_value_before_patch<'a>(
+    key: &str,
+    inherited: Option<&'a str>,
     env: &'a HashMap<String, String>,
     env_remove: &[String],
 ) -> Option<&'a str> {
-    if let Some(path) = env.get("PATH") {
-        return Some(path.as_str());
+    if let Some(value) = env.get(key) {
+        return Some(value.as_str());
     }
-    if env_remove.iter().any(|key| key == "PATH") {
+    if env_remove.iter().any(|remove_key| remove_key == key) {
         return None;
     }
-    inherited_path
+    inherited
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -137,12 +951,278 @@ pub struct BashcompConfig {
     pub rhai_function: Option<String>,
 }
 
+/// A value that `[env_append]`/`[env_prepend]` join onto a variable's
+/// current value. The separator is only used when that value is non-empty.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EnvJoin {
+    pub value: String,
+    pub separator: String,
+}
+
+impl EnvJoin {
+    /// Used when a join does not name a separator.
+    pub const DEFAULT_SEPARATOR: &'static str = " ";
+}
+
+/// An `[env_secret]` descriptor. Values are never shown; see
+/// [`SecretSource::redacted`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretSource {
+    /// File contents, minus one trailing newline.
+    File(PathBuf),
+    /// Stdout of a command run without a shell, minus one trailing newline.
+    Command(Vec<String>),
+    /// Payload of a Linux kernel keyring key, as `type:description`.
+    Keyring(String),
+}
+
+impl SecretSource {
+    /// The placeholder shown wherever the secret value would otherwise appear.
+    pub fn redacted(&self) -> String {
+        match self {
+            Self::File(path) => format!("<redacted: file {}>", path.display()),
+            Self::Command(argv) => format!("<redacted: command {}>", argv.join(" ")),
+            Self::Keyring(key) => format!("<redacted: keyring {key}>"),
+        }
+    }
+}
+
+/// Whether the child starts from chopper's environment or an empty one.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvMode {
+    #[default]
+    Inherit,
+    /// `env -i` semantics: only `env_keep` matches and configured values.
+    Clean,
+}
+
+/// `[guard]`: regexes checked against each argument of the final invocation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GuardConfig {
+    /// The invocation is refused when any argument matches one of these.
+    pub deny: Vec<String>,
+    /// Each pattern must match at least one argument.
+    pub require: Vec<String>,
+    /// A matching argument asks for confirmation on the terminal.
+    pub confirm: Vec<String>,
+    /// What `confirm` does when there is no terminal to ask on.
+    pub non_tty: GuardFallback,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardFallback {
+    #[default]
+    Refuse,
+    Allow,
+}
+
+/// `[hooks]`: commands run around the child, each an argv list run without a
+/// shell. Post hooks need chopper to outlive the child, so they switch the
+/// invocation from `exec` to the supervised path.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HooksConfig {
+    /// Run in order before the child starts; a failure aborts the launch.
+    pub pre: Vec<Vec<String>>,
+    /// Run in order after the child exits, with `CHOPPER_EXIT_CODE` set.
+    pub post: Vec<Vec<String>>,
+    /// Send hook output to the alias's `[journal]` namespace.
+    pub journal: bool,
+}
+
+/// `timeout`, `timeout_signal`, and `kill_after`: a deadline for the child,
+/// enforced on the supervised path like coreutils `timeout`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimeoutConfig {
+    pub after_ms: u64,
+    /// Signal name as understood by nix, for example `SIGTERM`.
+    pub signal: String,
+    /// Send SIGKILL this long after `signal` if the child is still running.
+    pub kill_after_ms: Option<u64>,
+}
+
+/// `[retry]`: re-runs a failed child on the supervised path, exporting the
+/// 1-based attempt number as `CHOPPER_ATTEMPT`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RetryConfig {
+    /// Total runs allowed, counting the first; always at least 2.
+    pub attempts: u32,
+    pub backoff: RetryBackoff,
+    /// Delay before the second attempt.
+    pub initial_delay_ms: u64,
+    /// Exit codes worth retrying; empty retries any non-zero exit.
+    pub on_exit_codes: Vec<i32>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryBackoff {
+    /// Wait `initial_delay` before every retry.
+    Fixed,
+    /// Double the delay after each retry.
+    #[default]
+    Exponential,
+}
+
+impl RetryConfig {
+    /// Whether a run ending in `exit_code` should be retried. Children killed
+    /// by a signal are not retried.
+    pub fn should_retry(&self, exit_code: Option<i32>) -> bool {
+        match exit_code {
+            Some(0) | None => false,
+            Some(code) => self.on_exit_codes.is_empty() || self.on_exit_codes.contains(&code),
+        }
+    }
+
+    /// Delay after the failed 1-based `attempt`, before the next one.
+    pub fn delay_after(&self, attempt: u32) -> Duration {
+        let millis = match self.backoff {
+            RetryBackoff::Fixed => self.initial_delay_ms,
+            RetryBackoff::Exponential => {
+                let factor = 1u64
+                    .checked_shl(attempt.saturating_sub(1))
+                    .unwrap_or(u64::MAX);
+                self.initial_delay_ms.saturating_mul(factor)
+            }
+        };
+        Duration::from_millis(millis)
+    }
+}
+
+/// `[concurrency]`: an `flock` taken before launch so only one instance of
+/// the alias (or of every alias sharing `lock_key`) runs at a time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConcurrencyConfig {
+    pub mode: ConcurrencyMode,
+    /// Lock name shared across aliases; defaults to the alias name.
+    pub lock_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConcurrencyMode {
+    /// Refuse to start while another instance holds the lock.
+    #[default]
+    Exclusive,
+    /// Block until the lock is free.
+    Wait,
+    /// Exit successfully without running the command.
+    Skip,
+}
+
+/// `[audit]`: whether launches of the alias go to the audit log, and
+/// argument redaction rules added to the global ones.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AuditConfig {
+    /// Records the alias's launches even when the global log is off;
+    /// `false` cannot opt out of a globally enabled log.
+    pub enabled: bool,
+    /// Options whose value is redacted, as in `--password=<value>` or
+    /// `--password <value>`.
+    pub redact_options: Vec<String>,
+    /// Regexes; a matching argument is redacted whole.
+    pub redact_matching: Vec<String>,
+}
+
+/// `[requires]`: the selected `exec` must report at least `min_version`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VersionRequirement {
+    /// Arguments passed to the selected `exec` to make it print its version.
+    pub version_cmd: Vec<String>,
+    /// The first capture group (or the whole match) is the reported version.
+    pub version_regex: String,
+    pub min_version: String,
+}
+
+/// Process attributes applied to the child between fork and exec. Each
+/// rlimit sets both the soft and hard limit, like `prlimit --<resource>=N`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LimitsConfig {
+    pub nofile: Option<RlimitValue>,
+    #[serde(rename = "as")]
+    pub address_space: Option<RlimitValue>,
+    pub cpu: Option<RlimitValue>,
+    pub core: Option<RlimitValue>,
+    pub nproc: Option<RlimitValue>,
+    pub nice: Option<i32>,
+    pub ioprio: Option<IoPriority>,
+    pub umask: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RlimitValue {
+    Limited(u64),
+    Unlimited,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IoPriority {
+    pub class: IoPriorityClass,
+    pub level: u8,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum IoPriorityClass {
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+/// Extra args, env, and PATH operations applied when the leading runtime args
+/// name a subcommand. `args` are inserted right after the matched words; the
+/// rest layers over the alias like a child in `extends`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SubcommandOverlay {
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub env_remove: Vec<String>,
+    pub path: Option<PathMutationConfig>,
+}
+
+/// A `[profile.<name>]` table. `exec`, `args`, and `journal` replace the
+/// alias values when set; `env`, `env_remove`, and `path` layer over them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProfileOverlay {
+    pub exec: Option<PathBuf>,
+    pub args: Option<Vec<String>>,
+    pub env: HashMap<String, String>,
+    pub env_remove: Vec<String>,
+    pub path: Option<PathMutationConfig>,
+    pub journal: Option<JournalConfig>,
+}
+
+/// How a profile was requested for one invocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileRequest {
+    /// A leading `--chopper-profile=<name>` runtime arg.
+    Flag(String),
+    /// The `CHOPPER_PROFILE` environment variable.
+    Env(String),
+}
+
+impl ProfileRequest {
+    /// `flag` wins over `CHOPPER_PROFILE`.
+    pub fn resolve(flag: Option<String>) -> Option<Self> {
+        flag.map(ProfileRequest::Flag)
+            .or_else(|| crate::env_util::env_value("CHOPPER_PROFILE").map(ProfileRequest::Env))
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RuntimePatch {
     pub replace_args: Option<Vec<String>>,
     pub append_args: Vec<String>,
     pub set_env: HashMap<String, String>,
+    pub default_env: HashMap<String, String>,
+    pub append_env: BTreeMap<String, EnvJoin>,
+    pub prepend_env: BTreeMap<String, EnvJoin>,
     pub remove_env: Vec<String>,
+    pub remove_env_matching: Vec<String>,
+    pub set_cwd: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -151,7 +1231,14 @@ pub struct Invocation {
     pub args: Vec<String>,
     pub env: HashMap<String, String>,
     pub env_remove: Vec<String>,
+    /// Start the child from an empty environment; `env` is then complete.
+    pub clear_env: bool,
+    pub cwd: Option<PathBuf>,
     pub journal: Option<JournalConfig>,
+    pub limits: Option<LimitsConfig>,
+    pub hooks: Option<HooksConfig>,
+    pub timeout: Option<TimeoutConfig>,
+    pub retry: Option<RetryConfig>,
 }
 
 fn dedupe_preserving_order(values: Vec<String>) -> Vec<String> {
@@ -167,12 +1254,17 @@ fn dedupe_preserving_order(values: Vec<String>) -> Vec<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::{Manifest, RuntimePatch};
+    use super::{
+        EnvJoin, EnvMode, JournalConfig, Manifest, ProfileOverlay, ProfileRequest, RetryBackoff,
+        RetryConfig, RuntimePatch, SubcommandOverlay,
+    };
     use crate::path_mutation::PathMutationConfig;
+    use crate::provenance::Origin;
     use crate::test_support::ENV_LOCK;
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
     use std::env;
     use std::path::PathBuf;
+    use std::time::Duration;
 
     #[test]
     fn invocation_merges_runtime_and_patch() {
@@ -215,6 +1307,232 @@ mod tests {
         assert_eq!(invocation.args, vec!["replaced", "extra"]);
     }
 
+    #[test]
+    fn runtime_arg_slots_place_args_and_patch_applies_on_top() {
+        let mut manifest = Manifest::simple(PathBuf::from("docker"));
+        manifest.args = vec!["run".into(), "--rm".into(), "{1}".into(), "{2..}".into()];
+        manifest.args_after = vec!["--extra".into()];
+        let runtime = vec!["image".to_string(), "sh".to_string(), "-l".to_string()];
+
+        let invocation = manifest
+            .build_invocation(&runtime, None)
+            .expect("build invocation");
+        assert_eq!(
+            invocation.args,
+            vec!["run", "--rm", "image", "sh", "-l", "--extra"]
+        );
+
+        let patch = RuntimePatch {
+            append_args: vec!["patched".into()],
+            ..RuntimePatch::default()
+        };
+        let invocation = manifest
+            .build_invocation(&runtime, Some(patch))
+            .expect("build invocation");
+        assert_eq!(
+            invocation.args,
+            vec!["run", "--rm", "image", "sh", "-l", "--extra", "patched"]
+        );
+
+        manifest.args = vec!["{{1}}".into(), "{1}".into(), "{{{@}}}".into()];
+        manifest.args_after = vec!["{{2..}}".into()];
+        let invocation = manifest
+            .build_invocation(&runtime, None)
+            .expect("build invocation");
+        assert_eq!(invocation.args, vec!["{1}", "image", "{{@}}", "{2..}"]);
+
+        let patch = RuntimePatch {
+            replace_args: Some(vec!["replaced".into()]),
+            ..RuntimePatch::default()
+        };
+        let invocation = manifest
+            .build_invocation(&runtime, Some(patch))
+            .expect("build invocation");
+        assert_eq!(invocation.args, vec!["replaced"]);
+    }
+
+    #[test]
+    fn subcommand_overlay_inserts_args_after_matched_words_and_layers_env() {
+        let mut manifest = Manifest::simple(PathBuf::from("kubectl"));
+        manifest.args = vec!["--context=prod".into()];
+        manifest.env = HashMap::from([("KUBECONFIG".into(), "/kube/config".into())]);
+        manifest.env_remove = vec!["KUBE_EDITOR".into()];
+        manifest.subcommands.insert(
+            "delete".into(),
+            SubcommandOverlay {
+                args: vec!["--dry-run=client".into()],
+                env: HashMap::from([("KUBE_EDITOR".into(), "vi".into())]),
+                env_remove: vec!["KUBECONFIG".into()],
+                path: None,
+            },
+        );
+        manifest.subcommands.insert(
+            "rollout status".into(),
+            SubcommandOverlay {
+                args: vec!["--watch=false".into()],
+                ..SubcommandOverlay::default()
+            },
+        );
+        manifest.subcommands.insert(
+            "rollout".into(),
+            SubcommandOverlay {
+                args: vec!["--unused".into()],
+                ..SubcommandOverlay::default()
+            },
+        );
+
+        let runtime = vec!["delete".to_string(), "pod".to_string(), "x".to_string()];
+        let patch = RuntimePatch {
+            append_args: vec!["--dry-run=none".into()],
+            ..RuntimePatch::default()
+        };
+        let invocation = manifest
+            .build_invocation(&runtime, Some(patch))
+            .expect("build invocation");
+        assert_eq!(
+            invocation.args,
+            vec![
+                "--context=prod",
+                "delete",
+                "--dry-run=client",
+                "pod",
+                "x",
+                "--dry-run=none"
+            ]
+        );
+        assert_eq!(invocation.env.get("KUBE_EDITOR"), Some(&"vi".to_string()));
+        assert!(!invocation.env.contains_key("KUBECONFIG"));
+        assert_eq!(invocation.env_remove, vec!["KUBECONFIG".to_string()]);
+
+        let runtime = vec!["rollout".to_string(), "status".to_string(), "d".to_string()];
+        let invocation = manifest
+            .build_invocation(&runtime, None)
+            .expect("build invocation");
+        assert_eq!(
+            invocation.args,
+            vec!["--context=prod", "rollout", "status", "--watch=false", "d"]
+        );
+
+        let runtime = vec!["get".to_string(), "delete".to_string()];
+        let invocation = manifest
+            .build_invocation(&runtime, None)
+            .expect("build invocation");
+        assert_eq!(invocation.args, vec!["--context=prod", "get", "delete"]);
+        assert_eq!(invocation.env_remove, vec!["KUBE_EDITOR".to_string()]);
+    }
+
+    #[test]
+    fn apply_profile_overrides_exec_args_journal_and_layers_env() {
+        let mut manifest =
+            Manifest::simple(PathBuf::from("kubectl")).with_args(vec!["--context=dev".into()]);
+        manifest.env = HashMap::from([("STAGE".into(), "dev".into())]);
+        manifest.env_remove = vec!["PROD_TOKEN".into()];
+        manifest.profiles.insert(
+            "prod".into(),
+            ProfileOverlay {
+                exec: Some(PathBuf::from("/opt/kubectl-prod")),
+                args: Some(vec!["--context=prod".into()]),
+                env: HashMap::from([
+                    ("STAGE".into(), "prod".into()),
+                    ("PROD_TOKEN".into(), "t".into()),
+                ]),
+                journal: Some(JournalConfig {
+                    namespace: "prod".into(),
+                    stderr: true,
+                    identifier: None,
+                    user_scope: false,
+                    ensure: false,
+                    max_use: None,
+                    rate_limit_interval_usec: None,
+                    rate_limit_burst: None,
+                }),
+                ..ProfileOverlay::default()
+            },
+        );
+        manifest
+            .profiles
+            .insert("staging".into(), ProfileOverlay::default());
+        manifest.default_profile = Some("staging".into());
+
+        let prod = manifest
+            .apply_profile(Some(&ProfileRequest::Env("prod".into())))
+            .expect("apply prod");
+        assert_eq!(prod.exec, PathBuf::from("/opt/kubectl-prod"));
+        assert_eq!(prod.args, vec!["--context=prod"]);
+        assert_eq!(prod.env.get("STAGE"), Some(&"prod".to_string()));
+        assert!(prod.env_remove.is_empty());
+        assert_eq!(prod.journal.expect("journal").namespace, "prod");
+
+        let default = manifest.apply_profile(None).expect("apply default");
+        assert_eq!(default.args, vec!["--context=dev"]);
+
+        let err = manifest
+            .apply_profile(Some(&ProfileRequest::Flag("qa".into())))
+            .expect_err("unknown flag profile");
+        assert!(
+            err.to_string()
+                .contains("--chopper-profile selects unknown profile `qa`"),
+            "{err}"
+        );
+
+        let plain = Manifest::simple(PathBuf::from("echo"));
+        assert_eq!(
+            plain
+                .apply_profile(Some(&ProfileRequest::Env("prod".into())))
+                .expect("env profile is ignored without profiles"),
+            plain
+        );
+        assert!(plain
+            .apply_profile(Some(&ProfileRequest::Flag("prod".into())))
+            .is_err());
+    }
+
+    #[test]
+    fn select_exec_picks_first_existing_candidate() {
+        let temp = tempfile::TempDir::new().expect("create tempdir");
+        let tool = temp.path().join("vim");
+        std::fs::write(&tool, "#!/bin/sh\n").expect("write tool");
+        std::fs::set_permissions(&tool, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+            .expect("chmod tool");
+
+        let mut manifest = Manifest::simple(temp.path().join("nvim"));
+        manifest.exec_fallbacks = vec![tool.clone(), PathBuf::from("/bin/sh")];
+        let selected = manifest.select_exec().expect("select exec");
+        assert_eq!(selected.exec, tool);
+        assert!(selected.exec_fallbacks.is_empty());
+
+        manifest.exec_fallbacks = vec![temp.path().join("vi")];
+        let err = manifest.select_exec().expect_err("no candidate exists");
+        assert!(
+            err.to_string()
+                .contains("none of the `exec` candidates were found"),
+            "{err}"
+        );
+
+        let plain = Manifest::simple(PathBuf::from("/definitely/missing"));
+        assert_eq!(plain.select_exec().expect("single exec").exec, plain.exec);
+    }
+
+    #[test]
+    fn patch_set_cwd_overrides_alias_cwd() {
+        let mut manifest = Manifest::simple(PathBuf::from("echo"));
+        manifest.cwd = Some(PathBuf::from("/srv/repo"));
+
+        let invocation = manifest
+            .build_invocation(&[], None)
+            .expect("build invocation");
+        assert_eq!(invocation.cwd, Some(PathBuf::from("/srv/repo")));
+
+        let patch = RuntimePatch {
+            set_cwd: Some(PathBuf::from("/srv/other")),
+            ..RuntimePatch::default()
+        };
+        let invocation = manifest
+            .build_invocation(&[], Some(patch))
+            .expect("build invocation");
+        assert_eq!(invocation.cwd, Some(PathBuf::from("/srv/other")));
+    }
+
     #[test]
     fn patch_set_env_overrides_alias_env_remove() {
         let mut manifest = Manifest::simple(PathBuf::from("echo"));
@@ -323,4 +1641,322 @@ mod tests {
             None => env::remove_var("PATH"),
         }
     }
+
+    #[test]
+    fn clean_env_mode_keeps_only_listed_variables_and_composes_with_path() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        let original_path = env::var("PATH").ok();
+        env::set_var("PATH", "/usr/bin:/bin");
+        env::set_var("CHOPPER_KEEP_EXACT", "kept");
+        env::set_var("CHOPPER_KEEP_EXACT_NOT", "dropped");
+        env::set_var("LC_CHOPPER_TEST", "kept-by-regex");
+
+        let mut manifest = Manifest::simple(PathBuf::from("echo"));
+        manifest.env_mode = EnvMode::Clean;
+        manifest.env_keep = vec!["CHOPPER_KEEP_EXACT".into(), "^LC_CHOPPER".into()];
+        manifest.env = HashMap::from([("MODE".into(), "clean".into())]);
+        manifest.path = Some(PathMutationConfig {
+            prepend_one: vec!["/custom/bin".into()],
+            ..PathMutationConfig::default()
+        });
+
+        let invocation = manifest
+            .build_invocation(&[], None)
+            .expect("build invocation");
+        assert!(invocation.clear_env);
+        assert_eq!(
+            invocation.env,
+            HashMap::from([
+                ("CHOPPER_KEEP_EXACT".into(), "kept".into()),
+                ("LC_CHOPPER_TEST".into(), "kept-by-regex".into()),
+                ("MODE".into(), "clean".into()),
+                ("PATH".into(), "/custom/bin".into()),
+            ])
+        );
+
+        manifest.env_keep.push("PATH".into());
+        let invocation = manifest
+            .build_invocation(&[], None)
+            .expect("build invocation");
+        assert_eq!(
+            invocation.env.get("PATH").map(String::as_str),
+            Some("/custom/bin:/usr/bin:/bin")
+        );
+
+        env::remove_var("CHOPPER_KEEP_EXACT");
+        env::remove_var("CHOPPER_KEEP_EXACT_NOT");
+        env::remove_var("LC_CHOPPER_TEST");
+        match original_path {
+            Some(value) => env::set_var("PATH", value),
+            None => env::remove_var("PATH"),
+        }
+    }
+
+    #[test]
+    fn env_remove_matching_unsets_inherited_variables_but_not_configured_ones() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        env::set_var("CHOPPER_RM_AWS_PROFILE", "dev");
+        env::set_var("CHOPPER_RM_AWS_REGION", "eu");
+        env::set_var("CHOPPER_RM_KUBECONFIG", "/kube");
+        env::set_var("CHOPPER_RM_KEEP", "kept");
+        env::set_var("X_CHOPPER_RM_KUBE", "kept");
+
+        let mut manifest = Manifest::simple(PathBuf::from("echo"));
+        manifest.env_remove_matching =
+            vec!["^CHOPPER_RM_AWS_".into(), "glob:CHOPPER_RM_KUBE*".into()];
+        manifest.env = HashMap::from([("CHOPPER_RM_AWS_REGION".into(), "us".into())]);
+
+        let invocation = manifest
+            .build_invocation(&[], None)
+            .expect("build invocation");
+        assert!(invocation
+            .env_remove
+            .contains(&"CHOPPER_RM_AWS_PROFILE".to_string()));
+        assert!(invocation
+            .env_remove
+            .contains(&"CHOPPER_RM_KUBECONFIG".to_string()));
+        assert!(!invocation
+            .env_remove
+            .contains(&"CHOPPER_RM_AWS_REGION".to_string()));
+        assert!(!invocation
+            .env_remove
+            .contains(&"CHOPPER_RM_KEEP".to_string()));
+        assert!(!invocation
+            .env_remove
+            .contains(&"X_CHOPPER_RM_KUBE".to_string()));
+        assert_eq!(
+            invocation
+                .env
+                .get("CHOPPER_RM_AWS_REGION")
+                .map(String::as_str),
+            Some("us")
+        );
+
+        let patch = RuntimePatch {
+            remove_env_matching: vec!["^CHOPPER_RM_AWS_".into()],
+            ..RuntimePatch::default()
+        };
+        let invocation = manifest
+            .build_invocation(&[], Some(patch))
+            .expect("build patched invocation");
+        assert!(!invocation.env.contains_key("CHOPPER_RM_AWS_REGION"));
+        assert!(invocation
+            .env_remove
+            .contains(&"CHOPPER_RM_AWS_REGION".to_string()));
+
+        manifest.env_remove_matching = vec!["(".into()];
+        let err = manifest
+            .build_invocation(&[], None)
+            .expect_err("invalid pattern should fail");
+        assert!(
+            err.to_string()
+                .contains("field `env_remove_matching` contains invalid regex `(`"),
+            "{err}"
+        );
+
+        for key in [
+            "CHOPPER_RM_AWS_PROFILE",
+            "CHOPPER_RM_AWS_REGION",
+            "CHOPPER_RM_KUBECONFIG",
+            "CHOPPER_RM_KEEP",
+            "X_CHOPPER_RM_KUBE",
+        ] {
+            env::remove_var(key);
+        }
+    }
+
+    #[test]
+    fn env_default_append_and_prepend_build_on_the_current_value() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        env::set_var("CHOPPER_OPS_EDITOR", "nano");
+        env::set_var("CHOPPER_OPS_FLAGS", "-O");
+        env::set_var("CHOPPER_OPS_EMPTY", "");
+        env::remove_var("CHOPPER_OPS_UNSET");
+        env::remove_var("CHOPPER_OPS_PATCHED");
+
+        let join = |value: &str, separator: &str| EnvJoin {
+            value: value.into(),
+            separator: separator.into(),
+        };
+        let mut manifest = Manifest::simple(PathBuf::from("echo"));
+        manifest.env_default = HashMap::from([
+            ("CHOPPER_OPS_EDITOR".into(), "vi".into()),
+            ("CHOPPER_OPS_UNSET".into(), "fallback".into()),
+        ]);
+        manifest.env_append = BTreeMap::from([
+            ("CHOPPER_OPS_FLAGS".into(), join("-g", " ")),
+            ("CHOPPER_OPS_EMPTY".into(), join("x", ":")),
+            ("CHOPPER_OPS_UNSET".into(), join("more", ",")),
+        ]);
+        manifest.env_prepend = BTreeMap::from([("CHOPPER_OPS_FLAGS".into(), join("-Wall", " "))]);
+
+        let invocation = manifest
+            .build_invocation(&[], None)
+            .expect("build invocation");
+        let get = |key: &str| invocation.env.get(key).map(String::as_str);
+        assert_eq!(get("CHOPPER_OPS_EDITOR"), None);
+        assert_eq!(get("CHOPPER_OPS_UNSET"), Some("fallback,more"));
+        assert_eq!(get("CHOPPER_OPS_FLAGS"), Some("-Wall -O -g"));
+        assert_eq!(get("CHOPPER_OPS_EMPTY"), Some("x"));
+
+        manifest.env_remove = vec!["CHOPPER_OPS_EDITOR".into()];
+        let patch = RuntimePatch {
+            default_env: HashMap::from([("CHOPPER_OPS_PATCHED".into(), "p".into())]),
+            append_env: BTreeMap::from([("CHOPPER_OPS_PATCHED".into(), join("q", "/"))]),
+            ..RuntimePatch::default()
+        };
+        let invocation = manifest
+            .build_invocation(&[], Some(patch))
+            .expect("build patched invocation");
+        let get = |key: &str| invocation.env.get(key).map(String::as_str);
+        assert_eq!(get("CHOPPER_OPS_EDITOR"), Some("vi"));
+        assert_eq!(get("CHOPPER_OPS_PATCHED"), Some("p/q"));
+
+        for key in [
+            "CHOPPER_OPS_EDITOR",
+            "CHOPPER_OPS_FLAGS",
+            "CHOPPER_OPS_EMPTY",
+        ] {
+            env::remove_var(key);
+        }
+    }
+
+    #[test]
+    fn build_invocation_traced_records_each_value_origin() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        env::set_var("CHOPPER_TRACE_DROP", "1");
+        env::remove_var("CHOPPER_TRACE_SET");
+
+        let mut manifest = Manifest::simple(PathBuf::from("/usr/bin/kubectl"))
+            .with_args(vec!["get".into(), "{1}".into()]);
+        manifest.args_after = vec!["--watch".into()];
+        manifest.env = HashMap::from([("CHOPPER_TRACE_SET".into(), "cfg".into())]);
+        manifest.env_remove = vec!["CHOPPER_TRACE_DROP".into()];
+        manifest.path = Some(PathMutationConfig {
+            append_one: vec!["/opt/kube/bin".into()],
+            ..PathMutationConfig::default()
+        });
+        let patch = RuntimePatch {
+            append_args: vec!["-o".into()],
+            set_env: HashMap::from([("CHOPPER_TRACE_PATCH".into(), "yes".into())]),
+            ..RuntimePatch::default()
+        };
+
+        let (invocation, provenance) = manifest
+            .build_invocation_traced(&["pods".into(), "ignored".into()], Some(patch))
+            .expect("build traced invocation");
+        assert_eq!(invocation.args, vec!["get", "pods", "--watch", "-o"]);
+        assert_eq!(
+            provenance.args,
+            vec![
+                Origin::config("args[0]"),
+                Origin::Runtime { position: 1 },
+                Origin::config("args_after[0]"),
+                Origin::reconcile("append_args[0]"),
+            ]
+        );
+        assert_eq!(
+            provenance.env.get("CHOPPER_TRACE_SET"),
+            Some(&Origin::config("env.CHOPPER_TRACE_SET"))
+        );
+        assert_eq!(
+            provenance.env.get("CHOPPER_TRACE_PATCH"),
+            Some(&Origin::reconcile("set_env.CHOPPER_TRACE_PATCH"))
+        );
+        assert_eq!(
+            provenance.env_remove.get("CHOPPER_TRACE_DROP"),
+            Some(&Origin::config("env_remove"))
+        );
+        let path = provenance.path.expect("path provenance");
+        assert_eq!(path.base, Some(Origin::Inherited));
+        let last = path.components.last().expect("appended component");
+        assert_eq!(last.value, "/opt/kube/bin");
+        assert_eq!(
+            last.step.as_ref().map(|step| step.field.as_str()),
+            Some("path.append_one[0]")
+        );
+
+        env::remove_var("CHOPPER_TRACE_DROP");
+    }
+
+    #[test]
+    fn retry_policy_filters_exit_codes_and_backs_off() {
+        let mut retry = RetryConfig {
+            attempts: 5,
+            backoff: RetryBackoff::Exponential,
+            initial_delay_ms: 1000,
+            on_exit_codes: vec![1, 75],
+        };
+        assert!(retry.should_retry(Some(75)));
+        assert!(!retry.should_retry(Some(2)));
+        assert!(!retry.should_retry(Some(0)));
+        assert!(!retry.should_retry(None));
+        assert_eq!(retry.delay_after(1), Duration::from_secs(1));
+        assert_eq!(retry.delay_after(3), Duration::from_secs(4));
+        assert_eq!(retry.delay_after(80), Duration::from_millis(u64::MAX));
+
+        retry.on_exit_codes.clear();
+        retry.backoff = RetryBackoff::Fixed;
+        assert!(retry.should_retry(Some(2)));
+        assert_eq!(retry.delay_after(3), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn expand_placeholders_rewrites_exec_args_env_and_path_entries() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        env::set_var("CHOPPER_MANIFEST_INTERP", "ctx");
+
+        let mut manifest = Manifest::simple(PathBuf::from("./bin/${alias}"));
+        manifest.alias_dir = Some(PathBuf::from("/cfg/kpods"));
+        manifest.args = vec!["--context=${env:CHOPPER_MANIFEST_INTERP}".into()];
+        manifest.env = HashMap::from([("KUBECONFIG".into(), "${alias_dir}/kubeconfig".into())]);
+        manifest.path = Some(PathMutationConfig {
+            prepend_one: vec!["${alias_dir}/bin".into()],
+            ..PathMutationConfig::default()
+        });
+
+        let expanded = manifest.expand_placeholders("kpods").expect("expand");
+        assert_eq!(expanded.exec, PathBuf::from("/cfg/kpods/./bin/kpods"));
+        assert_eq!(expanded.args, vec!["--context=ctx"]);
+        assert_eq!(
+            expanded.env.get("KUBECONFIG"),
+            Some(&"/cfg/kpods/kubeconfig".to_string())
+        );
+        assert_eq!(
+            expanded.path.expect("path config").prepend_one,
+            vec!["/cfg/kpods/bin"]
+        );
+        assert_eq!(
+            manifest.args,
+            vec!["--context=${env:CHOPPER_MANIFEST_INTERP}"]
+        );
+
+        env::remove_var("CHOPPER_MANIFEST_INTERP");
+    }
+
+    #[test]
+    fn expand_placeholders_names_the_failing_field() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        env::remove_var("CHOPPER_MANIFEST_INTERP_MISSING");
+
+        let mut manifest = Manifest::simple(PathBuf::from("echo"));
+        manifest.env = HashMap::from([(
+            "TOKEN".into(),
+            "${env:CHOPPER_MANIFEST_INTERP_MISSING}".into(),
+        )]);
+        let err = manifest
+            .expand_placeholders("demo")
+            .expect_err("unset variable should fail");
+        assert!(err.to_string().contains("field `env.TOKEN`"), "{err}");
+
+        let manifest = Manifest::simple(PathBuf::from("${env:CHOPPER_MANIFEST_INTERP_MISSING:-}"));
+        let err = manifest
+            .expand_placeholders("demo")
+            .expect_err("empty exec should fail");
+        assert!(
+            err.to_string()
+                .contains("field `exec` expands to an empty value"),
+            "{err}"
+        );
+    }
 }
@@ -1,16 +1,96 @@
+use crate::alias_validation::{self, AliasViolation};
+use crate::arg_placement::{self, SlotViolation};
 use crate::arg_validation::{self, ArgViolation};
+use crate::durations;
 use crate::env_validation::{self, EnvKeyViolation, EnvValueViolation};
+use crate::interpolation;
 use crate::journal_validation::{self, JournalIdentifierViolation, JournalNamespaceViolation};
-use crate::manifest::{BashcompConfig, JournalConfig, Manifest, ReconcileConfig};
-use crate::path_mutation::PathMutationConfig;
+use crate::manifest::{
+    AuditConfig, BashcompConfig, ConcurrencyConfig, ConcurrencyMode, EnvJoin, EnvMode, GuardConfig,
+    GuardFallback, HooksConfig, JournalConfig, LimitsConfig, Manifest, ProfileOverlay,
+    ReconcileConfig, RetryBackoff, RetryConfig, RlimitValue, SecretSource, SubcommandOverlay,
+    TimeoutConfig, VersionRequirement,
+};
+use crate::path_mutation::{PathListConfig, PathMutationConfig};
+use crate::process_limits;
 use crate::rhai_wiring::shared_rhai_path_for_alias_doc;
+use crate::secrets;
+use crate::signal_relay;
+use crate::version_requirement;
 use anyhow::{anyhow, Context, Result};
+use nix::sys::signal::Signal;
 use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 pub fn parse(path: &Path) -> Result<Manifest> {
+    parse_with_ancestry(path, &mut Vec::new())
+}
+
+/// Returns `path` followed by every other file its manifest is assembled
+/// from: `include` fragments, then the `extends` parent (and, recursively, the
+/// parent's fragments and parents). Resolution is best-effort: unreadable,
+/// malformed, missing, or cyclic references end the walk, and `parse` reports
+/// the actual error.
+pub fn config_source_files(path: &Path) -> Vec<PathBuf> {
+    config_layers(path)
+        .into_iter()
+        .flat_map(|layer| std::iter::once(layer.file).chain(layer.fragments))
+        .collect()
+}
+
+/// One alias file of an `extends` chain and the `include` fragments it
+/// splices in, in listed order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConfigLayer {
+    pub(crate) file: PathBuf,
+    pub(crate) fragments: Vec<PathBuf>,
+}
+
+/// The `extends` chain of `path`, the alias itself first, resolved as
+/// best-effort as [`config_source_files`].
+pub(crate) fn config_layers(path: &Path) -> Vec<ConfigLayer> {
+    let mut layers = Vec::new();
+    let mut seen = HashSet::from([config_identity(path)]);
+    let mut current = path.to_path_buf();
+    loop {
+        let probe = read_source_probe(&current);
+        let mut layer = ConfigLayer {
+            file: current.clone(),
+            fragments: Vec::new(),
+        };
+        let Some(probe) = probe else {
+            layers.push(layer);
+            break;
+        };
+        let base_dir = config_base_dir(&current);
+        for include in &probe.include {
+            let fragment = resolve_script_path(&base_dir, include.trim());
+            if fragment.is_file() && seen.insert(config_identity(&fragment)) {
+                layer.fragments.push(fragment);
+            }
+        }
+        layers.push(layer);
+
+        let Some(parent) = probe
+            .extends
+            .map(|alias| alias.trim().to_string())
+            .filter(|alias| !alias.is_empty())
+            .and_then(|alias| crate::find_config(&alias))
+        else {
+            break;
+        };
+        if !seen.insert(config_identity(&parent)) {
+            break;
+        }
+        current = parent;
+    }
+    layers
+}
+
+fn parse_with_ancestry(path: &Path, ancestry: &mut Vec<PathBuf>) -> Result<Manifest> {
     if !is_toml_path(path) {
         return Err(anyhow!(
             "unsupported alias config format `{}`; expected a .toml file",
@@ -18,9 +98,34 @@ pub fn parse(path: &Path) -> Result<Manifest> {
         ));
     }
 
+    let identity = config_identity(path);
+    if let Some(start) = ancestry.iter().position(|seen| *seen == identity) {
+        let cycle = ancestry[start..]
+            .iter()
+            .chain(std::iter::once(&identity))
+            .map(|entry| entry.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        return Err(anyhow!(
+            "field `extends` forms an inheritance cycle: {cycle}"
+        ));
+    }
+
     let content = fs::read_to_string(path)
         .with_context(|| format!("failed to read alias config {}", path.display()))?;
-    parse_toml(strip_utf8_bom(&content), path)
+    ancestry.push(identity);
+    let manifest = parse_toml(strip_utf8_bom(&content), path, ancestry);
+    ancestry.pop();
+    manifest
+}
+
+fn config_identity(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn read_source_probe(path: &Path) -> Option<SourceProbe> {
+    let content = fs::read_to_string(path).ok()?;
+    toml::from_str(strip_utf8_bom(&content)).ok()
 }
 
 fn is_toml_path(path: &Path) -> bool {
@@ -34,81 +139,123 @@ fn strip_utf8_bom(content: &str) -> &str {
     content.strip_prefix('\u{feff}').unwrap_or(content)
 }
 
-fn parse_toml(content: &str, path: &Path) -> Result<Manifest> {
-    let parsed: AliasConfig =
+fn parse_toml(content: &str, path: &Path, ancestry: &mut Vec<PathBuf>) -> Result<Manifest> {
+    let mut parsed: AliasConfig =
         toml::from_str(content).with_context(|| format!("invalid TOML in {}", path.display()))?;
     let base_dir = config_base_dir(path);
+    splice_include_fragments(&mut parsed, &base_dir)?;
 
-    let exec = parsed.exec.trim();
-    if exec.is_empty() {
-        return Err(anyhow!("field `exec` cannot be empty"));
-    }
-    if exec.contains('\0') {
-        return Err(anyhow!("field `exec` cannot contain NUL bytes"));
-    }
-    if exec == "." || exec == ".." {
-        return Err(anyhow!("field `exec` cannot be `.` or `..`"));
-    }
-    if ends_with_path_separator(exec) {
-        return Err(anyhow!("field `exec` cannot end with a path separator"));
+    let parent = match parsed.extends.as_deref() {
+        Some(extends) => Some(parse_parent_alias(extends, path, ancestry)?),
+        None => None,
+    };
+
+    let (exec, exec_fallbacks) = match (parsed.exec, parent.as_ref()) {
+        (Some(exec), _) => normalize_exec_candidates(&base_dir, exec)?,
+        (None, Some(parent)) => (parent.exec.clone(), parent.exec_fallbacks.clone()),
+        (None, None) => {
+            return Err(anyhow!("field `exec` is required unless `extends` is set"));
+        }
+    };
+    validate_arg_values(&parsed.args, "field `args`")?;
+    validate_arg_values(&parsed.args_after, "field `args_after`")?;
+    validate_runtime_arg_slots(&parsed.args, &parsed.args_after)?;
+
+    let overrides = InheritanceOverrides {
+        journal: parsed.journal.is_some(),
+        reconcile: parsed.reconcile.is_some(),
+        bashcomp: parsed.bashcomp.is_some(),
+        limits: parsed.limits.is_some(),
+        requires: parsed.requires.is_some(),
+        guard: parsed.guard.is_some(),
+        hooks: parsed.hooks.is_some(),
+        timeout: parsed.timeout.is_some(),
+        retry: parsed.retry.is_some(),
+        concurrency: parsed.concurrency.is_some(),
+        audit: parsed.audit.is_some(),
+        env_mode: parsed.env_mode.is_some(),
+    };
+
+    let mut manifest = Manifest::simple(exec).with_args(parsed.args);
+    manifest.exec_fallbacks = exec_fallbacks;
+    manifest.args_after = parsed.args_after;
+    manifest.cwd = normalize_cwd(&base_dir, parsed.cwd.as_deref())?;
+    manifest.alias_dir = Some(base_dir.clone());
+    manifest.env = normalize_env_map(parsed.env, "env")?;
+    manifest.env_remove = normalize_env_remove(parsed.env_remove, "env_remove")?;
+    manifest.env_remove_matching = normalize_env_remove_matching(parsed.env_remove_matching)?;
+    manifest.env_default = normalize_env_map(parsed.env_default, "env_default")?;
+    manifest.env_append = normalize_env_joins(parsed.env_append, "env_append")?;
+    manifest.env_prepend = normalize_env_joins(parsed.env_prepend, "env_prepend")?;
+    manifest.env_file = normalize_env_files(&base_dir, &parsed.env_file)?;
+    manifest.env_secret = normalize_env_secrets(&base_dir, parsed.env_secret)?;
+    if let Some(env_mode) = parsed.env_mode.as_deref() {
+        manifest.env_mode = normalize_env_mode(env_mode)?;
     }
-    if ends_with_dot_component(exec) {
+    manifest.env_keep = normalize_env_keep(parsed.env_keep)?;
+    if let Some(key) = manifest
+        .env_secret
+        .keys()
+        .find(|key| manifest.env.contains_key(*key))
+    {
         return Err(anyhow!(
-            "field `exec` cannot end with `.` or `..` path components"
+            "field `env_secret` key `{key}` is also set in field `env`"
         ));
     }
-    if looks_like_relative_exec_path(exec) && !has_meaningful_relative_segment(exec) {
+    if let Some(key) = manifest
+        .env_default
+        .keys()
+        .find(|key| manifest.env.contains_key(*key) || manifest.env_secret.contains_key(*key))
+    {
         return Err(anyhow!(
-            "field `exec` must include a path segment when using relative path notation"
+            "field `env_default` key `{key}` is also set in field `env` or `env_secret`"
         ));
     }
-    validate_arg_values(&parsed.args, "field `args`")?;
-
-    let exec = resolve_exec_path(&base_dir, exec);
-
-    let mut manifest = Manifest::simple(exec).with_args(parsed.args);
-    manifest.env = normalize_env_map(parsed.env)?;
-    manifest.env_remove = normalize_env_remove(parsed.env_remove)?;
     if let Some(path) = parsed.path.filter(|config| !config.is_empty()) {
         path.validate("path")?;
         manifest.path = Some(path);
     }
+    manifest.pathlists = normalize_pathlists(parsed.pathlists)?;
+    manifest.subcommands = normalize_subcommands(parsed.subcommands)?;
+    manifest.profiles = normalize_profiles(&base_dir, parsed.profile)?;
+    manifest.default_profile = normalize_default_profile(parsed.default_profile.as_deref())?;
+
+    if let Some(limits) = parsed.limits {
+        manifest.limits = normalize_limits(limits)?;
+    }
+
+    if let Some(requires) = parsed.requires {
+        manifest.requires = Some(normalize_requires(requires)?);
+    }
+
+    if let Some(guard) = parsed.guard {
+        manifest.guard = normalize_guard(guard)?;
+    }
+
+    if let Some(hooks) = parsed.hooks {
+        manifest.hooks = normalize_hooks(hooks)?;
+    }
+
+    manifest.timeout = normalize_timeout(
+        parsed.timeout.as_deref(),
+        parsed.timeout_signal.as_deref(),
+        parsed.kill_after.as_deref(),
+    )?;
+
+    if let Some(retry) = parsed.retry {
+        manifest.retry = normalize_retry(retry)?;
+    }
+
+    if let Some(concurrency) = parsed.concurrency {
+        manifest.concurrency = Some(normalize_concurrency(concurrency)?);
+    }
+
+    if let Some(audit) = parsed.audit {
+        manifest.audit = Some(normalize_audit(audit)?);
+    }
 
     if let Some(journal) = parsed.journal {
-        let namespace = match journal_validation::normalize_namespace(&journal.namespace) {
-            Ok(namespace) => namespace,
-            Err(JournalNamespaceViolation::Empty) => {
-                return Err(anyhow!("field `journal.namespace` cannot be empty"));
-            }
-            Err(JournalNamespaceViolation::ContainsNul) => {
-                return Err(anyhow!(
-                    "field `journal.namespace` cannot contain NUL bytes"
-                ));
-            }
-        };
-        let identifier = match journal_validation::normalize_optional_identifier_for_config(
-            journal.identifier.as_deref(),
-        ) {
-            Ok(identifier) => identifier,
-            Err(JournalIdentifierViolation::ContainsNul) => {
-                return Err(anyhow!(
-                    "field `journal.identifier` cannot contain NUL bytes"
-                ));
-            }
-            Err(JournalIdentifierViolation::Blank) => {
-                unreachable!("blank identifiers are normalized to None for config parsing")
-            }
-        };
-        manifest = manifest.with_journal(JournalConfig {
-            namespace,
-            stderr: journal.stderr,
-            identifier,
-            user_scope: journal.user_scope,
-            ensure: journal.ensure,
-            max_use: journal.max_use.clone(),
-            rate_limit_interval_usec: journal.rate_limit_interval_usec,
-            rate_limit_burst: journal.rate_limit_burst,
-        });
+        manifest = manifest.with_journal(normalize_journal(journal, "journal")?);
     }
 
     if let Some(reconcile) = parsed.reconcile {
@@ -183,354 +330,1931 @@ fn parse_toml(content: &str, path: &Path) -> Result<Manifest> {
         });
     }
 
+    let manifest = match parent {
+        Some(parent) => merge_parent_manifest(parent, manifest, overrides),
+        None => manifest,
+    };
+    if !manifest.env_keep.is_empty() && manifest.env_mode != EnvMode::Clean {
+        return Err(anyhow!("field `env_keep` requires `env_mode = \"clean\"`"));
+    }
+    if let Some(default_profile) = manifest.default_profile.as_deref() {
+        if !manifest.profiles.contains_key(default_profile) {
+            return Err(anyhow!(
+                "field `default_profile` references undefined profile `{default_profile}`"
+            ));
+        }
+    }
     Ok(manifest)
 }
 
-fn normalize_env_map(env: HashMap<String, String>) -> Result<HashMap<String, String>> {
-    let mut normalized = HashMap::with_capacity(env.len());
-    for (key, value) in env {
-        let normalized_key = key.trim();
-        if normalized_key.is_empty() {
-            return Err(anyhow!("field `env` cannot contain empty keys"));
+/// Folds `include` fragments into the alias before validation. Fragments apply
+/// in listed order and the alias file itself is applied last: `[env]` keys
+/// override earlier ones, `env_remove` and `[path]` entries accumulate, and the
+/// last `[journal]` table wins.
+fn splice_include_fragments(parsed: &mut AliasConfig, base_dir: &Path) -> Result<()> {
+    if parsed.include.is_empty() {
+        return Ok(());
+    }
+
+    let mut env = HashMap::new();
+    let mut env_remove = Vec::new();
+    let mut path = PathMutationConfig::default();
+    let mut journal = None;
+    for include in &parsed.include {
+        validate_optional_script_value(include, "field `include` entries")?;
+        let fragment_path = resolve_script_path(base_dir, include.trim());
+        let fragment = parse_include_fragment(&fragment_path)?;
+        env.extend(fragment.env);
+        env_remove.extend(fragment.env_remove);
+        if let Some(fragment_path) = fragment.path {
+            path.extend(fragment_path);
         }
-        match env_validation::validate_env_key(normalized_key) {
-            Ok(()) => {}
-            Err(EnvKeyViolation::ContainsEquals) => {
-                return Err(anyhow!(
-                    "field `env` keys cannot contain `=`: `{normalized_key}`"
-                ));
-            }
-            Err(EnvKeyViolation::ContainsNul) => {
-                return Err(anyhow!("field `env` keys cannot contain NUL bytes"));
-            }
+        if fragment.journal.is_some() {
+            journal = fragment.journal;
         }
-        if matches!(
-            env_validation::validate_env_value(&value),
-            Err(EnvValueViolation::ContainsNul)
-        ) {
-            return Err(anyhow!(
-                "field `env` values cannot contain NUL bytes for key `{normalized_key}`"
-            ));
+    }
+
+    env.extend(std::mem::take(&mut parsed.env));
+    env_remove.append(&mut parsed.env_remove);
+    if let Some(own_path) = parsed.path.take() {
+        path.extend(own_path);
+    }
+    parsed.env = env;
+    parsed.env_remove = env_remove;
+    parsed.path = Some(path);
+    if parsed.journal.is_none() {
+        parsed.journal = journal;
+    }
+    Ok(())
+}
+
+fn parse_include_fragment(path: &Path) -> Result<IncludeFragment> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("failed to read include fragment {}", path.display()))?;
+    toml::from_str(strip_utf8_bom(&content))
+        .with_context(|| format!("invalid TOML in include fragment {}", path.display()))
+}
+
+/// An `exec = [...]` list keeps every candidate; the first one present on the
+/// machine is chosen per invocation by `Manifest::select_exec`.
+fn normalize_exec_candidates(base_dir: &Path, exec: ExecInput) -> Result<(PathBuf, Vec<PathBuf>)> {
+    match exec {
+        ExecInput::Command(exec) => {
+            Ok((normalize_exec(base_dir, &exec, "field `exec`")?, Vec::new()))
         }
-        if normalized.contains_key(normalized_key) {
-            return Err(anyhow!(
-                "field `env` contains duplicate keys after trimming: `{normalized_key}`"
-            ));
+        ExecInput::Candidates(candidates) => {
+            let mut normalized = candidates
+                .iter()
+                .map(|candidate| normalize_exec(base_dir, candidate, "field `exec` entries"))
+                .collect::<Result<Vec<_>>>()?;
+            if normalized.is_empty() {
+                return Err(anyhow!("field `exec` cannot be an empty list"));
+            }
+            let exec = normalized.remove(0);
+            Ok((exec, normalized))
         }
-        normalized.insert(normalized_key.to_string(), value);
     }
-    Ok(normalized)
 }
 
-fn normalize_env_remove(env_remove: Vec<String>) -> Result<Vec<String>> {
-    let mut seen = HashSet::with_capacity(env_remove.len());
-    let mut normalized = Vec::with_capacity(env_remove.len());
-    for key in env_remove {
-        let normalized_key = key.trim();
-        if normalized_key.is_empty() {
-            continue;
-        }
-        match env_validation::validate_env_key(normalized_key) {
-            Ok(()) => {}
-            Err(EnvKeyViolation::ContainsEquals) => {
-                return Err(anyhow!(
-                    "field `env_remove` entries cannot contain `=`: `{normalized_key}`"
-                ));
+fn normalize_requires(requires: RequiresInput) -> Result<VersionRequirement> {
+    let min_version = requires
+        .min_version
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("field `requires.min_version` is required"))?;
+    if version_requirement::parse_version(min_version).is_none() {
+        return Err(anyhow!(
+            "field `requires.min_version` must be a dotted version like `1.2.3`: `{min_version}`"
+        ));
+    }
+
+    let version_cmd = requires
+        .version_cmd
+        .unwrap_or_else(|| vec![version_requirement::DEFAULT_VERSION_CMD.to_string()]);
+    validate_arg_values(&version_cmd, "field `requires.version_cmd`")?;
+
+    let version_regex = requires
+        .version_regex
+        .filter(|pattern| !pattern.trim().is_empty())
+        .unwrap_or_else(|| version_requirement::DEFAULT_VERSION_REGEX.to_string());
+    regex::Regex::new(&version_regex)
+        .map_err(|err| anyhow!("field `requires.version_regex` is not a valid regex: {err}"))?;
+
+    Ok(VersionRequirement {
+        version_cmd,
+        version_regex,
+        min_version: min_version.to_string(),
+    })
+}
+
+fn normalize_guard(guard: GuardInput) -> Result<Option<GuardConfig>> {
+    for (kind, rules) in [
+        ("deny", &guard.deny),
+        ("require", &guard.require),
+        ("confirm", &guard.confirm),
+    ] {
+        for rule in rules {
+            if rule.trim().is_empty() {
+                return Err(anyhow!("field `guard.{kind}` entries cannot be blank"));
             }
-            Err(EnvKeyViolation::ContainsNul) => {
+            if rule.contains('\0') {
                 return Err(anyhow!(
-                    "field `env_remove` entries cannot contain NUL bytes"
+                    "field `guard.{kind}` entries cannot contain NUL bytes"
                 ));
             }
+            regex::Regex::new(rule).map_err(|err| {
+                anyhow!("field `guard.{kind}` entry `{rule}` is not a valid regex: {err}")
+            })?;
         }
-        let normalized_key = normalized_key.to_string();
-        if seen.insert(normalized_key.clone()) {
-            normalized.push(normalized_key);
+    }
+    let non_tty = match guard.non_tty.as_deref().map(str::trim) {
+        None | Some("refuse") => GuardFallback::Refuse,
+        Some("allow") => GuardFallback::Allow,
+        Some(other) => {
+            return Err(anyhow!(
+                "field `guard.non_tty` must be `refuse` or `allow`: `{other}`"
+            ));
         }
+    };
+    if guard.deny.is_empty() && guard.require.is_empty() && guard.confirm.is_empty() {
+        return Ok(None);
     }
-    Ok(normalized)
+    Ok(Some(GuardConfig {
+        deny: guard.deny,
+        require: guard.require,
+        confirm: guard.confirm,
+        non_tty,
+    }))
 }
 
-fn validate_arg_values(values: &[String], field: &str) -> Result<()> {
-    for value in values {
-        if matches!(
-            arg_validation::validate_arg_value(value),
-            Err(ArgViolation::ContainsNul)
-        ) {
-            return Err(anyhow!("{field} entries cannot contain NUL bytes"));
+fn normalize_hooks(hooks: HooksInput) -> Result<Option<HooksConfig>> {
+    for (stage, commands) in [("pre", &hooks.pre), ("post", &hooks.post)] {
+        for command in commands {
+            if command
+                .first()
+                .is_none_or(|program| program.trim().is_empty())
+            {
+                return Err(anyhow!("field `hooks.{stage}` commands cannot be empty"));
+            }
+            validate_arg_values(command, &format!("field `hooks.{stage}` command"))?;
         }
     }
-    Ok(())
+    if hooks.pre.is_empty() && hooks.post.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(HooksConfig {
+        pre: hooks.pre,
+        post: hooks.post,
+        journal: hooks.journal,
+    }))
 }
 
-fn normalize_optional_method_name(value: Option<&str>, field: &str) -> Result<Option<String>> {
-    let Some(value) = value else {
+fn normalize_timeout(
+    timeout: Option<&str>,
+    timeout_signal: Option<&str>,
+    kill_after: Option<&str>,
+) -> Result<Option<TimeoutConfig>> {
+    let parse = |value: &str, field: &str| {
+        durations::parse_duration(value).ok_or_else(|| {
+            anyhow!(
+                "field `{field}` must be a duration like \"90s\", \"30m\", or \"2h\": `{value}`"
+            )
+        })
+    };
+    let Some(timeout) = timeout else {
+        if timeout_signal.is_some() || kill_after.is_some() {
+            return Err(anyhow!(
+                "fields `timeout_signal` and `kill_after` require `timeout`"
+            ));
+        }
         return Ok(None);
     };
-    let method = value.trim();
-    if method.is_empty() {
+    let after = parse(timeout, "timeout")?;
+    let signal = match timeout_signal {
+        Some(value) => signal_relay::parse_signal(value).ok_or_else(|| {
+            anyhow!("field `timeout_signal` must be a signal name or number: `{value}`")
+        })?,
+        None => Signal::SIGTERM,
+    };
+    let kill_after = match kill_after {
+        Some(value) => Some(parse(value, "kill_after")?).filter(|kill_after| !kill_after.is_zero()),
+        None => None,
+    };
+    // Like coreutils `timeout`, a zero duration disables the deadline.
+    if after.is_zero() {
         return Ok(None);
     }
-    if method.contains('\0') {
-        return Err(anyhow!("{field} cannot contain NUL bytes"));
+    Ok(Some(TimeoutConfig {
+        after_ms: duration_millis(after),
+        signal: signal.as_str().to_string(),
+        kill_after_ms: kill_after.map(duration_millis),
+    }))
+}
+
+fn normalize_retry(retry: RetryInput) -> Result<Option<RetryConfig>> {
+    let attempts = match retry.attempts {
+        Some(attempts) => u32::try_from(attempts)
+            .ok()
+            .filter(|attempts| *attempts >= 1)
+            .ok_or_else(|| anyhow!("field `retry.attempts` must be at least 1: `{attempts}`"))?,
+        None => 3,
+    };
+    let backoff = match retry.backoff.as_deref().map(str::trim) {
+        None | Some("exponential") => RetryBackoff::Exponential,
+        Some("fixed") => RetryBackoff::Fixed,
+        Some(other) => {
+            return Err(anyhow!(
+                "field `retry.backoff` must be `fixed` or `exponential`: `{other}`"
+            ));
+        }
+    };
+    let initial_delay = match retry.initial_delay.as_deref() {
+        Some(value) => durations::parse_duration(value).ok_or_else(|| {
+            anyhow!(
+                "field `retry.initial_delay` must be a duration like \"500ms\" or \"1s\": `{value}`"
+            )
+        })?,
+        None => Duration::from_secs(1),
+    };
+    let mut on_exit_codes = Vec::with_capacity(retry.on_exit_codes.len());
+    for code in retry.on_exit_codes {
+        let code = i32::try_from(code)
+            .ok()
+            .filter(|code| (1..=255).contains(code))
+            .ok_or_else(|| {
+                anyhow!("field `retry.on_exit_codes` entries must be between 1 and 255: `{code}`")
+            })?;
+        if !on_exit_codes.contains(&code) {
+            on_exit_codes.push(code);
+        }
     }
-    Ok(Some(method.to_string()))
+    if attempts == 1 {
+        return Ok(None);
+    }
+    Ok(Some(RetryConfig {
+        attempts,
+        backoff,
+        initial_delay_ms: u64::try_from(initial_delay.as_millis()).unwrap_or(u64::MAX),
+        on_exit_codes,
+    }))
 }
 
-fn validate_optional_script_value(value: &str, field: &str) -> Result<()> {
-    let trimmed = value.trim();
-    if trimmed.is_empty() {
+fn normalize_concurrency(concurrency: ConcurrencyInput) -> Result<ConcurrencyConfig> {
+    let mode = match concurrency.mode.as_deref().map(str::trim) {
+        None | Some("exclusive") => ConcurrencyMode::Exclusive,
+        Some("wait") => ConcurrencyMode::Wait,
+        Some("skip") => ConcurrencyMode::Skip,
+        Some(other) => {
+            return Err(anyhow!(
+                "field `concurrency.mode` must be `exclusive`, `wait`, or `skip`: `{other}`"
+            ));
+        }
+    };
+    let lock_key = match concurrency.lock_key.as_deref().map(str::trim) {
+        None => None,
+        Some(key) => {
+            // The key names a file in the lock directory, so it follows the
+            // same rules as alias names.
+            if alias_validation::validate_alias_identifier(key).is_err() {
+                return Err(anyhow!(
+                    "field `concurrency.lock_key` must be a valid alias-style name: `{key}`"
+                ));
+            }
+            Some(key.to_string())
+        }
+    };
+    Ok(ConcurrencyConfig { mode, lock_key })
+}
+
+fn normalize_audit(audit: AuditInput) -> Result<AuditConfig> {
+    crate::audit::validate_redaction_rules("audit", &audit.redact_options, &audit.redact_matching)?;
+    Ok(AuditConfig {
+        enabled: audit.enabled.unwrap_or(true),
+        redact_options: audit.redact_options,
+        redact_matching: audit.redact_matching,
+    })
+}
+
+fn duration_millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis())
+        .unwrap_or(u64::MAX)
+        .max(1)
+}
+
+fn normalize_exec(base_dir: &Path, exec: &str, field: &str) -> Result<PathBuf> {
+    let exec = exec.trim();
+    if exec.is_empty() {
         return Err(anyhow!("{field} cannot be empty"));
     }
-    if trimmed.contains('\0') {
+    if exec.contains('\0') {
         return Err(anyhow!("{field} cannot contain NUL bytes"));
     }
-    if trimmed == "." || trimmed == ".." {
+    if exec == "." || exec == ".." {
         return Err(anyhow!("{field} cannot be `.` or `..`"));
     }
-    if ends_with_path_separator(trimmed) {
+    if ends_with_path_separator(exec) {
         return Err(anyhow!("{field} cannot end with a path separator"));
     }
-    if ends_with_dot_component(trimmed) {
+    if ends_with_dot_component(exec) {
         return Err(anyhow!(
             "{field} cannot end with `.` or `..` path components"
         ));
     }
-    if !Path::new(trimmed).is_absolute() && !has_meaningful_relative_segment(trimmed) {
+    if looks_like_relative_exec_path(exec) && !has_meaningful_relative_segment(exec) {
         return Err(anyhow!(
-            "{field} must include a file path when using relative path notation"
+            "{field} must include a path segment when using relative path notation"
         ));
     }
-    Ok(())
+    if interpolation::contains_placeholders(exec) {
+        return Ok(PathBuf::from(exec));
+    }
+    Ok(resolve_exec_path(base_dir, exec))
 }
 
-fn ends_with_path_separator(value: &str) -> bool {
-    value.ends_with('/') || value.ends_with(std::path::MAIN_SEPARATOR)
-}
+/// Keys are split on whitespace and rejoined with single spaces, so
+/// `"rollout  status"` and `"rollout status"` name the same overlay.
+fn normalize_subcommands(
+    subcommands: HashMap<String, SubcommandInput>,
+) -> Result<BTreeMap<String, SubcommandOverlay>> {
+    let mut normalized = BTreeMap::new();
+    for (key, overlay) in subcommands {
+        if key.contains('\0') {
+            return Err(anyhow!("field `subcommands` keys cannot contain NUL bytes"));
+        }
+        let name = key.split_whitespace().collect::<Vec<_>>().join(" ");
+        if name.is_empty() {
+            return Err(anyhow!("field `subcommands` cannot contain empty keys"));
+        }
+        if normalized.contains_key(&name) {
+            return Err(anyhow!(
+                "field `subcommands` contains duplicate keys after trimming: `{name}`"
+            ));
+        }
 
-fn resolve_script_path(base_dir: &Path, script: &str) -> PathBuf {
-    let script_path = PathBuf::from(script);
-    if script_path.is_absolute() {
-        script_path
-    } else {
-        base_dir.join(script_path)
+        let prefix = format!("subcommands.{name}");
+        let args_field = format!("field `{prefix}.args`");
+        validate_arg_values(&overlay.args, &args_field)?;
+        for arg in &overlay.args {
+            if !matches!(arg_placement::parse_slot(arg), Ok(None)) {
+                return Err(anyhow!(
+                    "{args_field} cannot contain runtime-arg slot `{arg}`"
+                ));
+            }
+        }
+        let path = match overlay.path.filter(|config| !config.is_empty()) {
+            Some(path) => {
+                path.validate(&format!("{prefix}.path"))?;
+                Some(path)
+            }
+            None => None,
+        };
+        let overlay = SubcommandOverlay {
+            args: overlay.args,
+            env: normalize_env_map(overlay.env, &format!("{prefix}.env"))?,
+            env_remove: normalize_env_remove(overlay.env_remove, &format!("{prefix}.env_remove"))?,
+            path,
+        };
+        normalized.insert(name, overlay);
     }
+    Ok(normalized)
 }
 
-fn config_base_dir(config_path: &Path) -> PathBuf {
-    if let Ok(canonical) = fs::canonicalize(config_path) {
-        if let Some(parent) = canonical.parent() {
-            return parent.to_path_buf();
-        }
+fn normalize_env_files(base_dir: &Path, env_files: &[String]) -> Result<Vec<PathBuf>> {
+    let mut normalized = Vec::with_capacity(env_files.len());
+    for env_file in env_files {
+        validate_optional_script_value(env_file, "field `env_file` entries")?;
+        let env_file = env_file.trim();
+        normalized.push(if interpolation::contains_placeholders(env_file) {
+            PathBuf::from(env_file)
+        } else {
+            resolve_script_path(base_dir, env_file)
+        });
     }
-    config_path
-        .parent()
-        .unwrap_or_else(|| Path::new("."))
-        .to_path_buf()
+    Ok(normalized)
 }
 
-fn resolve_exec_path(base_dir: &Path, exec: &str) -> PathBuf {
-    let exec_path = PathBuf::from(exec);
-    if exec_path.is_absolute() {
-        return exec_path;
+fn normalize_env_mode(env_mode: &str) -> Result<EnvMode> {
+    match env_mode.trim() {
+        "inherit" => Ok(EnvMode::Inherit),
+        "clean" => Ok(EnvMode::Clean),
+        other => Err(anyhow!(
+            "field `env_mode` must be `inherit` or `clean`: `{other}`"
+        )),
     }
+}
 
-    if looks_like_relative_exec_path(exec) {
-        return base_dir.join(exec_path);
+fn normalize_env_keep(env_keep: Vec<String>) -> Result<Vec<String>> {
+    let mut normalized = Vec::with_capacity(env_keep.len());
+    for entry in env_keep {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        if entry.contains('\0') {
+            return Err(anyhow!("field `env_keep` entries cannot contain NUL bytes"));
+        }
+        if !crate::manifest::is_exact_env_keep(entry) {
+            regex::Regex::new(entry).map_err(|err| {
+                anyhow!("field `env_keep` entry `{entry}` is not a valid regex: {err}")
+            })?;
+        }
+        if !normalized.iter().any(|seen| seen == entry) {
+            normalized.push(entry.to_string());
+        }
     }
+    Ok(normalized)
+}
 
-    crate::exec_resolution::resolve_command_path(exec)
+fn normalize_env_remove_matching(patterns: Vec<String>) -> Result<Vec<String>> {
+    let mut normalized = Vec::with_capacity(patterns.len());
+    for pattern in patterns {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            continue;
+        }
+        if pattern.contains('\0') {
+            return Err(anyhow!(
+                "field `env_remove_matching` entries cannot contain NUL bytes"
+            ));
+        }
+        crate::manifest::compile_env_pattern(pattern, "field `env_remove_matching`")?;
+        if !normalized.iter().any(|seen| seen == pattern) {
+            normalized.push(pattern.to_string());
+        }
+    }
+    Ok(normalized)
 }
 
-fn looks_like_relative_exec_path(exec: &str) -> bool {
-    let path = Path::new(exec);
-    !path.is_absolute()
-        && (exec == "."
-            || exec == ".."
-            || exec.contains('/')
-            || exec.contains(std::path::MAIN_SEPARATOR))
+fn normalize_env_secrets(
+    base_dir: &Path,
+    env_secret: HashMap<String, SecretInput>,
+) -> Result<BTreeMap<String, SecretSource>> {
+    let mut normalized = BTreeMap::new();
+    for (key, secret) in env_secret {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(anyhow!("field `env_secret` cannot contain empty keys"));
+        }
+        match env_validation::validate_env_key(key) {
+            Ok(()) => {}
+            Err(EnvKeyViolation::ContainsEquals) => {
+                return Err(anyhow!(
+                    "field `env_secret` keys cannot contain `=`: `{key}`"
+                ));
+            }
+            Err(EnvKeyViolation::ContainsNul) => {
+                return Err(anyhow!("field `env_secret` keys cannot contain NUL bytes"));
+            }
+        }
+        let source = match (secret.file, secret.command, secret.keyring) {
+            (Some(file), None, None) => {
+                validate_optional_script_value(&file, &format!("field `env_secret.{key}.file`"))?;
+                let file = file.trim();
+                SecretSource::File(if interpolation::contains_placeholders(file) {
+                    PathBuf::from(file)
+                } else {
+                    resolve_script_path(base_dir, file)
+                })
+            }
+            (None, Some(command), None) => {
+                if command
+                    .first()
+                    .is_none_or(|program| program.trim().is_empty())
+                {
+                    return Err(anyhow!("field `env_secret.{key}.command` cannot be empty"));
+                }
+                validate_arg_values(&command, &format!("field `env_secret.{key}.command`"))?;
+                SecretSource::Command(command)
+            }
+            (None, None, Some(keyring)) => {
+                let keyring = keyring.trim();
+                if secrets::parse_keyring_spec(keyring).is_none() {
+                    return Err(anyhow!(
+                        "field `env_secret.{key}.keyring` must be `type:description` or a `user` key description"
+                    ));
+                }
+                SecretSource::Keyring(keyring.to_string())
+            }
+            _ => {
+                return Err(anyhow!(
+                    "field `env_secret.{key}` must set exactly one of `file`, `command`, or `keyring`"
+                ));
+            }
+        };
+        if normalized.insert(key.to_string(), source).is_some() {
+            return Err(anyhow!(
+                "field `env_secret` contains duplicate keys after trimming: `{key}`"
+            ));
+        }
+    }
+    Ok(normalized)
+}
+
+fn normalize_journal(journal: JournalConfigInput, field: &str) -> Result<JournalConfig> {
+    let namespace = match journal_validation::normalize_namespace(&journal.namespace) {
+        Ok(namespace) => namespace,
+        Err(JournalNamespaceViolation::Empty) => {
+            return Err(anyhow!("field `{field}.namespace` cannot be empty"));
+        }
+        Err(JournalNamespaceViolation::ContainsNul) => {
+            return Err(anyhow!(
+                "field `{field}.namespace` cannot contain NUL bytes"
+            ));
+        }
+    };
+    let identifier = match journal_validation::normalize_optional_identifier_for_config(
+        journal.identifier.as_deref(),
+    ) {
+        Ok(identifier) => identifier,
+        Err(JournalIdentifierViolation::ContainsNul) => {
+            return Err(anyhow!(
+                "field `{field}.identifier` cannot contain NUL bytes"
+            ));
+        }
+        Err(JournalIdentifierViolation::Blank) => {
+            unreachable!("blank identifiers are normalized to None for config parsing")
+        }
+    };
+    Ok(JournalConfig {
+        namespace,
+        stderr: journal.stderr,
+        identifier,
+        user_scope: journal.user_scope,
+        ensure: journal.ensure,
+        max_use: journal.max_use,
+        rate_limit_interval_usec: journal.rate_limit_interval_usec,
+        rate_limit_burst: journal.rate_limit_burst,
+    })
+}
+
+fn normalize_profile_name(name: &str, field: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("{field} cannot be empty"));
+    }
+    if name.contains('\0') {
+        return Err(anyhow!("{field} cannot contain NUL bytes"));
+    }
+    if name.chars().any(char::is_whitespace) {
+        return Err(anyhow!("{field} cannot contain whitespace: `{name}`"));
+    }
+    Ok(name.to_string())
+}
+
+fn normalize_profiles(
+    base_dir: &Path,
+    profiles: HashMap<String, ProfileInput>,
+) -> Result<BTreeMap<String, ProfileOverlay>> {
+    let mut normalized = BTreeMap::new();
+    for (key, profile) in profiles {
+        let name = normalize_profile_name(&key, "field `profile` names")?;
+        if normalized.contains_key(&name) {
+            return Err(anyhow!(
+                "field `profile` contains duplicate names after trimming: `{name}`"
+            ));
+        }
+
+        let prefix = format!("profile.{name}");
+        let exec = match profile.exec.as_deref() {
+            Some(exec) => Some(normalize_exec(
+                base_dir,
+                exec,
+                &format!("field `{prefix}.exec`"),
+            )?),
+            None => None,
+        };
+        if let Some(args) = &profile.args {
+            let args_field = format!("field `{prefix}.args`");
+            validate_arg_values(args, &args_field)?;
+            for arg in args {
+                if let Err(SlotViolation::ZeroPosition) = arg_placement::parse_slot(arg) {
+                    return Err(anyhow!(
+                        "{args_field} runtime-arg slot `{arg}` must use a position of 1 or greater"
+                    ));
+                }
+            }
+        }
+        let path = match profile.path.filter(|config| !config.is_empty()) {
+            Some(path) => {
+                path.validate(&format!("{prefix}.path"))?;
+                Some(path)
+            }
+            None => None,
+        };
+        let journal = match profile.journal {
+            Some(journal) => Some(normalize_journal(journal, &format!("{prefix}.journal"))?),
+            None => None,
+        };
+        let overlay = ProfileOverlay {
+            exec,
+            args: profile.args,
+            env: normalize_env_map(profile.env, &format!("{prefix}.env"))?,
+            env_remove: normalize_env_remove(profile.env_remove, &format!("{prefix}.env_remove"))?,
+            path,
+            journal,
+        };
+        normalized.insert(name, overlay);
+    }
+    Ok(normalized)
+}
+
+fn normalize_default_profile(value: Option<&str>) -> Result<Option<String>> {
+    match value {
+        Some(value) if !value.trim().is_empty() => {
+            normalize_profile_name(value, "field `default_profile`").map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+fn normalize_limits(limits: LimitsConfigInput) -> Result<Option<LimitsConfig>> {
+    let nice = match limits.nice {
+        Some(nice) if !process_limits::NICE_RANGE.contains(&nice) => {
+            return Err(anyhow!("field `limits.nice` must be between -20 and 19"));
+        }
+        nice => nice.map(|nice| nice as i32),
+    };
+    let ioprio = match limits.ioprio.as_deref() {
+        Some(ioprio) => Some(process_limits::parse_ioprio(ioprio).ok_or_else(|| {
+            anyhow!(
+                "field `limits.ioprio` must be `realtime[:0-7]`, `best-effort[:0-7]`, or `idle`"
+            )
+        })?),
+        None => None,
+    };
+    // A TOML integer has lost its notation by now, so `umask = 22` would be
+    // taken as decimal; only strings say which digits were meant.
+    let umask = match limits.umask {
+        Some(LimitValueInput::Integer(_)) => {
+            return Err(anyhow!(
+                "field `limits.umask` must be an octal string such as \"022\", not an integer"
+            ));
+        }
+        Some(LimitValueInput::Text(mode)) => Some(
+            process_limits::parse_umask(&mode)
+                .ok_or_else(|| anyhow!("field `limits.umask` must be an octal mode up to 0o777"))?,
+        ),
+        None => None,
+    };
+
+    let normalized = LimitsConfig {
+        nofile: normalize_rlimit(limits.nofile, "nofile", false)?,
+        address_space: normalize_rlimit(limits.address_space, "as", true)?,
+        cpu: normalize_rlimit(limits.cpu, "cpu", false)?,
+        core: normalize_rlimit(limits.core, "core", true)?,
+        nproc: normalize_rlimit(limits.nproc, "nproc", false)?,
+        nice,
+        ioprio,
+        umask,
+    };
+    if normalized == LimitsConfig::default() {
+        return Ok(None);
+    }
+    Ok(Some(normalized))
+}
+
+fn normalize_rlimit(
+    value: Option<LimitValueInput>,
+    name: &str,
+    accepts_size: bool,
+) -> Result<Option<RlimitValue>> {
+    let invalid = || {
+        if accepts_size {
+            anyhow!(
+                "field `limits.{name}` must be a byte count, a size like \"2G\", or \"unlimited\""
+            )
+        } else {
+            anyhow!("field `limits.{name}` must be a non-negative integer or \"unlimited\"")
+        }
+    };
+    match value {
+        None => Ok(None),
+        Some(LimitValueInput::Integer(limit)) => u64::try_from(limit)
+            .map(|limit| Some(RlimitValue::Limited(limit)))
+            .map_err(|_| invalid()),
+        Some(LimitValueInput::Text(text)) => {
+            let text = text.trim();
+            if text.eq_ignore_ascii_case("unlimited") {
+                return Ok(Some(RlimitValue::Unlimited));
+            }
+            let limit = if accepts_size {
+                process_limits::parse_byte_size(text)
+            } else {
+                text.parse::<u64>().ok()
+            };
+            limit
+                .map(|limit| Some(RlimitValue::Limited(limit)))
+                .ok_or_else(invalid)
+        }
+    }
+}
+
+fn normalize_cwd(base_dir: &Path, cwd: Option<&str>) -> Result<Option<PathBuf>> {
+    let Some(cwd) = cwd.map(str::trim) else {
+        return Ok(None);
+    };
+    if cwd.is_empty() {
+        return Ok(None);
+    }
+    if cwd.contains('\0') {
+        return Err(anyhow!("field `cwd` cannot contain NUL bytes"));
+    }
+    if interpolation::contains_placeholders(cwd) {
+        return Ok(Some(PathBuf::from(cwd)));
+    }
+    Ok(Some(resolve_script_path(base_dir, cwd)))
+}
+
+fn parse_parent_alias(extends: &str, path: &Path, ancestry: &mut Vec<PathBuf>) -> Result<Manifest> {
+    let alias = extends.trim();
+    match alias_validation::validate_alias_identifier(alias) {
+        Ok(()) => {}
+        Err(AliasViolation::Empty) => {
+            return Err(anyhow!("field `extends` cannot be empty"));
+        }
+        Err(AliasViolation::ContainsNul) => {
+            return Err(anyhow!("field `extends` cannot contain NUL bytes"));
+        }
+        Err(_) => {
+            return Err(anyhow!(
+                "field `extends` must be a valid alias name: `{alias}`"
+            ));
+        }
+    }
+    let parent_path = crate::find_config(alias)
+        .ok_or_else(|| anyhow!("field `extends` references unknown alias `{alias}`"))?;
+    parse_with_ancestry(&parent_path, ancestry).with_context(|| {
+        format!(
+            "failed to load alias `{alias}` extended by {}",
+            path.display()
+        )
+    })
+}
+
+/// Which optional tables the child alias declared itself. A declared table
+/// replaces the inherited one wholesale, even when it normalizes to unset.
+struct InheritanceOverrides {
+    journal: bool,
+    reconcile: bool,
+    bashcomp: bool,
+    limits: bool,
+    requires: bool,
+    guard: bool,
+    hooks: bool,
+    timeout: bool,
+    retry: bool,
+    concurrency: bool,
+    audit: bool,
+    env_mode: bool,
+}
+
+/// Layers a child manifest over its `extends` parent: `args` append to the
+/// parent's, `env`, `env_default`, `env_append`, and `env_prepend` keys
+/// override, `env_remove`, `env_remove_matching`, `env_file`, `env_keep`,
+/// `[path]`, and `[pathlists.<VAR>]` operations accumulate parent-first,
+/// `[subcommands]` and `[profile]` overlays replace the parent's per key,
+/// `env_mode`, `timeout`, and `default_profile` are inherited unless set,
+/// and
+/// `[journal]`/`[reconcile]`/`[bashcomp]`/`[limits]`/`[requires]`/`[guard]`/
+/// `[hooks]`/`[retry]`/`[concurrency]`/`[audit]` replace the parent's table
+/// when the child declares one.
+fn merge_parent_manifest(
+    parent: Manifest,
+    child: Manifest,
+    overrides: InheritanceOverrides,
+) -> Manifest {
+    let mut merged = parent;
+    merged.exec = child.exec;
+    merged.exec_fallbacks = child.exec_fallbacks;
+    merged.alias_dir = child.alias_dir;
+    if child.cwd.is_some() {
+        merged.cwd = child.cwd;
+    }
+    merged.args.extend(child.args);
+    merged.args_after.extend(child.args_after);
+
+    merged
+        .env_remove
+        .retain(|key| !child.env.contains_key(key) && !child.env_secret.contains_key(key));
+    for key in child.env_remove {
+        merged.env.remove(&key);
+        merged.env_secret.remove(&key);
+        merged.env_default.remove(&key);
+        merged.env_append.remove(&key);
+        merged.env_prepend.remove(&key);
+        if !merged.env_remove.contains(&key) {
+            merged.env_remove.push(key);
+        }
+    }
+    merged
+        .env_secret
+        .retain(|key, _| !child.env.contains_key(key));
+    merged
+        .env
+        .retain(|key, _| !child.env_secret.contains_key(key));
+    merged
+        .env_default
+        .retain(|key, _| !child.env.contains_key(key) && !child.env_secret.contains_key(key));
+    merged.env.extend(child.env);
+    merged.env_secret.extend(child.env_secret);
+    merged.env_default.extend(child.env_default);
+    merged.env_append.extend(child.env_append);
+    merged.env_prepend.extend(child.env_prepend);
+    merged.env_file.extend(child.env_file);
+    if overrides.timeout {
+        merged.timeout = child.timeout;
+    }
+    if overrides.env_mode {
+        merged.env_mode = child.env_mode;
+    }
+    for entry in child.env_keep {
+        if !merged.env_keep.contains(&entry) {
+            merged.env_keep.push(entry);
+        }
+    }
+    for pattern in child.env_remove_matching {
+        if !merged.env_remove_matching.contains(&pattern) {
+            merged.env_remove_matching.push(pattern);
+        }
+    }
+
+    if let Some(child_path) = child.path {
+        merged
+            .path
+            .get_or_insert_with(PathMutationConfig::default)
+            .extend(child_path);
+    }
+    for (key, list) in child.pathlists {
+        merged.pathlists.entry(key).or_default().extend(list);
+    }
+
+    merged.subcommands.extend(child.subcommands);
+    merged.profiles.extend(child.profiles);
+    if child.default_profile.is_some() {
+        merged.default_profile = child.default_profile;
+    }
+
+    if overrides.journal {
+        merged.journal = child.journal;
+    }
+    if overrides.reconcile {
+        merged.reconcile = child.reconcile;
+    }
+    if overrides.bashcomp {
+        merged.bashcomp = child.bashcomp;
+    }
+    if overrides.limits {
+        merged.limits = child.limits;
+    }
+    if overrides.requires {
+        merged.requires = child.requires;
+    }
+    if overrides.guard {
+        merged.guard = child.guard;
+    }
+    if overrides.retry {
+        merged.retry = child.retry;
+    }
+    if overrides.concurrency {
+        merged.concurrency = child.concurrency;
+    }
+    if overrides.audit {
+        merged.audit = child.audit;
+    }
+    if overrides.hooks {
+        merged.hooks = child.hooks;
+    }
+    merged
+}
+
+fn normalize_env_map(env: HashMap<String, String>, field: &str) -> Result<HashMap<String, String>> {
+    let mut normalized = HashMap::with_capacity(env.len());
+    for (key, value) in env {
+        let normalized_key = key.trim();
+        if normalized_key.is_empty() {
+            return Err(anyhow!("field `{field}` cannot contain empty keys"));
+        }
+        match env_validation::validate_env_key(normalized_key) {
+            Ok(()) => {}
+            Err(EnvKeyViolation::ContainsEquals) => {
+                return Err(anyhow!(
+                    "field `{field}` keys cannot contain `=`: `{normalized_key}`"
+                ));
+            }
+            Err(EnvKeyViolation::ContainsNul) => {
+                return Err(anyhow!("field `{field}` keys cannot contain NUL bytes"));
+            }
+        }
+        if matches!(
+            env_validation::validate_env_value(&value),
+            Err(EnvValueViolation::ContainsNul)
+        ) {
+            return Err(anyhow!(
+                "field `{field}` values cannot contain NUL bytes for key `{normalized_key}`"
+            ));
+        }
+        if normalized.contains_key(normalized_key) {
+            return Err(anyhow!(
+                "field `{field}` contains duplicate keys after trimming: `{normalized_key}`"
+            ));
+        }
+        normalized.insert(normalized_key.to_string(), value);
+    }
+    Ok(normalized)
+}
+
+fn normalize_env_joins(
+    joins: HashMap<String, EnvJoinInput>,
+    field: &str,
+) -> Result<BTreeMap<String, EnvJoin>> {
+    let mut values = HashMap::with_capacity(joins.len());
+    let mut separators = HashMap::new();
+    for (key, join) in joins {
+        let (value, separator) = match join {
+            EnvJoinInput::Value(value) => (value, None),
+            EnvJoinInput::Table(table) => (table.value, table.separator),
+        };
+        if let Some(separator) = separator {
+            if separator.contains('\0') {
+                return Err(anyhow!(
+                    "field `{field}.{}.separator` cannot contain NUL bytes",
+                    key.trim()
+                ));
+            }
+            separators.insert(key.trim().to_string(), separator);
+        }
+        values.insert(key, value);
+    }
+    Ok(normalize_env_map(values, field)?
+        .into_iter()
+        .map(|(key, value)| {
+            let separator = separators
+                .remove(&key)
+                .unwrap_or_else(|| EnvJoin::DEFAULT_SEPARATOR.to_string());
+            (key, EnvJoin { value, separator })
+        })
+        .collect())
+}
+
+fn normalize_pathlists(
+    pathlists: HashMap<String, PathListConfig>,
+) -> Result<BTreeMap<String, PathListConfig>> {
+    let mut normalized = BTreeMap::new();
+    for (key, list) in pathlists {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(anyhow!("field `pathlists` cannot contain empty keys"));
+        }
+        match env_validation::validate_env_key(key) {
+            Ok(()) => {}
+            Err(EnvKeyViolation::ContainsEquals) => {
+                return Err(anyhow!(
+                    "field `pathlists` keys cannot contain `=`: `{key}`"
+                ));
+            }
+            Err(EnvKeyViolation::ContainsNul) => {
+                return Err(anyhow!("field `pathlists` keys cannot contain NUL bytes"));
+            }
+        }
+        if normalized.contains_key(key) {
+            return Err(anyhow!(
+                "field `pathlists` contains duplicate keys after trimming: `{key}`"
+            ));
+        }
+        if list.is_empty() {
+            continue;
+        }
+        list.validate(&format!("pathlists.{key}"))?;
+        normalized.insert(key.to_string(), list);
+    }
+    Ok(normalized)
+}
+
+fn normalize_env_remove(env_remove: Vec<String>, field: &str) -> Result<Vec<String>> {
+    let mut seen = HashSet::with_capacity(env_remove.len());
+    let mut normalized = Vec::with_capacity(env_remove.len());
+    for key in env_remove {
+        let normalized_key = key.trim();
+        if normalized_key.is_empty() {
+            continue;
+        }
+        match env_validation::validate_env_key(normalized_key) {
+            Ok(()) => {}
+            Err(EnvKeyViolation::ContainsEquals) => {
+                return Err(anyhow!(
+                    "field `{field}` entries cannot contain `=`: `{normalized_key}`"
+                ));
+            }
+            Err(EnvKeyViolation::ContainsNul) => {
+                return Err(anyhow!("field `{field}` entries cannot contain NUL bytes"));
+            }
+        }
+        let normalized_key = normalized_key.to_string();
+        if seen.insert(normalized_key.clone()) {
+            normalized.push(normalized_key);
+        }
+    }
+    Ok(normalized)
+}
+
+fn validate_arg_values(values: &[String], field: &str) -> Result<()> {
+    for value in values {
+        if matches!(
+            arg_validation::validate_arg_value(value),
+            Err(ArgViolation::ContainsNul)
+        ) {
+            return Err(anyhow!("{field} entries cannot contain NUL bytes"));
+        }
+    }
+    Ok(())
+}
+
+fn validate_runtime_arg_slots(args: &[String], args_after: &[String]) -> Result<()> {
+    for arg in args {
+        if let Err(SlotViolation::ZeroPosition) = arg_placement::parse_slot(arg) {
+            return Err(anyhow!(
+                "field `args` runtime-arg slot `{arg}` must use a position of 1 or greater"
+            ));
+        }
+    }
+    for arg in args_after {
+        if !matches!(arg_placement::parse_slot(arg), Ok(None)) {
+            return Err(anyhow!(
+                "field `args_after` cannot contain runtime-arg slot `{arg}`; place it in `args`"
+            ));
+        }
+    }
+    Ok(())
 }
 
-fn has_meaningful_relative_segment(value: &str) -> bool {
-    crate::path_validation::has_meaningful_relative_segment(value)
-}
+fn normalize_optional_method_name(value: Option<&str>, field: &str) -> Result<Option<String>> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let method = value.trim();
+    if method.is_empty() {
+        return Ok(None);
+    }
+    if method.contains('\0') {
+        return Err(anyhow!("{field} cannot contain NUL bytes"));
+    }
+    Ok(Some(method.to_string()))
+}
+
+fn validate_optional_script_value(value: &str, field: &str) -> Result<()> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Err(anyhow!("{field} cannot be empty"));
+    }
+    if trimmed.contains('\0') {
+        return Err(anyhow!("{field} cannot contain NUL bytes"));
+    }
+    if trimmed == "." || trimmed == ".." {
+        return Err(anyhow!("{field} cannot be `.` or `..`"));
+    }
+    if ends_with_path_separator(trimmed) {
+        return Err(anyhow!("{field} cannot end with a path separator"));
+    }
+    if ends_with_dot_component(trimmed) {
+        return Err(anyhow!(
+            "{field} cannot end with `.` or `..` path components"
+        ));
+    }
+    if !Path::new(trimmed).is_absolute() && !has_meaningful_relative_segment(trimmed) {
+        return Err(anyhow!(
+            "{field} must include a file path when using relative path notation"
+        ));
+    }
+    Ok(())
+}
+
+fn ends_with_path_separator(value: &str) -> bool {
+    value.ends_with('/') || value.ends_with(std::path::MAIN_SEPARATOR)
+}
+
+fn resolve_script_path(base_dir: &Path, script: &str) -> PathBuf {
+    let script_path = PathBuf::from(script);
+    if script_path.is_absolute() {
+        script_path
+    } else {
+        base_dir.join(script_path)
+    }
+}
+
+fn config_base_dir(config_path: &Path) -> PathBuf {
+    if let Ok(canonical) = fs::canonicalize(config_path) {
+        if let Some(parent) = canonical.parent() {
+            return parent.to_path_buf();
+        }
+    }
+    config_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf()
+}
+
+pub(crate) fn resolve_exec_path(base_dir: &Path, exec: &str) -> PathBuf {
+    let exec_path = PathBuf::from(exec);
+    if exec_path.is_absolute() {
+        return exec_path;
+    }
+
+    if looks_like_relative_exec_path(exec) {
+        return base_dir.join(exec_path);
+    }
+
+    crate::exec_resolution::resolve_command_path(exec)
+}
+
+fn looks_like_relative_exec_path(exec: &str) -> bool {
+    let path = Path::new(exec);
+    !path.is_absolute()
+        && (exec == "."
+            || exec == ".."
+            || exec.contains('/')
+            || exec.contains(std::path::MAIN_SEPARATOR))
+}
+
+fn has_meaningful_relative_segment(value: &str) -> bool {
+    crate::path_validation::has_meaningful_relative_segment(value)
+}
+
+fn ends_with_dot_component(value: &str) -> bool {
+    crate::path_validation::ends_with_dot_component(value)
+}
+
+#[derive(Debug, Deserialize)]
+struct AliasConfig {
+    extends: Option<String>,
+    #[serde(default)]
+    include: Vec<String>,
+    exec: Option<ExecInput>,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    args_after: Vec<String>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    #[serde(default)]
+    env_remove: Vec<String>,
+    #[serde(default)]
+    env_remove_matching: Vec<String>,
+    #[serde(default)]
+    env_default: HashMap<String, String>,
+    #[serde(default)]
+    env_append: HashMap<String, EnvJoinInput>,
+    #[serde(default)]
+    env_prepend: HashMap<String, EnvJoinInput>,
+    #[serde(default)]
+    env_file: Vec<String>,
+    #[serde(default)]
+    env_secret: HashMap<String, SecretInput>,
+    env_mode: Option<String>,
+    #[serde(default)]
+    env_keep: Vec<String>,
+    timeout: Option<String>,
+    timeout_signal: Option<String>,
+    kill_after: Option<String>,
+    path: Option<PathMutationConfig>,
+    #[serde(default)]
+    pathlists: HashMap<String, PathListConfig>,
+    cwd: Option<String>,
+    journal: Option<JournalConfigInput>,
+    reconcile: Option<ReconcileConfigInput>,
+    bashcomp: Option<BashcompConfigInput>,
+    limits: Option<LimitsConfigInput>,
+    requires: Option<RequiresInput>,
+    guard: Option<GuardInput>,
+    hooks: Option<HooksInput>,
+    retry: Option<RetryInput>,
+    concurrency: Option<ConcurrencyInput>,
+    audit: Option<AuditInput>,
+    #[serde(default)]
+    subcommands: HashMap<String, SubcommandInput>,
+    #[serde(default)]
+    profile: HashMap<String, ProfileInput>,
+    default_profile: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ExecInput {
+    Command(String),
+    Candidates(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RequiresInput {
+    version_cmd: Option<Vec<String>>,
+    version_regex: Option<String>,
+    min_version: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HooksInput {
+    #[serde(default)]
+    pre: Vec<Vec<String>>,
+    #[serde(default)]
+    post: Vec<Vec<String>>,
+    #[serde(default)]
+    journal: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RetryInput {
+    attempts: Option<i64>,
+    backoff: Option<String>,
+    initial_delay: Option<String>,
+    #[serde(default)]
+    on_exit_codes: Vec<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConcurrencyInput {
+    mode: Option<String>,
+    lock_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AuditInput {
+    enabled: Option<bool>,
+    #[serde(default)]
+    redact_options: Vec<String>,
+    #[serde(default)]
+    redact_matching: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GuardInput {
+    #[serde(default)]
+    deny: Vec<String>,
+    #[serde(default)]
+    require: Vec<String>,
+    #[serde(default)]
+    confirm: Vec<String>,
+    non_tty: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum EnvJoinInput {
+    Value(String),
+    Table(EnvJoinTableInput),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EnvJoinTableInput {
+    value: String,
+    separator: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SecretInput {
+    file: Option<String>,
+    command: Option<Vec<String>>,
+    keyring: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourceProbe {
+    extends: Option<String>,
+    #[serde(default)]
+    include: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IncludeFragment {
+    #[serde(default)]
+    env: HashMap<String, String>,
+    #[serde(default)]
+    env_remove: Vec<String>,
+    path: Option<PathMutationConfig>,
+    journal: Option<JournalConfigInput>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JournalConfigInput {
+    namespace: String,
+    #[serde(default = "default_true")]
+    stderr: bool,
+    identifier: Option<String>,
+    #[serde(default = "default_true")]
+    user_scope: bool,
+    #[serde(default)]
+    ensure: bool,
+    #[serde(default)]
+    max_use: Option<String>,
+    #[serde(default)]
+    rate_limit_interval_usec: Option<u64>,
+    #[serde(default)]
+    rate_limit_burst: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReconcileConfigInput {
+    #[serde(default)]
+    script: Option<String>,
+    function: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BashcompConfigInput {
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default)]
+    passthrough: bool,
+    script: Option<String>,
+    rhai_script: Option<String>,
+    rhai_function: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SubcommandInput {
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    #[serde(default)]
+    env_remove: Vec<String>,
+    path: Option<PathMutationConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileInput {
+    exec: Option<String>,
+    args: Option<Vec<String>>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    #[serde(default)]
+    env_remove: Vec<String>,
+    path: Option<PathMutationConfig>,
+    journal: Option<JournalConfigInput>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LimitsConfigInput {
+    nofile: Option<LimitValueInput>,
+    #[serde(rename = "as")]
+    address_space: Option<LimitValueInput>,
+    cpu: Option<LimitValueInput>,
+    core: Option<LimitValueInput>,
+    nproc: Option<LimitValueInput>,
+    nice: Option<i64>,
+    ioprio: Option<String>,
+    umask: Option<LimitValueInput>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LimitValueInput {
+    Integer(i64),
+    Text(String),
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{looks_like_relative_exec_path, normalize_env_map, normalize_env_remove, parse};
+    use crate::manifest::{
+        ConcurrencyMode, EnvJoin, EnvMode, GuardFallback, IoPriority, IoPriorityClass,
+        RetryBackoff, RlimitValue, SecretSource,
+    };
+    use crate::test_support::ENV_LOCK;
+    use anyhow::Result;
+    use std::collections::{BTreeMap, HashMap};
+    use std::env;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn rejects_non_toml_alias_configs() {
+        let temp = TempDir::new().expect("create tempdir");
+        let alias = temp.path().join("legacy");
+        fs::write(&alias, "echo hello world").expect("write config");
+
+        let err = parse(&alias).expect_err("expected parse failure");
+        assert!(err.to_string().contains("expected a .toml file"), "{err}");
+    }
+
+    #[test]
+    fn parses_toml_alias_with_journal_and_reconcile() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("svc.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "echo"
+args = ["base"]
+env_remove = ["REMOVE_ME"]
+
+[env]
+FOO = "bar"
+
+[journal]
+namespace = "ops"
+stderr = true
+identifier = "svc"
+
+[reconcile]
+script = "hooks/reconcile.rhai"
+function = "reconcile"
+"#,
+        )
+        .expect("write toml");
+
+        let manifest = parse(&config).expect("parse toml config");
+        assert_eq!(manifest.args, vec!["base"]);
+        assert_eq!(manifest.env.get("FOO"), Some(&"bar".to_string()));
+        assert_eq!(manifest.env_remove, vec!["REMOVE_ME"]);
+        assert_eq!(
+            manifest.journal.as_ref().map(|j| j.namespace.as_str()),
+            Some("ops")
+        );
+        assert_eq!(manifest.journal.as_ref().map(|j| j.user_scope), Some(true));
+        assert_eq!(manifest.journal.as_ref().map(|j| j.ensure), Some(false));
+        assert_eq!(
+            manifest
+                .reconcile
+                .as_ref()
+                .map(|r| r.function.as_str())
+                .unwrap_or(""),
+            "reconcile"
+        );
+        assert_eq!(
+            manifest
+                .reconcile
+                .as_ref()
+                .expect("reconcile config")
+                .script,
+            temp.path().join("svc.rhai")
+        );
+    }
+
+    #[test]
+    fn parses_path_mutation_config() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("svc.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "echo"
+
+[path]
+remove_all = ["^/tmp"]
+append_one = ["/custom/bin"]
+"#,
+        )
+        .expect("write toml");
+
+        let manifest = parse(&config).expect("parse toml config");
+        let path = manifest.path.expect("path config");
+        assert_eq!(path.remove_all, vec!["^/tmp"]);
+        assert_eq!(path.append_one, vec!["/custom/bin"]);
+    }
+
+    #[test]
+    fn rejects_path_entries_containing_nul_bytes() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("svc.toml");
+        fs::write(
+            &config,
+            "exec = \"echo\"\n[path]\nprepend_one = [\"bad\\u0000path\"]\n",
+        )
+        .expect("write toml");
+
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(
+            err.to_string()
+                .contains("field `path.prepend_one` entries cannot contain NUL bytes"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn parses_toml_alias_with_dynamic_journal_scope_fields() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("svc-dynamic.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "echo"
+
+[journal]
+namespace = "ops"
+stderr = true
+user_scope = true
+ensure = true
+"#,
+        )
+        .expect("write toml");
+
+        let manifest = parse(&config).expect("parse toml config");
+        let journal = manifest.journal.expect("journal config");
+        assert_eq!(journal.namespace, "ops");
+        assert!(journal.user_scope);
+        assert!(journal.ensure);
+    }
+
+    #[test]
+    fn parses_toml_alias_with_utf8_bom() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("svc.toml");
+        fs::write(
+            &config,
+            "\u{feff}exec = \"echo\"\nargs = [\"hello\", \"toml\"]\n",
+        )
+        .expect("write toml");
+
+        let manifest = parse(&config).expect("parse bom toml");
+        assert_eq!(
+            manifest.exec.file_name().and_then(|x| x.to_str()),
+            Some("echo")
+        );
+        assert_eq!(manifest.args, vec!["hello", "toml"]);
+    }
+
+    #[test]
+    fn rejects_empty_exec_field_in_toml() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "   "
+"#,
+        )
+        .expect("write toml");
+
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err.to_string().contains("field `exec` cannot be empty"));
+    }
+
+    #[test]
+    fn rejects_exec_field_containing_nul_bytes() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        fs::write(&config, "exec = \"echo\\u0000tool\"\n").expect("write toml");
+
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `exec` cannot contain NUL bytes"));
+    }
+
+    #[test]
+    fn rejects_args_field_containing_nul_bytes() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "echo"
+args = ["ok", "bad\u0000arg"]
+"#,
+        )
+        .expect("write toml");
+
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `args` entries cannot contain NUL bytes"));
+    }
+
+    #[test]
+    fn preserves_toml_args_with_empty_unicode_and_whitespace_values() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("ok.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "echo"
+args = ["", "emoji🚀", " spaced value "]
+"#,
+        )
+        .expect("write toml");
+
+        let manifest = parse(&config).expect("parse args with empty/unicode/whitespace values");
+        assert_eq!(
+            manifest.args,
+            vec![
+                "".to_string(),
+                "emoji🚀".to_string(),
+                " spaced value ".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn preserves_toml_args_with_symbolic_and_pathlike_values() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("ok.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "echo"
+args = [
+  "--flag=value",
+  "../relative/path",
+  "semi;colon&and",
+  "$DOLLAR",
+  "brace{value}"
+]
+"#,
+        )
+        .expect("write toml");
+
+        let manifest = parse(&config).expect("parse args with symbolic/pathlike values");
+        assert_eq!(
+            manifest.args,
+            vec![
+                "--flag=value".to_string(),
+                "../relative/path".to_string(),
+                "semi;colon&and".to_string(),
+                "$DOLLAR".to_string(),
+                "brace{value}".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_dot_exec_field_in_toml() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "."
+"#,
+        )
+        .expect("write toml");
+
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `exec` cannot be `.` or `..`"));
+    }
+
+    #[test]
+    fn rejects_parent_exec_field_in_toml() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        fs::write(
+            &config,
+            r#"
+exec = ".."
+"#,
+        )
+        .expect("write toml");
+
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `exec` cannot be `.` or `..`"));
+    }
+
+    #[test]
+    fn rejects_exec_field_ending_in_dot_component() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "bin/.."
+"#,
+        )
+        .expect("write toml");
+
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `exec` cannot end with `.` or `..` path components"));
+    }
 
-fn ends_with_dot_component(value: &str) -> bool {
-    crate::path_validation::ends_with_dot_component(value)
-}
+    #[test]
+    fn rejects_exec_field_ending_in_dot_component_with_platform_separator() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        let sep = std::path::MAIN_SEPARATOR;
+        let exec = format!("bin{sep}..");
+        fs::write(&config, format!("exec = '{exec}'\n")).expect("write toml");
 
-#[derive(Debug, Deserialize)]
-struct AliasConfig {
-    exec: String,
-    #[serde(default)]
-    args: Vec<String>,
-    #[serde(default)]
-    env: HashMap<String, String>,
-    #[serde(default)]
-    env_remove: Vec<String>,
-    path: Option<PathMutationConfig>,
-    journal: Option<JournalConfigInput>,
-    reconcile: Option<ReconcileConfigInput>,
-    bashcomp: Option<BashcompConfigInput>,
-}
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `exec` cannot end with `.` or `..` path components"));
+    }
 
-#[derive(Debug, Deserialize)]
-struct JournalConfigInput {
-    namespace: String,
-    #[serde(default = "default_true")]
-    stderr: bool,
-    identifier: Option<String>,
-    #[serde(default = "default_true")]
-    user_scope: bool,
-    #[serde(default)]
-    ensure: bool,
-    #[serde(default)]
-    max_use: Option<String>,
-    #[serde(default)]
-    rate_limit_interval_usec: Option<u64>,
-    #[serde(default)]
-    rate_limit_burst: Option<u32>,
-}
+    #[test]
+    fn rejects_absolute_exec_field_ending_in_dot_component() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "/usr/bin/.."
+"#,
+        )
+        .expect("write toml");
 
-#[derive(Debug, Deserialize)]
-struct ReconcileConfigInput {
-    #[serde(default)]
-    script: Option<String>,
-    function: Option<String>,
-}
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `exec` cannot end with `.` or `..` path components"));
+    }
 
-#[derive(Debug, Deserialize)]
-struct BashcompConfigInput {
-    #[serde(default)]
-    disabled: bool,
-    #[serde(default)]
-    passthrough: bool,
-    script: Option<String>,
-    rhai_script: Option<String>,
-    rhai_function: Option<String>,
-}
+    #[test]
+    fn rejects_dot_slash_exec_field_in_toml() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "./"
+"#,
+        )
+        .expect("write toml");
 
-fn default_true() -> bool {
-    true
-}
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `exec` cannot end with a path separator"));
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::{looks_like_relative_exec_path, normalize_env_map, normalize_env_remove, parse};
-    use anyhow::Result;
-    use std::collections::HashMap;
-    use std::fs;
-    use std::os::unix::fs::symlink;
-    use tempfile::TempDir;
+    #[test]
+    fn rejects_exec_field_trailing_platform_separator() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        let sep = std::path::MAIN_SEPARATOR;
+        let exec = format!(".{sep}");
+        fs::write(&config, format!("exec = '{exec}'\n")).expect("write toml");
+
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `exec` cannot end with a path separator"));
+    }
 
     #[test]
-    fn rejects_non_toml_alias_configs() {
+    fn rejects_trailing_separator_exec_field_in_toml() {
         let temp = TempDir::new().expect("create tempdir");
-        let alias = temp.path().join("legacy");
-        fs::write(&alias, "echo hello world").expect("write config");
+        let config = temp.path().join("bad.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "./bin/"
+"#,
+        )
+        .expect("write toml");
 
-        let err = parse(&alias).expect_err("expected parse failure");
-        assert!(err.to_string().contains("expected a .toml file"), "{err}");
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `exec` cannot end with a path separator"));
     }
 
     #[test]
-    fn parses_toml_alias_with_journal_and_reconcile() {
+    fn rejects_absolute_trailing_separator_exec_field_in_toml() {
         let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "/usr/bin/"
+"#,
+        )
+        .expect("write toml");
+
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `exec` cannot end with a path separator"));
+    }
+
+    #[test]
+    fn blank_reconcile_function_disables_reconcile() -> Result<()> {
+        let temp = TempDir::new()?;
         let config = temp.path().join("svc.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-args = ["base"]
-env_remove = ["REMOVE_ME"]
 
-[env]
-FOO = "bar"
+[reconcile]
+script = "hooks/reconcile.rhai"
+function = "   "
+"#,
+        )?;
 
-[journal]
-namespace = "ops"
-stderr = true
-identifier = "svc"
+        let manifest = parse(&config)?;
+        assert!(manifest.reconcile.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn mixed_whitespace_blank_reconcile_function_disables_reconcile() -> Result<()> {
+        let temp = TempDir::new()?;
+        let config = temp.path().join("svc.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "echo"
 
 [reconcile]
 script = "hooks/reconcile.rhai"
-function = "reconcile"
+function = "\n\t  \t\n"
 "#,
-        )
-        .expect("write toml");
+        )?;
 
-        let manifest = parse(&config).expect("parse toml config");
-        assert_eq!(manifest.args, vec!["base"]);
-        assert_eq!(manifest.env.get("FOO"), Some(&"bar".to_string()));
-        assert_eq!(manifest.env_remove, vec!["REMOVE_ME"]);
-        assert_eq!(
-            manifest.journal.as_ref().map(|j| j.namespace.as_str()),
-            Some("ops")
-        );
-        assert_eq!(manifest.journal.as_ref().map(|j| j.user_scope), Some(true));
-        assert_eq!(manifest.journal.as_ref().map(|j| j.ensure), Some(false));
-        assert_eq!(
-            manifest
-                .reconcile
-                .as_ref()
-                .map(|r| r.function.as_str())
-                .unwrap_or(""),
-            "reconcile"
-        );
-        assert_eq!(
-            manifest
-                .reconcile
-                .as_ref()
-                .expect("reconcile config")
-                .script,
-            temp.path().join("svc.rhai")
-        );
+        let manifest = parse(&config)?;
+        assert!(manifest.reconcile.is_none());
+        Ok(())
     }
 
     #[test]
-    fn parses_path_mutation_config() {
-        let temp = TempDir::new().expect("create tempdir");
+    fn reconcile_function_without_legacy_script_uses_deterministic_shared_script() -> Result<()> {
+        let temp = TempDir::new()?;
         let config = temp.path().join("svc.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
 
-[path]
-remove_all = ["^/tmp"]
-append_one = ["/custom/bin"]
+[reconcile]
+function = "custom_reconcile"
+"#,
+        )?;
+
+        let manifest = parse(&config)?;
+        let reconcile = manifest.reconcile.expect("reconcile config");
+        assert_eq!(reconcile.script, temp.path().join("svc.rhai"));
+        assert_eq!(reconcile.function, "custom_reconcile");
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_empty_journal_namespace() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "echo"
+
+[journal]
+namespace = "  "
 "#,
         )
         .expect("write toml");
 
-        let manifest = parse(&config).expect("parse toml config");
-        let path = manifest.path.expect("path config");
-        assert_eq!(path.remove_all, vec!["^/tmp"]);
-        assert_eq!(path.append_one, vec!["/custom/bin"]);
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `journal.namespace` cannot be empty"));
     }
 
     #[test]
-    fn rejects_path_entries_containing_nul_bytes() {
+    fn rejects_journal_namespace_containing_nul_bytes() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("svc.toml");
+        let config = temp.path().join("bad.toml");
         fs::write(
             &config,
-            "exec = \"echo\"\n[path]\nprepend_one = [\"bad\\u0000path\"]\n",
+            r#"
+exec = "echo"
+
+[journal]
+namespace = "ops\u0000prod"
+"#,
         )
         .expect("write toml");
 
         let err = parse(&config).expect_err("expected parse failure");
-        assert!(
-            err.to_string()
-                .contains("field `path.prepend_one` entries cannot contain NUL bytes"),
-            "{err}"
-        );
+        assert!(err
+            .to_string()
+            .contains("field `journal.namespace` cannot contain NUL bytes"));
     }
 
     #[test]
-    fn parses_toml_alias_with_dynamic_journal_scope_fields() {
+    fn rejects_journal_identifier_containing_nul_bytes() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("svc-dynamic.toml");
+        let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
@@ -538,75 +2262,91 @@ exec = "echo"
 
 [journal]
 namespace = "ops"
-stderr = true
-user_scope = true
-ensure = true
+identifier = "svc\u0000id"
 "#,
         )
         .expect("write toml");
 
-        let manifest = parse(&config).expect("parse toml config");
-        let journal = manifest.journal.expect("journal config");
-        assert_eq!(journal.namespace, "ops");
-        assert!(journal.user_scope);
-        assert!(journal.ensure);
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `journal.identifier` cannot contain NUL bytes"));
     }
 
     #[test]
-    fn parses_toml_alias_with_utf8_bom() {
+    fn rejects_dot_reconcile_script_field() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("svc.toml");
+        let config = temp.path().join("bad.toml");
         fs::write(
             &config,
-            "\u{feff}exec = \"echo\"\nargs = [\"hello\", \"toml\"]\n",
+            r#"
+exec = "echo"
+
+[reconcile]
+script = "."
+"#,
         )
         .expect("write toml");
 
-        let manifest = parse(&config).expect("parse bom toml");
-        assert_eq!(
-            manifest.exec.file_name().and_then(|x| x.to_str()),
-            Some("echo")
-        );
-        assert_eq!(manifest.args, vec!["hello", "toml"]);
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `reconcile.script` cannot be `.` or `..`"));
     }
 
     #[test]
-    fn rejects_empty_exec_field_in_toml() {
+    fn rejects_reconcile_script_field_containing_nul_bytes() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
-exec = "   "
+exec = "echo"
+
+[reconcile]
+script = "hooks/reconcile\u0000.rhai"
 "#,
         )
         .expect("write toml");
 
         let err = parse(&config).expect_err("expected parse failure");
-        assert!(err.to_string().contains("field `exec` cannot be empty"));
+        assert!(err
+            .to_string()
+            .contains("field `reconcile.script` cannot contain NUL bytes"));
     }
 
     #[test]
-    fn rejects_exec_field_containing_nul_bytes() {
+    fn rejects_parent_reconcile_script_field() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
-        fs::write(&config, "exec = \"echo\\u0000tool\"\n").expect("write toml");
+        fs::write(
+            &config,
+            r#"
+exec = "echo"
+
+[reconcile]
+script = ".."
+"#,
+        )
+        .expect("write toml");
 
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `exec` cannot contain NUL bytes"));
+            .contains("field `reconcile.script` cannot be `.` or `..`"));
     }
 
     #[test]
-    fn rejects_args_field_containing_nul_bytes() {
+    fn rejects_reconcile_script_field_ending_in_dot_component() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-args = ["ok", "bad\u0000arg"]
+
+[reconcile]
+script = "hooks/.."
 "#,
         )
         .expect("write toml");
@@ -614,91 +2354,80 @@ args = ["ok", "bad\u0000arg"]
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `args` entries cannot contain NUL bytes"));
+            .contains("field `reconcile.script` cannot end with `.` or `..` path components"));
     }
 
     #[test]
-    fn preserves_toml_args_with_empty_unicode_and_whitespace_values() {
+    fn rejects_absolute_reconcile_script_field_ending_in_dot_component() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("ok.toml");
+        let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-args = ["", "emoji🚀", " spaced value "]
+
+[reconcile]
+script = "/tmp/.."
 "#,
         )
         .expect("write toml");
 
-        let manifest = parse(&config).expect("parse args with empty/unicode/whitespace values");
-        assert_eq!(
-            manifest.args,
-            vec![
-                "".to_string(),
-                "emoji🚀".to_string(),
-                " spaced value ".to_string()
-            ]
-        );
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `reconcile.script` cannot end with `.` or `..` path components"));
     }
 
     #[test]
-    fn preserves_toml_args_with_symbolic_and_pathlike_values() {
+    fn rejects_dot_slash_reconcile_script_field() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("ok.toml");
+        let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-args = [
-  "--flag=value",
-  "../relative/path",
-  "semi;colon&and",
-  "$DOLLAR",
-  "brace{value}"
-]
+
+[reconcile]
+script = "./"
 "#,
         )
         .expect("write toml");
 
-        let manifest = parse(&config).expect("parse args with symbolic/pathlike values");
-        assert_eq!(
-            manifest.args,
-            vec![
-                "--flag=value".to_string(),
-                "../relative/path".to_string(),
-                "semi;colon&and".to_string(),
-                "$DOLLAR".to_string(),
-                "brace{value}".to_string(),
-            ]
-        );
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `reconcile.script` cannot end with a path separator"));
     }
 
     #[test]
-    fn rejects_dot_exec_field_in_toml() {
+    fn rejects_reconcile_script_trailing_platform_separator() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
+        let sep = std::path::MAIN_SEPARATOR;
+        let script = format!(".{sep}hooks{sep}");
         fs::write(
             &config,
-            r#"
-exec = "."
-"#,
+            format!("exec = \"echo\"\n\n[reconcile]\nscript = '{script}'\n"),
         )
         .expect("write toml");
 
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `exec` cannot be `.` or `..`"));
+            .contains("field `reconcile.script` cannot end with a path separator"));
     }
 
     #[test]
-    fn rejects_parent_exec_field_in_toml() {
+    fn rejects_trailing_separator_reconcile_script_field() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
-exec = ".."
+exec = "echo"
+
+[reconcile]
+script = "./hooks/"
 "#,
         )
         .expect("write toml");
@@ -706,17 +2435,20 @@ exec = ".."
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `exec` cannot be `.` or `..`"));
+            .contains("field `reconcile.script` cannot end with a path separator"));
     }
 
     #[test]
-    fn rejects_exec_field_ending_in_dot_component() {
+    fn rejects_absolute_trailing_separator_reconcile_script_field() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
-exec = "bin/.."
+exec = "echo"
+
+[reconcile]
+script = "/tmp/"
 "#,
         )
         .expect("write toml");
@@ -724,81 +2456,142 @@ exec = "bin/.."
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `exec` cannot end with `.` or `..` path components"));
+            .contains("field `reconcile.script` cannot end with a path separator"));
     }
 
     #[test]
-    fn rejects_exec_field_ending_in_dot_component_with_platform_separator() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
-        let sep = std::path::MAIN_SEPARATOR;
-        let exec = format!("bin{sep}..");
-        fs::write(&config, format!("exec = '{exec}'\n")).expect("write toml");
+    fn trims_exec_and_journal_fields() -> Result<()> {
+        let temp = TempDir::new()?;
+        let config = temp.path().join("trimmed.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "  echo  "
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `exec` cannot end with `.` or `..` path components"));
+[journal]
+namespace = "  ops  "
+identifier = "   "
+"#,
+        )?;
+
+        let manifest = parse(&config)?;
+        assert_eq!(
+            manifest.exec.file_name().and_then(|x| x.to_str()),
+            Some("echo")
+        );
+        let journal = manifest.journal.expect("journal config");
+        assert_eq!(journal.namespace, "ops");
+        assert_eq!(journal.identifier, None);
+        Ok(())
+    }
+
+    #[test]
+    fn trims_mixed_whitespace_exec_and_journal_fields() -> Result<()> {
+        let temp = TempDir::new()?;
+        let config = temp.path().join("trimmed-mixed.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "\n\t echo \t\n"
+
+[journal]
+namespace = "\n\t ops \t\n"
+identifier = "\n\t  \t\n"
+"#,
+        )?;
+
+        let manifest = parse(&config)?;
+        assert_eq!(
+            manifest.exec.file_name().and_then(|x| x.to_str()),
+            Some("echo")
+        );
+        let journal = manifest.journal.expect("journal config");
+        assert_eq!(journal.namespace, "ops");
+        assert_eq!(journal.identifier, None);
+        Ok(())
+    }
+
+    #[test]
+    fn preserves_journal_symbolic_and_pathlike_namespace_and_identifier() -> Result<()> {
+        let temp = TempDir::new()?;
+        let config = temp.path().join("journal-symbols.toml");
+        fs::write(
+            &config,
+            r#"
+exec = "echo"
+
+[journal]
+namespace = "  ops/ns.prod@2026  "
+identifier = '  svc.id/worker\edge@2026  '
+"#,
+        )?;
+
+        let manifest = parse(&config)?;
+        let journal = manifest.journal.expect("journal config");
+        assert_eq!(journal.namespace, "ops/ns.prod@2026");
+        assert_eq!(
+            journal.identifier.as_deref(),
+            Some(r"svc.id/worker\edge@2026")
+        );
+        Ok(())
     }
 
     #[test]
-    fn rejects_absolute_exec_field_ending_in_dot_component() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+    fn trims_reconcile_script_and_function() -> Result<()> {
+        let temp = TempDir::new()?;
+        let config = temp.path().join("trimmed.toml");
         fs::write(
             &config,
             r#"
-exec = "/usr/bin/.."
+exec = "echo"
+
+[reconcile]
+script = "  hooks/reconcile.rhai  "
+function = "  custom_reconcile  "
 "#,
-        )
-        .expect("write toml");
+        )?;
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `exec` cannot end with `.` or `..` path components"));
+        let manifest = parse(&config)?;
+        let reconcile = manifest.reconcile.expect("reconcile config");
+        assert_eq!(reconcile.script, temp.path().join("trimmed.rhai"));
+        assert_eq!(reconcile.function, "custom_reconcile");
+        Ok(())
     }
 
     #[test]
-    fn rejects_dot_slash_exec_field_in_toml() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+    fn trims_mixed_whitespace_reconcile_script_and_function() -> Result<()> {
+        let temp = TempDir::new()?;
+        let config = temp.path().join("trimmed-mixed.toml");
         fs::write(
             &config,
             r#"
-exec = "./"
-"#,
-        )
-        .expect("write toml");
-
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `exec` cannot end with a path separator"));
-    }
+exec = "echo"
 
-    #[test]
-    fn rejects_exec_field_trailing_platform_separator() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
-        let sep = std::path::MAIN_SEPARATOR;
-        let exec = format!(".{sep}");
-        fs::write(&config, format!("exec = '{exec}'\n")).expect("write toml");
+[reconcile]
+script = "\n\thooks/reconcile.rhai\t\n"
+function = "\n\tcustom_reconcile\t\n"
+"#,
+        )?;
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `exec` cannot end with a path separator"));
+        let manifest = parse(&config)?;
+        let reconcile = manifest.reconcile.expect("reconcile config");
+        assert_eq!(reconcile.script, temp.path().join("trimmed-mixed.rhai"));
+        assert_eq!(reconcile.function, "custom_reconcile");
+        Ok(())
     }
 
     #[test]
-    fn rejects_trailing_separator_exec_field_in_toml() {
+    fn rejects_reconcile_function_containing_nul_bytes() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
-exec = "./bin/"
+exec = "echo"
+
+[reconcile]
+script = "hooks/reconcile.rhai"
+function = "reconcile\u0000hook"
 "#,
         )
         .expect("write toml");
@@ -806,120 +2599,155 @@ exec = "./bin/"
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `exec` cannot end with a path separator"));
+            .contains("field `reconcile.function` cannot contain NUL bytes"));
     }
 
     #[test]
-    fn rejects_absolute_trailing_separator_exec_field_in_toml() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+    fn trims_env_remove_entries_and_drops_blank_values() -> Result<()> {
+        let temp = TempDir::new()?;
+        let config = temp.path().join("trimmed.toml");
         fs::write(
             &config,
             r#"
-exec = "/usr/bin/"
+exec = "echo"
+env_remove = ["  FOO  ", "   ", "BAR"]
 "#,
-        )
-        .expect("write toml");
+        )?;
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `exec` cannot end with a path separator"));
+        let manifest = parse(&config)?;
+        assert_eq!(manifest.env_remove, vec!["FOO", "BAR"]);
+        Ok(())
     }
 
     #[test]
-    fn blank_reconcile_function_disables_reconcile() -> Result<()> {
+    fn dedupes_env_remove_entries_after_trimming() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("svc.toml");
+        let config = temp.path().join("trimmed.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-
-[reconcile]
-script = "hooks/reconcile.rhai"
-function = "   "
+env_remove = ["  FOO  ", "FOO", "BAR", " BAR "]
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        assert!(manifest.reconcile.is_none());
+        assert_eq!(manifest.env_remove, vec!["FOO", "BAR"]);
         Ok(())
     }
 
     #[test]
-    fn mixed_whitespace_blank_reconcile_function_disables_reconcile() -> Result<()> {
+    fn preserves_env_remove_entries_with_symbolic_and_pathlike_shapes() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("svc.toml");
+        let config = temp.path().join("env-remove.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-
-[reconcile]
-script = "hooks/reconcile.rhai"
-function = "\n\t  \t\n"
+env_remove = [
+  " KEY-WITH-DASH ",
+  "KEY.WITH.DOT",
+  "KEY/WITH/SLASH",
+  "KEY/WITH/SLASH"
+]
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        assert!(manifest.reconcile.is_none());
+        assert_eq!(
+            manifest.env_remove,
+            vec![
+                "KEY-WITH-DASH".to_string(),
+                "KEY.WITH.DOT".to_string(),
+                "KEY/WITH/SLASH".to_string(),
+            ]
+        );
         Ok(())
     }
 
     #[test]
-    fn reconcile_function_without_legacy_script_uses_deterministic_shared_script() -> Result<()> {
+    fn preserves_env_values_with_symbolic_and_pathlike_shapes() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("svc.toml");
+        let config = temp.path().join("env-values.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
 
-[reconcile]
-function = "custom_reconcile"
+[env]
+CHOPPER_EQ = "--flag=value"
+CHOPPER_REL = "../relative/path"
+CHOPPER_SHELL = "semi;colon&and"
+CHOPPER_DOLLAR = "$DOLLAR"
+" CHOPPER_BRACE " = "brace{value}"
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        let reconcile = manifest.reconcile.expect("reconcile config");
-        assert_eq!(reconcile.script, temp.path().join("svc.rhai"));
-        assert_eq!(reconcile.function, "custom_reconcile");
+        assert_eq!(
+            manifest.env.get("CHOPPER_EQ").map(String::as_str),
+            Some("--flag=value")
+        );
+        assert_eq!(
+            manifest.env.get("CHOPPER_REL").map(String::as_str),
+            Some("../relative/path")
+        );
+        assert_eq!(
+            manifest.env.get("CHOPPER_SHELL").map(String::as_str),
+            Some("semi;colon&and")
+        );
+        assert_eq!(
+            manifest.env.get("CHOPPER_DOLLAR").map(String::as_str),
+            Some("$DOLLAR")
+        );
+        assert_eq!(
+            manifest.env.get("CHOPPER_BRACE").map(String::as_str),
+            Some("brace{value}")
+        );
         Ok(())
     }
 
     #[test]
-    fn rejects_empty_journal_namespace() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+    fn preserves_env_keys_with_symbolic_and_pathlike_shapes() -> Result<()> {
+        let temp = TempDir::new()?;
+        let config = temp.path().join("env-keys.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
 
-[journal]
-namespace = "  "
+[env]
+" KEY-WITH-DASH " = "dash"
+"KEY.WITH.DOT" = "dot"
+"KEY/WITH/SLASH" = "slash"
 "#,
-        )
-        .expect("write toml");
+        )?;
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `journal.namespace` cannot be empty"));
+        let manifest = parse(&config)?;
+        assert_eq!(
+            manifest.env.get("KEY-WITH-DASH").map(String::as_str),
+            Some("dash")
+        );
+        assert_eq!(
+            manifest.env.get("KEY.WITH.DOT").map(String::as_str),
+            Some("dot")
+        );
+        assert_eq!(
+            manifest.env.get("KEY/WITH/SLASH").map(String::as_str),
+            Some("slash")
+        );
+        Ok(())
     }
 
     #[test]
-    fn rejects_journal_namespace_containing_nul_bytes() {
+    fn rejects_env_remove_entries_containing_equals_sign() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-
-[journal]
-namespace = "ops\u0000prod"
+env_remove = ["BAD=KEY"]
 "#,
         )
         .expect("write toml");
@@ -927,11 +2755,11 @@ namespace = "ops\u0000prod"
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `journal.namespace` cannot contain NUL bytes"));
+            .contains("field `env_remove` entries cannot contain `=`"));
     }
 
     #[test]
-    fn rejects_journal_identifier_containing_nul_bytes() {
+    fn rejects_empty_env_keys_after_trimming() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
@@ -939,9 +2767,8 @@ namespace = "ops\u0000prod"
             r#"
 exec = "echo"
 
-[journal]
-namespace = "ops"
-identifier = "svc\u0000id"
+[env]
+"   " = "value"
 "#,
         )
         .expect("write toml");
@@ -949,11 +2776,11 @@ identifier = "svc\u0000id"
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `journal.identifier` cannot contain NUL bytes"));
+            .contains("field `env` cannot contain empty keys"));
     }
 
     #[test]
-    fn rejects_dot_reconcile_script_field() {
+    fn rejects_duplicate_env_keys_after_trimming() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
@@ -961,8 +2788,9 @@ identifier = "svc\u0000id"
             r#"
 exec = "echo"
 
-[reconcile]
-script = "."
+[env]
+FOO = "base"
+" FOO " = "collision"
 "#,
         )
         .expect("write toml");
@@ -970,11 +2798,11 @@ script = "."
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `reconcile.script` cannot be `.` or `..`"));
+            .contains("contains duplicate keys after trimming"));
     }
 
     #[test]
-    fn rejects_reconcile_script_field_containing_nul_bytes() {
+    fn rejects_env_keys_containing_equals_sign() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
@@ -982,20 +2810,54 @@ script = "."
             r#"
 exec = "echo"
 
-[reconcile]
-script = "hooks/reconcile\u0000.rhai"
+[env]
+"BAD=KEY" = "value"
 "#,
         )
         .expect("write toml");
 
         let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `reconcile.script` cannot contain NUL bytes"));
+        assert!(err.to_string().contains("keys cannot contain `=`"));
+    }
+
+    #[test]
+    fn rejects_env_keys_containing_nul_bytes() {
+        let err = normalize_env_map(
+            HashMap::from([("BAD\0KEY".to_string(), "value".to_string())]),
+            "env",
+        )
+        .expect_err("expected env key validation failure");
+        assert!(
+            err.to_string().contains("cannot contain NUL bytes"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rejects_env_values_containing_nul_bytes() {
+        let err = normalize_env_map(
+            HashMap::from([("GOOD_KEY".to_string(), "bad\0value".to_string())]),
+            "env",
+        )
+        .expect_err("expected env value validation failure");
+        assert!(
+            err.to_string().contains("cannot contain NUL bytes"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rejects_env_remove_entries_containing_nul_bytes() {
+        let err = normalize_env_remove(vec!["BAD\0KEY".to_string()], "env_remove")
+            .expect_err("expected env_remove validation failure");
+        assert!(
+            err.to_string().contains("cannot contain NUL bytes"),
+            "{err}"
+        );
     }
 
     #[test]
-    fn rejects_parent_reconcile_script_field() {
+    fn rejects_env_key_with_nul_escape_in_toml() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
@@ -1003,8 +2865,8 @@ script = "hooks/reconcile\u0000.rhai"
             r#"
 exec = "echo"
 
-[reconcile]
-script = ".."
+[env]
+"BAD\u0000KEY" = "value"
 "#,
         )
         .expect("write toml");
@@ -1012,11 +2874,11 @@ script = ".."
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `reconcile.script` cannot be `.` or `..`"));
+            .contains("field `env` keys cannot contain NUL bytes"));
     }
 
     #[test]
-    fn rejects_reconcile_script_field_ending_in_dot_component() {
+    fn rejects_env_value_with_nul_escape_in_toml() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
@@ -1024,8 +2886,8 @@ script = ".."
             r#"
 exec = "echo"
 
-[reconcile]
-script = "hooks/.."
+[env]
+GOOD_KEY = "bad\u0000value"
 "#,
         )
         .expect("write toml");
@@ -1033,20 +2895,18 @@ script = "hooks/.."
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `reconcile.script` cannot end with `.` or `..` path components"));
+            .contains("field `env` values cannot contain NUL bytes"));
     }
 
     #[test]
-    fn rejects_absolute_reconcile_script_field_ending_in_dot_component() {
+    fn rejects_env_remove_entry_with_nul_escape_in_toml() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-
-[reconcile]
-script = "/tmp/.."
+env_remove = ["BAD\u0000KEY"]
 "#,
         )
         .expect("write toml");
@@ -1054,412 +2914,398 @@ script = "/tmp/.."
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `reconcile.script` cannot end with `.` or `..` path components"));
+            .contains("field `env_remove` entries cannot contain NUL bytes"));
     }
 
     #[test]
-    fn rejects_dot_slash_reconcile_script_field() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+    fn resolves_reconcile_script_relative_to_symlink_target_directory() -> Result<()> {
+        let temp = TempDir::new()?;
+        let target_dir = temp.path().join("shared");
+        let aliases_dir = temp.path().join("aliases");
+        fs::create_dir_all(&target_dir)?;
+        fs::create_dir_all(&aliases_dir)?;
+
+        let target_config = target_dir.join("linked.toml");
         fs::write(
-            &config,
+            &target_config,
             r#"
 exec = "echo"
 
 [reconcile]
-script = "./"
+script = "hooks/reconcile.rhai"
+function = "custom_reconcile"
 "#,
-        )
-        .expect("write toml");
-
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `reconcile.script` cannot end with a path separator"));
-    }
-
-    #[test]
-    fn rejects_reconcile_script_trailing_platform_separator() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
-        let sep = std::path::MAIN_SEPARATOR;
-        let script = format!(".{sep}hooks{sep}");
-        fs::write(
-            &config,
-            format!("exec = \"echo\"\n\n[reconcile]\nscript = '{script}'\n"),
-        )
-        .expect("write toml");
+        )?;
+        let symlink_config = aliases_dir.join("linked.toml");
+        symlink(&target_config, &symlink_config)?;
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `reconcile.script` cannot end with a path separator"));
+        let manifest = parse(&symlink_config)?;
+        assert_eq!(
+            manifest
+                .reconcile
+                .as_ref()
+                .expect("reconcile config")
+                .script,
+            target_dir.join("linked.rhai")
+        );
+        Ok(())
     }
 
     #[test]
-    fn rejects_trailing_separator_reconcile_script_field() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+    fn resolves_parent_relative_reconcile_script_against_config_directory() -> Result<()> {
+        let temp = TempDir::new()?;
+        let aliases_dir = temp.path().join("aliases");
+        fs::create_dir_all(&aliases_dir)?;
+        let config = aliases_dir.join("local.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
 
 [reconcile]
-script = "./hooks/"
+script = "../hooks/reconcile.rhai"
+function = "custom_reconcile"
 "#,
-        )
-        .expect("write toml");
+        )?;
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `reconcile.script` cannot end with a path separator"));
+        let manifest = parse(&config)?;
+        assert_eq!(
+            manifest
+                .reconcile
+                .as_ref()
+                .expect("reconcile config")
+                .script,
+            aliases_dir.join("local.rhai")
+        );
+        Ok(())
     }
 
     #[test]
-    fn rejects_absolute_trailing_separator_reconcile_script_field() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+    fn resolves_dot_prefixed_reconcile_script_against_config_directory() -> Result<()> {
+        let temp = TempDir::new()?;
+        let aliases_dir = temp.path().join("aliases");
+        fs::create_dir_all(&aliases_dir)?;
+        let config = aliases_dir.join("local.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
 
 [reconcile]
-script = "/tmp/"
+script = "./hooks/reconcile.rhai"
+function = "custom_reconcile"
 "#,
-        )
-        .expect("write toml");
+        )?;
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `reconcile.script` cannot end with a path separator"));
+        let manifest = parse(&config)?;
+        assert_eq!(
+            manifest
+                .reconcile
+                .as_ref()
+                .expect("reconcile config")
+                .script,
+            aliases_dir.join("local.rhai")
+        );
+        Ok(())
     }
 
     #[test]
-    fn trims_exec_and_journal_fields() -> Result<()> {
+    fn resolves_symbolic_relative_reconcile_script_against_config_directory() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("trimmed.toml");
+        let aliases_dir = temp.path().join("aliases");
+        fs::create_dir_all(&aliases_dir)?;
+        let config = aliases_dir.join("local.toml");
         fs::write(
             &config,
             r#"
-exec = "  echo  "
+exec = "echo"
 
-[journal]
-namespace = "  ops  "
-identifier = "   "
+[reconcile]
+script = './hooks/reconcile @v1.rhai'
+function = "custom_reconcile"
 "#,
         )?;
 
         let manifest = parse(&config)?;
         assert_eq!(
-            manifest.exec.file_name().and_then(|x| x.to_str()),
-            Some("echo")
+            manifest
+                .reconcile
+                .as_ref()
+                .expect("reconcile config")
+                .script,
+            aliases_dir.join("local.rhai")
         );
-        let journal = manifest.journal.expect("journal config");
-        assert_eq!(journal.namespace, "ops");
-        assert_eq!(journal.identifier, None);
         Ok(())
     }
 
     #[test]
-    fn trims_mixed_whitespace_exec_and_journal_fields() -> Result<()> {
+    fn resolves_relative_exec_path_against_config_directory() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("trimmed-mixed.toml");
+        let aliases_dir = temp.path().join("aliases");
+        fs::create_dir_all(&aliases_dir)?;
+        let config = aliases_dir.join("local.toml");
         fs::write(
             &config,
             r#"
-exec = "\n\t echo \t\n"
-
-[journal]
-namespace = "\n\t ops \t\n"
-identifier = "\n\t  \t\n"
+exec = "bin/runner"
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        assert_eq!(
-            manifest.exec.file_name().and_then(|x| x.to_str()),
-            Some("echo")
-        );
-        let journal = manifest.journal.expect("journal config");
-        assert_eq!(journal.namespace, "ops");
-        assert_eq!(journal.identifier, None);
+        assert_eq!(manifest.exec, aliases_dir.join("bin/runner"));
         Ok(())
     }
 
     #[test]
-    fn preserves_journal_symbolic_and_pathlike_namespace_and_identifier() -> Result<()> {
+    fn resolves_dot_prefixed_relative_exec_path_against_config_directory() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("journal-symbols.toml");
+        let aliases_dir = temp.path().join("aliases");
+        fs::create_dir_all(&aliases_dir)?;
+        let config = aliases_dir.join("local.toml");
         fs::write(
             &config,
             r#"
-exec = "echo"
-
-[journal]
-namespace = "  ops/ns.prod@2026  "
-identifier = '  svc.id/worker\edge@2026  '
+exec = "./bin/runner"
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        let journal = manifest.journal.expect("journal config");
-        assert_eq!(journal.namespace, "ops/ns.prod@2026");
-        assert_eq!(
-            journal.identifier.as_deref(),
-            Some(r"svc.id/worker\edge@2026")
-        );
+        assert_eq!(manifest.exec, aliases_dir.join("./bin/runner"));
         Ok(())
     }
 
     #[test]
-    fn trims_reconcile_script_and_function() -> Result<()> {
+    fn resolves_symbolic_relative_exec_path_against_config_directory() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("trimmed.toml");
+        let aliases_dir = temp.path().join("aliases");
+        fs::create_dir_all(&aliases_dir)?;
+        let config = aliases_dir.join("local.toml");
         fs::write(
             &config,
             r#"
-exec = "echo"
-
-[reconcile]
-script = "  hooks/reconcile.rhai  "
-function = "  custom_reconcile  "
+exec = './bin/runner @v1'
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        let reconcile = manifest.reconcile.expect("reconcile config");
-        assert_eq!(reconcile.script, temp.path().join("trimmed.rhai"));
-        assert_eq!(reconcile.function, "custom_reconcile");
+        assert_eq!(manifest.exec, aliases_dir.join("./bin/runner @v1"));
         Ok(())
     }
 
     #[test]
-    fn trims_mixed_whitespace_reconcile_script_and_function() -> Result<()> {
+    fn resolves_parent_relative_exec_path_against_config_directory() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("trimmed-mixed.toml");
+        let aliases_dir = temp.path().join("aliases");
+        fs::create_dir_all(&aliases_dir)?;
+        let config = aliases_dir.join("local.toml");
         fs::write(
             &config,
             r#"
-exec = "echo"
-
-[reconcile]
-script = "\n\thooks/reconcile.rhai\t\n"
-function = "\n\tcustom_reconcile\t\n"
+exec = "../bin/runner"
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        let reconcile = manifest.reconcile.expect("reconcile config");
-        assert_eq!(reconcile.script, temp.path().join("trimmed-mixed.rhai"));
-        assert_eq!(reconcile.function, "custom_reconcile");
+        assert_eq!(manifest.exec, aliases_dir.join("../bin/runner"));
         Ok(())
     }
 
-    #[test]
-    fn rejects_reconcile_function_containing_nul_bytes() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+    #[test]
+    fn detects_path_like_relative_exec_tokens() {
+        let sep = std::path::MAIN_SEPARATOR;
+        let with_platform_sep = format!(".{sep}bin{sep}runner");
+
+        assert!(looks_like_relative_exec_path("."));
+        assert!(looks_like_relative_exec_path(".."));
+        assert!(looks_like_relative_exec_path("./bin/runner"));
+        assert!(looks_like_relative_exec_path("../bin/runner"));
+        assert!(looks_like_relative_exec_path("bin/runner"));
+        assert!(looks_like_relative_exec_path(&with_platform_sep));
+
+        assert!(!looks_like_relative_exec_path("echo"));
+        assert!(!looks_like_relative_exec_path("kubectl.prod"));
+        assert!(!looks_like_relative_exec_path("emoji🚀"));
+    }
+
+    #[test]
+    fn resolves_relative_exec_path_against_symlink_target_directory() -> Result<()> {
+        let temp = TempDir::new()?;
+        let target_dir = temp.path().join("shared");
+        let aliases_dir = temp.path().join("aliases");
+        fs::create_dir_all(&target_dir)?;
+        fs::create_dir_all(&aliases_dir)?;
+
+        let target_config = target_dir.join("linked.toml");
         fs::write(
-            &config,
+            &target_config,
             r#"
-exec = "echo"
-
-[reconcile]
-script = "hooks/reconcile.rhai"
-function = "reconcile\u0000hook"
+exec = "bin/runner"
 "#,
-        )
-        .expect("write toml");
+        )?;
+        let symlink_config = aliases_dir.join("linked.toml");
+        symlink(&target_config, &symlink_config)?;
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `reconcile.function` cannot contain NUL bytes"));
+        let manifest = parse(&symlink_config)?;
+        assert_eq!(manifest.exec, target_dir.join("bin/runner"));
+        Ok(())
     }
 
+    // ------------------------------------------------------------------
+    // [bashcomp] table parsing tests
+    // ------------------------------------------------------------------
+
     #[test]
-    fn trims_env_remove_entries_and_drops_blank_values() -> Result<()> {
+    fn parses_bashcomp_disabled_field() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("trimmed.toml");
+        let config = temp.path().join("bc.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-env_remove = ["  FOO  ", "   ", "BAR"]
+
+[bashcomp]
+disabled = true
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        assert_eq!(manifest.env_remove, vec!["FOO", "BAR"]);
+        let bashcomp = manifest.bashcomp.expect("bashcomp config");
+        assert!(bashcomp.disabled);
+        assert!(!bashcomp.passthrough);
+        assert!(bashcomp.script.is_none());
         Ok(())
     }
 
     #[test]
-    fn dedupes_env_remove_entries_after_trimming() -> Result<()> {
+    fn parses_bashcomp_passthrough_field() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("trimmed.toml");
+        let config = temp.path().join("bc.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-env_remove = ["  FOO  ", "FOO", "BAR", " BAR "]
+
+[bashcomp]
+passthrough = true
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        assert_eq!(manifest.env_remove, vec!["FOO", "BAR"]);
+        let bashcomp = manifest.bashcomp.expect("bashcomp config");
+        assert!(!bashcomp.disabled);
+        assert!(bashcomp.passthrough);
+        assert!(bashcomp.script.is_none());
         Ok(())
     }
 
     #[test]
-    fn preserves_env_remove_entries_with_symbolic_and_pathlike_shapes() -> Result<()> {
+    fn parses_bashcomp_script_field() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("env-remove.toml");
+        let config = temp.path().join("bc.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-env_remove = [
-  " KEY-WITH-DASH ",
-  "KEY.WITH.DOT",
-  "KEY/WITH/SLASH",
-  "KEY/WITH/SLASH"
-]
+
+[bashcomp]
+script = "completions/custom.bash"
 "#,
         )?;
 
         let manifest = parse(&config)?;
+        let bashcomp = manifest.bashcomp.expect("bashcomp config");
+        assert!(!bashcomp.disabled);
+        assert!(!bashcomp.passthrough);
         assert_eq!(
-            manifest.env_remove,
-            vec![
-                "KEY-WITH-DASH".to_string(),
-                "KEY.WITH.DOT".to_string(),
-                "KEY/WITH/SLASH".to_string(),
-            ]
+            bashcomp.script,
+            Some(temp.path().join("completions/custom.bash"))
         );
         Ok(())
     }
 
     #[test]
-    fn preserves_env_values_with_symbolic_and_pathlike_shapes() -> Result<()> {
+    fn parses_bashcomp_all_fields() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("env-values.toml");
+        let config = temp.path().join("bc.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
 
-[env]
-CHOPPER_EQ = "--flag=value"
-CHOPPER_REL = "../relative/path"
-CHOPPER_SHELL = "semi;colon&and"
-CHOPPER_DOLLAR = "$DOLLAR"
-" CHOPPER_BRACE " = "brace{value}"
+[bashcomp]
+disabled = true
+passthrough = true
+script = "completions/custom.bash"
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        assert_eq!(
-            manifest.env.get("CHOPPER_EQ").map(String::as_str),
-            Some("--flag=value")
-        );
-        assert_eq!(
-            manifest.env.get("CHOPPER_REL").map(String::as_str),
-            Some("../relative/path")
-        );
-        assert_eq!(
-            manifest.env.get("CHOPPER_SHELL").map(String::as_str),
-            Some("semi;colon&and")
-        );
-        assert_eq!(
-            manifest.env.get("CHOPPER_DOLLAR").map(String::as_str),
-            Some("$DOLLAR")
-        );
-        assert_eq!(
-            manifest.env.get("CHOPPER_BRACE").map(String::as_str),
-            Some("brace{value}")
-        );
+        let bashcomp = manifest.bashcomp.expect("bashcomp config");
+        assert!(bashcomp.disabled);
+        assert!(bashcomp.passthrough);
+        assert!(bashcomp.script.is_some());
         Ok(())
     }
 
     #[test]
-    fn preserves_env_keys_with_symbolic_and_pathlike_shapes() -> Result<()> {
+    fn bashcomp_defaults_to_none_when_absent() -> Result<()> {
         let temp = TempDir::new()?;
-        let config = temp.path().join("env-keys.toml");
+        let config = temp.path().join("bc.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-
-[env]
-" KEY-WITH-DASH " = "dash"
-"KEY.WITH.DOT" = "dot"
-"KEY/WITH/SLASH" = "slash"
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        assert_eq!(
-            manifest.env.get("KEY-WITH-DASH").map(String::as_str),
-            Some("dash")
-        );
-        assert_eq!(
-            manifest.env.get("KEY.WITH.DOT").map(String::as_str),
-            Some("dot")
-        );
-        assert_eq!(
-            manifest.env.get("KEY/WITH/SLASH").map(String::as_str),
-            Some("slash")
-        );
+        assert!(manifest.bashcomp.is_none());
         Ok(())
     }
 
     #[test]
-    fn rejects_env_remove_entries_containing_equals_sign() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+    fn bashcomp_empty_table_defaults_fields_to_false() -> Result<()> {
+        let temp = TempDir::new()?;
+        let config = temp.path().join("bc.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-env_remove = ["BAD=KEY"]
+
+[bashcomp]
 "#,
-        )
-        .expect("write toml");
+        )?;
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `env_remove` entries cannot contain `=`"));
+        let manifest = parse(&config)?;
+        let bashcomp = manifest.bashcomp.expect("bashcomp config");
+        assert!(!bashcomp.disabled);
+        assert!(!bashcomp.passthrough);
+        assert!(bashcomp.script.is_none());
+        Ok(())
     }
 
     #[test]
-    fn rejects_empty_env_keys_after_trimming() {
-        let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+    fn bashcomp_script_blank_value_is_treated_as_none() -> Result<()> {
+        let temp = TempDir::new()?;
+        let config = temp.path().join("bc.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
 
-[env]
-"   " = "value"
+[bashcomp]
+script = "   "
 "#,
-        )
-        .expect("write toml");
+        )?;
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `env` cannot contain empty keys"));
+        let manifest = parse(&config)?;
+        let bashcomp = manifest.bashcomp.expect("bashcomp config");
+        assert!(bashcomp.script.is_none());
+        Ok(())
     }
 
     #[test]
-    fn rejects_duplicate_env_keys_after_trimming() {
+    fn rejects_bashcomp_script_containing_nul_bytes() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
@@ -1467,9 +3313,8 @@ exec = "echo"
             r#"
 exec = "echo"
 
-[env]
-FOO = "base"
-" FOO " = "collision"
+[bashcomp]
+script = "comp\u0000lete.bash"
 "#,
         )
         .expect("write toml");
@@ -1477,11 +3322,11 @@ FOO = "base"
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("contains duplicate keys after trimming"));
+            .contains("field `bashcomp.script` cannot contain NUL bytes"));
     }
 
     #[test]
-    fn rejects_env_keys_containing_equals_sign() {
+    fn rejects_bashcomp_script_dot_value() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
@@ -1489,54 +3334,20 @@ FOO = "base"
             r#"
 exec = "echo"
 
-[env]
-"BAD=KEY" = "value"
+[bashcomp]
+script = "."
 "#,
         )
         .expect("write toml");
 
         let err = parse(&config).expect_err("expected parse failure");
-        assert!(err.to_string().contains("keys cannot contain `=`"));
-    }
-
-    #[test]
-    fn rejects_env_keys_containing_nul_bytes() {
-        let err = normalize_env_map(HashMap::from([(
-            "BAD\0KEY".to_string(),
-            "value".to_string(),
-        )]))
-        .expect_err("expected env key validation failure");
-        assert!(
-            err.to_string().contains("cannot contain NUL bytes"),
-            "{err}"
-        );
-    }
-
-    #[test]
-    fn rejects_env_values_containing_nul_bytes() {
-        let err = normalize_env_map(HashMap::from([(
-            "GOOD_KEY".to_string(),
-            "bad\0value".to_string(),
-        )]))
-        .expect_err("expected env value validation failure");
-        assert!(
-            err.to_string().contains("cannot contain NUL bytes"),
-            "{err}"
-        );
-    }
-
-    #[test]
-    fn rejects_env_remove_entries_containing_nul_bytes() {
-        let err = normalize_env_remove(vec!["BAD\0KEY".to_string()])
-            .expect_err("expected env_remove validation failure");
-        assert!(
-            err.to_string().contains("cannot contain NUL bytes"),
-            "{err}"
-        );
+        assert!(err
+            .to_string()
+            .contains("field `bashcomp.script` cannot be `.` or `..`"));
     }
 
     #[test]
-    fn rejects_env_key_with_nul_escape_in_toml() {
+    fn rejects_bashcomp_script_trailing_separator() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
@@ -1544,8 +3355,8 @@ exec = "echo"
             r#"
 exec = "echo"
 
-[env]
-"BAD\u0000KEY" = "value"
+[bashcomp]
+script = "completions/"
 "#,
         )
         .expect("write toml");
@@ -1553,39 +3364,38 @@ exec = "echo"
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `env` keys cannot contain NUL bytes"));
+            .contains("field `bashcomp.script` cannot end with a path separator"));
     }
 
     #[test]
-    fn rejects_env_value_with_nul_escape_in_toml() {
+    fn rejects_bashcomp_script_trailing_platform_separator() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
+        let sep = std::path::MAIN_SEPARATOR;
+        let script = format!("completions{sep}");
         fs::write(
             &config,
-            r#"
-exec = "echo"
-
-[env]
-GOOD_KEY = "bad\u0000value"
-"#,
+            format!("exec = \"echo\"\n\n[bashcomp]\nscript = '{script}'\n"),
         )
         .expect("write toml");
 
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `env` values cannot contain NUL bytes"));
+            .contains("field `bashcomp.script` cannot end with a path separator"));
     }
 
     #[test]
-    fn rejects_env_remove_entry_with_nul_escape_in_toml() {
+    fn rejects_bashcomp_script_ending_in_dot_component() {
         let temp = TempDir::new().expect("create tempdir");
         let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
-env_remove = ["BAD\u0000KEY"]
+
+[bashcomp]
+script = "completions/.."
 "#,
         )
         .expect("write toml");
@@ -1593,680 +3403,1412 @@ env_remove = ["BAD\u0000KEY"]
         let err = parse(&config).expect_err("expected parse failure");
         assert!(err
             .to_string()
-            .contains("field `env_remove` entries cannot contain NUL bytes"));
+            .contains("field `bashcomp.script` cannot end with `.` or `..` path components"));
     }
 
+    // ------------------------------------------------------------------
+    // bashcomp.rhai_script / rhai_function tests
+    // ------------------------------------------------------------------
+
     #[test]
-    fn resolves_reconcile_script_relative_to_symlink_target_directory() -> Result<()> {
+    fn bashcomp_rhai_script_alone_does_not_enable_rhai_completion() -> Result<()> {
         let temp = TempDir::new()?;
-        let target_dir = temp.path().join("shared");
-        let aliases_dir = temp.path().join("aliases");
-        fs::create_dir_all(&target_dir)?;
-        fs::create_dir_all(&aliases_dir)?;
-
-        let target_config = target_dir.join("linked.toml");
+        let config = temp.path().join("bc.toml");
         fs::write(
-            &target_config,
+            &config,
             r#"
 exec = "echo"
 
-[reconcile]
-script = "hooks/reconcile.rhai"
-function = "custom_reconcile"
+[bashcomp]
+rhai_script = "completions/custom.rhai"
 "#,
         )?;
-        let symlink_config = aliases_dir.join("linked.toml");
-        symlink(&target_config, &symlink_config)?;
 
-        let manifest = parse(&symlink_config)?;
-        assert_eq!(
-            manifest
-                .reconcile
-                .as_ref()
-                .expect("reconcile config")
-                .script,
-            target_dir.join("linked.rhai")
-        );
+        let manifest = parse(&config)?;
+        let bashcomp = manifest.bashcomp.expect("bashcomp config");
+        assert!(bashcomp.rhai_script.is_none());
+        assert!(bashcomp.rhai_function.is_none());
         Ok(())
     }
 
     #[test]
-    fn resolves_parent_relative_reconcile_script_against_config_directory() -> Result<()> {
+    fn parses_bashcomp_rhai_script_and_function() -> Result<()> {
         let temp = TempDir::new()?;
-        let aliases_dir = temp.path().join("aliases");
-        fs::create_dir_all(&aliases_dir)?;
-        let config = aliases_dir.join("local.toml");
+        let config = temp.path().join("bc.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
 
-[reconcile]
-script = "../hooks/reconcile.rhai"
-function = "custom_reconcile"
+[bashcomp]
+rhai_script = "completions/custom.rhai"
+rhai_function = "my_completer"
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        assert_eq!(
-            manifest
-                .reconcile
-                .as_ref()
-                .expect("reconcile config")
-                .script,
-            aliases_dir.join("local.rhai")
-        );
+        let bashcomp = manifest.bashcomp.expect("bashcomp config");
+        assert_eq!(bashcomp.rhai_script, Some(temp.path().join("bc.rhai")));
+        assert_eq!(bashcomp.rhai_function, Some("my_completer".to_string()));
         Ok(())
     }
 
     #[test]
-    fn resolves_dot_prefixed_reconcile_script_against_config_directory() -> Result<()> {
+    fn bashcomp_rhai_script_blank_value_fails_validation() -> Result<()> {
         let temp = TempDir::new()?;
-        let aliases_dir = temp.path().join("aliases");
-        fs::create_dir_all(&aliases_dir)?;
-        let config = aliases_dir.join("local.toml");
+        let config = temp.path().join("bc.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
 
-[reconcile]
-script = "./hooks/reconcile.rhai"
-function = "custom_reconcile"
+[bashcomp]
+rhai_script = "   "
 "#,
         )?;
 
-        let manifest = parse(&config)?;
-        assert_eq!(
-            manifest
-                .reconcile
-                .as_ref()
-                .expect("reconcile config")
-                .script,
-            aliases_dir.join("local.rhai")
+        let err = parse(&config).expect_err("blank rhai_script should fail");
+        assert!(
+            err.to_string()
+                .contains("field `bashcomp.rhai_script` cannot be empty"),
+            "{err}"
         );
         Ok(())
     }
 
     #[test]
-    fn resolves_symbolic_relative_reconcile_script_against_config_directory() -> Result<()> {
+    fn bashcomp_rhai_function_blank_value_is_treated_as_none() -> Result<()> {
         let temp = TempDir::new()?;
-        let aliases_dir = temp.path().join("aliases");
-        fs::create_dir_all(&aliases_dir)?;
-        let config = aliases_dir.join("local.toml");
+        let config = temp.path().join("bc.toml");
         fs::write(
             &config,
             r#"
 exec = "echo"
 
-[reconcile]
-script = './hooks/reconcile @v1.rhai'
-function = "custom_reconcile"
+[bashcomp]
+rhai_function = "   "
 "#,
         )?;
 
         let manifest = parse(&config)?;
-        assert_eq!(
-            manifest
-                .reconcile
-                .as_ref()
-                .expect("reconcile config")
-                .script,
-            aliases_dir.join("local.rhai")
-        );
+        let bashcomp = manifest.bashcomp.expect("bashcomp config");
+        assert!(bashcomp.rhai_function.is_none());
         Ok(())
     }
 
     #[test]
-    fn resolves_relative_exec_path_against_config_directory() -> Result<()> {
-        let temp = TempDir::new()?;
-        let aliases_dir = temp.path().join("aliases");
-        fs::create_dir_all(&aliases_dir)?;
-        let config = aliases_dir.join("local.toml");
+    fn rejects_bashcomp_rhai_script_containing_nul_bytes() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
         fs::write(
             &config,
-            r#"
-exec = "bin/runner"
-"#,
-        )?;
+            "exec = \"echo\"\n\n[bashcomp]\nrhai_script = \"comp\\u0000lete.rhai\"\n",
+        )
+        .expect("write toml");
 
-        let manifest = parse(&config)?;
-        assert_eq!(manifest.exec, aliases_dir.join("bin/runner"));
-        Ok(())
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `bashcomp.rhai_script` cannot contain NUL bytes"));
     }
 
     #[test]
-    fn resolves_dot_prefixed_relative_exec_path_against_config_directory() -> Result<()> {
-        let temp = TempDir::new()?;
-        let aliases_dir = temp.path().join("aliases");
-        fs::create_dir_all(&aliases_dir)?;
-        let config = aliases_dir.join("local.toml");
+    fn rejects_bashcomp_rhai_script_dot_value() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
-exec = "./bin/runner"
+exec = "echo"
+
+[bashcomp]
+rhai_script = "."
 "#,
-        )?;
+        )
+        .expect("write toml");
 
-        let manifest = parse(&config)?;
-        assert_eq!(manifest.exec, aliases_dir.join("./bin/runner"));
-        Ok(())
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `bashcomp.rhai_script` cannot be `.` or `..`"));
     }
 
     #[test]
-    fn resolves_symbolic_relative_exec_path_against_config_directory() -> Result<()> {
-        let temp = TempDir::new()?;
-        let aliases_dir = temp.path().join("aliases");
-        fs::create_dir_all(&aliases_dir)?;
-        let config = aliases_dir.join("local.toml");
+    fn rejects_bashcomp_rhai_script_trailing_separator() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
-exec = './bin/runner @v1'
+exec = "echo"
+
+[bashcomp]
+rhai_script = "completions/"
 "#,
-        )?;
+        )
+        .expect("write toml");
 
-        let manifest = parse(&config)?;
-        assert_eq!(manifest.exec, aliases_dir.join("./bin/runner @v1"));
-        Ok(())
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `bashcomp.rhai_script` cannot end with a path separator"));
     }
 
     #[test]
-    fn resolves_parent_relative_exec_path_against_config_directory() -> Result<()> {
-        let temp = TempDir::new()?;
-        let aliases_dir = temp.path().join("aliases");
-        fs::create_dir_all(&aliases_dir)?;
-        let config = aliases_dir.join("local.toml");
+    fn rejects_bashcomp_rhai_function_containing_nul_bytes() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        fs::write(
+            &config,
+            "exec = \"echo\"\n\n[bashcomp]\nrhai_script = \"comp.rhai\"\nrhai_function = \"bad\\u0000func\"\n",
+        )
+        .expect("write toml");
+
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(err
+            .to_string()
+            .contains("field `bashcomp.rhai_function` cannot contain NUL bytes"));
+    }
+
+    #[test]
+    fn bashcomp_rhai_function_without_legacy_script_uses_deterministic_shared_script() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
         fs::write(
             &config,
             r#"
-exec = "../bin/runner"
+exec = "echo"
+
+[bashcomp]
+rhai_function = "complete"
 "#,
-        )?;
+        )
+        .expect("write toml");
+
+        let manifest = parse(&config).expect("expected parse success");
+        let bashcomp = manifest.bashcomp.expect("bashcomp config");
+        assert_eq!(bashcomp.rhai_script, Some(temp.path().join("bad.rhai")));
+        assert_eq!(bashcomp.rhai_function, Some("complete".to_string()));
+    }
+
+    fn write_alias(config_root: &std::path::Path, alias: &str, content: &str) -> PathBuf {
+        let path = config_root.join(alias).join("exe.toml");
+        fs::create_dir_all(path.parent().expect("alias dir")).expect("create alias dir");
+        fs::write(&path, content).expect("write alias config");
+        path
+    }
+
+    #[test]
+    fn extends_merges_parent_manifest_with_child_overrides() -> Result<()> {
+        let _guard = ENV_LOCK.lock().expect("lock env");
+        let temp = TempDir::new().expect("create tempdir");
+        env::set_var("CHOPPER_CONFIG_DIR", temp.path());
+        write_alias(
+            temp.path(),
+            "kube-base",
+            r#"
+exec = "kubectl"
+args = ["--context", "prod"]
+env_remove = ["KUBECONFIG", "AWS_PROFILE"]
+
+[env]
+KUBE_EDITOR = "vim"
+LANG = "C"
+
+[path]
+prepend_one = ["/opt/kube/bin"]
+
+[journal]
+namespace = "ops"
+
+[reconcile]
+function = "reconcile"
+"#,
+        );
+        let child = write_alias(
+            temp.path(),
+            "kpods",
+            r#"
+extends = "kube-base"
+args = ["get", "pods"]
+env_remove = ["LANG"]
+
+[env]
+KUBECONFIG = "/etc/kube/prod"
+
+[path]
+append_one = ["/opt/extra/bin"]
+
+[journal]
+namespace = "pods"
+"#,
+        );
+
+        let manifest = parse(&child);
+        env::remove_var("CHOPPER_CONFIG_DIR");
+        let manifest = manifest?;
+
+        assert_eq!(
+            manifest.exec.file_name().and_then(|x| x.to_str()),
+            Some("kubectl")
+        );
+        assert_eq!(manifest.args, vec!["--context", "prod", "get", "pods"]);
+        assert_eq!(
+            manifest.env,
+            HashMap::from([
+                ("KUBE_EDITOR".to_string(), "vim".to_string()),
+                ("KUBECONFIG".to_string(), "/etc/kube/prod".to_string()),
+            ])
+        );
+        assert_eq!(manifest.env_remove, vec!["AWS_PROFILE", "LANG"]);
+        let path = manifest.path.expect("path config");
+        assert_eq!(path.prepend_one, vec!["/opt/kube/bin"]);
+        assert_eq!(path.append_one, vec!["/opt/extra/bin"]);
+        assert_eq!(manifest.journal.expect("journal").namespace, "pods");
+        assert_eq!(
+            manifest.reconcile.expect("inherited reconcile").script,
+            temp.path().join("kube-base").join("exe.rhai")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn extends_child_exec_and_empty_tables_override_parent() -> Result<()> {
+        let _guard = ENV_LOCK.lock().expect("lock env");
+        let temp = TempDir::new().expect("create tempdir");
+        env::set_var("CHOPPER_CONFIG_DIR", temp.path());
+        write_alias(
+            temp.path(),
+            "base",
+            "exec = \"echo\"\n[reconcile]\nfunction = \"reconcile\"\n",
+        );
+        let child = write_alias(
+            temp.path(),
+            "child",
+            "extends = \"base\"\nexec = \"/bin/true\"\n[reconcile]\nfunction = \"\"\n",
+        );
 
-        let manifest = parse(&config)?;
-        assert_eq!(manifest.exec, aliases_dir.join("../bin/runner"));
+        let manifest = parse(&child);
+        env::remove_var("CHOPPER_CONFIG_DIR");
+        let manifest = manifest?;
+        assert_eq!(manifest.exec, PathBuf::from("/bin/true"));
+        assert!(manifest.reconcile.is_none());
         Ok(())
     }
 
     #[test]
-    fn detects_path_like_relative_exec_tokens() {
-        let sep = std::path::MAIN_SEPARATOR;
-        let with_platform_sep = format!(".{sep}bin{sep}runner");
+    fn extends_reports_inheritance_cycles_with_full_chain() {
+        let _guard = ENV_LOCK.lock().expect("lock env");
+        let temp = TempDir::new().expect("create tempdir");
+        env::set_var("CHOPPER_CONFIG_DIR", temp.path());
+        let first = write_alias(temp.path(), "first", "extends = \"second\"\n");
+        write_alias(temp.path(), "second", "extends = \"first\"\n");
 
-        assert!(looks_like_relative_exec_path("."));
-        assert!(looks_like_relative_exec_path(".."));
-        assert!(looks_like_relative_exec_path("./bin/runner"));
-        assert!(looks_like_relative_exec_path("../bin/runner"));
-        assert!(looks_like_relative_exec_path("bin/runner"));
-        assert!(looks_like_relative_exec_path(&with_platform_sep));
+        let err = parse(&first).expect_err("expected cycle failure");
+        let chain = super::config_source_files(&first);
+        env::remove_var("CHOPPER_CONFIG_DIR");
 
-        assert!(!looks_like_relative_exec_path("echo"));
-        assert!(!looks_like_relative_exec_path("kubectl.prod"));
-        assert!(!looks_like_relative_exec_path("emoji🚀"));
+        let message = format!("{err:#}");
+        assert!(
+            message.contains("field `extends` forms an inheritance cycle"),
+            "{message}"
+        );
+        assert!(
+            message.contains("failed to load alias `second` extended by"),
+            "{message}"
+        );
+        assert!(message.contains("first/exe.toml -> "), "{message}");
+        assert_eq!(chain.len(), 2);
     }
 
     #[test]
-    fn resolves_relative_exec_path_against_symlink_target_directory() -> Result<()> {
-        let temp = TempDir::new()?;
-        let target_dir = temp.path().join("shared");
-        let aliases_dir = temp.path().join("aliases");
-        fs::create_dir_all(&target_dir)?;
-        fs::create_dir_all(&aliases_dir)?;
+    fn extends_rejects_unknown_parent_alias() {
+        let _guard = ENV_LOCK.lock().expect("lock env");
+        let temp = TempDir::new().expect("create tempdir");
+        env::set_var("CHOPPER_CONFIG_DIR", temp.path());
+        let child = write_alias(temp.path(), "child", "extends = \"missing\"\n");
 
-        let target_config = target_dir.join("linked.toml");
+        let err = parse(&child).expect_err("expected unknown parent failure");
+        env::remove_var("CHOPPER_CONFIG_DIR");
+        assert!(
+            err.to_string()
+                .contains("field `extends` references unknown alias `missing`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn include_fragments_splice_before_alias_values() -> Result<()> {
+        let temp = TempDir::new().expect("create tempdir");
+        let shared = temp.path().join("_shared");
+        fs::create_dir_all(&shared).expect("create shared dir");
         fs::write(
-            &target_config,
+            shared.join("aws.toml"),
             r#"
-exec = "bin/runner"
-"#,
-        )?;
-        let symlink_config = aliases_dir.join("linked.toml");
-        symlink(&target_config, &symlink_config)?;
-
-        let manifest = parse(&symlink_config)?;
-        assert_eq!(manifest.exec, target_dir.join("bin/runner"));
-        Ok(())
-    }
+env_remove = ["AWS_SESSION_TOKEN"]
 
-    // ------------------------------------------------------------------
-    // [bashcomp] table parsing tests
-    // ------------------------------------------------------------------
+[env]
+AWS_REGION = "us-east-1"
+AWS_PROFILE = "shared"
 
-    #[test]
-    fn parses_bashcomp_disabled_field() -> Result<()> {
-        let temp = TempDir::new()?;
-        let config = temp.path().join("bc.toml");
+[journal]
+namespace = "shared"
+"#,
+        )
+        .expect("write aws fragment");
         fs::write(
-            &config,
+            shared.join("paths.toml"),
+            "[path]\nprepend_one = [\"/opt/shared/bin\"]\n",
+        )
+        .expect("write paths fragment");
+        let config = write_alias(
+            temp.path(),
+            "svc",
             r#"
 exec = "echo"
+include = ["../_shared/aws.toml", "../_shared/paths.toml"]
+env_remove = ["LANG"]
 
-[bashcomp]
-disabled = true
+[env]
+AWS_PROFILE = "svc"
+
+[path]
+append_one = ["/opt/svc/bin"]
 "#,
-        )?;
+        );
 
         let manifest = parse(&config)?;
-        let bashcomp = manifest.bashcomp.expect("bashcomp config");
-        assert!(bashcomp.disabled);
-        assert!(!bashcomp.passthrough);
-        assert!(bashcomp.script.is_none());
+        assert_eq!(
+            manifest.env,
+            HashMap::from([
+                ("AWS_REGION".to_string(), "us-east-1".to_string()),
+                ("AWS_PROFILE".to_string(), "svc".to_string()),
+            ])
+        );
+        assert_eq!(manifest.env_remove, vec!["AWS_SESSION_TOKEN", "LANG"]);
+        let path = manifest.path.expect("path config");
+        assert_eq!(path.prepend_one, vec!["/opt/shared/bin"]);
+        assert_eq!(path.append_one, vec!["/opt/svc/bin"]);
+        assert_eq!(manifest.journal.expect("journal").namespace, "shared");
+
+        let sources = super::config_source_files(&config)
+            .into_iter()
+            .map(|source| fs::canonicalize(source).expect("canonical source"))
+            .collect::<Vec<_>>();
+        let shared = fs::canonicalize(&shared).expect("canonical shared dir");
+        assert_eq!(
+            sources,
+            vec![
+                fs::canonicalize(&config).expect("canonical config"),
+                shared.join("aws.toml"),
+                shared.join("paths.toml"),
+            ]
+        );
         Ok(())
     }
 
     #[test]
-    fn parses_bashcomp_passthrough_field() -> Result<()> {
-        let temp = TempDir::new()?;
-        let config = temp.path().join("bc.toml");
-        fs::write(
-            &config,
-            r#"
-exec = "echo"
+    fn include_fragments_reject_alias_only_fields() {
+        let temp = TempDir::new().expect("create tempdir");
+        fs::write(temp.path().join("frag.toml"), "exec = \"rm\"\n").expect("write fragment");
+        let config = temp.path().join("svc.toml");
+        fs::write(&config, "exec = \"echo\"\ninclude = [\"frag.toml\"]\n").expect("write toml");
 
-[bashcomp]
-passthrough = true
-"#,
-        )?;
+        let err = parse(&config).expect_err("expected fragment failure");
+        let message = format!("{err:#}");
+        assert!(
+            message.contains("invalid TOML in include fragment"),
+            "{message}"
+        );
+        assert!(message.contains("unknown field `exec`"), "{message}");
+    }
 
-        let manifest = parse(&config)?;
-        let bashcomp = manifest.bashcomp.expect("bashcomp config");
-        assert!(!bashcomp.disabled);
-        assert!(bashcomp.passthrough);
-        assert!(bashcomp.script.is_none());
-        Ok(())
+    #[test]
+    fn include_fragments_report_missing_files() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("svc.toml");
+        fs::write(&config, "exec = \"echo\"\ninclude = [\"missing.toml\"]\n").expect("write toml");
+
+        let err = parse(&config).expect_err("expected missing fragment failure");
+        assert!(
+            err.to_string().contains("failed to read include fragment"),
+            "{err}"
+        );
     }
 
     #[test]
-    fn parses_bashcomp_script_field() -> Result<()> {
-        let temp = TempDir::new()?;
-        let config = temp.path().join("bc.toml");
+    fn rejects_blank_include_entries() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("svc.toml");
+        fs::write(&config, "exec = \"echo\"\ninclude = [\"  \"]\n").expect("write toml");
+
+        let err = parse(&config).expect_err("expected include failure");
+        assert!(
+            err.to_string()
+                .contains("field `include` entries cannot be empty"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rejects_missing_exec_without_extends() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("bad.toml");
+        fs::write(&config, "args = [\"x\"]\n").expect("write toml");
+
+        let err = parse(&config).expect_err("expected parse failure");
+        assert!(
+            err.to_string()
+                .contains("field `exec` is required unless `extends` is set"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn keeps_placeholder_values_raw_and_records_alias_dir() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("templated.toml");
         fs::write(
             &config,
             r#"
-exec = "echo"
+exec = "${alias_dir}/bin/tool"
+args = ["--home=${HOME}"]
 
-[bashcomp]
-script = "completions/custom.bash"
+[env]
+CACHE = "~/.cache/tool"
 "#,
-        )?;
+        )
+        .expect("write toml");
 
-        let manifest = parse(&config)?;
-        let bashcomp = manifest.bashcomp.expect("bashcomp config");
-        assert!(!bashcomp.disabled);
-        assert!(!bashcomp.passthrough);
+        let manifest = parse(&config).expect("parse templated config");
+        assert_eq!(manifest.exec, PathBuf::from("${alias_dir}/bin/tool"));
+        assert_eq!(manifest.args, vec!["--home=${HOME}"]);
         assert_eq!(
-            bashcomp.script,
-            Some(temp.path().join("completions/custom.bash"))
+            manifest.env.get("CACHE"),
+            Some(&"~/.cache/tool".to_string())
+        );
+        assert_eq!(
+            manifest.alias_dir,
+            Some(fs::canonicalize(temp.path()).expect("canonical tempdir"))
         );
-        Ok(())
     }
 
     #[test]
-    fn parses_bashcomp_all_fields() -> Result<()> {
-        let temp = TempDir::new()?;
-        let config = temp.path().join("bc.toml");
+    fn parses_args_after_and_validates_runtime_arg_slots() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("slots.toml");
         fs::write(
             &config,
             r#"
-exec = "echo"
-
-[bashcomp]
-disabled = true
-passthrough = true
-script = "completions/custom.bash"
+exec = "docker"
+args = ["run", "--rm", "{@}"]
+args_after = ["--extra"]
 "#,
-        )?;
+        )
+        .expect("write toml");
+        let manifest = parse(&config).expect("parse slots config");
+        assert_eq!(manifest.args, vec!["run", "--rm", "{@}"]);
+        assert_eq!(manifest.args_after, vec!["--extra"]);
 
-        let manifest = parse(&config)?;
-        let bashcomp = manifest.bashcomp.expect("bashcomp config");
-        assert!(bashcomp.disabled);
-        assert!(bashcomp.passthrough);
-        assert!(bashcomp.script.is_some());
-        Ok(())
+        fs::write(&config, "exec = \"docker\"\nargs = [\"{0}\"]\n").expect("write toml");
+        let err = parse(&config).expect_err("zero position should fail");
+        assert!(
+            err.to_string()
+                .contains("field `args` runtime-arg slot `{0}` must use a position of 1"),
+            "{err}"
+        );
+
+        fs::write(&config, "exec = \"docker\"\nargs_after = [\"{@}\"]\n").expect("write toml");
+        let err = parse(&config).expect_err("slot in args_after should fail");
+        assert!(
+            err.to_string()
+                .contains("field `args_after` cannot contain runtime-arg slot `{@}`"),
+            "{err}"
+        );
     }
 
     #[test]
-    fn bashcomp_defaults_to_none_when_absent() -> Result<()> {
-        let temp = TempDir::new()?;
-        let config = temp.path().join("bc.toml");
+    fn resolves_relative_cwd_against_config_directory() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("cwd.toml");
+        fs::write(&config, "exec = \"make\"\ncwd = \"  ../repo  \"\n").expect("write toml");
+
+        let manifest = parse(&config).expect("parse cwd config");
+        let base = fs::canonicalize(temp.path()).expect("canonical tempdir");
+        assert_eq!(manifest.cwd, Some(base.join("../repo")));
+
+        fs::write(&config, "exec = \"make\"\ncwd = \"   \"\n").expect("write toml");
+        let manifest = parse(&config).expect("parse blank cwd config");
+        assert_eq!(manifest.cwd, None);
+
+        fs::write(&config, "exec = \"make\"\ncwd = \"bad\\u0000dir\"\n").expect("write toml");
+        let err = parse(&config).expect_err("NUL cwd should fail");
+        assert!(
+            err.to_string()
+                .contains("field `cwd` cannot contain NUL bytes"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn parses_env_mode_and_env_keep() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("build.toml");
         fs::write(
             &config,
-            r#"
-exec = "echo"
-"#,
-        )?;
-
-        let manifest = parse(&config)?;
-        assert!(manifest.bashcomp.is_none());
-        Ok(())
+            "exec = \"make\"\nenv_mode = \" clean \"\nenv_keep = [\"HOME\", \" ^LC_ \", \"\", \"HOME\"]\n",
+        )
+        .expect("write toml");
+        let manifest = parse(&config).expect("parse clean env config");
+        assert_eq!(manifest.env_mode, EnvMode::Clean);
+        assert_eq!(manifest.env_keep, vec!["HOME", "^LC_"]);
+
+        for (body, expected) in [
+            (
+                "env_mode = \"empty\"",
+                "field `env_mode` must be `inherit` or `clean`: `empty`",
+            ),
+            (
+                "env_keep = [\"HOME\"]",
+                "field `env_keep` requires `env_mode = \"clean\"`",
+            ),
+            (
+                "env_mode = \"clean\"\nenv_keep = [\"LC_(\"]",
+                "field `env_keep` entry `LC_(` is not a valid regex",
+            ),
+        ] {
+            fs::write(&config, format!("exec = \"x\"\n{body}\n")).expect("write toml");
+            let err = parse(&config).expect_err("invalid env_mode config should fail");
+            assert!(err.to_string().contains(expected), "{err}");
+        }
     }
 
     #[test]
-    fn bashcomp_empty_table_defaults_fields_to_false() -> Result<()> {
-        let temp = TempDir::new()?;
-        let config = temp.path().join("bc.toml");
+    fn parses_env_remove_matching_and_rejects_invalid_patterns() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("deploy.toml");
         fs::write(
             &config,
-            r#"
-exec = "echo"
+            "exec = \"deploy\"\nenv_remove_matching = [\" ^AWS_ \", \"\", \"^KUBE\", \"^AWS_\"]\n",
+        )
+        .expect("write toml");
+        let manifest = parse(&config).expect("parse env_remove_matching");
+        assert_eq!(manifest.env_remove_matching, vec!["^AWS_", "^KUBE"]);
 
-[bashcomp]
-"#,
-        )?;
+        fs::write(
+            &config,
+            "exec = \"x\"\nenv_remove_matching = [\"^AWS_(\"]\n",
+        )
+        .expect("write toml");
+        let err = parse(&config).expect_err("invalid pattern should fail");
+        assert!(
+            err.to_string()
+                .contains("field `env_remove_matching` contains invalid regex `^AWS_(`"),
+            "{err}"
+        );
 
-        let manifest = parse(&config)?;
-        let bashcomp = manifest.bashcomp.expect("bashcomp config");
-        assert!(!bashcomp.disabled);
-        assert!(!bashcomp.passthrough);
-        assert!(bashcomp.script.is_none());
-        Ok(())
+        fs::write(&config, "exec = \"x\"\nenv_remove_matching = [\"AWS_*\"]\n")
+            .expect("write toml");
+        let err = parse(&config).expect_err("bare glob should fail");
+        assert!(
+            err.to_string().contains(
+                "field `env_remove_matching` pattern `AWS_*` looks like a glob but would be read as an unanchored regex; write `glob:AWS_*`"
+            ),
+            "{err}"
+        );
     }
 
     #[test]
-    fn bashcomp_script_blank_value_is_treated_as_none() -> Result<()> {
-        let temp = TempDir::new()?;
-        let config = temp.path().join("bc.toml");
+    fn parses_env_default_append_and_prepend() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("cargo.toml");
         fs::write(
             &config,
             r#"
-exec = "echo"
+exec = "cargo"
 
-[bashcomp]
-script = "   "
+[env_default]
+" CARGO_TERM_COLOR " = "always"
+
+[env_append]
+RUSTFLAGS = "-Dwarnings"
+
+[env_prepend]
+JAVA_OPTS = { value = "-Xmx1g", separator = "," }
 "#,
-        )?;
+        )
+        .expect("write toml");
+
+        let manifest = parse(&config).expect("parse env operations");
+        assert_eq!(
+            manifest
+                .env_default
+                .get("CARGO_TERM_COLOR")
+                .map(String::as_str),
+            Some("always")
+        );
+        assert_eq!(
+            manifest.env_append.get("RUSTFLAGS"),
+            Some(&EnvJoin {
+                value: "-Dwarnings".into(),
+                separator: " ".into(),
+            })
+        );
+        assert_eq!(
+            manifest.env_prepend.get("JAVA_OPTS"),
+            Some(&EnvJoin {
+                value: "-Xmx1g".into(),
+                separator: ",".into(),
+            })
+        );
+
+        fs::write(
+            &config,
+            "exec = \"x\"\n[env]\nEDITOR = \"vim\"\n[env_default]\nEDITOR = \"vi\"\n",
+        )
+        .expect("write toml");
+        let err = parse(&config).expect_err("conflicting env_default should fail");
+        assert!(
+            err.to_string().contains(
+                "field `env_default` key `EDITOR` is also set in field `env` or `env_secret`"
+            ),
+            "{err}"
+        );
 
-        let manifest = parse(&config)?;
-        let bashcomp = manifest.bashcomp.expect("bashcomp config");
-        assert!(bashcomp.script.is_none());
-        Ok(())
+        fs::write(
+            &config,
+            "exec = \"x\"\n[env_append]\nRUSTFLAGS = { value = \"-g\", sep = \",\" }\n",
+        )
+        .expect("write toml");
+        assert!(parse(&config).is_err());
     }
 
     #[test]
-    fn rejects_bashcomp_script_containing_nul_bytes() {
+    fn parses_pathlists_tables_and_rejects_invalid_ones() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+        let config = temp.path().join("py.toml");
         fs::write(
             &config,
             r#"
-exec = "echo"
+exec = "python3"
 
-[bashcomp]
-script = "comp\u0000lete.bash"
+[pathlists." PYTHONPATH "]
+prepend_one = ["/srv/py"]
+separator = ":"
+dedupe = true
+
+[pathlists.MANPATH]
 "#,
         )
         .expect("write toml");
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `bashcomp.script` cannot contain NUL bytes"));
+        let manifest = parse(&config).expect("parse pathlists");
+        assert_eq!(
+            manifest.pathlists.keys().collect::<Vec<_>>(),
+            vec!["PYTHONPATH"]
+        );
+        let list = &manifest.pathlists["PYTHONPATH"];
+        assert_eq!(list.prepend_one, vec!["/srv/py"]);
+        assert!(list.dedupe);
+        assert!(!list.prune_missing);
+
+        for (body, expected) in [
+            (
+                "[pathlists.\"A=B\"]\ndedupe = true",
+                "field `pathlists` keys cannot contain `=`",
+            ),
+            (
+                "[pathlists.MANPATH]\nseparator = \"\"",
+                "field `pathlists.MANPATH.separator` cannot be empty",
+            ),
+            (
+                "[pathlists.MANPATH]\nprepend = [\"/x\"]",
+                "unknown field `prepend`",
+            ),
+        ] {
+            fs::write(&config, format!("exec = \"x\"\n{body}\n")).expect("write toml");
+            let err = parse(&config).expect_err("invalid pathlists should fail");
+            assert!(format!("{err:#}").contains(expected), "{err:#}");
+        }
     }
 
     #[test]
-    fn rejects_bashcomp_script_dot_value() {
+    fn parses_hooks_table_and_rejects_empty_commands() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+        let config = temp.path().join("migrate.toml");
         fs::write(
             &config,
             r#"
-exec = "echo"
+exec = "migrate"
 
-[bashcomp]
-script = "."
+[hooks]
+pre = [["pg_isready", "-q"]]
+post = [["notify-send", "migrate finished"], ["sh", "-c", "exit 0"]]
+journal = true
 "#,
         )
         .expect("write toml");
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `bashcomp.script` cannot be `.` or `..`"));
+        let hooks = parse(&config).expect("parse hooks").hooks.expect("hooks");
+        assert_eq!(hooks.pre, vec![vec!["pg_isready", "-q"]]);
+        assert_eq!(hooks.post.len(), 2);
+        assert!(hooks.journal);
+
+        fs::write(&config, "exec = \"x\"\n[hooks]\njournal = true\n").expect("write toml");
+        assert_eq!(parse(&config).expect("parse empty hooks").hooks, None);
+
+        for (body, expected) in [
+            (
+                "[hooks]\npre = [[]]",
+                "field `hooks.pre` commands cannot be empty",
+            ),
+            (
+                "[hooks]\npost = [[\" \", \"x\"]]",
+                "field `hooks.post` commands cannot be empty",
+            ),
+            ("[hooks]\non_error = [[\"x\"]]", "unknown field `on_error`"),
+        ] {
+            fs::write(&config, format!("exec = \"x\"\n{body}\n")).expect("write toml");
+            let err = parse(&config).expect_err("invalid hooks should fail");
+            assert!(format!("{err:#}").contains(expected), "{err:#}");
+        }
     }
 
     #[test]
-    fn rejects_bashcomp_script_trailing_separator() {
+    fn parses_timeout_fields_and_rejects_invalid_values() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+        let config = temp.path().join("backup.toml");
         fs::write(
             &config,
-            r#"
-exec = "echo"
-
-[bashcomp]
-script = "completions/"
-"#,
+            "exec = \"backup\"\ntimeout = \"30m\"\ntimeout_signal = \"int\"\nkill_after = \"1.5s\"\n",
         )
         .expect("write toml");
-
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `bashcomp.script` cannot end with a path separator"));
+        let timeout = parse(&config)
+            .expect("parse timeout")
+            .timeout
+            .expect("timeout");
+        assert_eq!(timeout.after_ms, 30 * 60 * 1000);
+        assert_eq!(timeout.signal, "SIGINT");
+        assert_eq!(timeout.kill_after_ms, Some(1500));
+
+        fs::write(&config, "exec = \"x\"\ntimeout = \"10\"\n").expect("write toml");
+        let timeout = parse(&config)
+            .expect("parse bare timeout")
+            .timeout
+            .expect("timeout");
+        assert_eq!(timeout.after_ms, 10_000);
+        assert_eq!(timeout.signal, "SIGTERM");
+        assert_eq!(timeout.kill_after_ms, None);
+
+        fs::write(&config, "exec = \"x\"\ntimeout = \"0\"\n").expect("write toml");
+        assert_eq!(parse(&config).expect("parse zero timeout").timeout, None);
+
+        for (body, expected) in [
+            (
+                "timeout = \"soon\"",
+                "field `timeout` must be a duration like \"90s\", \"30m\", or \"2h\": `soon`",
+            ),
+            (
+                "timeout = \"1m\"\ntimeout_signal = \"NOPE\"",
+                "field `timeout_signal` must be a signal name or number: `NOPE`",
+            ),
+            (
+                "kill_after = \"5s\"",
+                "fields `timeout_signal` and `kill_after` require `timeout`",
+            ),
+        ] {
+            fs::write(&config, format!("exec = \"x\"\n{body}\n")).expect("write toml");
+            let err = parse(&config).expect_err("invalid timeout should fail");
+            assert!(format!("{err:#}").contains(expected), "{err:#}");
+        }
     }
 
     #[test]
-    fn rejects_bashcomp_script_trailing_platform_separator() {
+    fn parses_retry_table_and_rejects_invalid_values() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
-        let sep = std::path::MAIN_SEPARATOR;
-        let script = format!("completions{sep}");
+        let config = temp.path().join("sync.toml");
         fs::write(
             &config,
-            format!("exec = \"echo\"\n\n[bashcomp]\nscript = '{script}'\n"),
+            "exec = \"sync\"\n[retry]\nattempts = 4\nbackoff = \"fixed\"\ninitial_delay = \"250ms\"\non_exit_codes = [1, 75, 1]\n",
         )
         .expect("write toml");
-
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `bashcomp.script` cannot end with a path separator"));
+        let retry = parse(&config).expect("parse retry").retry.expect("retry");
+        assert_eq!(retry.attempts, 4);
+        assert_eq!(retry.backoff, RetryBackoff::Fixed);
+        assert_eq!(retry.initial_delay_ms, 250);
+        assert_eq!(retry.on_exit_codes, vec![1, 75]);
+
+        fs::write(&config, "exec = \"x\"\n[retry]\n").expect("write toml");
+        let retry = parse(&config)
+            .expect("parse default retry")
+            .retry
+            .expect("retry");
+        assert_eq!(retry.attempts, 3);
+        assert_eq!(retry.backoff, RetryBackoff::Exponential);
+        assert_eq!(retry.initial_delay_ms, 1000);
+        assert!(retry.on_exit_codes.is_empty());
+
+        fs::write(&config, "exec = \"x\"\n[retry]\nattempts = 1\n").expect("write toml");
+        assert_eq!(parse(&config).expect("parse single attempt").retry, None);
+
+        for (body, expected) in [
+            (
+                "attempts = 0",
+                "field `retry.attempts` must be at least 1: `0`",
+            ),
+            (
+                "backoff = \"linear\"",
+                "field `retry.backoff` must be `fixed` or `exponential`: `linear`",
+            ),
+            (
+                "initial_delay = \"soon\"",
+                "field `retry.initial_delay` must be a duration like \"500ms\" or \"1s\": `soon`",
+            ),
+            (
+                "on_exit_codes = [0]",
+                "field `retry.on_exit_codes` entries must be between 1 and 255: `0`",
+            ),
+            ("max_delay = \"1m\"", "unknown field `max_delay`"),
+        ] {
+            fs::write(&config, format!("exec = \"x\"\n[retry]\n{body}\n")).expect("write toml");
+            let err = parse(&config).expect_err("invalid retry should fail");
+            assert!(format!("{err:#}").contains(expected), "{err:#}");
+        }
     }
 
     #[test]
-    fn rejects_bashcomp_script_ending_in_dot_component() {
+    fn parses_concurrency_table_and_rejects_invalid_values() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+        let config = temp.path().join("migrate.toml");
         fs::write(
             &config,
-            r#"
-exec = "echo"
-
-[bashcomp]
-script = "completions/.."
-"#,
+            "exec = \"migrate\"\n[concurrency]\nmode = \"wait\"\nlock_key = \" db-prod \"\n",
         )
         .expect("write toml");
-
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `bashcomp.script` cannot end with `.` or `..` path components"));
+        let concurrency = parse(&config)
+            .expect("parse concurrency")
+            .concurrency
+            .expect("concurrency");
+        assert_eq!(concurrency.mode, ConcurrencyMode::Wait);
+        assert_eq!(concurrency.lock_key.as_deref(), Some("db-prod"));
+
+        fs::write(&config, "exec = \"x\"\n[concurrency]\n").expect("write toml");
+        let concurrency = parse(&config)
+            .expect("parse default concurrency")
+            .concurrency
+            .expect("concurrency");
+        assert_eq!(concurrency.mode, ConcurrencyMode::Exclusive);
+        assert_eq!(concurrency.lock_key, None);
+
+        for (body, expected) in [
+            (
+                "mode = \"queue\"",
+                "field `concurrency.mode` must be `exclusive`, `wait`, or `skip`: `queue`",
+            ),
+            (
+                "lock_key = \"../db\"",
+                "field `concurrency.lock_key` must be a valid alias-style name: `../db`",
+            ),
+            ("timeout = \"1m\"", "unknown field `timeout`"),
+        ] {
+            fs::write(&config, format!("exec = \"x\"\n[concurrency]\n{body}\n"))
+                .expect("write toml");
+            let err = parse(&config).expect_err("invalid concurrency should fail");
+            assert!(format!("{err:#}").contains(expected), "{err:#}");
+        }
     }
 
-    // ------------------------------------------------------------------
-    // bashcomp.rhai_script / rhai_function tests
-    // ------------------------------------------------------------------
-
     #[test]
-    fn bashcomp_rhai_script_alone_does_not_enable_rhai_completion() -> Result<()> {
-        let temp = TempDir::new()?;
-        let config = temp.path().join("bc.toml");
+    fn parses_audit_table_and_rejects_invalid_rules() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("psql.toml");
         fs::write(
             &config,
-            r#"
-exec = "echo"
-
-[bashcomp]
-rhai_script = "completions/custom.rhai"
-"#,
-        )?;
-
-        let manifest = parse(&config)?;
-        let bashcomp = manifest.bashcomp.expect("bashcomp config");
-        assert!(bashcomp.rhai_script.is_none());
-        assert!(bashcomp.rhai_function.is_none());
-        Ok(())
+            "exec = \"psql\"\n[audit]\nredact_options = [\"--password\"]\nredact_matching = [\"^postgres://\"]\n",
+        )
+        .expect("write toml");
+        let audit = parse(&config).expect("parse audit").audit.expect("audit");
+        assert!(audit.enabled);
+        assert_eq!(audit.redact_options, vec!["--password"]);
+        assert_eq!(audit.redact_matching, vec!["^postgres://"]);
+
+        fs::write(&config, "exec = \"psql\"\n[audit]\nenabled = false\n").expect("write toml");
+        let audit = parse(&config).expect("parse opt-out").audit.expect("audit");
+        assert!(!audit.enabled);
+
+        for (body, expected) in [
+            (
+                "redact_options = [\"--password=x\"]",
+                "field `audit.redact_options` entries must be option names",
+            ),
+            (
+                "redact_matching = [\"[\"]",
+                "field `audit.redact_matching` entry `[` is not a valid regex",
+            ),
+            ("path = \"/tmp/audit.jsonl\"", "unknown field `path`"),
+        ] {
+            fs::write(&config, format!("exec = \"psql\"\n[audit]\n{body}\n")).expect("write toml");
+            let err = parse(&config).expect_err("invalid audit should fail");
+            assert!(format!("{err:#}").contains(expected), "{err:#}");
+        }
     }
 
     #[test]
-    fn parses_bashcomp_rhai_script_and_function() -> Result<()> {
-        let temp = TempDir::new()?;
-        let config = temp.path().join("bc.toml");
+    fn parses_guard_table_and_rejects_invalid_rules() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("tf.toml");
         fs::write(
             &config,
             r#"
-exec = "echo"
+exec = "terraform"
 
-[bashcomp]
-rhai_script = "completions/custom.rhai"
-rhai_function = "my_completer"
+[guard]
+deny = ["^-auto-approve$"]
+confirm = ["^apply$"]
+non_tty = "allow"
 "#,
-        )?;
+        )
+        .expect("write toml");
 
-        let manifest = parse(&config)?;
-        let bashcomp = manifest.bashcomp.expect("bashcomp config");
-        assert_eq!(bashcomp.rhai_script, Some(temp.path().join("bc.rhai")));
-        assert_eq!(bashcomp.rhai_function, Some("my_completer".to_string()));
-        Ok(())
+        let guard = parse(&config).expect("parse guard").guard.expect("guard");
+        assert_eq!(guard.deny, vec!["^-auto-approve$"]);
+        assert!(guard.require.is_empty());
+        assert_eq!(guard.confirm, vec!["^apply$"]);
+        assert_eq!(guard.non_tty, GuardFallback::Allow);
+
+        fs::write(&config, "exec = \"x\"\n[guard]\n").expect("write toml");
+        assert_eq!(parse(&config).expect("parse empty guard").guard, None);
+
+        for (body, expected) in [
+            (
+                "[guard]\ndeny = [\"(\"]",
+                "field `guard.deny` entry `(` is not a valid regex",
+            ),
+            (
+                "[guard]\nrequire = [\" \"]",
+                "field `guard.require` entries cannot be blank",
+            ),
+            (
+                "[guard]\nconfirm = [\"x\"]\nnon_tty = \"ask\"",
+                "field `guard.non_tty` must be `refuse` or `allow`: `ask`",
+            ),
+            ("[guard]\nallow = [\"x\"]", "unknown field `allow`"),
+        ] {
+            fs::write(&config, format!("exec = \"x\"\n{body}\n")).expect("write toml");
+            let err = parse(&config).expect_err("invalid guard should fail");
+            assert!(format!("{err:#}").contains(expected), "{err:#}");
+        }
     }
 
     #[test]
-    fn bashcomp_rhai_script_blank_value_fails_validation() -> Result<()> {
-        let temp = TempDir::new()?;
-        let config = temp.path().join("bc.toml");
+    fn parses_exec_candidates_and_requires_table() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("editor.toml");
         fs::write(
             &config,
             r#"
-exec = "echo"
+exec = ["/opt/missing/nvim", "./bin/vim"]
 
-[bashcomp]
-rhai_script = "   "
+[requires]
+min_version = "0.9"
 "#,
-        )?;
+        )
+        .expect("write toml");
 
-        let err = parse(&config).expect_err("blank rhai_script should fail");
-        assert!(
-            err.to_string()
-                .contains("field `bashcomp.rhai_script` cannot be empty"),
-            "{err}"
+        let manifest = parse(&config).expect("parse exec candidates");
+        assert_eq!(manifest.exec, PathBuf::from("/opt/missing/nvim"));
+        assert_eq!(
+            manifest.exec_fallbacks,
+            vec![super::config_base_dir(&config).join("./bin/vim")]
         );
-        Ok(())
+        let requires = manifest.requires.expect("requires");
+        assert_eq!(requires.version_cmd, vec!["--version".to_string()]);
+        assert_eq!(requires.version_regex, r"(\d+(?:\.\d+)+)");
+        assert_eq!(requires.min_version, "0.9");
+
+        fs::write(&config, "exec = [\"/bin/true\"]\n").expect("write toml");
+        let manifest = parse(&config).expect("parse single candidate");
+        assert_eq!(manifest.exec, PathBuf::from("/bin/true"));
+        assert!(manifest.exec_fallbacks.is_empty());
+
+        for (body, expected) in [
+            ("exec = []", "field `exec` cannot be an empty list"),
+            (
+                "exec = [\"/bin/true\", \" \"]",
+                "field `exec` entries cannot be empty",
+            ),
+            (
+                "exec = \"x\"\n[requires]\nversion_cmd = [\"-V\"]",
+                "field `requires.min_version` is required",
+            ),
+            (
+                "exec = \"x\"\n[requires]\nmin_version = \"1.x\"",
+                "field `requires.min_version` must be a dotted version",
+            ),
+            (
+                "exec = \"x\"\n[requires]\nmin_version = \"1\"\nversion_regex = \"(\"",
+                "field `requires.version_regex` is not a valid regex",
+            ),
+        ] {
+            fs::write(&config, format!("{body}\n")).expect("write toml");
+            let err = parse(&config).expect_err("invalid exec or requires should fail");
+            assert!(format!("{err:#}").contains(expected), "{body}: {err:#}");
+        }
     }
 
     #[test]
-    fn bashcomp_rhai_function_blank_value_is_treated_as_none() -> Result<()> {
-        let temp = TempDir::new()?;
-        let config = temp.path().join("bc.toml");
+    fn parses_limits_table_and_reports_invalid_values() {
+        let temp = TempDir::new().expect("create tempdir");
+        let config = temp.path().join("limits.toml");
         fs::write(
             &config,
             r#"
-exec = "echo"
-
-[bashcomp]
-rhai_function = "   "
+exec = "make"
+
+[limits]
+nofile = 1024
+as = "2G"
+cpu = "unlimited"
+nice = 5
+ioprio = "idle"
+umask = "027"
 "#,
-        )?;
+        )
+        .expect("write toml");
 
-        let manifest = parse(&config)?;
-        let bashcomp = manifest.bashcomp.expect("bashcomp config");
-        assert!(bashcomp.rhai_function.is_none());
-        Ok(())
+        let manifest = parse(&config).expect("parse limits config");
+        let limits = manifest.limits.expect("limits");
+        assert_eq!(limits.nofile, Some(RlimitValue::Limited(1024)));
+        assert_eq!(limits.address_space, Some(RlimitValue::Limited(2 << 30)));
+        assert_eq!(limits.cpu, Some(RlimitValue::Unlimited));
+        assert_eq!(limits.core, None);
+        assert_eq!(limits.nice, Some(5));
+        assert_eq!(
+            limits.ioprio,
+            Some(IoPriority {
+                class: IoPriorityClass::Idle,
+                level: 0
+            })
+        );
+        assert_eq!(limits.umask, Some(0o027));
+
+        fs::write(&config, "exec = \"make\"\n[limits]\n").expect("write toml");
+        let manifest = parse(&config).expect("parse empty limits config");
+        assert_eq!(manifest.limits, None);
+
+        for (body, expected) in [
+            (
+                "nice = 40",
+                "field `limits.nice` must be between -20 and 19",
+            ),
+            ("ioprio = \"fast\"", "field `limits.ioprio` must be"),
+            (
+                "umask = \"999\"",
+                "field `limits.umask` must be an octal mode",
+            ),
+            (
+                "umask = 22",
+                "field `limits.umask` must be an octal string such as \"022\", not an integer",
+            ),
+            (
+                "nofile = -1",
+                "field `limits.nofile` must be a non-negative integer",
+            ),
+            ("as = \"lots\"", "field `limits.as` must be a byte count"),
+        ] {
+            fs::write(&config, format!("exec = \"make\"\n[limits]\n{body}\n")).expect("write toml");
+            let err = parse(&config).expect_err("invalid limits should fail");
+            assert!(err.to_string().contains(expected), "{body}: {err}");
+        }
     }
 
     #[test]
-    fn rejects_bashcomp_rhai_script_containing_nul_bytes() {
+    fn parses_subcommand_overlays_with_normalized_keys() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+        let config = temp.path().join("kubectl.toml");
         fs::write(
             &config,
-            "exec = \"echo\"\n\n[bashcomp]\nrhai_script = \"comp\\u0000lete.rhai\"\n",
+            r#"
+exec = "kubectl"
+
+[subcommands.delete]
+args = ["--dry-run=client"]
+
+[subcommands."  rollout   status "]
+env_remove = [" KUBE_EDITOR "]
+
+[subcommands.rollout.env]
+KUBECTL_EXTERNAL_DIFF = "diff -u"
+
+[subcommands.logs.path]
+prepend_one = ["/opt/logs/bin"]
+"#,
         )
         .expect("write toml");
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `bashcomp.rhai_script` cannot contain NUL bytes"));
+        let manifest = parse(&config).expect("parse subcommand config");
+        assert_eq!(
+            manifest.subcommands.keys().collect::<Vec<_>>(),
+            vec!["delete", "logs", "rollout", "rollout status"]
+        );
+        assert_eq!(
+            manifest.subcommands["delete"].args,
+            vec!["--dry-run=client"]
+        );
+        assert_eq!(
+            manifest.subcommands["rollout status"].env_remove,
+            vec!["KUBE_EDITOR"]
+        );
+        assert_eq!(
+            manifest.subcommands["logs"]
+                .path
+                .as_ref()
+                .expect("logs path")
+                .prepend_one,
+            vec!["/opt/logs/bin"]
+        );
+
+        for (body, expected) in [
+            (
+                "[subcommands.\"  \"]\nargs = [\"x\"]",
+                "field `subcommands` cannot contain empty keys",
+            ),
+            (
+                "[subcommands.\"a b\"]\n[subcommands.\"a  b\"]",
+                "field `subcommands` contains duplicate keys after trimming: `a b`",
+            ),
+            (
+                "[subcommands.run]\nargs = [\"{1}\"]",
+                "field `subcommands.run.args` cannot contain runtime-arg slot `{1}`",
+            ),
+            (
+                "[subcommands.run.env]\n\"A=B\" = \"x\"",
+                "field `subcommands.run.env` keys cannot contain `=`",
+            ),
+            (
+                "[subcommands.run]\nexec = \"other\"",
+                "unknown field `exec`",
+            ),
+        ] {
+            fs::write(&config, format!("exec = \"kubectl\"\n{body}\n")).expect("write toml");
+            let err = parse(&config).expect_err("invalid subcommands should fail");
+            assert!(format!("{err:#}").contains(expected), "{body}: {err:#}");
+        }
     }
 
     #[test]
-    fn rejects_bashcomp_rhai_script_dot_value() {
+    fn extends_replaces_subcommand_overlays_per_key() -> Result<()> {
+        let _guard = ENV_LOCK.lock().expect("lock env");
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
-        fs::write(
-            &config,
+        env::set_var("CHOPPER_CONFIG_DIR", temp.path());
+        write_alias(
+            temp.path(),
+            "base",
             r#"
-exec = "echo"
+exec = "kubectl"
 
-[bashcomp]
-rhai_script = "."
+[subcommands.delete]
+args = ["--dry-run=client"]
+
+[subcommands.logs]
+args = ["--tail=200"]
 "#,
-        )
-        .expect("write toml");
+        );
+        let child = write_alias(
+            temp.path(),
+            "child",
+            "extends = \"base\"\n[subcommands.logs]\nargs = [\"--tail=20\"]\n",
+        );
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `bashcomp.rhai_script` cannot be `.` or `..`"));
+        let manifest = parse(&child);
+        env::remove_var("CHOPPER_CONFIG_DIR");
+        let manifest = manifest?;
+        assert_eq!(
+            manifest.subcommands["delete"].args,
+            vec!["--dry-run=client"]
+        );
+        assert_eq!(manifest.subcommands["logs"].args, vec!["--tail=20"]);
+        Ok(())
     }
 
     #[test]
-    fn rejects_bashcomp_rhai_script_trailing_separator() {
+    fn parses_profiles_and_validates_default_profile() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+        let config = temp.path().join("kube.toml");
         fs::write(
             &config,
             r#"
-exec = "echo"
+exec = "kubectl"
+default_profile = " dev "
 
-[bashcomp]
-rhai_script = "completions/"
+[profile.dev]
+args = ["--context", "dev"]
+
+[profile.prod]
+exec = "/opt/kube/prod/kubectl"
+env_remove = [" KUBE_EDITOR "]
+
+[profile.prod.env]
+STAGE = "prod"
+
+[profile.prod.journal]
+namespace = " prod-ops "
 "#,
         )
         .expect("write toml");
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `bashcomp.rhai_script` cannot end with a path separator"));
+        let manifest = parse(&config).expect("parse profile config");
+        assert_eq!(manifest.default_profile.as_deref(), Some("dev"));
+        assert_eq!(
+            manifest.profiles["dev"].args,
+            Some(vec!["--context".to_string(), "dev".to_string()])
+        );
+        let prod = &manifest.profiles["prod"];
+        assert_eq!(prod.exec, Some(PathBuf::from("/opt/kube/prod/kubectl")));
+        assert_eq!(prod.env_remove, vec!["KUBE_EDITOR"]);
+        assert_eq!(
+            prod.journal.as_ref().expect("journal").namespace,
+            "prod-ops"
+        );
+
+        for (body, expected) in [
+            (
+                "default_profile = \"qa\"",
+                "field `default_profile` references undefined profile `qa`",
+            ),
+            (
+                "[profile.\"two words\"]",
+                "field `profile` names cannot contain whitespace",
+            ),
+            (
+                "[profile.prod]\nexec = \"bin/\"",
+                "field `profile.prod.exec` cannot end with a path separator",
+            ),
+            (
+                "[profile.prod.journal]\nnamespace = \" \"",
+                "field `profile.prod.journal.namespace` cannot be empty",
+            ),
+            ("[profile.prod]\ncwd = \"/tmp\"", "unknown field `cwd`"),
+        ] {
+            fs::write(&config, format!("exec = \"kubectl\"\n{body}\n")).expect("write toml");
+            let err = parse(&config).expect_err("invalid profile should fail");
+            assert!(format!("{err:#}").contains(expected), "{body}: {err:#}");
+        }
     }
 
     #[test]
-    fn rejects_bashcomp_rhai_function_containing_nul_bytes() {
+    fn resolves_env_files_against_config_directory() {
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
+        let config = temp.path().join("svc.toml");
         fs::write(
             &config,
-            "exec = \"echo\"\n\n[bashcomp]\nrhai_script = \"comp.rhai\"\nrhai_function = \"bad\\u0000func\"\n",
+            "exec = \"svc\"\nenv_file = [\" .env \", \"/etc/svc/prod.env\", \"${HOME}/svc.env\"]\n",
         )
         .expect("write toml");
 
-        let err = parse(&config).expect_err("expected parse failure");
-        assert!(err
-            .to_string()
-            .contains("field `bashcomp.rhai_function` cannot contain NUL bytes"));
+        let manifest = parse(&config).expect("parse env_file config");
+        let base = fs::canonicalize(temp.path()).expect("canonical tempdir");
+        assert_eq!(
+            manifest.env_file,
+            vec![
+                base.join(".env"),
+                PathBuf::from("/etc/svc/prod.env"),
+                PathBuf::from("${HOME}/svc.env"),
+            ]
+        );
+
+        fs::write(&config, "exec = \"svc\"\nenv_file = [\"envs/\"]\n").expect("write toml");
+        let err = parse(&config).expect_err("directory-shaped env_file should fail");
+        assert!(
+            err.to_string()
+                .contains("field `env_file` entries cannot end with a path separator"),
+            "{err}"
+        );
     }
 
     #[test]
-    fn bashcomp_rhai_function_without_legacy_script_uses_deterministic_shared_script() {
+    fn parses_env_secret_descriptors_and_merges_them_with_extends() -> Result<()> {
+        let _guard = ENV_LOCK.lock().expect("lock env");
         let temp = TempDir::new().expect("create tempdir");
-        let config = temp.path().join("bad.toml");
-        fs::write(
-            &config,
+        env::set_var("CHOPPER_CONFIG_DIR", temp.path());
+        write_alias(
+            temp.path(),
+            "gh-base",
             r#"
-exec = "echo"
+exec = "gh"
 
-[bashcomp]
-rhai_function = "complete"
+[env]
+GH_HOST = "github.com"
+
+[env_secret]
+GH_TOKEN = { file = "token" }
+NPM_TOKEN = { keyring = "npm" }
 "#,
-        )
-        .expect("write toml");
+        );
+        let child = write_alias(
+            temp.path(),
+            "gh-work",
+            r#"
+extends = "gh-base"
+env_remove = ["NPM_TOKEN"]
 
-        let manifest = parse(&config).expect("expected parse success");
-        let bashcomp = manifest.bashcomp.expect("bashcomp config");
-        assert_eq!(bashcomp.rhai_script, Some(temp.path().join("bad.rhai")));
-        assert_eq!(bashcomp.rhai_function, Some("complete".to_string()));
+[env_secret]
+" GH_HOST " = { command = ["pass", "show", "gh-host"] }
+HOME_TOKEN = { file = "~/.secrets/token" }
+"#,
+        );
+
+        let manifest = parse(&child);
+        env::remove_var("CHOPPER_CONFIG_DIR");
+        let manifest = manifest?;
+        let base = fs::canonicalize(temp.path())?;
+        assert!(manifest.env.is_empty(), "{:?}", manifest.env);
+        assert_eq!(
+            manifest.env_secret,
+            BTreeMap::from([
+                (
+                    "GH_HOST".to_string(),
+                    SecretSource::Command(vec![
+                        "pass".to_string(),
+                        "show".to_string(),
+                        "gh-host".to_string(),
+                    ])
+                ),
+                (
+                    "GH_TOKEN".to_string(),
+                    SecretSource::File(base.join("gh-base").join("token"))
+                ),
+                (
+                    "HOME_TOKEN".to_string(),
+                    SecretSource::File(PathBuf::from("~/.secrets/token"))
+                ),
+            ])
+        );
+        assert_eq!(manifest.env_remove, vec!["NPM_TOKEN"]);
+
+        let config = temp.path().join("bad.toml");
+        for (content, expected) in [
+            (
+                "exec = \"x\"\n[env_secret]\nT = { file = \"a\", keyring = \"b\" }\n",
+                "field `env_secret.T` must set exactly one of `file`, `command`, or `keyring`",
+            ),
+            (
+                "exec = \"x\"\n[env_secret]\nT = { command = [] }\n",
+                "field `env_secret.T.command` cannot be empty",
+            ),
+            (
+                "exec = \"x\"\n[env_secret]\nT = { keyring = \"user:\" }\n",
+                "field `env_secret.T.keyring` must be `type:description`",
+            ),
+            (
+                "exec = \"x\"\n[env]\nT = \"plain\"\n[env_secret]\nT = { file = \"a\" }\n",
+                "field `env_secret` key `T` is also set in field `env`",
+            ),
+        ] {
+            fs::write(&config, content)?;
+            let err = parse(&config).expect_err("invalid env_secret should fail");
+            assert!(err.to_string().contains(expected), "{err}");
+        }
+        Ok(())
     }
 }
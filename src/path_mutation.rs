@@ -32,6 +32,16 @@ impl PathMutationConfig {
             && self.prepend_one.is_empty()
     }
 
+    /// Appends every operation list from `other` after the existing entries.
+    pub fn extend(&mut self, other: PathMutationConfig) {
+        self.remove_all.extend(other.remove_all);
+        self.remove_one.extend(other.remove_one);
+        self.append_all.extend(other.append_all);
+        self.append_one.extend(other.append_one);
+        self.prepend_all.extend(other.prepend_all);
+        self.prepend_one.extend(other.prepend_one);
+    }
+
     pub fn validate(&self, field_prefix: &str) -> Result<()> {
         validate_values(&self.remove_all, field_prefix, "remove_all")?;
         validate_values(&self.remove_one, field_prefix, "remove_one")?;
@@ -43,6 +53,138 @@ impl PathMutationConfig {
     }
 }
 
+/// One `[pathlists.<VAR>]` table: the `[path]` operations applied to another
+/// separator-delimited variable, followed by optional cleanup passes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PathListConfig {
+    #[serde(default)]
+    pub remove_all: Vec<String>,
+    #[serde(default)]
+    pub remove_one: Vec<String>,
+    #[serde(default)]
+    pub append_all: Vec<String>,
+    #[serde(default)]
+    pub append_one: Vec<String>,
+    #[serde(default)]
+    pub prepend_all: Vec<String>,
+    #[serde(default)]
+    pub prepend_one: Vec<String>,
+    /// Defaults to `:`.
+    #[serde(default)]
+    pub separator: Option<String>,
+    #[serde(default)]
+    pub dedupe: bool,
+    #[serde(default)]
+    pub prune_missing: bool,
+}
+
+impl PathListConfig {
+    pub fn is_empty(&self) -> bool {
+        self.operations().is_empty()
+            && self.separator.is_none()
+            && !self.dedupe
+            && !self.prune_missing
+    }
+
+    pub fn separator(&self) -> &str {
+        self.separator.as_deref().unwrap_or(":")
+    }
+
+    pub fn operations(&self) -> PathMutationConfig {
+        PathMutationConfig {
+            remove_all: self.remove_all.clone(),
+            remove_one: self.remove_one.clone(),
+            append_all: self.append_all.clone(),
+            append_one: self.append_one.clone(),
+            prepend_all: self.prepend_all.clone(),
+            prepend_one: self.prepend_one.clone(),
+        }
+    }
+
+    pub fn operation_lists(&self) -> [(&'static str, &[String]); 6] {
+        [
+            ("remove_all", &self.remove_all),
+            ("remove_one", &self.remove_one),
+            ("append_all", &self.append_all),
+            ("append_one", &self.append_one),
+            ("prepend_all", &self.prepend_all),
+            ("prepend_one", &self.prepend_one),
+        ]
+    }
+
+    pub fn operations_mut(&mut self) -> [(&'static str, &mut Vec<String>); 6] {
+        [
+            ("remove_all", &mut self.remove_all),
+            ("remove_one", &mut self.remove_one),
+            ("append_all", &mut self.append_all),
+            ("append_one", &mut self.append_one),
+            ("prepend_all", &mut self.prepend_all),
+            ("prepend_one", &mut self.prepend_one),
+        ]
+    }
+
+    /// Layers `other` over this list: operations accumulate, a set
+    /// `separator` wins, and either side can turn the cleanup passes on.
+    pub fn extend(&mut self, other: PathListConfig) {
+        self.remove_all.extend(other.remove_all);
+        self.remove_one.extend(other.remove_one);
+        self.append_all.extend(other.append_all);
+        self.append_one.extend(other.append_one);
+        self.prepend_all.extend(other.prepend_all);
+        self.prepend_one.extend(other.prepend_one);
+        if other.separator.is_some() {
+            self.separator = other.separator;
+        }
+        self.dedupe |= other.dedupe;
+        self.prune_missing |= other.prune_missing;
+    }
+
+    pub fn validate(&self, field_prefix: &str) -> Result<()> {
+        self.operations().validate(field_prefix)?;
+        if let Some(separator) = &self.separator {
+            if separator.is_empty() {
+                return Err(anyhow!("field `{field_prefix}.separator` cannot be empty"));
+            }
+            if separator.contains('\0') {
+                return Err(anyhow!(
+                    "field `{field_prefix}.separator` cannot contain NUL bytes"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One component of a mutated list and the operation that placed it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathComponent {
+    pub value: String,
+    /// `None` for components carried over from the value being mutated.
+    pub step: Option<PathStep>,
+}
+
+impl PathComponent {
+    fn carried(value: String) -> Self {
+        Self { value, step: None }
+    }
+}
+
+impl AsRef<str> for PathComponent {
+    fn as_ref(&self) -> &str {
+        &self.value
+    }
+}
+
+/// A single operation entry, numbered (from 1) in the order operations run:
+/// removals first, then appends, then prepends.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathStep {
+    pub number: usize,
+    /// The entry's field, e.g. `path.prepend_one[0]`.
+    pub field: String,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SinglePathOpKind {
     RemoveAll,
@@ -69,10 +211,65 @@ struct MatcherState {
     regexes: HashMap<String, Regex>,
 }
 
-pub fn apply_runtime_path(base_path: Option<&str>, config: &PathMutationConfig) -> Result<String> {
-    let components = split_runtime_path(base_path);
+/// Returns the mutated PATH and each of its components with the step that
+/// placed it.
+pub fn apply_runtime_path(
+    base_path: Option<&str>,
+    config: &PathMutationConfig,
+) -> Result<(String, Vec<PathComponent>)> {
+    let components = split_runtime_path(base_path)
+        .into_iter()
+        .map(PathComponent::carried)
+        .collect();
     let components = apply_components(components, config, "path")?;
-    join_runtime_path(&components)
+    let values = components
+        .iter()
+        .map(|component| component.value.clone())
+        .collect::<Vec<_>>();
+    Ok((join_runtime_path(&values)?, components))
+}
+
+/// Applies a `[pathlists.<VAR>]` table to `base`. Returns `None` when the
+/// resulting list is empty, since an empty component in most search-path
+/// variables means the current directory.
+pub fn apply_path_list(
+    base: Option<&str>,
+    config: &PathListConfig,
+    field_prefix: &str,
+) -> Result<Option<String>> {
+    let separator = config.separator();
+    let components = match base {
+        Some(base) if !base.is_empty() => base
+            .split(separator)
+            .map(|component| PathComponent::carried(component.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let mut components = apply_components(components, &config.operations(), field_prefix)?
+        .into_iter()
+        .map(|component| component.value)
+        .collect::<Vec<_>>();
+    if config.prune_missing {
+        components
+            .retain(|component| !component.is_empty() && std::path::Path::new(component).exists());
+    }
+    if config.dedupe {
+        let mut state = MatcherState::default();
+        let mut kept: Vec<String> = Vec::with_capacity(components.len());
+        for component in components {
+            let seen = kept.iter().any(|existing| {
+                *existing == component || components_equivalent(existing, &component, &mut state)
+            });
+            if !seen {
+                kept.push(component);
+            }
+        }
+        components = kept;
+    }
+    if components.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(components.join(separator)))
 }
 
 pub fn apply_single_colon_list_op(
@@ -132,13 +329,22 @@ pub fn join_colon_list(components: &[String]) -> String {
 }
 
 fn apply_components(
-    mut components: Vec<String>,
+    mut components: Vec<PathComponent>,
     config: &PathMutationConfig,
     field_prefix: &str,
-) -> Result<Vec<String>> {
+) -> Result<Vec<PathComponent>> {
     let mut state = MatcherState::default();
+    let mut steps = 0;
+    let mut step = |op: &str, index: usize| {
+        steps += 1;
+        PathStep {
+            number: steps,
+            field: format!("{field_prefix}.{op}[{index}]"),
+        }
+    };
 
-    for pattern in &config.remove_all {
+    for (index, pattern) in config.remove_all.iter().enumerate() {
+        step("remove_all", index);
         remove_all_matching(
             &mut components,
             pattern,
@@ -146,7 +352,8 @@ fn apply_components(
             &mut state,
         )?;
     }
-    for pattern in &config.remove_one {
+    for (index, pattern) in config.remove_one.iter().enumerate() {
+        step("remove_one", index);
         remove_first_matching(
             &mut components,
             pattern,
@@ -154,21 +361,39 @@ fn apply_components(
             &mut state,
         )?;
     }
-    for path in &config.append_all {
+    for (index, path) in config.append_all.iter().enumerate() {
         remove_all_equivalent(&mut components, path, &mut state);
-        components.push(path.clone());
+        components.push(PathComponent {
+            value: path.clone(),
+            step: Some(step("append_all", index)),
+        });
     }
-    for path in &config.append_one {
+    for (index, path) in config.append_one.iter().enumerate() {
         remove_first_equivalent(&mut components, path, &mut state);
-        components.push(path.clone());
+        components.push(PathComponent {
+            value: path.clone(),
+            step: Some(step("append_one", index)),
+        });
     }
-    for path in &config.prepend_all {
+    for (index, path) in config.prepend_all.iter().enumerate() {
         remove_all_equivalent(&mut components, path, &mut state);
-        components.insert(0, path.clone());
+        components.insert(
+            0,
+            PathComponent {
+                value: path.clone(),
+                step: Some(step("prepend_all", index)),
+            },
+        );
     }
-    for path in &config.prepend_one {
+    for (index, path) in config.prepend_one.iter().enumerate() {
         remove_first_equivalent(&mut components, path, &mut state);
-        components.insert(0, path.clone());
+        components.insert(
+            0,
+            PathComponent {
+                value: path.clone(),
+                step: Some(step("prepend_one", index)),
+            },
+        );
     }
 
     Ok(components)
@@ -206,8 +431,8 @@ fn join_runtime_path(components: &[String]) -> Result<String> {
         .map_err(|_| anyhow!("effective PATH cannot be represented as UTF-8"))
 }
 
-fn remove_first_matching(
-    components: &mut Vec<String>,
+fn remove_first_matching<C: AsRef<str>>(
+    components: &mut Vec<C>,
     pattern: &str,
     context: &str,
     state: &mut MatcherState,
@@ -215,21 +440,21 @@ fn remove_first_matching(
     let regex = compile_regex(pattern, context, state)?;
     if let Some(index) = components
         .iter()
-        .position(|component| regex.is_match(component))
+        .position(|component| regex.is_match(component.as_ref()))
     {
         components.remove(index);
     }
     Ok(())
 }
 
-fn remove_all_matching(
-    components: &mut Vec<String>,
+fn remove_all_matching<C: AsRef<str>>(
+    components: &mut Vec<C>,
     pattern: &str,
     context: &str,
     state: &mut MatcherState,
 ) -> Result<()> {
     let regex = compile_regex(pattern, context, state)?;
-    components.retain(|component| !regex.is_match(component));
+    components.retain(|component| !regex.is_match(component.as_ref()));
     Ok(())
 }
 
@@ -249,17 +474,25 @@ fn compile_regex<'a>(
         .expect("regex cache should contain compiled pattern"))
 }
 
-fn remove_first_equivalent(components: &mut Vec<String>, path: &str, state: &mut MatcherState) {
+fn remove_first_equivalent<C: AsRef<str>>(
+    components: &mut Vec<C>,
+    path: &str,
+    state: &mut MatcherState,
+) {
     if let Some(index) = components
         .iter()
-        .position(|component| components_equivalent(component, path, state))
+        .position(|component| components_equivalent(component.as_ref(), path, state))
     {
         components.remove(index);
     }
 }
 
-fn remove_all_equivalent(components: &mut Vec<String>, path: &str, state: &mut MatcherState) {
-    components.retain(|component| !components_equivalent(component, path, state));
+fn remove_all_equivalent<C: AsRef<str>>(
+    components: &mut Vec<C>,
+    path: &str,
+    state: &mut MatcherState,
+) {
+    components.retain(|component| !components_equivalent(component.as_ref(), path, state));
 }
 
 fn components_equivalent(left: &str, right: &str, state: &mut MatcherState) -> bool {
@@ -306,13 +539,26 @@ fn path_identity(path: &std::path::Path) -> Option<PathIdentity> {
 #[cfg(test)]
 mod tests {
     use super::{
-        apply_runtime_path, apply_single_colon_list_op, join_colon_list, split_colon_list,
-        PathMutationConfig, SinglePathOpKind,
+        apply_path_list, apply_runtime_path, apply_single_colon_list_op, join_colon_list,
+        split_colon_list, PathComponent, PathListConfig, PathMutationConfig, PathStep,
+        SinglePathOpKind,
     };
     use anyhow::Result;
     use std::fs;
     use tempfile::TempDir;
 
+    fn apply_components(
+        components: Vec<String>,
+        config: &PathMutationConfig,
+        field_prefix: &str,
+    ) -> Result<Vec<String>> {
+        let components = components.into_iter().map(PathComponent::carried).collect();
+        Ok(super::apply_components(components, config, field_prefix)?
+            .into_iter()
+            .map(|component| component.value)
+            .collect())
+    }
+
     #[test]
     fn split_and_join_colon_lists_round_trip_empty_components() {
         let components = split_colon_list(":/usr/bin::/bin:");
@@ -357,7 +603,7 @@ mod tests {
             format!("{}:{}", keep_target.display(), append_target.display())
         );
 
-        let out = super::apply_components(
+        let out = apply_components(
             vec![
                 remove_target.display().to_string(),
                 keep_target.display().to_string(),
@@ -389,7 +635,7 @@ mod tests {
             append_one: vec![real.display().to_string()],
             ..PathMutationConfig::default()
         };
-        let out = super::apply_components(
+        let out = apply_components(
             vec![
                 alias.display().to_string(),
                 alias.display().to_string(),
@@ -421,7 +667,7 @@ mod tests {
             append_all: vec![real.display().to_string()],
             ..PathMutationConfig::default()
         };
-        let out = super::apply_components(
+        let out = apply_components(
             vec![
                 alias.display().to_string(),
                 "/bin".to_string(),
@@ -440,8 +686,7 @@ mod tests {
             append_all: vec!["/definitely/missing".to_string()],
             ..PathMutationConfig::default()
         };
-        let out =
-            super::apply_components(vec!["/definitely/missing".to_string()], &config, "path")?;
+        let out = apply_components(vec!["/definitely/missing".to_string()], &config, "path")?;
         assert_eq!(
             out,
             vec![
@@ -486,8 +731,79 @@ mod tests {
             prepend_one: vec!["/custom/bin".to_string()],
             ..PathMutationConfig::default()
         };
-        let out = apply_runtime_path(None, &config)?;
+        let (out, _) = apply_runtime_path(None, &config)?;
         assert_eq!(out, "/custom/bin");
         Ok(())
     }
+
+    #[test]
+    fn path_lists_use_their_separator_and_cleanup_passes() -> Result<()> {
+        let temp = TempDir::new()?;
+        let real = temp.path().join("real");
+        fs::create_dir(&real)?;
+        let link = temp.path().join("link");
+        std::os::unix::fs::symlink(&real, &link)?;
+        let (real, link) = (real.display().to_string(), link.display().to_string());
+
+        let config = PathListConfig {
+            remove_all: vec!["^/old".to_string()],
+            append_one: vec!["/srv/py".to_string()],
+            separator: Some(";".to_string()),
+            ..PathListConfig::default()
+        };
+        let out = apply_path_list(Some("/old/a;/lib;/lib"), &config, "pathlists.PYTHONPATH")?;
+        assert_eq!(out.as_deref(), Some("/lib;/lib;/srv/py"));
+
+        let config = PathListConfig {
+            prepend_one: vec![real.clone()],
+            dedupe: true,
+            prune_missing: true,
+            ..PathListConfig::default()
+        };
+        let base = format!("{link}::/definitely/missing:{real}");
+        let out = apply_path_list(Some(&base), &config, "pathlists.LD_LIBRARY_PATH")?;
+        assert_eq!(out, Some(real.clone()));
+
+        let config = PathListConfig {
+            prune_missing: true,
+            ..PathListConfig::default()
+        };
+        assert_eq!(
+            apply_path_list(Some("/definitely/missing"), &config, "x")?,
+            None
+        );
+        assert_eq!(apply_path_list(None, &config, "x")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn traced_path_numbers_steps_in_application_order() -> Result<()> {
+        let config = PathMutationConfig {
+            remove_all: vec!["^/old$".to_string()],
+            append_one: vec!["/opt/tail".to_string()],
+            prepend_one: vec!["/opt/a".to_string(), "/opt/b".to_string()],
+            ..PathMutationConfig::default()
+        };
+        let (path, components) = apply_runtime_path(Some("/old:/usr/bin"), &config)?;
+        assert_eq!(path, "/opt/b:/opt/a:/usr/bin:/opt/tail");
+        let step = |number: usize, field: &str| {
+            Some(PathStep {
+                number,
+                field: field.to_string(),
+            })
+        };
+        assert_eq!(
+            components
+                .into_iter()
+                .map(|component| component.step)
+                .collect::<Vec<_>>(),
+            vec![
+                step(4, "path.prepend_one[1]"),
+                step(3, "path.prepend_one[0]"),
+                None,
+                step(2, "path.append_one[0]"),
+            ]
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,173 @@
+use crate::manifest::{IoPriority, IoPriorityClass, LimitsConfig, RlimitValue};
+use nix::libc;
+use nix::sys::resource::{setrlimit, Resource, RLIM_INFINITY};
+use nix::sys::stat::{umask, Mode};
+use std::io;
+
+pub(crate) const NICE_RANGE: std::ops::RangeInclusive<i64> = -20..=19;
+pub(crate) const MAX_IOPRIO_LEVEL: u8 = 7;
+pub(crate) const MAX_UMASK: u32 = 0o777;
+
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+const IOPRIO_CLASS_SHIFT: u32 = 13;
+const DEFAULT_IOPRIO_LEVEL: u8 = 4;
+
+/// Parses a byte count with an optional binary `K`/`M`/`G`/`T` suffix
+/// (case-insensitive, optional trailing `B` or `iB`).
+pub(crate) fn parse_byte_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let upper = value.to_ascii_uppercase();
+    let digits_end = upper
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(upper.len());
+    if digits_end == 0 {
+        return None;
+    }
+    let number: u64 = upper[..digits_end].parse().ok()?;
+    let shift = match upper[digits_end..].trim_start() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 10,
+        "M" | "MB" | "MIB" => 20,
+        "G" | "GB" | "GIB" => 30,
+        "T" | "TB" | "TIB" => 40,
+        _ => return None,
+    };
+    number.checked_mul(1u64 << shift)
+}
+
+/// Parses `realtime[:N]`, `best-effort[:N]`, or `idle`.
+pub(crate) fn parse_ioprio(value: &str) -> Option<IoPriority> {
+    let value = value.trim();
+    let (class, level) = match value.split_once(':') {
+        Some((class, level)) => (class.trim(), Some(level.trim())),
+        None => (value, None),
+    };
+    let class = match class.to_ascii_lowercase().as_str() {
+        "realtime" | "rt" => IoPriorityClass::Realtime,
+        "best-effort" | "be" => IoPriorityClass::BestEffort,
+        "idle" => IoPriorityClass::Idle,
+        _ => return None,
+    };
+    let level = match (class, level) {
+        (IoPriorityClass::Idle, None) => 0,
+        (IoPriorityClass::Idle, Some(_)) => return None,
+        (_, None) => DEFAULT_IOPRIO_LEVEL,
+        (_, Some(level)) => level.parse::<u8>().ok()?,
+    };
+    if level > MAX_IOPRIO_LEVEL {
+        return None;
+    }
+    Some(IoPriority { class, level })
+}
+
+/// Parses an octal mode such as `022`, `0022`, or `0o022`.
+pub(crate) fn parse_umask(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let digits = value.strip_prefix("0o").unwrap_or(value);
+    if digits.is_empty() {
+        return None;
+    }
+    let mode = u32::from_str_radix(digits, 8).ok()?;
+    (mode <= MAX_UMASK).then_some(mode)
+}
+
+/// Applies `limits` to the calling process. Runs in the forked child before
+/// exec, so it only makes async-signal-safe system calls and never allocates.
+pub(crate) fn apply(limits: &LimitsConfig) -> io::Result<()> {
+    for (resource, value) in [
+        (Resource::RLIMIT_NOFILE, limits.nofile),
+        (Resource::RLIMIT_AS, limits.address_space),
+        (Resource::RLIMIT_CPU, limits.cpu),
+        (Resource::RLIMIT_CORE, limits.core),
+        (Resource::RLIMIT_NPROC, limits.nproc),
+    ] {
+        let Some(value) = value else {
+            continue;
+        };
+        let raw = match value {
+            RlimitValue::Limited(limit) => limit as libc::rlim_t,
+            RlimitValue::Unlimited => RLIM_INFINITY,
+        };
+        setrlimit(resource, raw, raw).map_err(io::Error::from)?;
+    }
+
+    if let Some(nice) = limits.nice {
+        // SAFETY: setpriority has no memory-safety preconditions.
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    if let Some(ioprio) = limits.ioprio {
+        let class = match ioprio.class {
+            IoPriorityClass::Realtime => 1,
+            IoPriorityClass::BestEffort => 2,
+            IoPriorityClass::Idle => 3,
+        };
+        let value = (class << IOPRIO_CLASS_SHIFT) | libc::c_int::from(ioprio.level);
+        // SAFETY: ioprio_set takes plain integers and touches no user memory.
+        if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, value) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    if let Some(mask) = limits.umask {
+        umask(Mode::from_bits_truncate(mask as libc::mode_t));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_byte_size, parse_ioprio, parse_umask};
+    use crate::manifest::{IoPriority, IoPriorityClass};
+
+    #[test]
+    fn parses_byte_sizes_with_binary_suffixes() {
+        assert_eq!(parse_byte_size("4096"), Some(4096));
+        assert_eq!(parse_byte_size("2G"), Some(2 << 30));
+        assert_eq!(parse_byte_size("512 MiB"), Some(512 << 20));
+        assert_eq!(parse_byte_size("1kb"), Some(1024));
+        for invalid in ["", "G", "1.5G", "-1", "1P", "99999999999T"] {
+            assert_eq!(parse_byte_size(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parses_ioprio_classes_and_levels() {
+        assert_eq!(
+            parse_ioprio("best-effort:7"),
+            Some(IoPriority {
+                class: IoPriorityClass::BestEffort,
+                level: 7
+            })
+        );
+        assert_eq!(
+            parse_ioprio("realtime"),
+            Some(IoPriority {
+                class: IoPriorityClass::Realtime,
+                level: 4
+            })
+        );
+        assert_eq!(
+            parse_ioprio("idle"),
+            Some(IoPriority {
+                class: IoPriorityClass::Idle,
+                level: 0
+            })
+        );
+        for invalid in ["", "idle:3", "best-effort:8", "fast", "be:x"] {
+            assert_eq!(parse_ioprio(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parses_octal_umask_values() {
+        assert_eq!(parse_umask("022"), Some(0o022));
+        assert_eq!(parse_umask("0o077"), Some(0o077));
+        assert_eq!(parse_umask("0777"), Some(0o777));
+        for invalid in ["", "8", "1000", "0o", "abc"] {
+            assert_eq!(parse_umask(invalid), None, "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::path_mutation::PathComponent;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Where one element of a built invocation came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum Origin {
+    /// chopper's own environment, passed through unchanged.
+    Inherited,
+    /// A field of the alias config, e.g. `args[1]`, `env.FOO`, or
+    /// `subcommands."get pods".env_remove`.
+    Config { field: String },
+    /// A line of an `env_file`.
+    EnvFile { path: PathBuf, line: usize },
+    /// The runtime argument at `position` (1-based, as in `{N}` slots).
+    Runtime { position: usize },
+    /// A field of the reconcile patch, e.g. `append_args[0]`.
+    Reconcile { field: String },
+}
+
+impl Origin {
+    pub(crate) fn config(field: impl Into<String>) -> Self {
+        Self::Config {
+            field: field.into(),
+        }
+    }
+
+    pub(crate) fn reconcile(field: impl Into<String>) -> Self {
+        Self::Reconcile {
+            field: field.into(),
+        }
+    }
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Inherited => f.write_str("inherited"),
+            Self::Config { field } => write!(f, "config {field}"),
+            Self::EnvFile { path, line } => write!(f, "env_file {}:{line}", path.display()),
+            Self::Runtime { position } => write!(f, "runtime arg {position}"),
+            Self::Reconcile { field } => write!(f, "reconcile {field}"),
+        }
+    }
+}
+
+/// Origins recorded by [`Manifest::build_invocation_traced`] for the
+/// [`Invocation`] built alongside.
+///
+/// [`Manifest::build_invocation_traced`]: crate::manifest::Manifest::build_invocation_traced
+/// [`Invocation`]: crate::manifest::Invocation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Provenance {
+    /// One origin per `Invocation::args` entry.
+    pub args: Vec<Origin>,
+    /// The last writer of each `Invocation::env` variable.
+    pub env: HashMap<String, Origin>,
+    /// What dropped each `Invocation::env_remove` variable.
+    pub env_remove: HashMap<String, Origin>,
+    pub cwd: Option<Origin>,
+    /// Set when `[path]` rewrote PATH.
+    pub path: Option<PathProvenance>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathProvenance {
+    /// Where the PATH that `[path]` started from came from; `None` when
+    /// there was none.
+    pub base: Option<Origin>,
+    pub components: Vec<PathComponent>,
+}
@@ -1,12 +1,13 @@
 use crate::arg_validation::{self, ArgViolation};
 use crate::env_util;
 use crate::env_validation::{self, EnvKeyViolation, EnvValueViolation};
-use crate::manifest::{Manifest, RuntimePatch};
+use crate::manifest::{self, EnvJoin, Manifest, RuntimePatch};
 use crate::rhai_engine::{build_engine, RhaiEngineProfile};
 use anyhow::{anyhow, Context, Result};
 use rhai::{Array, Dynamic, ImmutableString, Map, Scope};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
+use std::path::PathBuf;
 
 pub fn maybe_reconcile(
     manifest: &Manifest,
@@ -58,6 +59,14 @@ fn build_context(manifest: &Manifest, runtime_args: &[String]) -> Map {
     );
     ctx.insert("alias_args".into(), to_array(&manifest.args));
     ctx.insert("alias_env".into(), to_string_map(manifest.env.clone()));
+    ctx.insert(
+        "alias_cwd".into(),
+        manifest
+            .cwd
+            .as_ref()
+            .map(|cwd| Dynamic::from(ImmutableString::from(cwd.to_string_lossy().as_ref())))
+            .unwrap_or(Dynamic::UNIT),
+    );
     ctx
 }
 
@@ -93,14 +102,31 @@ fn parse_patch(value: Dynamic) -> Result<RuntimePatch> {
     )?;
     let set_env =
         normalize_patch_set_env(optional_string_map(&map, "set_env")?.unwrap_or_default())?;
+    let default_env = normalize_patch_env_map(
+        optional_string_map(&map, "default_env")?.unwrap_or_default(),
+        "default_env",
+    )?;
+    let append_env = optional_env_joins(&map, "append_env")?;
+    let prepend_env = optional_env_joins(&map, "prepend_env")?;
     let remove_env =
         normalize_patch_remove_env(optional_string_array(&map, "remove_env")?.unwrap_or_default())?;
+    let remove_env_matching = normalize_patch_remove_env_matching(
+        optional_string_array(&map, "remove_env_matching")?.unwrap_or_default(),
+    )?;
+    let set_cwd = optional_string(&map, "set_cwd")?
+        .map(normalize_patch_set_cwd)
+        .transpose()?;
 
     Ok(RuntimePatch {
         replace_args,
         append_args,
         set_env,
+        default_env,
+        append_env,
+        prepend_env,
         remove_env,
+        remove_env_matching,
+        set_cwd,
     })
 }
 
@@ -108,11 +134,19 @@ fn validate_patch_keys(map: &Map) -> Result<()> {
     for key in map.keys() {
         let supported = matches!(
             key.as_str(),
-            "append_args" | "replace_args" | "set_env" | "remove_env"
+            "append_args"
+                | "replace_args"
+                | "set_env"
+                | "default_env"
+                | "append_env"
+                | "prepend_env"
+                | "remove_env"
+                | "remove_env_matching"
+                | "set_cwd"
         );
         if !supported {
             return Err(anyhow!(
-                "unsupported reconcile patch key `{}`; supported keys: append_args, replace_args, set_env, remove_env",
+                "unsupported reconcile patch key `{}`; supported keys: append_args, replace_args, set_env, default_env, append_env, prepend_env, remove_env, remove_env_matching, set_cwd",
                 key
             ));
         }
@@ -136,6 +170,13 @@ fn optional_string_array(map: &Map, key: &str) -> Result<Option<Vec<String>>> {
     Ok(Some(out))
 }
 
+fn optional_string(map: &Map, key: &str) -> Result<Option<String>> {
+    let Some(value) = map.get(key) else {
+        return Ok(None);
+    };
+    dynamic_to_string(value.clone(), key).map(Some)
+}
+
 fn optional_string_map(map: &Map, key: &str) -> Result<Option<HashMap<String, String>>> {
     let Some(value) = map.get(key) else {
         return Ok(None);
@@ -152,22 +193,77 @@ fn optional_string_map(map: &Map, key: &str) -> Result<Option<HashMap<String, St
     Ok(Some(out))
 }
 
+/// Reads an `append_env`/`prepend_env` map whose values are either a string
+/// or `#{ value: "...", separator: "..." }`.
+fn optional_env_joins(map: &Map, key: &str) -> Result<BTreeMap<String, EnvJoin>> {
+    let Some(value) = map.get(key) else {
+        return Ok(BTreeMap::new());
+    };
+    let inner = value
+        .clone()
+        .try_cast::<Map>()
+        .ok_or_else(|| anyhow!("`{key}` must be an object/map"))?;
+
+    let mut values = HashMap::with_capacity(inner.len());
+    let mut separators = HashMap::new();
+    for (name, entry) in inner {
+        let name = name.to_string();
+        if let Some(table) = entry.clone().try_cast::<Map>() {
+            for field in table.keys() {
+                if !matches!(field.as_str(), "value" | "separator") {
+                    return Err(anyhow!(
+                        "`{key}.{name}` supports only `value` and `separator`; got `{field}`"
+                    ));
+                }
+            }
+            let value = optional_string(&table, "value")?
+                .ok_or_else(|| anyhow!("`{key}.{name}` is missing `value`"))?;
+            if let Some(separator) = optional_string(&table, "separator")? {
+                if separator.contains('\0') {
+                    return Err(anyhow!("`{key}.{name}.separator` cannot contain NUL bytes"));
+                }
+                separators.insert(name.trim().to_string(), separator);
+            }
+            values.insert(name, value);
+        } else {
+            values.insert(name, dynamic_to_string(entry, key)?);
+        }
+    }
+
+    Ok(normalize_patch_env_map(values, key)?
+        .into_iter()
+        .map(|(name, value)| {
+            let separator = separators
+                .remove(&name)
+                .unwrap_or_else(|| EnvJoin::DEFAULT_SEPARATOR.to_string());
+            (name, EnvJoin { value, separator })
+        })
+        .collect())
+}
+
 fn normalize_patch_set_env(values: HashMap<String, String>) -> Result<HashMap<String, String>> {
+    normalize_patch_env_map(values, "set_env")
+}
+
+fn normalize_patch_env_map(
+    values: HashMap<String, String>,
+    field: &str,
+) -> Result<HashMap<String, String>> {
     let mut normalized = HashMap::with_capacity(values.len());
     for (key, value) in values {
         let normalized_key = key.trim();
         if normalized_key.is_empty() {
-            return Err(anyhow!("`set_env` cannot contain empty keys"));
+            return Err(anyhow!("`{field}` cannot contain empty keys"));
         }
         match env_validation::validate_env_key(normalized_key) {
             Ok(()) => {}
             Err(EnvKeyViolation::ContainsEquals) => {
                 return Err(anyhow!(
-                    "`set_env` keys cannot contain `=`: `{normalized_key}`"
+                    "`{field}` keys cannot contain `=`: `{normalized_key}`"
                 ));
             }
             Err(EnvKeyViolation::ContainsNul) => {
-                return Err(anyhow!("`set_env` keys cannot contain NUL bytes"));
+                return Err(anyhow!("`{field}` keys cannot contain NUL bytes"));
             }
         }
         if matches!(
@@ -175,12 +271,12 @@ fn normalize_patch_set_env(values: HashMap<String, String>) -> Result<HashMap<St
             Err(EnvValueViolation::ContainsNul)
         ) {
             return Err(anyhow!(
-                "`set_env` values cannot contain NUL bytes for key `{normalized_key}`"
+                "`{field}` values cannot contain NUL bytes for key `{normalized_key}`"
             ));
         }
         if normalized.contains_key(normalized_key) {
             return Err(anyhow!(
-                "`set_env` contains duplicate keys after trimming: `{normalized_key}`"
+                "`{field}` contains duplicate keys after trimming: `{normalized_key}`"
             ));
         }
         normalized.insert(normalized_key.to_string(), value);
@@ -188,6 +284,17 @@ fn normalize_patch_set_env(values: HashMap<String, String>) -> Result<HashMap<St
     Ok(normalized)
 }
 
+fn normalize_patch_set_cwd(value: String) -> Result<PathBuf> {
+    let cwd = value.trim();
+    if cwd.is_empty() {
+        return Err(anyhow!("`set_cwd` cannot be empty"));
+    }
+    if cwd.contains('\0') {
+        return Err(anyhow!("`set_cwd` cannot contain NUL bytes"));
+    }
+    Ok(PathBuf::from(cwd))
+}
+
 fn normalize_patch_args(values: Vec<String>, field: &str) -> Result<Vec<String>> {
     for value in &values {
         if matches!(
@@ -227,6 +334,22 @@ fn normalize_patch_remove_env(values: Vec<String>) -> Result<Vec<String>> {
     Ok(normalized)
 }
 
+fn normalize_patch_remove_env_matching(values: Vec<String>) -> Result<Vec<String>> {
+    let mut seen = HashSet::with_capacity(values.len());
+    let mut normalized = Vec::with_capacity(values.len());
+    for pattern in values {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            continue;
+        }
+        manifest::compile_env_pattern(pattern, "`remove_env_matching`")?;
+        if seen.insert(pattern.to_string()) {
+            normalized.push(pattern.to_string());
+        }
+    }
+    Ok(normalized)
+}
+
 fn dynamic_to_string(value: Dynamic, field: &str) -> Result<String> {
     if let Some(text) = value.clone().try_cast::<ImmutableString>() {
         return Ok(text.to_string());
@@ -572,6 +695,47 @@ fn reconcile(_ctx) {
         assert!(err.contains("all values in `set_env` must be strings"));
     }
 
+    #[test]
+    fn reconcile_set_cwd_sees_alias_cwd_and_rejects_blank_values() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        env::remove_var("CHOPPER_DISABLE_RECONCILE");
+        let dir = TempDir::new().expect("tempdir");
+        let script_path = dir.path().join("cwd.rhai");
+        fs::write(
+            &script_path,
+            r#"
+fn reconcile(ctx) {
+  #{ set_cwd: ctx.alias_cwd + "/sub" }
+}
+
+fn blank(_ctx) {
+  #{ set_cwd: "  " }
+}
+"#,
+        )
+        .expect("write script");
+
+        let mut manifest = Manifest::simple(PathBuf::from("echo"));
+        manifest.cwd = Some(PathBuf::from("/srv/repo"));
+        manifest.reconcile = Some(ReconcileConfig {
+            script: script_path.clone(),
+            function: "reconcile".into(),
+        });
+        let patch = maybe_reconcile(&manifest, &[])
+            .expect("reconcile call")
+            .expect("patch present");
+        assert_eq!(patch.set_cwd, Some(PathBuf::from("/srv/repo/sub")));
+
+        manifest.reconcile = Some(ReconcileConfig {
+            script: script_path,
+            function: "blank".into(),
+        });
+        let err = maybe_reconcile(&manifest, &[])
+            .expect_err("blank set_cwd should fail")
+            .to_string();
+        assert!(err.contains("`set_cwd` cannot be empty"), "{err}");
+    }
+
     #[test]
     fn reconcile_rejects_append_args_entries_containing_nul_bytes() {
         let _guard = ENV_LOCK.lock().expect("lock env mutex");
@@ -817,6 +981,103 @@ fn reconcile(_ctx) {
         );
     }
 
+    #[test]
+    fn reconcile_normalizes_and_validates_remove_env_matching_patterns() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        env::remove_var("CHOPPER_DISABLE_RECONCILE");
+        let dir = TempDir::new().expect("tempdir");
+        let script_path = dir.path().join("remove-env-matching.rhai");
+        fs::write(
+            &script_path,
+            r#"
+fn reconcile(ctx) {
+  if ctx.runtime_args.len() > 0 {
+    #{ remove_env_matching: ["^AWS_("] }
+  } else {
+    #{ remove_env_matching: [" ^AWS_ ", "", "^AWS_", "^KUBE"] }
+  }
+}
+"#,
+        )
+        .expect("write script");
+
+        let mut manifest = Manifest::simple(PathBuf::from("echo"));
+        manifest.reconcile = Some(ReconcileConfig {
+            script: script_path,
+            function: "reconcile".into(),
+        });
+
+        let patch = maybe_reconcile(&manifest, &[])
+            .expect("reconcile call")
+            .expect("patch present");
+        assert_eq!(patch.remove_env_matching, vec!["^AWS_", "^KUBE"]);
+
+        let err = maybe_reconcile(&manifest, &["bad".into()])
+            .expect_err("expected remove_env_matching validation error")
+            .to_string();
+        assert!(
+            err.contains("`remove_env_matching` contains invalid regex `^AWS_(`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn reconcile_parses_default_append_and_prepend_env_operations() {
+        let _guard = ENV_LOCK.lock().expect("lock env mutex");
+        env::remove_var("CHOPPER_DISABLE_RECONCILE");
+        let dir = TempDir::new().expect("tempdir");
+        let script_path = dir.path().join("env-operations.rhai");
+        fs::write(
+            &script_path,
+            r#"
+fn reconcile(ctx) {
+  if ctx.runtime_args.len() > 0 {
+    #{ append_env: #{ RUSTFLAGS: #{ value: "-g", sep: "," } } }
+  } else {
+    #{
+      default_env: #{ " EDITOR ": "vi" },
+      append_env: #{ RUSTFLAGS: "-Dwarnings" },
+      prepend_env: #{ JAVA_OPTS: #{ value: "-Xmx1g", separator: "," } }
+    }
+  }
+}
+"#,
+        )
+        .expect("write script");
+
+        let mut manifest = Manifest::simple(PathBuf::from("echo"));
+        manifest.reconcile = Some(ReconcileConfig {
+            script: script_path,
+            function: "reconcile".into(),
+        });
+
+        let patch = maybe_reconcile(&manifest, &[])
+            .expect("reconcile call")
+            .expect("patch present");
+        assert_eq!(
+            patch.default_env.get("EDITOR").map(String::as_str),
+            Some("vi")
+        );
+        let append = &patch.append_env["RUSTFLAGS"];
+        assert_eq!(
+            (append.value.as_str(), append.separator.as_str()),
+            ("-Dwarnings", " ")
+        );
+        let prepend = &patch.prepend_env["JAVA_OPTS"];
+        assert_eq!(
+            (prepend.value.as_str(), prepend.separator.as_str()),
+            ("-Xmx1g", ",")
+        );
+
+        let err = maybe_reconcile(&manifest, &["bad".into()])
+            .expect_err("expected append_env validation error")
+            .to_string();
+        assert!(
+            err.contains("`append_env.RUSTFLAGS` supports only `value` and `separator`; got `sep`"),
+            "{err}"
+        );
+    }
+
     #[test]
     fn reconcile_rejects_remove_env_entries_containing_equals_sign() {
         let _guard = ENV_LOCK.lock().expect("lock env mutex");
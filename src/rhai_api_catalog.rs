@@ -4,6 +4,7 @@ pub fn exported_api_names() -> Vec<&'static str> {
         "runtime_env",
         "alias_args",
         "alias_env",
+        "alias_cwd",
         "words",
         "cword",
         "current",
@@ -0,0 +1,227 @@
+use crate::env_validation::{self, EnvValueViolation};
+use crate::manifest::SecretSource;
+use anyhow::{anyhow, Context, Result};
+use nix::libc;
+use std::ffi::CString;
+use std::process::{Command, Stdio};
+
+const KEYCTL_SEARCH: libc::c_int = 10;
+const KEYCTL_READ: libc::c_int = 11;
+const KEY_SPEC_SESSION_KEYRING: libc::c_long = -3;
+const KEY_SPEC_USER_KEYRING: libc::c_long = -4;
+
+/// Reads one `[env_secret]` value. Errors name the key and the source but
+/// never include any part of the value.
+pub(crate) fn read(key: &str, source: &SecretSource) -> Result<String> {
+    let value = match source {
+        SecretSource::File(path) => fs_err::read_to_string(path)
+            .with_context(|| format!("failed to read env_secret `{key}` file"))?,
+        SecretSource::Command(argv) => run_command(key, argv)?,
+        SecretSource::Keyring(spec) => read_keyring(key, spec)?,
+    };
+    let value = strip_trailing_newline(value);
+    if matches!(
+        env_validation::validate_env_value(&value),
+        Err(EnvValueViolation::ContainsNul)
+    ) {
+        return Err(anyhow!("env_secret `{key}` value cannot contain NUL bytes"));
+    }
+    Ok(value)
+}
+
+/// Splits `type:description`; a bare description names a `user` key.
+pub(crate) fn parse_keyring_spec(spec: &str) -> Option<(&str, &str)> {
+    let (key_type, description) = spec.split_once(':').unwrap_or(("user", spec));
+    if key_type.is_empty() || description.is_empty() || spec.contains('\0') {
+        return None;
+    }
+    Some((key_type, description))
+}
+
+fn strip_trailing_newline(mut value: String) -> String {
+    if value.ends_with('\n') {
+        value.pop();
+        if value.ends_with('\r') {
+            value.pop();
+        }
+    }
+    value
+}
+
+fn run_command(key: &str, argv: &[String]) -> Result<String> {
+    let (program, args) = argv
+        .split_first()
+        .ok_or_else(|| anyhow!("env_secret `{key}` command is empty"))?;
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .with_context(|| format!("failed to run env_secret `{key}` command `{program}`"))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "env_secret `{key}` command `{program}` failed with {}",
+            output.status
+        ));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| anyhow!("env_secret `{key}` command `{program}` printed non-UTF-8 output"))
+}
+
+/// Searches the user keyring, then the session keyring, like
+/// `keyctl pipe %type:description`.
+fn read_keyring(key: &str, spec: &str) -> Result<String> {
+    let (key_type, description) = parse_keyring_spec(spec)
+        .ok_or_else(|| anyhow!("env_secret `{key}` keyring must be `type:description`"))?;
+    let key_type = CString::new(key_type)?;
+    let description = CString::new(description)?;
+
+    let mut serial = -1;
+    for keyring in [KEY_SPEC_USER_KEYRING, KEY_SPEC_SESSION_KEYRING] {
+        // SAFETY: both strings are valid NUL-terminated C strings that outlive
+        // the call, and KEYCTL_SEARCH only reads them.
+        serial = unsafe {
+            libc::syscall(
+                libc::SYS_keyctl,
+                KEYCTL_SEARCH,
+                keyring,
+                key_type.as_ptr(),
+                description.as_ptr(),
+                0,
+            )
+        };
+        if serial >= 0 {
+            break;
+        }
+    }
+    if serial < 0 {
+        return Err(anyhow!(
+            "env_secret `{key}` keyring key `{spec}` was not found: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    let mut buffer = vec![0u8; 256];
+    loop {
+        // SAFETY: the pointer and length describe `buffer`, which KEYCTL_READ
+        // fills up to the given length.
+        let len = unsafe {
+            libc::syscall(
+                libc::SYS_keyctl,
+                KEYCTL_READ,
+                serial,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+            )
+        };
+        if len < 0 {
+            return Err(anyhow!(
+                "env_secret `{key}` keyring key `{spec}` could not be read: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        let len = len as usize;
+        if len <= buffer.len() {
+            buffer.truncate(len);
+            break;
+        }
+        buffer.resize(len, 0);
+    }
+    String::from_utf8(buffer)
+        .map_err(|_| anyhow!("env_secret `{key}` keyring key `{spec}` is not UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_keyring_spec, read};
+    use crate::manifest::SecretSource;
+    use nix::libc;
+    use std::ffi::CString;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn reads_file_and_command_secrets_without_leaking_values() {
+        let temp = TempDir::new().expect("create tempdir");
+        let token = temp.path().join("token");
+        fs::write(&token, "s3cret\n").expect("write token");
+
+        assert_eq!(
+            read("TOKEN", &SecretSource::File(token)).expect("read file secret"),
+            "s3cret"
+        );
+        assert_eq!(
+            read(
+                "TOKEN",
+                &SecretSource::Command(vec!["printf".into(), "a\\nb\\r\\n".into()])
+            )
+            .expect("read command secret"),
+            "a\nb"
+        );
+
+        let err = read(
+            "TOKEN",
+            &SecretSource::Command(vec!["sh".into(), "-c".into(), "echo leaked; exit 3".into()]),
+        )
+        .expect_err("failing command should fail");
+        assert!(
+            err.to_string()
+                .contains("env_secret `TOKEN` command `sh` failed"),
+            "{err}"
+        );
+        assert!(!format!("{err:#}").contains("leaked"), "{err:#}");
+
+        let err = read("TOKEN", &SecretSource::File(temp.path().join("missing")))
+            .expect_err("missing file should fail");
+        assert!(
+            err.to_string()
+                .contains("failed to read env_secret `TOKEN` file"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn parses_keyring_specs() {
+        assert_eq!(
+            parse_keyring_spec("user:gh-token"),
+            Some(("user", "gh-token"))
+        );
+        assert_eq!(parse_keyring_spec("gh-token"), Some(("user", "gh-token")));
+        assert_eq!(
+            parse_keyring_spec("logon:svc:db"),
+            Some(("logon", "svc:db"))
+        );
+        for invalid in ["", ":x", "user:"] {
+            assert_eq!(parse_keyring_spec(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn reads_keyring_secret_when_the_session_keyring_is_available() {
+        let key_type = CString::new("user").expect("key type");
+        let description =
+            CString::new(format!("chopper-test-{}", std::process::id())).expect("description");
+        let payload = b"from-keyring";
+        // SAFETY: all pointers reference live buffers of the stated length.
+        let serial = unsafe {
+            libc::syscall(
+                libc::SYS_add_key,
+                key_type.as_ptr(),
+                description.as_ptr(),
+                payload.as_ptr(),
+                payload.len(),
+                super::KEY_SPEC_SESSION_KEYRING,
+            )
+        };
+        if serial < 0 {
+            // Sandboxes commonly deny keyring access; nothing to check.
+            return;
+        }
+
+        let spec = format!("user:{}", description.to_str().expect("utf-8"));
+        let value = read("TOKEN", &SecretSource::Keyring(spec));
+        // SAFETY: KEYCTL_INVALIDATE (21) takes only the key serial.
+        unsafe { libc::syscall(libc::SYS_keyctl, 21, serial) };
+        assert_eq!(value.expect("read keyring secret"), "from-keyring");
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::{Context, Result};
+use nix::libc;
+use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+use std::io::{self, IsTerminal};
+use std::os::fd::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+/// Signals chopper passes on to a child it waits for.
+const RELAYED_SIGNALS: [Signal; 7] = [
+    Signal::SIGTERM,
+    Signal::SIGHUP,
+    Signal::SIGINT,
+    Signal::SIGQUIT,
+    Signal::SIGUSR1,
+    Signal::SIGUSR2,
+    Signal::SIGWINCH,
+];
+
+/// The child's pid, or its negated process group id when it runs in its own
+/// group. Zero while no child is attached.
+static RELAY_TARGET: AtomicI32 = AtomicI32::new(0);
+/// A signal that arrived before the child was attached.
+static PENDING_SIGNAL: AtomicI32 = AtomicI32::new(0);
+/// Set once SIGTERM, SIGINT, SIGHUP, or SIGQUIT reached chopper while relaying.
+static TERMINATION_RELAYED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn relay_signal(signal: libc::c_int, info: *mut libc::siginfo_t, _: *mut libc::c_void) {
+    if matches!(
+        signal,
+        libc::SIGTERM | libc::SIGINT | libc::SIGHUP | libc::SIGQUIT
+    ) {
+        TERMINATION_RELAYED.store(true, Ordering::SeqCst);
+    }
+    let target = RELAY_TARGET.load(Ordering::SeqCst);
+    // SAFETY: the kernel passes a valid siginfo_t to SA_SIGINFO handlers.
+    let from_kernel = !info.is_null() && unsafe { (*info).si_code } > 0;
+    // Keys like ^C and ^\ signal the whole foreground process group, so a
+    // child sharing chopper's group already has them.
+    if target > 0 && from_kernel && matches!(signal, libc::SIGINT | libc::SIGQUIT) {
+        return;
+    }
+    if target == 0 {
+        PENDING_SIGNAL.store(signal, Ordering::SeqCst);
+        return;
+    }
+    // SAFETY: kill(2) is async-signal-safe.
+    unsafe {
+        libc::kill(target, signal);
+    }
+}
+
+/// Forwards SIGTERM, SIGHUP, SIGINT, SIGQUIT, SIGUSR1, SIGUSR2, and SIGWINCH
+/// from chopper to a child it spawned and waits for, so `kill <pid>` and
+/// service managers reach the real command. Install it before spawning;
+/// the previous dispositions come back on drop.
+///
+/// When chopper runs in the foreground of a terminal the child shares its
+/// process group, keeping job control and terminal reads working, and
+/// terminal-generated SIGINT/SIGQUIT are not sent a second time. Otherwise
+/// the child gets its own process group and signals go to the whole group,
+/// so helpers it starts are not orphaned.
+pub(crate) struct SignalRelay {
+    own_group: bool,
+    previous: Vec<(Signal, SigAction)>,
+}
+
+impl SignalRelay {
+    pub(crate) fn install() -> Result<Self> {
+        RELAY_TARGET.store(0, Ordering::SeqCst);
+        PENDING_SIGNAL.store(0, Ordering::SeqCst);
+        TERMINATION_RELAYED.store(false, Ordering::SeqCst);
+        let action = SigAction::new(
+            SigHandler::SigAction(relay_signal),
+            SaFlags::SA_RESTART | SaFlags::SA_SIGINFO,
+            SigSet::empty(),
+        );
+        let mut relay = Self {
+            own_group: !in_terminal_foreground(),
+            previous: Vec::with_capacity(RELAYED_SIGNALS.len()),
+        };
+        for signal in RELAYED_SIGNALS {
+            // SAFETY: `relay_signal` only touches atomics and calls kill(2).
+            let previous = unsafe { sigaction(signal, &action) }
+                .with_context(|| format!("failed to install {signal} relay handler"))?;
+            relay.previous.push((signal, previous));
+        }
+        Ok(relay)
+    }
+
+    /// Places the child in its own process group when that is the mode.
+    pub(crate) fn prepare(&self, cmd: &mut Command) {
+        if self.own_group {
+            cmd.process_group(0);
+        }
+    }
+
+    /// Starts relaying to the spawned child, delivering any signal that
+    /// arrived while it was being started.
+    pub(crate) fn attach(&self, pid: u32) {
+        let pid = pid as i32;
+        let target = if self.own_group { -pid } else { pid };
+        RELAY_TARGET.store(target, Ordering::SeqCst);
+        let pending = PENDING_SIGNAL.swap(0, Ordering::SeqCst);
+        if pending != 0 {
+            // SAFETY: plain kill(2) on the child we just spawned.
+            unsafe {
+                libc::kill(target, pending);
+            }
+        }
+    }
+
+    /// Sends `signal` where relayed signals go: the child, or its group.
+    pub(crate) fn signal_child(&self, signal: Signal) {
+        let target = RELAY_TARGET.load(Ordering::SeqCst);
+        if target != 0 {
+            // SAFETY: plain kill(2) on the attached child.
+            unsafe {
+                libc::kill(target, signal as libc::c_int);
+            }
+        }
+    }
+}
+
+impl Drop for SignalRelay {
+    fn drop(&mut self) {
+        RELAY_TARGET.store(0, Ordering::SeqCst);
+        for (signal, action) in self.previous.iter().rev() {
+            // SAFETY: restores a disposition previously returned by sigaction.
+            let _ = unsafe { sigaction(*signal, action) };
+        }
+        PENDING_SIGNAL.store(0, Ordering::SeqCst);
+    }
+}
+
+/// Whether a termination signal (SIGTERM, SIGINT, SIGHUP, or SIGQUIT) was
+/// passed on since the last relay was installed, so callers can stop
+/// instead of starting the command again.
+pub(crate) fn termination_relayed() -> bool {
+    TERMINATION_RELAYED.load(Ordering::SeqCst)
+}
+
+/// Parses a signal given as `TERM`, `SIGTERM` (any case), or a number.
+pub(crate) fn parse_signal(value: &str) -> Option<Signal> {
+    let value = value.trim();
+    if let Ok(number) = value.parse::<libc::c_int>() {
+        return Signal::try_from(number).ok();
+    }
+    let upper = value.to_ascii_uppercase();
+    let name = if upper.starts_with("SIG") {
+        upper
+    } else {
+        format!("SIG{upper}")
+    };
+    Signal::from_str(&name).ok()
+}
+
+/// Whether chopper's process group owns its controlling terminal, judged by
+/// whichever standard stream is a terminal.
+fn in_terminal_foreground() -> bool {
+    let fds = [
+        io::stdin().as_raw_fd(),
+        io::stdout().as_raw_fd(),
+        io::stderr().as_raw_fd(),
+    ];
+    let is_terminal = [
+        io::stdin().is_terminal(),
+        io::stdout().is_terminal(),
+        io::stderr().is_terminal(),
+    ];
+    fds.into_iter()
+        .zip(is_terminal)
+        .find(|(_, is_terminal)| *is_terminal)
+        // SAFETY: tcgetpgrp and getpgrp only query the process state.
+        .is_some_and(|(fd, _)| unsafe { libc::tcgetpgrp(fd) == libc::getpgrp() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_signal;
+    use nix::sys::signal::Signal;
+
+    #[test]
+    fn parses_signal_names_and_numbers() {
+        assert_eq!(parse_signal("TERM"), Some(Signal::SIGTERM));
+        assert_eq!(parse_signal("sigint"), Some(Signal::SIGINT));
+        assert_eq!(parse_signal(" SIGKILL "), Some(Signal::SIGKILL));
+        assert_eq!(parse_signal("1"), Some(Signal::SIGHUP));
+        for invalid in ["", "SIG", "NOPE", "0", "999"] {
+            assert_eq!(parse_signal(invalid), None, "{invalid}");
+        }
+    }
+}
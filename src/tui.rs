@@ -93,12 +93,15 @@ enum TomlField {
     Args,
     Env,
     EnvRemove,
+    EnvSecret,
+    Cwd,
     PathRemoveAll,
     PathRemoveOne,
     PathAppendAll,
     PathAppendOne,
     PathPrependAll,
     PathPrependOne,
+    Pathlists,
     JournalEnabled,
     JournalNamespace,
     JournalStderr,
@@ -120,18 +123,21 @@ enum TomlField {
 }
 
 impl TomlField {
-    fn all() -> [Self; 28] {
+    fn all() -> [Self; 31] {
         [
             Self::Exec,
             Self::Args,
             Self::Env,
             Self::EnvRemove,
+            Self::EnvSecret,
+            Self::Cwd,
             Self::PathRemoveAll,
             Self::PathRemoveOne,
             Self::PathAppendAll,
             Self::PathAppendOne,
             Self::PathPrependAll,
             Self::PathPrependOne,
+            Self::Pathlists,
             Self::JournalEnabled,
             Self::JournalNamespace,
             Self::JournalStderr,
@@ -159,12 +165,15 @@ impl TomlField {
             Self::Args => "args",
             Self::Env => "env",
             Self::EnvRemove => "env_remove",
+            Self::EnvSecret => "env_secret",
+            Self::Cwd => "cwd",
             Self::PathRemoveAll => "path.remove_all",
             Self::PathRemoveOne => "path.remove_one",
             Self::PathAppendAll => "path.append_all",
             Self::PathAppendOne => "path.append_one",
             Self::PathPrependAll => "path.prepend_all",
             Self::PathPrependOne => "path.prepend_one",
+            Self::Pathlists => "pathlists",
             Self::JournalEnabled => "journal.enabled",
             Self::JournalNamespace => "journal.namespace",
             Self::JournalStderr => "journal.stderr",
@@ -229,6 +238,8 @@ struct AliasArtifacts {
     selected_alias: Option<String>,
     toml_path: Option<PathBuf>,
     shared_rhai_path: Option<PathBuf>,
+    /// Live instances in the registry when the alias was selected.
+    running_instances: usize,
 }
 
 #[derive(Debug)]
@@ -519,6 +530,12 @@ fn handle_toml_enter(state: &mut AppState) -> LoopAction {
         state.pending_method_editor = Some(field);
         return LoopAction::OpenPendingMethodEditor;
     }
+    if field == TomlField::EnvSecret {
+        state.alert_message = Some(String::from(
+            "env_secret values are never shown; edit the [env_secret] table in the alias TOML",
+        ));
+        return LoopAction::Continue;
+    }
     if field.is_toggle() {
         if let Err(err) = toggle_selected_alias_toml_field(state, field) {
             state.alert_message = Some(err.to_string());
@@ -905,7 +922,7 @@ fn prune_empty_path_doc(doc: &mut crate::alias_doc::AliasDoc) {
 
 fn toml_field_value(doc: &crate::alias_doc::AliasDoc, field: TomlField) -> String {
     match field {
-        TomlField::Exec => doc.exec.clone(),
+        TomlField::Exec => doc.exec.candidates().join(", "),
         TomlField::Args => doc.args.join(", "),
         TomlField::Env => {
             let mut entries: Vec<_> = doc
@@ -917,6 +934,13 @@ fn toml_field_value(doc: &crate::alias_doc::AliasDoc, field: TomlField) -> Strin
             entries.join(", ")
         }
         TomlField::EnvRemove => doc.env_remove.join(", "),
+        TomlField::EnvSecret => doc
+            .env_secret
+            .iter()
+            .map(|(key, secret)| format!("{key}={}", secret.redacted()))
+            .collect::<Vec<_>>()
+            .join(", "),
+        TomlField::Cwd => doc.cwd.clone().unwrap_or_default(),
         TomlField::PathRemoveAll => doc
             .path
             .as_ref()
@@ -947,6 +971,9 @@ fn toml_field_value(doc: &crate::alias_doc::AliasDoc, field: TomlField) -> Strin
             .as_ref()
             .map(|path| path.prepend_one.join(", "))
             .unwrap_or_default(),
+        TomlField::Pathlists => {
+            crate::alias_admin_parse::pathlist_assignments(&doc.pathlists).join(", ")
+        }
         TomlField::JournalEnabled => doc.journal.is_some().to_string(),
         TomlField::JournalNamespace => doc
             .journal
@@ -1093,7 +1120,7 @@ fn apply_toml_field_input(
 ) -> anyhow::Result<()> {
     match field {
         TomlField::Exec => {
-            doc.exec = input.to_string();
+            doc.exec = crate::alias_doc::AliasExecDoc::from_candidates(split_csv(input));
         }
         TomlField::Args => {
             doc.args = split_csv(input);
@@ -1108,6 +1135,18 @@ fn apply_toml_field_input(
         TomlField::EnvRemove => {
             doc.env_remove = split_csv(input);
         }
+        TomlField::EnvSecret => {
+            return Err(anyhow::anyhow!(
+                "field `env_secret` cannot be edited here; edit the alias TOML instead"
+            ));
+        }
+        TomlField::Cwd => {
+            doc.cwd = if input.trim().is_empty() {
+                None
+            } else {
+                Some(input.trim().to_string())
+            };
+        }
         TomlField::PathRemoveAll => {
             doc.path = Some(doc.path.clone().unwrap_or_else(default_path_doc));
             if let Some(path) = doc.path.as_mut() {
@@ -1150,6 +1189,14 @@ fn apply_toml_field_input(
             }
             prune_empty_path_doc(doc);
         }
+        TomlField::Pathlists => {
+            let mut pathlists = std::collections::BTreeMap::new();
+            for entry in split_csv(input) {
+                crate::alias_admin_parse::apply_pathlist_assignment(&mut pathlists, &entry)?;
+            }
+            pathlists.retain(|_, list| !list.is_empty());
+            doc.pathlists = pathlists;
+        }
         TomlField::JournalEnabled
         | TomlField::JournalStderr
         | TomlField::JournalUserScope
@@ -1564,10 +1611,15 @@ fn collect_alias_artifacts(alias: &str) -> AliasArtifacts {
         .as_ref()
         .map(|path| crate::rhai_wiring::shared_rhai_path_for_alias_doc(path));
 
+    let running_instances = crate::instances::live_instances(Some(alias))
+        .map(|instances| instances.len())
+        .unwrap_or(0);
+
     AliasArtifacts {
         selected_alias: Some(alias.to_string()),
         toml_path,
         shared_rhai_path,
+        running_instances,
     }
 }
 
@@ -1929,7 +1981,12 @@ fn render_inspector(frame: &mut Frame, area: Rect, state: &AppState, tab_mode: T
         .artifacts
         .selected_alias
         .as_ref()
-        .map(|name| format!("alias: {name}"))
+        .map(|name| {
+            format!(
+                "alias: {name} ({} running)",
+                state.artifacts.running_instances
+            )
+        })
         .unwrap_or_else(|| String::from("alias: <none>"));
     frame.render_widget(
         Paragraph::new(truncate_line(&alias_line, chunks[0].width as usize))
@@ -2139,6 +2196,13 @@ fn toml_property_entries(doc: &crate::alias_doc::AliasDoc) -> Vec<TomlPropertyEn
         TomlField::EnvRemove,
         !doc.env_remove.is_empty(),
     );
+    push_toml_entry(
+        &mut entries,
+        doc,
+        TomlField::EnvSecret,
+        !doc.env_secret.is_empty(),
+    );
+    push_toml_entry(&mut entries, doc, TomlField::Cwd, doc.cwd.is_some());
     push_toml_entry(
         &mut entries,
         doc,
@@ -2193,6 +2257,12 @@ fn toml_property_entries(doc: &crate::alias_doc::AliasDoc) -> Vec<TomlPropertyEn
             .map(|value| !value.prepend_one.is_empty())
             .unwrap_or(false),
     );
+    push_toml_entry(
+        &mut entries,
+        doc,
+        TomlField::Pathlists,
+        !doc.pathlists.is_empty(),
+    );
 
     push_toml_entry(
         &mut entries,
@@ -2870,6 +2940,54 @@ mod tests {
         assert!(doc.path.is_none());
     }
 
+    #[test]
+    fn apply_toml_field_input_replaces_pathlists_from_assignments() {
+        let mut doc = crate::alias_admin::minimal_alias_doc();
+        super::apply_toml_field_input(
+            &mut doc,
+            TomlField::Pathlists,
+            "LD_LIBRARY_PATH.prepend_one=/opt/lib, LD_LIBRARY_PATH.prune_missing=true",
+            "demo",
+        )
+        .expect("apply pathlists input");
+        let list = doc.pathlists.get("LD_LIBRARY_PATH").expect("pathlist");
+        assert_eq!(list.prepend_one, vec!["/opt/lib"]);
+        assert!(list.prune_missing);
+        assert_eq!(
+            super::toml_field_value(&doc, TomlField::Pathlists),
+            "LD_LIBRARY_PATH.prepend_one=/opt/lib, LD_LIBRARY_PATH.prune_missing=true"
+        );
+
+        super::apply_toml_field_input(&mut doc, TomlField::Pathlists, "", "demo")
+            .expect("clear pathlists input");
+        assert!(doc.pathlists.is_empty());
+    }
+
+    #[test]
+    fn env_secret_field_is_redacted_and_read_only() {
+        let mut doc = crate::alias_admin::minimal_alias_doc();
+        doc.env_secret.insert(
+            "API_TOKEN".to_string(),
+            crate::alias_doc::AliasSecretDoc {
+                command: Some(vec![
+                    "pass".to_string(),
+                    "show".to_string(),
+                    "x".to_string(),
+                ]),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            super::toml_field_value(&doc, TomlField::EnvSecret),
+            "API_TOKEN=<redacted: command pass show x>"
+        );
+        let before = doc.clone();
+        let err = super::apply_toml_field_input(&mut doc, TomlField::EnvSecret, "A=b", "demo")
+            .expect_err("env_secret should not be editable");
+        assert!(err.to_string().contains("cannot be edited here"), "{err}");
+        assert_eq!(doc, before);
+    }
+
     #[test]
     fn path_nodes_appear_between_env_remove_and_journal_fields() {
         let doc = crate::alias_admin::minimal_alias_doc();
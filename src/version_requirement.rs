@@ -0,0 +1,201 @@
+use crate::cache::{self, VersionProbeKey};
+use crate::manifest::VersionRequirement;
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use std::cmp::Ordering;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+pub(crate) const DEFAULT_VERSION_CMD: &str = "--version";
+pub(crate) const DEFAULT_VERSION_REGEX: &str = r"(\d+(?:\.\d+)+)";
+
+/// Parses a dotted numeric version such as `0.9.5` or `v1.2`.
+pub(crate) fn parse_version(value: &str) -> Option<Vec<u64>> {
+    let value = value.trim();
+    let value = value.strip_prefix(['v', 'V']).unwrap_or(value);
+    if value.is_empty() {
+        return None;
+    }
+    value
+        .split('.')
+        .map(|part| {
+            if part.is_empty() || !part.bytes().all(|byte| byte.is_ascii_digit()) {
+                return None;
+            }
+            part.parse::<u64>().ok()
+        })
+        .collect()
+}
+
+/// Compares dotted versions numerically; missing components count as zero.
+pub(crate) fn compare_versions(left: &[u64], right: &[u64]) -> Ordering {
+    let len = left.len().max(right.len());
+    (0..len)
+        .map(|idx| {
+            let left = left.get(idx).copied().unwrap_or(0);
+            let right = right.get(idx).copied().unwrap_or(0);
+            left.cmp(&right)
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Returns the first capture group of `regex` in `output`, or the whole match
+/// when the pattern has no groups.
+pub(crate) fn extract_version(output: &str, regex: &Regex) -> Option<String> {
+    let captures = regex.captures(output)?;
+    captures
+        .get(1)
+        .or_else(|| captures.get(0))
+        .map(|found| found.as_str().trim().to_string())
+}
+
+/// Fails unless `exec` reports at least `requirement.min_version`. The
+/// reported version is cached per binary identity (device, inode, mtime), so
+/// the probe only forks again after the binary is replaced.
+pub(crate) fn check(requirement: &VersionRequirement, exec: &Path, use_cache: bool) -> Result<()> {
+    let key = use_cache
+        .then(|| VersionProbeKey::for_binary(exec, requirement))
+        .flatten();
+    let reported = match key.as_ref().and_then(cache::load_version_probe) {
+        Some(reported) => reported,
+        None => {
+            let reported = probe(requirement, exec)?;
+            if let Some(key) = &key {
+                let _ = cache::store_version_probe(key, &reported);
+            }
+            reported
+        }
+    };
+
+    let found = parse_version(&reported).ok_or_else(|| {
+        anyhow!("`requires.version_regex` captured `{reported}`, which is not a dotted version")
+    })?;
+    let minimum = parse_version(&requirement.min_version).ok_or_else(|| {
+        anyhow!(
+            "`requires.min_version` is not a dotted version: `{}`",
+            requirement.min_version
+        )
+    })?;
+    if compare_versions(&found, &minimum) == Ordering::Less {
+        return Err(anyhow!(
+            "{} is version {reported}, but this alias requires at least {}",
+            exec.display(),
+            requirement.min_version
+        ));
+    }
+    Ok(())
+}
+
+fn probe(requirement: &VersionRequirement, exec: &Path) -> Result<String> {
+    let command_line = std::iter::once(exec.display().to_string())
+        .chain(requirement.version_cmd.iter().cloned())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let output = Command::new(exec)
+        .args(&requirement.version_cmd)
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("failed to run `{command_line}` to check its version"))?;
+    let regex = Regex::new(&requirement.version_regex).with_context(|| {
+        format!(
+            "invalid `requires.version_regex` `{}`",
+            requirement.version_regex
+        )
+    })?;
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    extract_version(&text, &regex).ok_or_else(|| {
+        anyhow!("`requires.version_regex` did not match the output of `{command_line}`")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, compare_versions, extract_version, parse_version, DEFAULT_VERSION_REGEX};
+    use crate::manifest::VersionRequirement;
+    use crate::test_support::ENV_LOCK;
+    use regex::Regex;
+    use std::cmp::Ordering;
+    use std::env;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    #[test]
+    fn parses_and_compares_dotted_versions() {
+        assert_eq!(parse_version("0.9.5"), Some(vec![0, 9, 5]));
+        assert_eq!(parse_version("v1.2"), Some(vec![1, 2]));
+        for invalid in ["", "v", "1..2", "1.x", "1.2-beta"] {
+            assert_eq!(parse_version(invalid), None, "{invalid}");
+        }
+        assert_eq!(compare_versions(&[1, 2], &[1, 2, 0]), Ordering::Equal);
+        assert_eq!(compare_versions(&[0, 10], &[0, 9, 5]), Ordering::Greater);
+        assert_eq!(compare_versions(&[1], &[1, 0, 1]), Ordering::Less);
+
+        let regex = Regex::new(DEFAULT_VERSION_REGEX).expect("default regex");
+        assert_eq!(
+            extract_version("NVIM v0.9.5\nBuild type: Release", &regex),
+            Some("0.9.5".to_string())
+        );
+        let whole = Regex::new(r"\d+\.\d+").expect("regex");
+        assert_eq!(
+            extract_version("tool 3.14", &whole),
+            Some("3.14".to_string())
+        );
+        assert_eq!(extract_version("no digits", &regex), None);
+    }
+
+    #[test]
+    fn check_rejects_old_versions_and_caches_the_probe_per_binary() {
+        let _guard = ENV_LOCK.lock().expect("lock env");
+        let temp = TempDir::new().expect("create tempdir");
+        let old_cache = env::var_os("CHOPPER_CACHE_DIR");
+        env::set_var("CHOPPER_CACHE_DIR", temp.path().join("cache"));
+
+        let counter = temp.path().join("probes");
+        let tool = temp.path().join("tool");
+        fs::write(
+            &tool,
+            format!(
+                "#!/bin/sh\necho probe >> '{}'\necho \"tool version 2.4.1\" >&2\n",
+                counter.display()
+            ),
+        )
+        .expect("write tool");
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).expect("chmod tool");
+
+        let requirement = |min: &str| VersionRequirement {
+            version_cmd: vec!["--version".to_string()],
+            version_regex: DEFAULT_VERSION_REGEX.to_string(),
+            min_version: min.to_string(),
+        };
+        check(&requirement("2.4"), &tool, true).expect("new enough");
+        let err = check(&requirement("2.10"), &tool, true).expect_err("too old");
+        assert!(
+            err.to_string()
+                .contains("is version 2.4.1, but this alias requires at least 2.10"),
+            "{err}"
+        );
+        let probes = fs::read_to_string(&counter).expect("read probe counter");
+        assert_eq!(probes.lines().count(), 1, "probe should be cached");
+
+        check(&requirement("1"), &tool, false).expect("uncached probe");
+        let probes = fs::read_to_string(&counter).expect("read probe counter");
+        assert_eq!(probes.lines().count(), 2);
+
+        let mut unmatched = requirement("1");
+        unmatched.version_regex = r"release (\d+)".to_string();
+        let err = check(&unmatched, &tool, false).expect_err("regex mismatch");
+        assert!(
+            err.to_string()
+                .contains("`requires.version_regex` did not match the output of"),
+            "{err}"
+        );
+
+        match old_cache {
+            Some(value) => env::set_var("CHOPPER_CACHE_DIR", value),
+            None => env::remove_var("CHOPPER_CACHE_DIR"),
+        }
+    }
+}
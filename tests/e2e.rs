@@ -1,7 +1,8 @@
-use nix::unistd::geteuid;
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::{geteuid, Pid};
 use std::fs;
 use std::os::unix::fs::{symlink, PermissionsExt};
-use std::os::unix::process::CommandExt;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Output, Stdio};
 use std::thread;
@@ -4080,6 +4081,2088 @@ args = ["SAFESTALENEWTOKEN9X"]
     );
 }
 
+#[test]
+fn extended_alias_inherits_parent_and_reparses_after_parent_edit() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let parent_dir = config_home.path().join("chopper/inherit-base");
+    let child_dir = config_home.path().join("chopper/inherit-child");
+    fs::create_dir_all(&parent_dir).expect("create parent alias dir");
+    fs::create_dir_all(&child_dir).expect("create child alias dir");
+    fs::write(
+        parent_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "printf 'ARGS=%s TOKEN=%s\n' \"$*\" \"$INHERIT_TOKEN\"", "_", "PARENTARG1"]
+
+[env]
+INHERIT_TOKEN = "parent-old"
+"#,
+    )
+    .expect("write parent alias config");
+    fs::write(
+        child_dir.join("exe.toml"),
+        r#"
+extends = "inherit-base"
+args = ["childarg"]
+"#,
+    )
+    .expect("write child alias config");
+
+    let output = run_chopper(&config_home, &cache_home, &["inherit-child", "runtime"]);
+    assert!(
+        output.status.success(),
+        "first run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("ARGS=PARENTARG1 childarg runtime TOKEN=parent-old"),
+        "{stdout}"
+    );
+
+    fs::write(
+        parent_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "printf 'ARGS=%s TOKEN=%s\n' \"$*\" \"$INHERIT_TOKEN\"", "_", "PARENTARG2"]
+
+[env]
+INHERIT_TOKEN = "parent-new"
+"#,
+    )
+    .expect("rewrite parent alias config");
+
+    let output = run_chopper(&config_home, &cache_home, &["inherit-child", "again"]);
+    assert!(
+        output.status.success(),
+        "second run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("ARGS=PARENTARG2 childarg again TOKEN=parent-new"),
+        "editing the parent should invalidate the child's cached manifest: {stdout}"
+    );
+}
+
+#[test]
+fn placeholders_expand_per_invocation_even_from_cached_manifest() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/interp");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "printf 'TARGET=%s ALIAS=%s HOME_OK=%s\n' \"$1\" \"$2\" \"$INTERP_HOME\"", "_", "${env:INTERP_TARGET:-none}", "${alias}"]
+
+[env]
+INTERP_HOME = "~/state"
+"#,
+    )
+    .expect("write alias config");
+    let home_state = config_home.path().join("home/state");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["interp"],
+        [("INTERP_TARGET", "first".to_string())],
+    );
+    assert!(
+        output.status.success(),
+        "first run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(&format!(
+            "TARGET=first ALIAS=interp HOME_OK={}",
+            home_state.display()
+        )),
+        "{stdout}"
+    );
+
+    let output = run_chopper(&config_home, &cache_home, &["interp"]);
+    assert!(
+        output.status.success(),
+        "second run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("TARGET=none ALIAS=interp"),
+        "cached manifest should re-expand against the new environment: {stdout}"
+    );
+}
+
+#[test]
+fn runtime_arg_slots_place_runtime_args_between_fixed_args() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/slots");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "printf '%s|' \"$@\"", "_", "first={1}", "{1}", "rest:", "{2..}"]
+args_after = ["--after"]
+"#,
+    )
+    .expect("write alias config");
+
+    let output = run_chopper(&config_home, &cache_home, &["slots", "one", "two", "three"]);
+    assert!(
+        output.status.success(),
+        "run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "first={1}|one|rest:|two|three|--after|"
+    );
+}
+
+#[test]
+fn env_files_load_beneath_env_table_and_are_reread_on_every_invocation() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/svc");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "printf '%s|%s|%s|%s' \"$HOST\" \"$URL\" \"$MODE\" \"${SECRET-unset}\""]
+env_file = [".env"]
+env_remove = ["SECRET"]
+
+[env]
+MODE = "from-toml"
+"#,
+    )
+    .expect("write alias config");
+    fs::write(
+        alias_dir.join(".env"),
+        "# service defaults\nexport HOST=db.local\nURL=\"pg://${HOST}/app\"\nMODE=from-dotenv\nSECRET='s3cret'\n",
+    )
+    .expect("write env file");
+
+    let output = run_chopper(&config_home, &cache_home, &["svc"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "db.local|pg://db.local/app|from-toml|unset"
+    );
+
+    fs::write(alias_dir.join(".env"), "HOST=db.other\n").expect("rewrite env file");
+    let output = run_chopper(&config_home, &cache_home, &["svc"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "db.other||from-toml|unset"
+    );
+
+    fs::write(alias_dir.join(".env"), "HOST=ok\nBROKEN LINE\n").expect("rewrite env file");
+    let output = run_chopper(&config_home, &cache_home, &["svc"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("line 2: expected `KEY=VALUE`"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn env_secrets_reach_the_child_but_never_the_cache_or_alias_get() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/gh");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(alias_dir.join("token"), "file-s3cret\n").expect("write token file");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "printf '%s|%s|%s' \"$GH_TOKEN\" \"$NPM_TOKEN\" \"${DROPPED-unset}\""]
+env_remove = ["DROPPED"]
+
+[env_secret]
+GH_TOKEN = { file = "token" }
+NPM_TOKEN = { command = ["printf", "%s-%s", "cmd", "s3cret"] }
+DROPPED = { command = ["false"] }
+"#,
+    )
+    .expect("write alias config");
+
+    let output = run_chopper(&config_home, &cache_home, &["gh"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "file-s3cret|cmd-s3cret|unset"
+    );
+
+    let mut pending = vec![cache_home.path().to_path_buf()];
+    let mut cached_files = 0;
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).expect("read cache dir") {
+            let path = entry.expect("cache dir entry").path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            cached_files += 1;
+            let bytes = fs::read(&path).expect("read cache file");
+            let text = String::from_utf8_lossy(&bytes);
+            assert!(
+                !text.contains("file-s3cret") && !text.contains("cmd-s3cret"),
+                "{} leaks a secret",
+                path.display()
+            );
+        }
+    }
+    assert!(cached_files > 0, "alias should have been cached");
+
+    let output = run_chopper(&config_home, &cache_home, &["--alias", "get", "gh"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("<redacted: command printf %s-%s cmd s3cret>"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("<redacted: file "), "{stdout}");
+    assert!(
+        !stdout.contains("file-s3cret") && !stdout.contains("cmd-s3cret"),
+        "{stdout}"
+    );
+
+    fs::remove_file(alias_dir.join("token")).expect("remove token file");
+    let output = run_chopper(&config_home, &cache_home, &["gh"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("failed to read env_secret `GH_TOKEN` file"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn guard_rules_refuse_with_a_distinct_exit_code_even_for_reconcile_args() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let aliases_dir = config_home.path().join("chopper/aliases");
+    fs::create_dir_all(&aliases_dir).expect("create aliases dir");
+    fs::write(
+        aliases_dir.join("tf.rhai"),
+        r#"
+fn reconcile(ctx) {
+  let out = #{};
+  if ctx.runtime_args.contains("--yolo") {
+    out["replace_args"] = ["apply", "-var-file=prod.tfvars", "-auto-approve"];
+  }
+  out
+}
+"#,
+    )
+    .expect("write reconcile script");
+    fs::write(
+        aliases_dir.join("tf.toml"),
+        r#"
+exec = "sh"
+args = ["-c", "printf 'ran %s' \"$*\"", "_"]
+
+[reconcile]
+function = "reconcile"
+
+[guard]
+deny = ["^-auto-approve$"]
+require = ["^-var-file="]
+confirm = ["^destroy$"]
+"#,
+    )
+    .expect("write alias config");
+
+    let output = run_chopper(
+        &config_home,
+        &cache_home,
+        &["tf", "plan", "-var-file=prod.tfvars"],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "ran plan -var-file=prod.tfvars"
+    );
+
+    for (args, expected) in [
+        (
+            &["tf", "apply", "-var-file=prod.tfvars", "-auto-approve"][..],
+            "guard deny rule `^-auto-approve$` matched argument `-auto-approve`",
+        ),
+        (
+            &["tf", "--yolo"][..],
+            "guard deny rule `^-auto-approve$` matched argument `-auto-approve`",
+        ),
+        (
+            &["tf", "plan"][..],
+            "guard require rule `^-var-file=` matched no argument",
+        ),
+        (
+            &["tf", "destroy", "-var-file=prod.tfvars"][..],
+            "guard confirm rule `^destroy$` matched argument `destroy`, and there is no terminal to confirm on",
+        ),
+    ] {
+        let output = run_chopper(&config_home, &cache_home, args);
+        assert_eq!(output.status.code(), Some(77), "{args:?}");
+        assert!(output.stdout.is_empty(), "{args:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(expected), "{args:?}: {stderr}");
+    }
+
+    let toml = fs::read_to_string(aliases_dir.join("tf.toml")).expect("read alias config");
+    fs::write(
+        aliases_dir.join("tf.toml"),
+        format!("{toml}non_tty = \"allow\"\n"),
+    )
+    .expect("rewrite alias config");
+    let output = run_chopper(
+        &config_home,
+        &cache_home,
+        &["tf", "destroy", "-var-file=prod.tfvars"],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // The alias's own args are not checked, so they cannot satisfy `require`.
+    let toml = fs::read_to_string(aliases_dir.join("tf.toml")).expect("read alias config");
+    fs::write(
+        aliases_dir.join("tf.toml"),
+        toml.replace("\"_\"]", "\"_\", \"-var-file=base.tfvars\"]"),
+    )
+    .expect("rewrite alias config");
+    let output = run_chopper(&config_home, &cache_home, &["tf", "plan"]);
+    assert_eq!(output.status.code(), Some(77));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("guard require rule `^-var-file=` matched no argument"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn guard_refusal_does_not_read_env_secrets() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let secret_read = config_home.path().join("secret-read");
+    let alias_dir = config_home.path().join("chopper/tf");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        format!(
+            r#"
+exec = "/bin/true"
+
+[env_secret]
+TF_TOKEN = {{ command = ["touch", "{secret_read}"] }}
+
+[guard]
+deny = ["^-auto-approve$"]
+"#,
+            secret_read = secret_read.display(),
+        ),
+    )
+    .expect("write alias config");
+
+    let output = run_chopper(&config_home, &cache_home, &["tf", "apply", "-auto-approve"]);
+    assert_eq!(output.status.code(), Some(77));
+    assert!(!secret_read.exists(), "secret command ran before the guard");
+
+    let output = run_chopper(&config_home, &cache_home, &["tf", "plan"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(secret_read.exists());
+}
+
+#[test]
+fn clean_env_mode_passes_only_kept_and_configured_variables() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/build");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/usr/bin/env"
+env_mode = "clean"
+env_keep = ["KEEP_ME", "^LC_E2E_"]
+
+[env]
+FROM_TOML = "yes"
+
+[path]
+prepend_one = ["/opt/build/bin"]
+"#,
+    )
+    .expect("write alias config");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["build"],
+        [
+            ("KEEP_ME", "kept".to_string()),
+            ("KEEP_ME_NOT", "dropped".to_string()),
+            ("LC_E2E_TEST", "regex".to_string()),
+            ("LEAKY", "dropped".to_string()),
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let mut lines: Vec<_> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect();
+    lines.sort();
+    assert_eq!(
+        lines,
+        vec![
+            "FROM_TOML=yes",
+            "KEEP_ME=kept",
+            "LC_E2E_TEST=regex",
+            "PATH=/opt/build/bin",
+        ]
+    );
+}
+
+#[test]
+fn env_remove_matching_unsets_inherited_variables_by_pattern() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/deploy");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/usr/bin/env"
+env_remove_matching = ["^E2E_AWS_", "^E2E_KUBE"]
+
+[env]
+E2E_AWS_REGION = "eu-west-1"
+"#,
+    )
+    .expect("write alias config");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["deploy"],
+        [
+            ("E2E_AWS_PROFILE", "prod".to_string()),
+            ("E2E_AWS_REGION", "us-east-1".to_string()),
+            ("E2E_KUBECONFIG", "/kube".to_string()),
+            ("E2E_KEEP", "kept".to_string()),
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let mut lines: Vec<_> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.starts_with("E2E_"))
+        .map(str::to_string)
+        .collect();
+    lines.sort();
+    assert_eq!(lines, vec!["E2E_AWS_REGION=eu-west-1", "E2E_KEEP=kept"]);
+}
+
+#[test]
+fn pathlists_rewrite_search_path_variables_other_than_path() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/py");
+    fs::create_dir_all(alias_dir.join("lib")).expect("create alias lib dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/usr/bin/env"
+
+[pathlists.LD_LIBRARY_PATH]
+prepend_one = ["${alias_dir}/lib"]
+dedupe = true
+prune_missing = true
+
+[pathlists.E2E_LIST]
+remove_all = ["^old$"]
+append_one = ["new"]
+separator = ";"
+"#,
+    )
+    .expect("write alias config");
+    let lib = alias_dir.join("lib").display().to_string();
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["py"],
+        [
+            (
+                "LD_LIBRARY_PATH",
+                format!("{lib}:/definitely/missing/lib:{lib}"),
+            ),
+            ("E2E_LIST", "old;keep".to_string()),
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line == format!("LD_LIBRARY_PATH={lib}")),
+        "{stdout}"
+    );
+    assert!(
+        stdout.lines().any(|line| line == "E2E_LIST=keep;new"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn env_default_append_and_prepend_build_on_inherited_values() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/build");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/usr/bin/env"
+
+[env_default]
+E2E_EDITOR = "vi"
+E2E_PAGER = "less"
+
+[env_append]
+E2E_RUSTFLAGS = "-Dwarnings"
+E2E_FRESH = { value = "b", separator = ":" }
+
+[env_prepend]
+E2E_JAVA_OPTS = { value = "-Xmx1g", separator = "," }
+"#,
+    )
+    .expect("write alias config");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["build"],
+        [
+            ("E2E_EDITOR", "nano".to_string()),
+            ("E2E_RUSTFLAGS", "-Copt-level=3".to_string()),
+            ("E2E_JAVA_OPTS", "-Dfoo=1".to_string()),
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let mut lines: Vec<_> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.starts_with("E2E_"))
+        .map(str::to_string)
+        .collect();
+    lines.sort();
+    assert_eq!(
+        lines,
+        vec![
+            "E2E_EDITOR=nano",
+            "E2E_FRESH=b",
+            "E2E_JAVA_OPTS=-Xmx1g,-Dfoo=1",
+            "E2E_PAGER=less",
+            "E2E_RUSTFLAGS=-Copt-level=3 -Dwarnings",
+        ]
+    );
+}
+
+#[test]
+fn hooks_run_around_a_supervised_child_and_preserve_its_exit_code() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let log = config_home.path().join("hooks.log");
+    let alias_dir = config_home.path().join("chopper/migrate");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "echo child >> \"$HOOK_LOG\"; exit 3"]
+
+[hooks]
+pre = [["sh", "-c", "echo pre >> \"$HOOK_LOG\""]]
+post = [
+  ["sh", "-c", "echo \"post $CHOPPER_EXIT_CODE\" >> \"$HOOK_LOG\""],
+  ["false"],
+  ["sh", "-c", "echo after-failure >> \"$HOOK_LOG\""],
+]
+"#,
+    )
+    .expect("write alias config");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["migrate"],
+        [("HOOK_LOG", log.display().to_string())],
+    );
+    assert_eq!(
+        output.status.code(),
+        Some(3),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(&log).expect("read hook log"),
+        "pre\nchild\npost 3\nafter-failure\n"
+    );
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("warning: post hook `false` failed"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn failing_pre_hook_aborts_the_launch() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let log = config_home.path().join("hooks.log");
+    let alias_dir = config_home.path().join("chopper/deploy");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "echo child >> \"$HOOK_LOG\""]
+
+[hooks]
+pre = [["sh", "-c", "exit 4"]]
+post = [["sh", "-c", "echo post >> \"$HOOK_LOG\""]]
+"#,
+    )
+    .expect("write alias config");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["deploy"],
+        [("HOOK_LOG", log.display().to_string())],
+    );
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("pre hook `sh -c 'exit 4'` failed with exit status: 4"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!log.exists());
+}
+
+fn wait_for_log_line(log: &Path, line: &str) {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(10) {
+        if fs::read_to_string(log).is_ok_and(|text| text.lines().any(|l| l == line)) {
+            return;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    panic!(
+        "timed out waiting for `{line}` in {}: {:?}",
+        log.display(),
+        fs::read_to_string(log)
+    );
+}
+
+/// Starts `alias` (whose child traps signals into `$SIGNAL_LOG`), sends
+/// chopper USR1, INT, and TERM, and returns chopper's exit status.
+fn relay_signals_through_chopper(
+    config_home: &TempDir,
+    cache_home: &TempDir,
+    alias: &str,
+    log: &Path,
+    path: &str,
+) -> std::process::ExitStatus {
+    prepare_reconcile_script_fixtures(config_home);
+    let home_dir = config_home.path().join("home");
+    fs::create_dir_all(&home_dir).expect("create test home dir");
+    let mut child = Command::new(chopper_bin())
+        .arg(alias)
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("XDG_CACHE_HOME", cache_home.path())
+        .env("HOME", &home_dir)
+        .env("CHOPPER_EXE_PATH", chopper_exe_bin())
+        .env("SIGNAL_LOG", log)
+        .env("PATH", path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn chopper");
+    let pid = Pid::from_raw(child.id() as i32);
+
+    wait_for_log_line(log, "ready");
+    kill(pid, Signal::SIGUSR1).expect("send SIGUSR1");
+    wait_for_log_line(log, "usr1");
+    kill(pid, Signal::SIGINT).expect("send SIGINT");
+    wait_for_log_line(log, "int");
+    kill(pid, Signal::SIGTERM).expect("send SIGTERM");
+
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait().expect("poll chopper") {
+            return status;
+        }
+        if start.elapsed() > Duration::from_secs(10) {
+            let _ = child.kill();
+            panic!("chopper did not exit after SIGTERM");
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+const SIGNAL_TRAPPING_CHILD: &str = r#"exec = "/bin/sh"
+args = ["-c", """
+trap 'echo usr1 >> "$SIGNAL_LOG"' USR1
+trap 'echo int >> "$SIGNAL_LOG"' INT
+trap 'echo term >> "$SIGNAL_LOG"; echo relayed-stderr >&2; exit 5' TERM
+echo ready >> "$SIGNAL_LOG"
+while :; do sleep 0.05; done
+"""]
+"#;
+
+#[test]
+fn supervised_mode_relays_signals_to_the_child() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let log = config_home.path().join("signals.log");
+    let alias_dir = config_home.path().join("chopper/worker");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        format!(
+            "{SIGNAL_TRAPPING_CHILD}\n[hooks]\npost = [[\"sh\", \"-c\", \"echo post $CHOPPER_EXIT_CODE >> \\\"$SIGNAL_LOG\\\"\"]]\n"
+        ),
+    )
+    .expect("write alias config");
+
+    let path = std::env::var("PATH").unwrap_or_default();
+    let status = relay_signals_through_chopper(&config_home, &cache_home, "worker", &log, &path);
+    assert_eq!(status.code(), Some(5));
+    assert_eq!(
+        fs::read_to_string(&log).expect("read signal log"),
+        "ready\nusr1\nint\nterm\npost 5\n"
+    );
+}
+
+#[test]
+fn journal_mode_relays_signals_and_drains_the_sink() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let log = config_home.path().join("signals.log");
+    let alias_dir = config_home.path().join("chopper/journaled-worker");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        format!(
+            "{SIGNAL_TRAPPING_CHILD}\n[journal]\nnamespace = \"ops-e2e\"\nstderr = true\nuser_scope = false\n"
+        ),
+    )
+    .expect("write alias config");
+
+    let fake_bin = TempDir::new().expect("create fake-bin dir");
+    let captured_err = fake_bin.path().join("captured-stderr.log");
+    write_executable_script(
+        &fake_bin.path().join("systemd-cat"),
+        &format!(
+            "#!/usr/bin/env bash\ncat > \"{}\"\n",
+            captured_err.display()
+        ),
+    );
+    let existing_path = std::env::var("PATH").unwrap_or_default();
+    let path = format!("{}:{existing_path}", fake_bin.path().display());
+
+    let status =
+        relay_signals_through_chopper(&config_home, &cache_home, "journaled-worker", &log, &path);
+    assert_eq!(status.code(), Some(5));
+    assert_eq!(
+        fs::read_to_string(&log).expect("read signal log"),
+        "ready\nusr1\nint\nterm\n"
+    );
+    let captured = fs::read_to_string(&captured_err).expect("read captured stderr");
+    assert!(captured.ends_with("relayed-stderr\n"), "{captured}");
+}
+
+#[test]
+fn timeout_stops_the_child_and_exits_124() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let log = config_home.path().join("timeout.log");
+    let alias_dir = config_home.path().join("chopper/nightly");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "trap 'echo term >> \"$TIMEOUT_LOG\"; exit 1' TERM; while :; do sleep 0.05; done"]
+timeout = "300ms"
+
+[hooks]
+post = [["sh", "-c", "echo post $CHOPPER_EXIT_CODE >> \"$TIMEOUT_LOG\""]]
+"#,
+    )
+    .expect("write alias config");
+
+    let started = Instant::now();
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["nightly"],
+        [("TIMEOUT_LOG", log.display().to_string())],
+    );
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(
+        output.status.code(),
+        Some(124),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("timed out after 300ms; sending SIGTERM"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(&log).expect("read timeout log"),
+        "term\npost 124\n"
+    );
+}
+
+#[test]
+fn timeout_kill_after_escalates_to_sigkill_and_exits_137() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/stubborn");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "trap '' USR1; while :; do sleep 0.05; done"]
+timeout = "200ms"
+timeout_signal = "USR1"
+kill_after = "200ms"
+"#,
+    )
+    .expect("write alias config");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["stubborn"],
+        std::iter::empty::<(&str, String)>(),
+    );
+    assert_eq!(
+        output.status.code(),
+        Some(137),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("sending SIGKILL"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn retry_reruns_the_child_until_an_unretried_exit_code() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let log = config_home.path().join("retry.log");
+    let alias_dir = config_home.path().join("chopper/flaky");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "echo \"attempt $CHOPPER_ATTEMPT\" >> \"$RETRY_LOG\"; [ \"$CHOPPER_ATTEMPT\" -lt 3 ] && exit 75; exit 9"]
+
+[retry]
+attempts = 5
+backoff = "exponential"
+initial_delay = "10ms"
+on_exit_codes = [75]
+
+[hooks]
+post = [["sh", "-c", "echo post $CHOPPER_EXIT_CODE >> \"$RETRY_LOG\""]]
+"#,
+    )
+    .expect("write alias config");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["flaky"],
+        [("RETRY_LOG", log.display().to_string())],
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(9), "{stderr}");
+    assert_eq!(
+        fs::read_to_string(&log).expect("read retry log"),
+        "attempt 1\nattempt 2\nattempt 3\npost 9\n"
+    );
+    assert!(
+        stderr.contains(
+            "warning: attempt 1/5 of /bin/sh failed with exit status: 75; retrying in 10ms"
+        ),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("attempt 2/5 of /bin/sh failed with exit status: 75; retrying in 20ms"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn retry_stops_after_relaying_a_termination_signal() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let log = config_home.path().join("retry.log");
+    let alias_dir = config_home.path().join("chopper/stubborn");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", """
+trap 'echo term >> "$RETRY_LOG"; exit 1' TERM
+echo "ready $CHOPPER_ATTEMPT" >> "$RETRY_LOG"
+while :; do sleep 0.05; done
+"""]
+
+[retry]
+attempts = 3
+initial_delay = "10ms"
+"#,
+    )
+    .expect("write alias config");
+    let home_dir = config_home.path().join("home");
+    fs::create_dir_all(&home_dir).expect("create test home dir");
+    let mut child = Command::new(chopper_bin())
+        .arg("stubborn")
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("XDG_CACHE_HOME", cache_home.path())
+        .env("HOME", &home_dir)
+        .env("CHOPPER_EXE_PATH", chopper_exe_bin())
+        .env("RETRY_LOG", &log)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn chopper");
+    let pid = Pid::from_raw(child.id() as i32);
+
+    wait_for_log_line(&log, "ready 1");
+    kill(pid, Signal::SIGTERM).expect("send SIGTERM");
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().expect("poll chopper") {
+            break status;
+        }
+        if start.elapsed() > Duration::from_secs(10) {
+            let _ = child.kill();
+            panic!("chopper did not exit after SIGTERM");
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+    assert_eq!(status.code(), Some(1));
+    assert_eq!(
+        fs::read_to_string(&log).expect("read retry log"),
+        "ready 1\nterm\n"
+    );
+}
+
+#[test]
+fn retry_annotates_each_attempt_in_the_journal_sink() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/journaled-flaky");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "echo \"try $CHOPPER_ATTEMPT\" >&2; exit 1"]
+
+[retry]
+attempts = 2
+backoff = "fixed"
+initial_delay = "10ms"
+
+[journal]
+namespace = "ops-e2e"
+stderr = true
+user_scope = false
+"#,
+    )
+    .expect("write alias config");
+
+    let fake_bin = TempDir::new().expect("create fake-bin dir");
+    let captured_err = fake_bin.path().join("captured-stderr.log");
+    write_executable_script(
+        &fake_bin.path().join("systemd-cat"),
+        &format!(
+            "#!/usr/bin/env bash\ncat > \"{}\"\n",
+            captured_err.display()
+        ),
+    );
+    let existing_path = std::env::var("PATH").unwrap_or_default();
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["journaled-flaky"],
+        [(
+            "PATH",
+            format!("{}:{existing_path}", fake_bin.path().display()),
+        )],
+    );
+    assert_eq!(
+        output.status.code(),
+        Some(1),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(&captured_err).expect("read captured stderr"),
+        "chopper: attempt 1/2 starting\n\
+         try 1\n\
+         chopper: attempt 1/2 of /bin/sh failed with exit status: 1; retrying in 10ms\n\
+         chopper: attempt 2/2 starting\n\
+         try 2\n"
+    );
+}
+
+/// Writes a `[concurrency]` alias whose child logs `started`, waits for
+/// `$LOCK_RELEASE` to exist, and logs `finished`.
+fn write_locked_alias(config_home: &TempDir, alias: &str, mode: &str) {
+    let alias_dir = config_home.path().join("chopper").join(alias);
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        format!(
+            r#"
+exec = "/bin/sh"
+args = ["-c", "echo started >> \"$LOCK_LOG\"; while [ ! -e \"$LOCK_RELEASE\" ]; do sleep 0.02; done; echo finished >> \"$LOCK_LOG\""]
+
+[concurrency]
+mode = "{mode}"
+lock_key = "shared-db"
+"#
+        ),
+    )
+    .expect("write alias config");
+}
+
+fn lock_env(runtime: &TempDir, log: &Path, release: &Path) -> [(&'static str, String); 3] {
+    [
+        ("XDG_RUNTIME_DIR", runtime.path().display().to_string()),
+        ("LOCK_LOG", log.display().to_string()),
+        ("LOCK_RELEASE", release.display().to_string()),
+    ]
+}
+
+fn spawn_background_alias(
+    config_home: &TempDir,
+    cache_home: &TempDir,
+    alias: &str,
+    env_vars: [(&'static str, String); 3],
+) -> std::process::Child {
+    prepare_reconcile_script_fixtures(config_home);
+    let home_dir = config_home.path().join("home");
+    fs::create_dir_all(&home_dir).expect("create test home dir");
+    Command::new(chopper_bin())
+        .arg(alias)
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("XDG_CACHE_HOME", cache_home.path())
+        .env("HOME", &home_dir)
+        .env("CHOPPER_EXE_PATH", chopper_exe_bin())
+        .envs(env_vars)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("spawn chopper")
+}
+
+#[test]
+fn exclusive_concurrency_refuses_a_second_instance_and_names_the_holder() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let runtime = TempDir::new().expect("create runtime dir");
+    let log = config_home.path().join("lock.log");
+    let release = config_home.path().join("release");
+    write_locked_alias(&config_home, "migrate", "exclusive");
+    write_locked_alias(&config_home, "migrate-again", "skip");
+
+    let mut holder = spawn_background_alias(
+        &config_home,
+        &cache_home,
+        "migrate",
+        lock_env(&runtime, &log, &release),
+    );
+    wait_for_log_line(&log, "started");
+
+    // chopper execs the command, so the holder's pid is the command's pid.
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["migrate"],
+        lock_env(&runtime, &log, &release),
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(75), "{stderr}");
+    assert!(
+        stderr.contains(&format!(
+            "`migrate` is already running (pid {} holds lock `shared-db`)",
+            holder.id()
+        )),
+        "{stderr}"
+    );
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["migrate-again"],
+        lock_env(&runtime, &log, &release),
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(0), "{stderr}");
+    assert!(
+        stderr.contains("chopper: skipping `migrate-again`: already running"),
+        "{stderr}"
+    );
+
+    fs::write(&release, "").expect("release holder");
+    assert!(holder.wait().expect("wait for holder").success());
+    assert_eq!(
+        fs::read_to_string(&log).expect("read lock log"),
+        "started\nfinished\n"
+    );
+}
+
+#[test]
+fn concurrency_lock_is_inherited_only_by_an_execed_command() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let runtime = TempDir::new().expect("create runtime dir");
+    let log = config_home.path().join("lock.log");
+    // Logs `<label> <number of open .lock descriptors>`.
+    let count_locks = r#"'n=0; for fd in /proc/$$/fd/*; do case "$(readlink "$fd")" in *.lock) n=$((n+1));; esac; done; echo "$1 $n" >> "$LOCK_LOG"'"#;
+    for (alias, hooks) in [
+        ("direct", String::new()),
+        (
+            "supervised",
+            format!("[hooks]\npost = [[\"/bin/sh\", \"-c\", {count_locks}, \"sh\", \"hook\"]]\n"),
+        ),
+    ] {
+        let alias_dir = config_home.path().join("chopper").join(alias);
+        fs::create_dir_all(&alias_dir).expect("create alias dir");
+        fs::write(
+            alias_dir.join("exe.toml"),
+            format!(
+                "exec = \"/bin/sh\"\nargs = [\"-c\", {count_locks}, \"sh\", \"{alias}\"]\n\n[concurrency]\nlock_key = \"{alias}\"\n\n{hooks}"
+            ),
+        )
+        .expect("write alias config");
+        let output = run_chopper_with(
+            chopper_bin(),
+            &config_home,
+            &cache_home,
+            &[alias],
+            [
+                ("XDG_RUNTIME_DIR", runtime.path().display().to_string()),
+                ("LOCK_LOG", log.display().to_string()),
+            ],
+        );
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    assert_eq!(
+        fs::read_to_string(&log).expect("read lock log"),
+        "direct 1\nsupervised 0\nhook 0\n"
+    );
+}
+
+#[test]
+fn wait_concurrency_runs_the_second_instance_after_the_first() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let runtime = TempDir::new().expect("create runtime dir");
+    let log = config_home.path().join("lock.log");
+    let release = config_home.path().join("release");
+    write_locked_alias(&config_home, "deploy", "wait");
+
+    let mut holder = spawn_background_alias(
+        &config_home,
+        &cache_home,
+        "deploy",
+        lock_env(&runtime, &log, &release),
+    );
+    wait_for_log_line(&log, "started");
+    let output = thread::scope(|scope| {
+        let waiter = scope.spawn(|| {
+            run_chopper_with(
+                chopper_bin(),
+                &config_home,
+                &cache_home,
+                &["deploy"],
+                lock_env(&runtime, &log, &release),
+            )
+        });
+        thread::sleep(Duration::from_millis(300));
+        assert_eq!(
+            fs::read_to_string(&log).expect("read lock log"),
+            "started\n"
+        );
+
+        fs::write(&release, "").expect("release holder");
+        assert!(holder.wait().expect("wait for holder").success());
+        waiter.join().expect("join waiter")
+    });
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(
+        stderr.contains(&format!(
+            "chopper: waiting for `deploy` to finish (pid {} holds lock `shared-db`)",
+            holder.id()
+        )),
+        "{stderr}"
+    );
+    assert_eq!(
+        fs::read_to_string(&log).expect("read lock log"),
+        "started\nfinished\nstarted\nfinished\n"
+    );
+}
+
+#[test]
+fn ps_lists_running_instances_and_kill_signals_them() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let runtime = TempDir::new().expect("create runtime dir");
+    let log = config_home.path().join("worker.log");
+    let release = config_home.path().join("release");
+    let alias_dir = config_home.path().join("chopper/worker");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "echo started >> \"$LOCK_LOG\"; while [ ! -e \"$LOCK_RELEASE\" ]; do sleep 0.02; done"]
+"#,
+    )
+    .expect("write alias config");
+
+    let mut worker = spawn_background_alias(
+        &config_home,
+        &cache_home,
+        "worker",
+        lock_env(&runtime, &log, &release),
+    );
+    wait_for_log_line(&log, "started");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["--ps", "worker", "--json"],
+        lock_env(&runtime, &log, &release),
+    );
+    assert!(output.status.success());
+    let listed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("parse --ps json");
+    let listed = listed.as_array().expect("instance array");
+    assert_eq!(listed.len(), 1, "{listed:?}");
+    assert_eq!(listed[0]["alias"], "worker");
+    assert_eq!(listed[0]["pid"], worker.id());
+    assert_eq!(listed[0]["argv"][0], "/bin/sh");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["--ps"],
+        lock_env(&runtime, &log, &release),
+    );
+    let table = String::from_utf8_lossy(&output.stdout);
+    assert!(table.starts_with("    PID  ALIAS"), "{table}");
+    assert!(
+        table.contains(&format!("{:>7}  worker", worker.id())),
+        "{table}"
+    );
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["--kill", "worker", "--signal=USR1"],
+        lock_env(&runtime, &log, &release),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let status = worker.wait().expect("wait for worker");
+    assert_eq!(status.signal(), Some(Signal::SIGUSR1 as i32));
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["--kill", "worker"],
+        lock_env(&runtime, &log, &release),
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("no running instances of `worker`"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn dry_run_reports_the_resolved_invocation_without_running_it() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/deploy");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    let ran = config_home.path().join("ran");
+    let secret_read = config_home.path().join("secret-read");
+    fs::write(
+        alias_dir.join("exe.rhai"),
+        r#"
+fn reconcile(_ctx) {
+  #{
+    append_args: ["--from-reconcile"]
+  }
+}
+"#,
+    )
+    .expect("write reconcile script");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        format!(
+            r#"
+exec = "/bin/sh"
+args = ["-c", "touch {ran}", "deploy"]
+env_remove = ["DRY_DROPPED"]
+
+[env]
+DRY_ADDED = "yes"
+DRY_CHANGED = "after"
+
+[env_secret]
+DRY_TOKEN = {{ command = ["touch", "{secret_read}"] }}
+
+[path]
+prepend_one = ["/opt/deploy/bin"]
+
+[journal]
+namespace = "ops"
+stderr = true
+user_scope = true
+
+[reconcile]
+script = "exe.rhai"
+function = "reconcile"
+"#,
+            ran = ran.display(),
+            secret_read = secret_read.display(),
+        ),
+    )
+    .expect("write alias config");
+    let env = [
+        ("PATH", "/usr/bin:/bin".to_string()),
+        ("USER", "Test User".to_string()),
+        ("DRY_CHANGED", "before".to_string()),
+        ("DRY_DROPPED", "gone".to_string()),
+    ];
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["--dry-run", "deploy", "prod"],
+        env.clone(),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let namespace = format!("u{}-test-user-ops", geteuid().as_raw());
+    for expected in [
+        "exec:    /bin/sh".to_string(),
+        "  + DRY_ADDED=yes".to_string(),
+        "  ~ DRY_CHANGED=after (was before)".to_string(),
+        "  - DRY_DROPPED".to_string(),
+        "  + DRY_TOKEN=<redacted: command touch".to_string(),
+        "PATH before: /usr/bin:/bin".to_string(),
+        "PATH after:  /opt/deploy/bin:/usr/bin:/bin".to_string(),
+        format!("journal: {namespace} (from `ops`), with stderr"),
+        "reconcile patch:\n  append_args: --from-reconcile".to_string(),
+    ] {
+        assert!(
+            stdout.contains(&expected),
+            "missing {expected:?} in:\n{stdout}"
+        );
+    }
+    assert!(stdout.contains("deploy prod --from-reconcile"), "{stdout}");
+    assert!(!ran.exists(), "dry run must not start the command");
+    assert!(!secret_read.exists(), "dry run must not read secrets");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["--dry-run", "--json", "deploy", "prod"],
+        env,
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("parse --dry-run json");
+    assert_eq!(json["exec"], "/bin/sh");
+    assert_eq!(json["args"][3], "prod");
+    assert_eq!(json["env"]["changed"]["DRY_CHANGED"]["before"], "before");
+    assert_eq!(json["env"]["removed"], serde_json::json!(["DRY_DROPPED"]));
+    assert_eq!(json["path"]["after"], "/opt/deploy/bin:/usr/bin:/bin");
+    assert_eq!(json["journal"]["configured_namespace"], "ops");
+    assert_eq!(json["journal"]["namespace"], namespace.as_str());
+    assert_eq!(
+        json["reconcile_patch"]["append_args"],
+        serde_json::json!(["--from-reconcile"])
+    );
+    assert!(!ran.exists());
+}
+
+#[test]
+fn explain_attributes_args_env_and_path_to_their_sources() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/deploy");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(alias_dir.join("deploy.env"), "# defaults\nFROM_FILE=1\n").expect("write env file");
+    fs::write(
+        alias_dir.join("exe.rhai"),
+        r#"
+fn reconcile(_ctx) {
+  #{
+    append_args: ["--from-reconcile"]
+  }
+}
+"#,
+    )
+    .expect("write reconcile script");
+    let config = alias_dir.join("exe.toml");
+    fs::write(
+        &config,
+        r#"exec = "/bin/echo"
+args = ["--base"]
+env_remove = ["EXPLAIN_DROPPED"]
+env_file = ["deploy.env"]
+
+[env]
+MODE = "dev"
+
+[path]
+prepend_one = ["/opt/deploy/bin"]
+
+[profile.prod.env]
+MODE = "prod"
+
+[subcommands.status]
+args = ["--verbose"]
+
+[reconcile]
+script = "exe.rhai"
+function = "reconcile"
+"#,
+    )
+    .expect("write alias config");
+    let env = [
+        ("PATH", "/usr/bin:/bin".to_string()),
+        ("EXPLAIN_DROPPED", "gone".to_string()),
+        ("EXPLAIN_KEPT", "kept".to_string()),
+    ];
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &[
+            "--explain",
+            "deploy",
+            "--chopper-profile=prod",
+            "status",
+            "now",
+        ],
+        env.clone(),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let at = |line: usize| format!("({}:{line})", config.display());
+    let lines = stdout.lines().map(str::trim_end).collect::<Vec<_>>();
+    let has = |entry: &str, source: &str| {
+        lines
+            .iter()
+            .any(|line| line.trim_start().starts_with(entry) && line.ends_with(source))
+    };
+    for (entry, source) in [
+        ("[0] --base", format!("<- config args[0] {}", at(2))),
+        ("[1] status", "<- runtime arg 1".to_string()),
+        (
+            "[2] --verbose",
+            format!("<- config subcommands.\"status\".args[0] {}", at(16)),
+        ),
+        ("[3] now", "<- runtime arg 2".to_string()),
+        (
+            "[4] --from-reconcile",
+            "<- reconcile append_args[0]".to_string(),
+        ),
+        ("+MODE=prod", format!("<- config env.MODE {}", at(13))),
+        (
+            "+FROM_FILE=1",
+            format!("<- env_file {}:2", alias_dir.join("deploy.env").display()),
+        ),
+        (
+            "-EXPLAIN_DROPPED",
+            format!("<- config env_remove {}", at(3)),
+        ),
+        ("EXPLAIN_KEPT=kept", "<- inherited".to_string()),
+        (
+            "/opt/deploy/bin",
+            format!("<- step 1: config path.prepend_one[0] {}", at(10)),
+        ),
+        ("/usr/bin", "<- starting PATH: inherited".to_string()),
+    ] {
+        assert!(
+            has(entry, &source),
+            "missing {entry} {source} in:\n{stdout}"
+        );
+    }
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["--explain", "--json", "deploy"],
+        env,
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("parse --explain json");
+    assert_eq!(json["args"][0]["origin"]["field"], "args[0]");
+    assert_eq!(json["args"][0]["location"]["line"], 2);
+    assert_eq!(json["args"][1]["origin"]["source"], "reconcile");
+    let mode = json["env"]
+        .as_array()
+        .expect("env array")
+        .iter()
+        .find(|var| var["name"] == "MODE")
+        .expect("MODE entry");
+    assert_eq!(mode["value"], "dev");
+    assert_eq!(mode["status"], "set");
+    assert_eq!(mode["location"]["line"], 7);
+    assert_eq!(json["path"][0]["step"], 1);
+    assert_eq!(json["path"][1]["origin"]["source"], "inherited");
+}
+
+#[test]
+fn audit_log_records_redacted_launches_and_supervised_exit_codes() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let log_dir = TempDir::new().expect("create log dir");
+    let config_root = config_home.path().join("chopper");
+    for (alias, body) in [
+        (
+            "psql",
+            "exec = \"/bin/echo\"\nargs = [\"--host=db\"]\n[audit]\nredact_matching = [\"^postgres://\"]\n",
+        ),
+        (
+            "quiet",
+            "exec = \"/bin/echo\"\n[audit]\nenabled = false\n",
+        ),
+        (
+            "fail",
+            "exec = \"/bin/sh\"\nargs = [\"-c\", \"exit 3\"]\ntimeout = \"30s\"\n",
+        ),
+    ] {
+        fs::create_dir_all(config_root.join(alias)).expect("create alias dir");
+        fs::write(config_root.join(alias).join("exe.toml"), body).expect("write alias config");
+    }
+    let log = log_dir.path().join("audit.jsonl");
+    fs::write(
+        config_root.join("chopper.toml"),
+        format!(
+            "[audit]\npath = \"{}\"\nredact_options = [\"--password\"]\n",
+            log.display()
+        ),
+    )
+    .expect("write settings");
+
+    for (args, code) in [
+        (
+            vec![
+                "psql",
+                "--password",
+                "hunter2",
+                "postgres://app:pw@db/prod",
+                "-l",
+            ],
+            0,
+        ),
+        (vec!["quiet", "--password", "hunter2"], 0),
+        (vec!["fail"], 3),
+    ] {
+        let output = run_chopper(&config_home, &cache_home, &args);
+        assert_eq!(
+            output.status.code(),
+            Some(code),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    let written = fs::read_to_string(&log).expect("read audit log");
+    assert!(!written.contains("hunter2"), "{written}");
+    assert!(!written.contains("app:pw"), "{written}");
+
+    let output = run_chopper(&config_home, &cache_home, &["--audit", "tail", "--json"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let records = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).expect("record json"))
+        .collect::<Vec<_>>();
+    assert_eq!(records.len(), 3, "{records:?}");
+    assert_eq!(records[0]["alias"], "psql");
+    assert_eq!(
+        records[0]["argv"],
+        serde_json::json!([
+            "/bin/echo",
+            "--host=db",
+            "--password",
+            "<redacted>",
+            "<redacted>",
+            "-l"
+        ])
+    );
+    assert_eq!(
+        records[0]["config"],
+        config_root.join("psql/exe.toml").display().to_string()
+    );
+    assert!(records[0]["config_fingerprint"]
+        .as_str()
+        .is_some_and(|fingerprint| fingerprint.starts_with("fnv1a64:")));
+    assert!(records[0]["uid"].is_u64());
+    assert!(records[0].get("exit_code").is_none());
+    // `enabled = false` cannot opt out of the globally enabled log.
+    assert_eq!(records[1]["alias"], "quiet");
+    assert_eq!(
+        records[1]["argv"],
+        serde_json::json!(["/bin/echo", "--password", "<redacted>"])
+    );
+    assert_eq!(records[2]["alias"], "fail");
+    assert_eq!(records[2]["exit_code"], 3);
+    assert!(records[2]["duration_ms"].is_u64());
+
+    let output = run_chopper(
+        &config_home,
+        &cache_home,
+        &["--audit", "query", "--alias", "fail", "--since", "1h"],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    assert!(stdout.contains("  fail  "), "{stdout}");
+    assert!(stdout.contains("exit 3 after "), "{stdout}");
+}
+
+#[test]
+fn malformed_global_settings_warn_without_failing_launches() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let log_dir = TempDir::new().expect("create log dir");
+    let config_root = config_home.path().join("chopper");
+    fs::create_dir_all(config_root.join("hello")).expect("create alias dir");
+    fs::write(
+        config_root.join("hello/exe.toml"),
+        "exec = \"/bin/echo\"\nargs = [\"hi\"]\n",
+    )
+    .expect("write alias config");
+    let log = log_dir.path().join("audit.jsonl");
+    fs::write(
+        config_root.join("chopper.toml"),
+        format!("[audit]\npath = \"{}\"\nkep = 3\n", log.display()),
+    )
+    .expect("write settings");
+
+    let output = run_chopper(&config_home, &cache_home, &["hello"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hi\n");
+    assert!(
+        stderr.contains("warning: ignoring global settings: failed to parse"),
+        "{stderr}"
+    );
+    assert!(!log.exists());
+
+    let output = run_chopper(&config_home, &cache_home, &["--audit", "tail"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("failed to parse"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn exec_candidates_fall_back_and_requires_checks_a_cached_version_probe() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/edit");
+    fs::create_dir_all(alias_dir.join("bin")).expect("create alias bin dir");
+    let probes = alias_dir.join("probes");
+    let tool_script = |version: &str| {
+        format!(
+            "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo probe >> '{}'; echo \"tool {version}\"; exit 0; fi\nprintf 'tool %s' \"$*\"\n",
+            probes.display()
+        )
+    };
+    write_executable_script(&alias_dir.join("bin/tool"), &tool_script("2.4.1"));
+    let write_config = |min_version: &str| {
+        fs::write(
+            alias_dir.join("exe.toml"),
+            format!(
+                "exec = [\"./bin/missing\", \"./bin/tool\"]\n\n[requires]\nmin_version = \"{min_version}\"\n"
+            ),
+        )
+        .expect("write alias config");
+    };
+    let probe_count = || {
+        fs::read_to_string(&probes)
+            .map(|content| content.lines().count())
+            .unwrap_or(0)
+    };
+
+    write_config("2.4");
+    for _ in 0..2 {
+        let output = run_chopper(&config_home, &cache_home, &["edit", "notes.txt"]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "tool notes.txt");
+    }
+    assert_eq!(probe_count(), 1, "version probe should be cached");
+
+    write_config("3.0");
+    let output = run_chopper(&config_home, &cache_home, &["edit"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("is version 2.4.1, but this alias requires at least 3.0"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(probe_count(), 1);
+
+    thread::sleep(Duration::from_millis(20));
+    write_executable_script(&alias_dir.join("bin/tool"), &tool_script("3.1"));
+    let output = run_chopper(&config_home, &cache_home, &["edit"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(probe_count(), 2, "replacing the binary should re-probe");
+
+    fs::remove_file(alias_dir.join("bin/tool")).expect("remove tool");
+    let output = run_chopper(&config_home, &cache_home, &["edit"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("none of the `exec` candidates were found"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn profiles_are_selected_by_flag_env_or_default_and_shown_by_alias_get() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/deploy");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "printf '%s|%s' \"$STAGE\" \"$*\"", "_"]
+default_profile = "dev"
+
+[env]
+STAGE = "base"
+
+[profile.dev.env]
+STAGE = "dev"
+
+[profile.prod]
+args = ["-c", "printf 'PROD %s|%s' \"$STAGE\" \"$*\"", "_"]
+
+[profile.prod.env]
+STAGE = "prod"
+"#,
+    )
+    .expect("write alias config");
+
+    let output = run_chopper(&config_home, &cache_home, &["deploy", "a"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "dev|a");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["deploy", "a"],
+        [("CHOPPER_PROFILE", "prod".to_string())],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "PROD prod|a");
+
+    let output = run_chopper_with(
+        chopper_bin(),
+        &config_home,
+        &cache_home,
+        &["deploy", "--chopper-profile=dev", "--", "b"],
+        [("CHOPPER_PROFILE", "prod".to_string())],
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "dev|b");
+
+    let output = run_chopper(
+        &config_home,
+        &cache_home,
+        &["deploy", "--chopper-profile=qa"],
+    );
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("--chopper-profile selects unknown profile `qa`"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let get = run_chopper(
+        &config_home,
+        &cache_home,
+        &["--alias", "get", "deploy", "--profile", "prod"],
+    );
+    assert!(
+        get.status.success(),
+        "{}",
+        String::from_utf8_lossy(&get.stderr)
+    );
+    let json: serde_json::Value =
+        serde_json::from_slice(&get.stdout).expect("parse alias get JSON");
+    assert_eq!(json["profile"], "prod");
+    assert_eq!(json["env"]["STAGE"], "prod");
+    assert_eq!(json["default_profile"], "dev");
+}
+
+#[test]
+fn subcommand_overlay_applies_only_when_leading_runtime_args_match() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/kctl");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "printf '%s|' \"$@\"; printf 'MODE=%s' \"${KCTL_MODE-unset}\"", "_"]
+
+[subcommands.delete]
+args = ["--dry-run=client"]
+
+[subcommands.delete.env]
+KCTL_MODE = "careful"
+"#,
+    )
+    .expect("write alias config");
+
+    let output = run_chopper(&config_home, &cache_home, &["kctl", "delete", "pod", "x"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "delete|--dry-run=client|pod|x|MODE=careful"
+    );
+
+    let output = run_chopper(&config_home, &cache_home, &["kctl", "get", "delete"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "get|delete|MODE=unset"
+    );
+}
+
+#[test]
+fn limits_table_applies_rlimits_and_umask_before_exec() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let alias_dir = config_home.path().join("chopper/limited");
+    fs::create_dir_all(&alias_dir).expect("create alias dir");
+    fs::write(
+        alias_dir.join("exe.toml"),
+        r#"
+exec = "/bin/sh"
+args = ["-c", "printf '%s %s\\n' \"$(ulimit -n)\" \"$(umask)\""]
+
+[limits]
+nofile = 64
+umask = "027"
+"#,
+    )
+    .expect("write alias config");
+
+    for attempt in ["first", "cached"] {
+        let output = run_chopper(&config_home, &cache_home, &["limited"]);
+        assert!(
+            output.status.success(),
+            "{attempt} run failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            "64 0027",
+            "{attempt} run"
+        );
+    }
+
+    fs::write(
+        alias_dir.join("exe.toml"),
+        "exec = \"/bin/true\"\n[limits]\nnice = 99\n",
+    )
+    .expect("rewrite alias config");
+    let output = run_chopper(&config_home, &cache_home, &["limited"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("field `limits.nice` must be between -20 and 19"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
 #[test]
 fn corrupted_safe_alias_cache_file_is_pruned_and_reparsed() {
     let config_home = TempDir::new().expect("create config home");
@@ -13899,6 +15982,25 @@ fn direct_bashcomp_completes_alias_admin_subcommands_and_options() {
         "{path_flags:?}"
     );
 
+    let env_remove_flags = run_direct_bash_completion(
+        &config_home,
+        &cache_home,
+        &["chopper", "--alias", "set", "foo-alias", "--env-r"],
+        4,
+    );
+    assert_eq!(
+        env_remove_flags,
+        vec!["--env-remove", "--env-remove-matching"]
+    );
+
+    let pathlist_flags = run_direct_bash_completion(
+        &config_home,
+        &cache_home,
+        &["chopper", "--alias", "set", "foo-alias", "--pathl"],
+        4,
+    );
+    assert_eq!(pathlist_flags, vec!["--pathlist", "--pathlist-clear"]);
+
     let bool_value = run_direct_bash_completion(
         &config_home,
         &cache_home,
@@ -14874,6 +16976,80 @@ fn alias_list_command_reports_managed_aliases() {
     assert!(stdout.contains("managed-b"), "{stdout}");
 }
 
+#[test]
+fn alias_cwd_runs_command_from_configured_directory_and_set_can_clear_it() {
+    let config_home = TempDir::new().expect("create config home");
+    let cache_home = TempDir::new().expect("create cache home");
+    let workdir = TempDir::new().expect("create workdir");
+    let workdir_real = fs::canonicalize(workdir.path()).expect("canonical workdir");
+
+    let add = run_chopper(
+        &config_home,
+        &cache_home,
+        &[
+            "--alias",
+            "add",
+            "inrepo",
+            "--exec",
+            "/bin/pwd",
+            "--cwd",
+            workdir_real.to_str().expect("utf-8 workdir"),
+            "--no-wrapper-sync",
+        ],
+    );
+    assert!(
+        add.status.success(),
+        "{}",
+        String::from_utf8_lossy(&add.stderr)
+    );
+
+    let output = run_chopper(&config_home, &cache_home, &["inrepo"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        workdir_real.display().to_string()
+    );
+
+    let set = run_chopper(
+        &config_home,
+        &cache_home,
+        &["--alias", "set", "inrepo", "--cwd", ""],
+    );
+    assert!(
+        set.status.success(),
+        "{}",
+        String::from_utf8_lossy(&set.stderr)
+    );
+    let config = fs::read_to_string(config_home.path().join("chopper/inrepo/exe.toml"))
+        .expect("read alias config");
+    assert!(!config.contains("cwd"), "{config}");
+
+    let missing = workdir_real.join("missing");
+    let set = run_chopper(
+        &config_home,
+        &cache_home,
+        &[
+            "--alias",
+            "set",
+            "inrepo",
+            "--cwd",
+            missing.to_str().expect("utf-8 path"),
+        ],
+    );
+    assert!(set.status.success());
+    let output = run_chopper(&config_home, &cache_home, &["inrepo"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("does not exist or is not a directory"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
 #[test]
 fn alias_set_command_updates_args_and_journal_fields() {
     let config_home = TempDir::new().expect("create config home");